@@ -0,0 +1,352 @@
+//! Procedural macros for [lincheck](https://docs.rs/lincheck).
+//!
+//! Use them through the re-exports of the `lincheck` crate with the `macros` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, FnArg, Ident, ImplItem, ItemImpl, Path, ReturnType, Type, Visibility,
+};
+
+/// Generates the operations of a data structure from its methods marked with `#[operation]`.
+///
+/// On the impl block of the sequential specification, it generates:
+/// - the enum of operations with a variant per method, named in `PascalCase`, holding the arguments;
+/// - the enum of results with a variant per method holding the returned value, or no value for `()`;
+/// - the `Arbitrary` implementation of the operations choosing a method uniformly with arbitrary arguments;
+/// - the `SequentialSpec` implementation calling the methods, which must take `&mut self`.
+///
+/// The enums are named after the type with the `Op` and `Ret` suffixes unless `op = ...` and `ret = ...` are given,
+/// and have the visibility given by `vis = ...`, private by default.
+///
+/// On the impl block of the concurrent implementation, `seq = ...` names the sequential specification,
+/// and it generates the `ConcurrentSpec` implementation calling the methods, which must take `&self`.
+/// `op = ...` and `ret = ...` name the enums if they aren't named after the sequential specification.
+///
+/// The methods without `#[operation]` are left as they are.
+#[proc_macro_attribute]
+pub fn operations(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = Args::default();
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("op") {
+            args.op = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("ret") {
+            args.ret = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("seq") {
+            args.seq = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("vis") {
+            args.vis = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("expected `op`, `ret`, `seq` or `vis`"));
+        }
+        Ok(())
+    });
+    parse_macro_input!(attr with parser);
+    let mut item = parse_macro_input!(item as ItemImpl);
+
+    let expanded = operations_of(&mut item).and_then(|operations| match &args.seq {
+        Some(seq) => concurrent_spec(&args, seq, &item, &operations),
+        None => sequential_spec(&args, &item, &operations),
+    });
+    match expanded {
+        Ok(expanded) => quote!(#item #expanded).into(),
+        Err(error) => {
+            let error = error.to_compile_error();
+            quote!(#item #error).into()
+        }
+    }
+}
+
+#[derive(Default)]
+struct Args {
+    op: Option<Path>,
+    ret: Option<Path>,
+    seq: Option<Path>,
+    vis: Option<Visibility>,
+}
+
+/// A method marked with `#[operation]`.
+struct Operation {
+    method: Ident,
+    variant: Ident,
+    receiver_is_mut: bool,
+    arg_types: Vec<Type>,
+    ret_type: Option<Type>,
+    span: Span,
+}
+
+impl Operation {
+    /// The bindings of the arguments in the match arms.
+    fn arg_names(&self) -> Vec<Ident> {
+        (0..self.arg_types.len())
+            .map(|i| format_ident!("__arg{}", i))
+            .collect()
+    }
+}
+
+/// Collects the operations and strips the `#[operation]` attributes.
+fn operations_of(item: &mut ItemImpl) -> syn::Result<Vec<Operation>> {
+    let mut operations = Vec::new();
+    for impl_item in item.items.iter_mut() {
+        let ImplItem::Fn(method) = impl_item else {
+            continue;
+        };
+        let attrs_len = method.attrs.len();
+        method
+            .attrs
+            .retain(|attr| !attr.path().is_ident("operation"));
+        if method.attrs.len() == attrs_len {
+            continue;
+        }
+
+        let sig = &method.sig;
+        let span = sig.ident.span();
+        let mut receiver_is_mut = None;
+        let mut arg_types = Vec::new();
+        for input in sig.inputs.iter() {
+            match input {
+                FnArg::Receiver(receiver) if receiver.reference.is_some() => {
+                    receiver_is_mut = Some(receiver.mutability.is_some())
+                }
+                FnArg::Receiver(receiver) => {
+                    return Err(syn::Error::new_spanned(
+                        receiver,
+                        "an operation must take `&self` or `&mut self`",
+                    ))
+                }
+                FnArg::Typed(arg) => arg_types.push((*arg.ty).clone()),
+            }
+        }
+        let Some(receiver_is_mut) = receiver_is_mut else {
+            return Err(syn::Error::new(
+                span,
+                "an operation must take `&self` or `&mut self`",
+            ));
+        };
+        let ret_type = match &sig.output {
+            ReturnType::Default => None,
+            ReturnType::Type(_, ty) if matches!(&**ty, Type::Tuple(tuple) if tuple.elems.is_empty()) => {
+                None
+            }
+            ReturnType::Type(_, ty) => Some((**ty).clone()),
+        };
+
+        operations.push(Operation {
+            method: sig.ident.clone(),
+            variant: Ident::new(&pascal_case(&sig.ident.to_string()), span),
+            receiver_is_mut,
+            arg_types,
+            ret_type,
+            span,
+        });
+    }
+
+    if operations.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.self_ty,
+            "no methods are marked with `#[operation]`",
+        ));
+    }
+    Ok(operations)
+}
+
+fn sequential_spec(
+    args: &Args,
+    item: &ItemImpl,
+    operations: &[Operation],
+) -> syn::Result<TokenStream2> {
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "the sequential specification can't be generic",
+        ));
+    }
+    let self_ty = &item.self_ty;
+    let Type::Path(self_path) = &**self_ty else {
+        return Err(syn::Error::new_spanned(
+            self_ty,
+            "expected the name of the sequential specification",
+        ));
+    };
+    let op = args
+        .op
+        .clone()
+        .unwrap_or_else(|| suffixed(&self_path.path, "Op"));
+    let ret = args
+        .ret
+        .clone()
+        .unwrap_or_else(|| suffixed(&self_path.path, "Ret"));
+    let (Some(op_name), Some(ret_name)) = (op.get_ident(), ret.get_ident()) else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`op` and `ret` must be identifiers",
+        ));
+    };
+    let vis = args.vis.clone().unwrap_or(Visibility::Inherited);
+
+    if let Some(operation) = operations
+        .iter()
+        .find(|operation| !operation.receiver_is_mut)
+    {
+        return Err(syn::Error::new(
+            operation.span,
+            "an operation of the sequential specification must take `&mut self`",
+        ));
+    }
+
+    let op_variants = operations.iter().map(|operation| {
+        let variant = &operation.variant;
+        let arg_types = &operation.arg_types;
+        if arg_types.is_empty() {
+            quote!(#variant)
+        } else {
+            quote!(#variant(#(#arg_types),*))
+        }
+    });
+    let ret_variants = operations.iter().map(|operation| {
+        let variant = &operation.variant;
+        match &operation.ret_type {
+            Some(ret_type) => quote!(#variant(#ret_type)),
+            None => quote!(#variant),
+        }
+    });
+
+    let proptest = quote!(::lincheck::__private::proptest);
+    let strategies = operations.iter().map(|operation| {
+        let variant = &operation.variant;
+        let arg_types = &operation.arg_types;
+        let arg_names = operation.arg_names();
+        if arg_types.is_empty() {
+            quote!(#proptest::strategy::Strategy::boxed(#proptest::strategy::Just(#op_name::#variant)))
+        } else {
+            quote! {
+                #proptest::strategy::Strategy::boxed(#proptest::strategy::Strategy::prop_map(
+                    (#(#proptest::arbitrary::any::<#arg_types>(),)*),
+                    |(#(#arg_names,)*)| #op_name::#variant(#(#arg_names),*),
+                ))
+            }
+        }
+    });
+
+    let arms = dispatch_arms(&op, &ret, operations);
+
+    Ok(quote! {
+        #[derive(Debug, Clone, PartialEq)]
+        #vis enum #op_name {
+            #(#op_variants,)*
+        }
+
+        #[derive(Debug, Clone, PartialEq)]
+        #vis enum #ret_name {
+            #(#ret_variants,)*
+        }
+
+        impl #proptest::arbitrary::Arbitrary for #op_name {
+            type Parameters = ();
+            type Strategy = #proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                #proptest::strategy::Strategy::boxed(#proptest::strategy::Union::new(vec![
+                    #(#strategies,)*
+                ]))
+            }
+        }
+
+        impl ::lincheck::SequentialSpec for #self_ty {
+            type Op = #op_name;
+            type Ret = #ret_name;
+
+            fn exec(&mut self, op: #op_name) -> #ret_name {
+                match op {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
+
+fn concurrent_spec(
+    args: &Args,
+    seq: &Path,
+    item: &ItemImpl,
+    operations: &[Operation],
+) -> syn::Result<TokenStream2> {
+    let op = args.op.clone().unwrap_or_else(|| suffixed(seq, "Op"));
+    let ret = args.ret.clone().unwrap_or_else(|| suffixed(seq, "Ret"));
+
+    if let Some(operation) = operations
+        .iter()
+        .find(|operation| operation.receiver_is_mut)
+    {
+        return Err(syn::Error::new(
+            operation.span,
+            "an operation of the concurrent implementation must take `&self`",
+        ));
+    }
+
+    let self_ty = &item.self_ty;
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+    let arms = dispatch_arms(&op, &ret, operations);
+
+    Ok(quote! {
+        impl #impl_generics ::lincheck::ConcurrentSpec for #self_ty #where_clause {
+            type Seq = #seq;
+
+            fn exec(&self, op: #op) -> #ret {
+                match op {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
+
+/// The match arms calling the method of each operation and wrapping its result.
+fn dispatch_arms<'a>(
+    op: &'a Path,
+    ret: &'a Path,
+    operations: &'a [Operation],
+) -> impl Iterator<Item = TokenStream2> + 'a {
+    operations.iter().map(move |operation| {
+        let method = &operation.method;
+        let variant = &operation.variant;
+        let arg_names = operation.arg_names();
+        let pattern = if arg_names.is_empty() {
+            quote!(#op::#variant)
+        } else {
+            quote!(#op::#variant(#(#arg_names),*))
+        };
+        let call = quote!(self.#method(#(#arg_names),*));
+        match operation.ret_type {
+            Some(_) => quote!(#pattern => #ret::#variant(#call)),
+            None => quote!(#pattern => {
+                #call;
+                #ret::#variant
+            }),
+        }
+    })
+}
+
+/// The path with the suffix appended to its last segment, e.g. `queue::SequentialQueueOp`.
+fn suffixed(path: &Path, suffix: &str) -> Path {
+    let mut path = path.clone();
+    if let Some(last) = path.segments.last_mut() {
+        last.ident = format_ident!("{}{}", last.ident, suffix);
+        last.arguments = syn::PathArguments::None;
+    }
+    path
+}
+
+/// Converts a `snake_case` method name to `PascalCase`.
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,242 @@
+//! Allocation failure injection on native threads.
+//!
+//! [FailingAllocator] wraps the global allocator and makes the allocations of the operations fail
+//! according to [AllocFailures]. The rest of the allocations, e.g. the ones made by the harness, never fail.
+//! It must be installed in the test binary:
+//! ```
+//! use lincheck::alloc::FailingAllocator;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: FailingAllocator = FailingAllocator::system();
+//! # fn main() {}
+//! ```
+//!
+//! Rust aborts the process if an infallible allocation fails, so only the fallible ones,
+//! e.g. [Vec::try_reserve], are worth testing this way.
+//! [verify_alloc_failures](crate::Lincheck::verify_alloc_failures) runs the scenarios on native threads
+//! and records the failed allocations as the `"alloc"` [fail point](crate::faults).
+//! So the sequential specification decides what an operation returns when an allocation fails
+//! with [FaultySpec::exec_faulted].
+//!
+//! Since native threads are used, the data structure must not use [loom] primitives.
+
+use proptest::prelude::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::checker::*;
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::faults::*;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// The number of times each scenario is executed.
+const RUNS_PER_SCENARIO: usize = 100;
+
+/// The name of the fail point the failed allocations are recorded as.
+const ALLOC_FAIL_POINT: &str = "alloc";
+
+/// How the allocations of the operations fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllocFailures {
+    /// Every allocation fails with the probability `1 / n`.
+    OneIn(u64),
+    /// The allocation number `n` (counting from zero) of every operation fails.
+    Nth(usize),
+}
+
+thread_local! {
+    /// How the allocations of the current thread fail. `None` if they don't.
+    static ARMED: Cell<Option<AllocFailures>> = const { Cell::new(None) };
+    /// The number of allocations since the current thread was armed.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    /// Whether an allocation has failed since the current thread was armed.
+    static FAILED: Cell<bool> = const { Cell::new(false) };
+    static RNG_STATE: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Seeds the random number generators of the threads.
+static NEXT_SEED: AtomicU64 = AtomicU64::new(0x9e37_79b9_7f4a_7c15);
+
+/// A global allocator that makes the allocations of the operations fail.
+///
+/// See the [module-level documentation](crate::alloc) for the details.
+pub struct FailingAllocator<A = System> {
+    inner: A,
+}
+
+impl FailingAllocator {
+    /// Wraps the system allocator.
+    pub const fn system() -> Self {
+        Self::new(System)
+    }
+}
+
+impl<A> FailingAllocator<A> {
+    /// Wraps the given allocator.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for FailingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if should_fail() {
+            return ptr::null_mut();
+        }
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if should_fail() {
+            return ptr::null_mut();
+        }
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if should_fail() {
+            return ptr::null_mut();
+        }
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Decides whether the current allocation fails. Must not allocate.
+fn should_fail() -> bool {
+    let Some(failures) = ARMED.try_with(Cell::get).ok().flatten() else {
+        return false;
+    };
+
+    let allocation = ALLOCATIONS.get();
+    ALLOCATIONS.set(allocation + 1);
+
+    let fail = match failures {
+        AllocFailures::OneIn(n) => next_random().is_multiple_of(n.max(1)),
+        AllocFailures::Nth(n) => allocation == n,
+    };
+    if fail {
+        FAILED.set(true);
+    }
+    fail
+}
+
+/// The xorshift generator of the current thread.
+fn next_random() -> u64 {
+    let mut state = RNG_STATE.get();
+    if state == 0 {
+        state = NEXT_SEED.fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed) | 1;
+    }
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    RNG_STATE.set(state);
+    state
+}
+
+/// Makes the allocations of the current thread fail.
+fn arm(failures: AllocFailures) {
+    ALLOCATIONS.set(0);
+    FAILED.set(false);
+    ARMED.set(Some(failures));
+}
+
+/// Stops the allocations of the current thread from failing and returns whether any of them failed.
+fn disarm() -> bool {
+    ARMED.set(None);
+    FAILED.get()
+}
+
+/// Checks that [FailingAllocator] is the global allocator.
+fn is_installed() -> bool {
+    arm(AllocFailures::Nth(0));
+    let result = Vec::<u8>::new().try_reserve(1);
+    disarm();
+    result.is_err()
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` stays linearizable when its allocations fail.
+    ///
+    /// See the [module-level documentation](crate::alloc) for the details.
+    /// Each scenario is executed several times since only one interleaving is observed per execution.
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [FailingAllocator] isn't the global allocator.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_alloc_failures<Conc>(
+        &self,
+        failures: AllocFailures,
+    ) -> Result<(), VerificationFailure<Faulted<ConcOp<Conc>>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Sync,
+        Conc::Seq: Default + FaultySpec,
+        ConcOp<Conc>: Send + Clone + Arbitrary + Debug + 'static,
+        ConcRet<Conc>: Send + PartialEq + Debug,
+    {
+        assert!(
+            is_installed(),
+            "FailingAllocator must be installed as the global allocator"
+        );
+
+        self.run_cases(
+            &self.scenarios(),
+            |scenario| {
+                scenario.map(|op: &ConcOp<Conc>| Faulted {
+                    op: op.clone(),
+                    faults: Vec::new(),
+                })
+            },
+            |scenario| {
+                for _ in 0..RUNS_PER_SCENARIO {
+                    let execution = execute_scenario_with_failing_allocations::<Conc>(
+                        scenario.clone(),
+                        failures,
+                    );
+                    if !LinearizabilityChecker::<Faulty<Conc::Seq>>::check(&execution) {
+                        return Err(VerificationFailure::NonLinearizable(execution));
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Executes the given scenario on native threads making the allocations of the operations fail
+/// and returns the resulting execution.
+fn execute_scenario_with_failing_allocations<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    failures: AllocFailures,
+) -> Execution<Faulted<ConcOp<Conc>>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Default + Sync,
+    ConcOp<Conc>: Send + Clone,
+    ConcRet<Conc>: Send,
+{
+    execute_scenario_with_threads_using(&Conc::default(), scenario, |conc: &Conc, op| {
+        arm(failures);
+        let ret = conc.exec(op);
+        (ret, disarm())
+    })
+    .map(|op, (ret, failed)| {
+        let faults = if failed {
+            vec![ALLOC_FAIL_POINT]
+        } else {
+            Vec::new()
+        };
+        (Faulted { op, faults }, ret)
+    })
+}
@@ -0,0 +1,243 @@
+//! Analysis of the discriminating power of a sequential specification.
+//!
+//! A linearizability check can only fail if the return values depend on the order of the operations.
+//! An operation that always returns the same value, e.g. `()`, never tells one interleaving from another,
+//! and a specification where no operation does verifies nothing while passing every test.
+//!
+//! [analyze_spec](crate::Lincheck::analyze_spec) generates scenarios like [verify](crate::Lincheck::verify),
+//! runs the sequential specification on random interleavings of each of them
+//! and reports which operations ever returned different values.
+
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRng;
+use std::fmt::Debug;
+use std::mem::{self, Discriminant};
+
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// The number of random interleavings of the parallel part run for each scenario.
+const INTERLEAVINGS_PER_SCENARIO: usize = 32;
+
+/// The result of [analyze_spec](crate::Lincheck::analyze_spec).
+#[derive(Clone, Debug)]
+pub struct SpecAnalysis<Op> {
+    /// The number of analyzed scenarios.
+    pub scenarios: usize,
+    /// The statistics of each kind of operation in the order of discovery.
+    pub ops: Vec<OpAnalysis<Op>>,
+}
+
+/// The statistics of a kind of operation, i.e. a variant of the `Op` enum.
+#[derive(Clone, Debug)]
+pub struct OpAnalysis<Op> {
+    /// The first generated operation of this kind.
+    pub example: Op,
+    /// The number of times the operation occurred in the parallel and post parts.
+    pub occurrences: usize,
+    /// The number of those occurrences that returned different values in different interleavings.
+    pub discriminating: usize,
+}
+
+impl<Op> OpAnalysis<Op> {
+    /// Whether the operation occurred but never told one interleaving from another.
+    pub fn is_blind(&self) -> bool {
+        self.occurrences > 0 && self.discriminating == 0
+    }
+}
+
+impl<Op: Debug> SpecAnalysis<Op> {
+    /// The operations that never told one interleaving from another.
+    pub fn blind_ops(&self) -> impl Iterator<Item = &OpAnalysis<Op>> {
+        self.ops.iter().filter(|op| op.is_blind())
+    }
+
+    /// Whether no operation ever told one interleaving from another,
+    /// so every execution is trivially linearizable.
+    pub fn is_weak(&self) -> bool {
+        self.ops.iter().all(|op| op.discriminating == 0)
+    }
+
+    /// Human-readable suggestions on how to strengthen the specification.
+    pub fn suggestions(&self) -> Vec<String> {
+        let mut suggestions: Vec<_> = self
+            .blind_ops()
+            .map(|op| {
+                format!(
+                    "{:?} never returned different values in different interleavings. \
+                     Consider returning the state it observed or replaced.",
+                    op.example
+                )
+            })
+            .collect();
+        if self.is_weak() {
+            suggestions.push(
+                "No operation observes the order of the others, so the test verifies nothing. \
+                 Add an observer operation, e.g. one reading the state."
+                    .to_owned(),
+            );
+        }
+        suggestions
+    }
+}
+
+impl Lincheck {
+    /// Analyzes the discriminating power of the sequential specification `Seq`.
+    ///
+    /// See the [module-level documentation](crate::analysis) for the details.
+    pub fn analyze_spec<Seq>(&self) -> SpecAnalysis<Seq::Op>
+    where
+        Seq: SequentialSpec + Default,
+        Seq::Op: Clone + Arbitrary + 'static,
+        Seq::Ret: PartialEq,
+    {
+        let strategy = self.scenarios();
+        let mut runner = self.runner();
+        let cases = runner.config().cases as usize;
+
+        let mut analysis = SpecAnalysis {
+            scenarios: 0,
+            ops: Vec::new(),
+        };
+        let mut kinds: Vec<Discriminant<Seq::Op>> = Vec::new();
+
+        for _ in 0..cases {
+            let scenario = strategy
+                .new_tree(&mut runner)
+                .expect("failed to generate a scenario")
+                .current();
+            analysis.scenarios += 1;
+
+            let first = run_interleaving::<Seq>(&scenario, runner.rng());
+            let mut varied = vec![false; first.len()];
+            for _ in 1..INTERLEAVINGS_PER_SCENARIO {
+                let returns = run_interleaving::<Seq>(&scenario, runner.rng());
+                for ((varied, (_, ret)), (_, first_ret)) in
+                    varied.iter_mut().zip(returns).zip(first.iter())
+                {
+                    *varied |= ret != *first_ret;
+                }
+            }
+
+            for ((op, _), varied) in first.into_iter().zip(varied) {
+                let kind = mem::discriminant(op);
+                let index = match kinds.iter().position(|&k| k == kind) {
+                    Some(index) => index,
+                    None => {
+                        kinds.push(kind);
+                        analysis.ops.push(OpAnalysis {
+                            example: op.clone(),
+                            occurrences: 0,
+                            discriminating: 0,
+                        });
+                        kinds.len() - 1
+                    }
+                };
+                analysis.ops[index].occurrences += 1;
+                analysis.ops[index].discriminating += usize::from(varied);
+            }
+        }
+
+        analysis
+    }
+}
+
+/// Runs `Seq` on the init part, a random interleaving of the parallel part and the post part.
+///
+/// It returns the operations of the parallel and post parts with their return values
+/// in the order of the scenario, so the results of different interleavings can be compared position-wise.
+fn run_interleaving<'a, Seq>(
+    scenario: &'a Scenario<Seq::Op>,
+    rng: &mut TestRng,
+) -> Vec<(&'a Seq::Op, Seq::Ret)>
+where
+    Seq: SequentialSpec + Default,
+    Seq::Op: Clone,
+{
+    let mut seq = Seq::default();
+    for op in scenario.init_part.iter() {
+        seq.exec(op.clone());
+    }
+
+    // the interleaving is uniform if the next thread is chosen proportionally to its remaining operations
+    let mut next = vec![0; scenario.parallel_part.len()];
+    let mut remaining: usize = scenario.parallel_part.iter().map(Vec::len).sum();
+    let mut parallel_returns: Vec<Vec<_>> = scenario
+        .parallel_part
+        .iter()
+        .map(|ops| Vec::with_capacity(ops.len()))
+        .collect();
+    while remaining > 0 {
+        let mut choice = (rng.next_u64() % remaining as u64) as usize;
+        let thread_id = scenario
+            .parallel_part
+            .iter()
+            .zip(next.iter())
+            .position(|(ops, &next)| {
+                let left = ops.len() - next;
+                if choice < left {
+                    true
+                } else {
+                    choice -= left;
+                    false
+                }
+            })
+            .expect("some thread has operations left");
+
+        let op = &scenario.parallel_part[thread_id][next[thread_id]];
+        parallel_returns[thread_id].push((op, seq.exec(op.clone())));
+        next[thread_id] += 1;
+        remaining -= 1;
+    }
+
+    let post_returns: Vec<_> = scenario
+        .post_part
+        .iter()
+        .map(|op| (op, seq.exec(op.clone())))
+        .collect();
+
+    parallel_returns
+        .into_iter()
+        .flatten()
+        .chain(post_returns)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::specs::*;
+
+    #[test]
+    fn register_reads_discriminate() {
+        let analysis = Lincheck::default().analyze_spec::<Register<u8>>();
+
+        let blind: Vec<_> = analysis.blind_ops().map(|op| op.example).collect();
+        assert!(matches!(blind[..], [RegisterOp::Write(_)]));
+        assert!(!analysis.is_weak());
+        assert_eq!(analysis.suggestions().len(), 1);
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct WriteOnly(Register<u8>);
+
+    impl SequentialSpec for WriteOnly {
+        type Op = u8;
+        type Ret = RegisterRet<u8>;
+
+        fn exec(&mut self, op: u8) -> Self::Ret {
+            self.0.exec(RegisterOp::Write(op))
+        }
+    }
+
+    #[test]
+    fn write_only_spec_is_weak() {
+        let analysis = Lincheck::default().analyze_spec::<WriteOnly>();
+
+        assert_eq!(analysis.scenarios, 256);
+        assert!(analysis.is_weak());
+        assert_eq!(analysis.suggestions().len(), 2);
+    }
+}
@@ -0,0 +1,224 @@
+//! Pluggable backends executing the scenarios.
+//!
+//! An [ExecutionBackend] decides how the threads of the parallel part of a [Scenario] are scheduled,
+//! e.g. exhaustively inside [loom] with [LoomBackend] or by the OS scheduler with [ThreadsBackend].
+//! [verify_with_backend](Lincheck::verify_with_backend) generates and shrinks the scenarios and checks
+//! the executions the backend records against the [correctness](Lincheck::correctness) condition,
+//! so a custom scheduler or runtime only has to implement [find_failure](ExecutionBackend::find_failure).
+//!
+//! The data structure must be built from the primitives the backend schedules,
+//! e.g. the [loom] ones for [LoomBackend] and the standard ones for [ThreadsBackend].
+
+#[cfg(feature = "proptest")]
+use proptest::prelude::*;
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+
+#[cfg(feature = "proptest")]
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::recorder::{self, Recorder};
+use crate::scenario::*;
+use crate::spec::*;
+#[cfg(feature = "proptest")]
+use crate::Lincheck;
+use crate::LoomConfig;
+
+/// A way of executing scenarios, e.g. inside a model checker or on a runtime.
+pub trait ExecutionBackend {
+    /// Executes the scenario on fresh instances of `Conc`, once or under several interleavings,
+    /// and returns the first recorded execution `check` rejects.
+    fn find_failure<Conc>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+        check: impl Fn(&Execution<ConcOp<Conc>, ConcRet<Conc>>) -> bool + Send + Sync + 'static,
+    ) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Clone + Debug + Send;
+}
+
+/// Explores every interleaving of the scenario inside [loom] model-checker configured with `config`.
+///
+/// It is the backend of [verify](Lincheck::verify).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoomBackend {
+    /// The configuration of [loom].
+    pub config: LoomConfig,
+}
+
+impl ExecutionBackend for LoomBackend {
+    fn find_failure<Conc>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+        check: impl Fn(&Execution<ConcOp<Conc>, ConcRet<Conc>>) -> bool + Send + Sync + 'static,
+    ) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Clone + Debug + Send,
+    {
+        find_failure_with_loom(&self.config, move || {
+            let execution = execute_scenario_with_loom::<Conc>(scenario.clone());
+            (!check(&execution)).then_some(execution)
+        })
+    }
+}
+
+/// Executes the scenario [runs](ThreadsBackend::runs) times on native threads,
+/// each time in the interleaving chosen by the OS scheduler.
+///
+/// A passing scenario doesn't prove anything, but the data structure may use any primitives.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadsBackend {
+    /// How many times each scenario is executed.
+    pub runs: usize,
+}
+
+impl Default for ThreadsBackend {
+    fn default() -> Self {
+        Self { runs: 100 }
+    }
+}
+
+impl ExecutionBackend for ThreadsBackend {
+    fn find_failure<Conc>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+        check: impl Fn(&Execution<ConcOp<Conc>, ConcRet<Conc>>) -> bool + Send + Sync + 'static,
+    ) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Clone + Debug + Send,
+    {
+        for _ in 0..self.runs {
+            let execution = execute_scenario_with_threads::<Conc>(scenario.clone());
+            if !check(&execution) {
+                return Err(execution);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Executes the scenario once on the current thread, running an operation of each thread of the parallel part in turn.
+///
+/// The execution is deterministic, so it only catches the bugs that show up without real concurrency,
+/// e.g. in the sequential logic of the data structure, but quickly and reproducibly.
+/// The [maintenance](ConcurrentSpec::run_maintenance) passes run between the turns.
+/// An operation blocking on another thread of the parallel part never returns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RoundRobinBackend;
+
+impl ExecutionBackend for RoundRobinBackend {
+    fn find_failure<Conc>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+        check: impl Fn(&Execution<ConcOp<Conc>, ConcRet<Conc>>) -> bool + Send + Sync + 'static,
+    ) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Clone + Debug + Send,
+    {
+        let execution = execute_scenario_round_robin::<Conc>(scenario);
+        if check(&execution) {
+            Ok(())
+        } else {
+            Err(execution)
+        }
+    }
+}
+
+/// Executes the scenario on the current thread as described in [RoundRobinBackend].
+fn execute_scenario_round_robin<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Default,
+    ConcOp<Conc>: Clone,
+{
+    let mut conc = Conc::default();
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+
+    // init part
+    for op in scenario.init_part {
+        recorder.record(op.clone(), || conc.exec_mut(op));
+    }
+
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
+    let recorder = recorder.record_parallel_part_with_capacity(total_parallel_ops);
+
+    // parallel part
+    {
+        let mut threads: Vec<_> = scenario
+            .parallel_part
+            .into_iter()
+            .map(|thread_ops| {
+                let recorder = recorder.record_thread_with_capacity(thread_ops.len());
+                (recorder, thread_ops.into_iter())
+            })
+            .collect();
+
+        let mut maintenance_passes = Conc::MAINTENANCE_PASSES;
+        let mut executed = true;
+        while executed {
+            executed = false;
+            for (recorder, ops) in threads.iter_mut() {
+                if let Some(op) = ops.next() {
+                    recorder.record(op.clone(), || conc.exec(op));
+                    executed = true;
+                }
+            }
+            if maintenance_passes > 0 {
+                conc.run_maintenance();
+                maintenance_passes -= 1;
+            }
+        }
+        // the thread recorders submit their records when dropped
+    }
+
+    // post part
+    let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+    for op in scenario.post_part {
+        recorder.record(op.clone(), || conc.exec_mut(op));
+    }
+
+    recorder.finish()
+}
+
+#[cfg(feature = "proptest")]
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` satisfies the [correctness](Lincheck::correctness) condition
+    /// with respect to its sequential specification, executing the scenarios with `backend`.
+    ///
+    /// See the [module-level documentation](crate::backend) for the details.
+    /// It returns a [VerificationFailure] if the test fails, e.g. the last failing execution of the shrunk scenario
+    /// or a panic.
+    pub fn verify_with_backend<Conc>(
+        &self,
+        backend: &impl ExecutionBackend,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Clone + Debug + Send,
+    {
+        let (correctness, timeout) = (self.correctness, self.check_timeout);
+        let checker = self.checker.clone();
+        let check = move |execution: &Execution<_, _>| {
+            // a timed out check passes, as the scenario may not fail again anyway
+            correctness.check_within(execution, &Conc::Seq::default, checker.as_ref(), timeout)
+                != Some(false)
+        };
+
+        self.run_cases(&self.scenarios(), Scenario::clone, |scenario| {
+            backend
+                .find_failure::<Conc>(scenario, check.clone())
+                .map_err(VerificationFailure::NonLinearizable)
+        })
+    }
+}
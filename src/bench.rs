@@ -0,0 +1,678 @@
+//! Throughput benchmarking of concurrent data structures.
+//!
+//! The benchmark reuses the scenario generator and the [ConcurrentSpec] implementation to measure
+//! how many operations per second the data structure performs under generated mixed workloads.
+//! Unlike [Lincheck](crate::Lincheck), it runs scenarios on real OS threads and does not check anything.
+//!
+//! Optionally, the benchmark records per-operation latencies into [LatencyHistogram]s.
+//! Operations are grouped by their enum variant.
+//!
+//! With the `serde` feature, the reports are serializable, e.g. to compare the runs in CI.
+//!
+//! [check_benchmark] measures the throughput of a checker, e.g. [LinearizabilityChecker](crate::checker::LinearizabilityChecker),
+//! on a corpus of executions instead.
+//!
+//! Note that [loom] primitives can't be used outside of the [loom] model-checker.
+//! So the data structure must be built with [std] primitives when benchmarked,
+//! e.g. by switching between them with `#[cfg(loom)]`.
+
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+use std::fmt::Debug;
+use std::mem::{self, Discriminant};
+use std::sync::Barrier;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::execution::*;
+use crate::scenario::*;
+use crate::spec::*;
+
+/// A throughput benchmark configuration.
+#[derive(Clone, Debug)]
+pub struct Benchmark {
+    /// The maximum number of threads. The benchmark is run for every number of threads from 1 up to this one.
+    pub num_threads: usize,
+    /// The maximum number of operations to run per thread in a scenario.
+    pub num_ops: usize,
+    /// The number of generated scenarios to run per number of threads.
+    pub num_scenarios: usize,
+    /// Whether to measure the latency of every operation.
+    /// It introduces a small overhead which affects the measured throughput.
+    pub record_latencies: bool,
+}
+
+impl Default for Benchmark {
+    fn default() -> Self {
+        Self {
+            num_threads: 4,
+            num_ops: 1000,
+            num_scenarios: 10,
+            record_latencies: false,
+        }
+    }
+}
+
+/// The result of a benchmark.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BenchmarkReport {
+    /// The throughput measured for each number of threads in increasing order.
+    pub throughput: Vec<Throughput>,
+}
+
+/// The throughput measured for a fixed number of threads.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Throughput {
+    /// The number of threads that executed the parallel parts.
+    pub num_threads: usize,
+    /// The total number of operations executed in the parallel parts.
+    pub total_ops: usize,
+    /// The total time spent executing the parallel parts.
+    pub elapsed: Duration,
+    /// The latency distributions per operation kind.
+    /// It is empty unless [record_latencies](Benchmark::record_latencies) is set.
+    pub latencies: Vec<OpLatencies>,
+}
+
+/// The latency distribution of a single operation kind.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OpLatencies {
+    /// The name of the operation kind, i.e. its enum variant.
+    pub op: String,
+    /// The latencies of the operations of this kind.
+    pub histogram: LatencyHistogram,
+}
+
+impl Throughput {
+    /// The number of operations per second, or zero if the parallel parts took no measurable time.
+    pub fn ops_per_sec(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+        self.total_ops as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl Benchmark {
+    /// Measures the throughput of the concurrent implementation `Conc` on generated scenarios.
+    ///
+    /// Only the parallel part of each scenario is timed.
+    /// The init part is run before and the post part is not run at all.
+    pub fn run<Conc>(&self) -> BenchmarkReport
+    where
+        Conc: ConcurrentSpec + Default + Sync,
+        ConcOp<Conc>: Arbitrary + Send + Debug + 'static,
+    {
+        let mut runner = TestRunner::default();
+
+        let throughput = (1..=self.num_threads)
+            .map(|num_threads| {
+                let strategy = crate::scenario_strategy::<ConcOp<Conc>>(
+                    num_threads..=num_threads,
+                    self.num_ops,
+                    &crate::ThreadDistribution::Uniform,
+                    None,
+                    crate::scenario::ScenarioConfig::default(),
+                );
+
+                let mut total_ops = 0;
+                let mut elapsed = Duration::ZERO;
+                let mut latencies = LatencyRecorder::default();
+                for _ in 0..self.num_scenarios {
+                    let scenario = strategy
+                        .new_tree(&mut runner)
+                        .expect("failed to generate a scenario")
+                        .current();
+                    total_ops += scenario.parallel_part.iter().map(Vec::len).sum::<usize>();
+                    elapsed += run_scenario_timed::<Conc>(
+                        scenario,
+                        self.record_latencies.then_some(&mut latencies),
+                    );
+                }
+
+                Throughput {
+                    num_threads,
+                    total_ops,
+                    elapsed,
+                    latencies: latencies.finish(),
+                }
+            })
+            .collect();
+
+        BenchmarkReport { throughput }
+    }
+}
+
+/// Runs the scenario on OS threads and returns the time it took to execute the parallel part.
+/// If `latencies` is provided, the latency of every operation in the parallel part is recorded into it.
+fn run_scenario_timed<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    latencies: Option<&mut LatencyRecorder<ConcOp<Conc>>>,
+) -> Duration
+where
+    Conc: ConcurrentSpec + Default + Sync,
+    ConcOp<Conc>: Send + Debug,
+{
+    let record_latencies = latencies.is_some();
+    // the names are rendered before the timer starts
+    let names = match &latencies {
+        Some(latencies) => latencies.unnamed_kinds(scenario.parallel_part.iter().flatten()),
+        None => Vec::new(),
+    };
+
+    let conc = Conc::default();
+
+    for op in scenario.init_part {
+        conc.exec(op);
+    }
+
+    // the main thread also waits on the barrier to start the timer when all the threads are ready
+    let barrier = Barrier::new(scenario.parallel_part.len() + 1);
+
+    let (start, thread_latencies) = thread::scope(|s| {
+        let handles: Vec<_> = scenario
+            .parallel_part
+            .into_iter()
+            .map(|thread_ops| {
+                let conc = &conc;
+                let barrier = &barrier;
+                s.spawn(move || {
+                    let mut latencies = LatencyRecorder::default();
+                    barrier.wait();
+                    for op in thread_ops {
+                        if record_latencies {
+                            let kind = mem::discriminant(&op);
+                            let start = Instant::now();
+                            conc.exec(op);
+                            latencies.record(kind, start.elapsed());
+                        } else {
+                            conc.exec(op);
+                        }
+                    }
+                    latencies
+                })
+            })
+            .collect();
+
+        barrier.wait();
+        let start = Instant::now();
+
+        let thread_latencies: Vec<_> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+        (start, thread_latencies)
+    });
+    let elapsed = start.elapsed();
+
+    if let Some(latencies) = latencies {
+        latencies.names.extend(names);
+        for thread_latencies in thread_latencies {
+            latencies.merge(thread_latencies);
+        }
+    }
+
+    elapsed
+}
+
+/// The name of the enum variant of the operation, e.g. `Push` for `Push(1)`.
+fn op_kind_name<Op: Debug>(op: &Op) -> String {
+    let debug = format!("{:?}", op);
+    let end = debug
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(debug.len());
+    debug[..end].to_string()
+}
+
+/// Accumulates latency histograms per operation kind.
+///
+/// The threads only record the enum discriminants, the names of the kinds are rendered outside the timed region.
+pub(crate) struct LatencyRecorder<Op> {
+    per_kind: Vec<(Discriminant<Op>, LatencyHistogram)>,
+    names: Vec<(Discriminant<Op>, String)>,
+}
+
+impl<Op> Default for LatencyRecorder<Op> {
+    fn default() -> Self {
+        Self {
+            per_kind: Vec::new(),
+            names: Vec::new(),
+        }
+    }
+}
+
+impl<Op> LatencyRecorder<Op> {
+    fn histogram(&mut self, kind: Discriminant<Op>) -> &mut LatencyHistogram {
+        let pos = match self.per_kind.iter().position(|(k, _)| *k == kind) {
+            Some(pos) => pos,
+            None => {
+                self.per_kind.push((kind, LatencyHistogram::new()));
+                self.per_kind.len() - 1
+            }
+        };
+        &mut self.per_kind[pos].1
+    }
+
+    pub(crate) fn record(&mut self, kind: Discriminant<Op>, latency: Duration) {
+        self.histogram(kind).record(latency);
+    }
+
+    pub(crate) fn merge(&mut self, other: LatencyRecorder<Op>) {
+        for (kind, histogram) in other.per_kind {
+            self.histogram(kind).merge(&histogram);
+        }
+    }
+
+    /// Names the kinds of `ops` that aren't named yet.
+    pub(crate) fn name_kinds<'a>(&mut self, ops: impl IntoIterator<Item = &'a Op>)
+    where
+        Op: Debug + 'a,
+    {
+        let names = self.unnamed_kinds(ops);
+        self.names.extend(names);
+    }
+
+    /// The names of the kinds of `ops` that aren't named yet.
+    fn unnamed_kinds<'a>(
+        &self,
+        ops: impl IntoIterator<Item = &'a Op>,
+    ) -> Vec<(Discriminant<Op>, String)>
+    where
+        Op: Debug + 'a,
+    {
+        let mut names: Vec<(Discriminant<Op>, String)> = Vec::new();
+        for op in ops {
+            let kind = mem::discriminant(op);
+            let named =
+                |names: &[(Discriminant<Op>, String)]| names.iter().any(|(k, _)| *k == kind);
+            if !named(&self.names) && !named(&names) {
+                names.push((kind, op_kind_name(op)));
+            }
+        }
+        names
+    }
+
+    pub(crate) fn finish(self) -> Vec<OpLatencies> {
+        let names = self.names;
+        let mut latencies: Vec<_> = (self.per_kind.into_iter())
+            .map(|(kind, histogram)| OpLatencies {
+                op: (names.iter())
+                    .find(|(k, _)| *k == kind)
+                    .map(|(_, name)| name.clone())
+                    .expect("every recorded kind is named"),
+                histogram,
+            })
+            .collect();
+        latencies.sort_by(|a, b| a.op.cmp(&b.op));
+        latencies
+    }
+}
+
+/// The time a checker took on a single execution.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CheckTiming {
+    /// The number of threads of the parallel part.
+    pub num_threads: usize,
+    /// The number of invocations in the parallel part.
+    pub parallel_ops: usize,
+    /// The verdict of the checker.
+    pub verdict: bool,
+    /// The fastest of the repeated checks.
+    pub min: Duration,
+    /// The mean of the repeated checks.
+    pub mean: Duration,
+}
+
+/// The result of [check_benchmark].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CheckBenchmarkReport {
+    /// The timings of every execution in the order of the corpus.
+    pub histories: Vec<CheckTiming>,
+    /// The total time spent checking, including the repetitions.
+    pub elapsed: Duration,
+}
+
+impl CheckBenchmarkReport {
+    /// The number of executions checked per second, based on the mean timings,
+    /// or zero if the checks took no measurable time.
+    pub fn histories_per_sec(&self) -> f64 {
+        let total: Duration = self.histories.iter().map(|timing| timing.mean).sum();
+        if total.is_zero() {
+            return 0.0;
+        }
+        self.histories.len() as f64 / total.as_secs_f64()
+    }
+}
+
+/// Times `check` on every execution of the corpus, repeating each check `repetitions` times.
+///
+/// ```
+/// # use lincheck::{bench::check_benchmark, checker::LinearizabilityChecker, Execution, SequentialSpec};
+/// fn report<Seq>(corpus: &[Execution<Seq::Op, Seq::Ret>])
+/// where
+///     Seq: SequentialSpec + Default,
+///     Seq::Op: Clone,
+///     Seq::Ret: PartialEq,
+/// {
+///     let report = check_benchmark(corpus, 10, |execution| {
+///         LinearizabilityChecker::<Seq>::check(execution)
+///     });
+///     println!("{:.1} histories/s", report.histories_per_sec());
+/// }
+/// ```
+pub fn check_benchmark<Op, Ret>(
+    executions: &[Execution<Op, Ret>],
+    repetitions: usize,
+    check: impl Fn(&Execution<Op, Ret>) -> bool,
+) -> CheckBenchmarkReport {
+    let repetitions = repetitions.max(1);
+    let mut elapsed = Duration::ZERO;
+
+    let histories = executions
+        .iter()
+        .map(|execution| {
+            let mut verdict = false;
+            let mut min = Duration::MAX;
+            let mut total = Duration::ZERO;
+            for _ in 0..repetitions {
+                let start = Instant::now();
+                verdict = check(execution);
+                let check_elapsed = start.elapsed();
+                min = min.min(check_elapsed);
+                total += check_elapsed;
+            }
+            elapsed += total;
+
+            CheckTiming {
+                num_threads: execution.parallel_part.num_threads(),
+                parallel_ops: execution.parallel_part.len(),
+                verdict,
+                min,
+                mean: total / repetitions as u32,
+            }
+        })
+        .collect();
+
+    CheckBenchmarkReport { histories, elapsed }
+}
+
+const SUB_BUCKET_BITS: u32 = 4;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+
+/// A latency histogram in the spirit of HDR histograms.
+///
+/// Values are stored in nanoseconds in logarithmic buckets, each split into 16 linear sub-buckets.
+/// So the values reported by [percentile](LatencyHistogram::percentile) are within 6.25% of the real ones.
+///
+/// With the `serde` feature, it is serialized as a summary: the count, the minimum, the mean,
+/// the 50th, 90th and 99th percentiles and the maximum.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    counts: Vec<u64>,
+    count: u64,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; (64 - SUB_BUCKET_BITS as usize + 1) * SUB_BUCKETS],
+            count: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    fn bucket_index(value: u64) -> usize {
+        if value < SUB_BUCKETS as u64 {
+            return value as usize;
+        }
+        let exponent = 63 - value.leading_zeros();
+        let sub_bucket = (value >> (exponent - SUB_BUCKET_BITS)) as usize & (SUB_BUCKETS - 1);
+        (exponent - SUB_BUCKET_BITS + 1) as usize * SUB_BUCKETS + sub_bucket
+    }
+
+    fn bucket_lower_bound(index: usize) -> u64 {
+        if index < SUB_BUCKETS {
+            return index as u64;
+        }
+        let exponent = (index / SUB_BUCKETS) as u32 + SUB_BUCKET_BITS - 1;
+        let sub_bucket = (index % SUB_BUCKETS) as u64;
+        (1 << exponent) | (sub_bucket << (exponent - SUB_BUCKET_BITS))
+    }
+
+    /// Records a single latency.
+    pub fn record(&mut self, latency: Duration) {
+        let value = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        self.counts[Self::bucket_index(value)] += 1;
+        self.count += 1;
+        self.sum += value as u128;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Adds all the latencies recorded in `other` to this histogram.
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (count, other_count) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other_count;
+        }
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// The number of recorded latencies.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The minimal recorded latency.
+    pub fn min(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_nanos(self.min))
+    }
+
+    /// The maximal recorded latency.
+    pub fn max(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_nanos(self.max))
+    }
+
+    /// The mean of the recorded latencies.
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_nanos((self.sum / self.count as u128) as u64))
+    }
+
+    /// The latency below which `percentile` percent of the recorded latencies fall, e.g. `percentile(99.0)`.
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((percentile / 100.0 * self.count as f64).ceil() as u64).clamp(1, self.count);
+
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let value = Self::bucket_lower_bound(index).clamp(self.min, self.max);
+                return Some(Duration::from_nanos(value));
+            }
+        }
+        unreachable!("the rank never exceeds the number of recorded latencies")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for LatencyHistogram {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut summary = serializer.serialize_struct("LatencyHistogram", 7)?;
+        summary.serialize_field("count", &self.count)?;
+        summary.serialize_field("min", &self.min())?;
+        summary.serialize_field("mean", &self.mean())?;
+        summary.serialize_field("p50", &self.percentile(50.0))?;
+        summary.serialize_field("p90", &self.percentile(90.0))?;
+        summary.serialize_field("p99", &self.percentile(99.0))?;
+        summary.serialize_field("max", &self.max())?;
+        summary.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::LinearizabilityChecker;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Op {
+        Increment,
+    }
+
+    impl Arbitrary for Op {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            Just(Op::Increment).boxed()
+        }
+    }
+
+    #[derive(Default)]
+    struct SequentialCounter {
+        x: usize,
+    }
+
+    impl SequentialSpec for SequentialCounter {
+        type Op = Op;
+        type Ret = usize;
+
+        fn exec(&mut self, _: Op) -> usize {
+            self.x += 1;
+            self.x - 1
+        }
+    }
+
+    #[derive(Default)]
+    struct AtomicCounter {
+        x: AtomicUsize,
+    }
+
+    impl ConcurrentSpec for AtomicCounter {
+        type Seq = SequentialCounter;
+
+        fn exec(&self, _: Op) -> usize {
+            self.x.fetch_add(1, Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn measures_every_thread_count() {
+        let report = Benchmark {
+            num_threads: 3,
+            num_ops: 10,
+            num_scenarios: 2,
+            record_latencies: true,
+        }
+        .run::<AtomicCounter>();
+
+        let thread_counts: Vec<_> = report.throughput.iter().map(|t| t.num_threads).collect();
+        assert_eq!(thread_counts, vec![1, 2, 3]);
+        assert!(report
+            .throughput
+            .iter()
+            .all(|t| t.total_ops <= t.num_threads * 10 * 2));
+        for throughput in &report.throughput {
+            let recorded: u64 = throughput
+                .latencies
+                .iter()
+                .map(|l| l.histogram.count())
+                .sum();
+            assert_eq!(recorded, throughput.total_ops as u64);
+            assert!(throughput.latencies.iter().all(|l| l.op == "Increment"));
+        }
+    }
+
+    #[test]
+    fn zero_elapsed_has_zero_throughput() {
+        let throughput = Throughput {
+            num_threads: 1,
+            total_ops: 10,
+            elapsed: Duration::ZERO,
+            latencies: Vec::new(),
+        };
+        assert_eq!(throughput.ops_per_sec(), 0.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn report_is_serializable() {
+        // a single scenario may leave the parallel part empty, timing no operation
+        let report = Benchmark {
+            num_threads: 1,
+            num_ops: 10,
+            num_scenarios: 10,
+            record_latencies: true,
+        }
+        .run::<AtomicCounter>();
+        let serialized = ron::to_string(&report).unwrap();
+        assert!(serialized.contains("op:\"Increment\""));
+        assert!(serialized.contains("p99:Some("));
+    }
+
+    #[test]
+    fn histogram_percentiles_are_approximate() {
+        let mut histogram = LatencyHistogram::new();
+        for nanos in 1..=1000 {
+            histogram.record(Duration::from_nanos(nanos));
+        }
+
+        assert_eq!(histogram.count(), 1000);
+        assert_eq!(histogram.min(), Some(Duration::from_nanos(1)));
+        assert_eq!(histogram.max(), Some(Duration::from_nanos(1000)));
+        for (percentile, expected) in [(50.0, 500.0), (90.0, 900.0), (99.0, 990.0)] {
+            let actual = histogram.percentile(percentile).unwrap().as_nanos() as f64;
+            assert!((actual - expected).abs() / expected <= 0.0625);
+        }
+    }
+
+    #[test]
+    fn times_every_history() {
+        let scenario = Scenario {
+            init_part: vec![Op::Increment],
+            parallel_part: vec![vec![Op::Increment; 3]; 2],
+            post_part: vec![],
+        };
+        let executions: Vec<_> = (0..3)
+            .map(|_| execute_scenario_with_threads::<AtomicCounter>(scenario.clone()))
+            .collect();
+
+        let report = check_benchmark(&executions, 2, |execution| {
+            LinearizabilityChecker::<SequentialCounter>::check(execution)
+        });
+
+        assert_eq!(report.histories.len(), 3);
+        for timing in &report.histories {
+            assert_eq!(timing.num_threads, 2);
+            assert_eq!(timing.parallel_ops, 6);
+            assert!(timing.verdict);
+            assert!(timing.min <= timing.mean);
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Panic injection (chaos mode).
+//!
+//! The data structure marks the points where a panic may occur with [chaos_point],
+//! e.g. between the steps of a multi-step update or around user-provided callbacks.
+//! [verify_chaos](crate::Lincheck::verify_chaos) picks one thread of the parallel part and one of its chaos points
+//! and makes it panic there in every interleaving explored by [loom]. The panicking thread stops executing its operations.
+//!
+//! The panicked operation may or may not have taken effect, so the checker tries both.
+//! The remaining operations, including the post part, must still be linearizable,
+//! and [validate](crate::ConcurrentSpec::validate) must succeed after the parallel part.
+//!
+//! Any other panic fails the test. In particular, [loom] mutexes can't be locked after a panic
+//! poisoned them, so they must not be held across chaos points.
+//!
+//! Outside of chaos mode [chaos_point] does nothing.
+//! The yield points of [loom] itself can't be hooked, so only explicit chaos points are considered.
+
+use proptest::prelude::*;
+use proptest::sample::Index;
+use proptest::test_runner::TestError;
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe, UnwindSafe};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use loom::thread;
+
+use crate::checker::*;
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::recorder::{self, *};
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{Lincheck, LoomConfig};
+
+/// The number of the first chaos points of the chosen thread that may panic.
+const MAX_CHAOS_POINTS: usize = 4;
+
+/// The number of the checks running in chaos mode. Guards the access to the [loom] thread locals
+/// so that [chaos_point] can be called outside of [loom] too.
+static CHAOS_MODE: AtomicUsize = AtomicUsize::new(0);
+
+loom::thread_local! {
+    /// The number of chaos points left before the panic in the current thread.
+    static COUNTDOWN: Cell<Option<usize>> = Cell::new(None);
+}
+
+/// The payload of the injected panics.
+struct InjectedPanic;
+
+/// Marks a point where chaos mode may inject a panic.
+pub fn chaos_point() {
+    if CHAOS_MODE.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+
+    let fire = COUNTDOWN.with(|countdown| match countdown.get() {
+        Some(0) => {
+            countdown.set(None);
+            true
+        }
+        Some(left) => {
+            countdown.set(Some(left - 1));
+            false
+        }
+        None => false,
+    });
+    if fire {
+        panic::panic_any(InjectedPanic);
+    }
+}
+
+/// Where to inject the panic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChaosTarget {
+    /// The thread of the parallel part.
+    pub thread: usize,
+    /// The number of chaos points the thread passes before panicking.
+    pub point: usize,
+}
+
+/// The result of an operation in chaos mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChaosRet<Ret> {
+    /// The operation returned.
+    Ret(Ret),
+    /// The operation panicked at the injected panic.
+    Panicked,
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` stays linearizable after an operation panics midway.
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution
+    /// or the invalid state after the parallel part.
+    ///
+    /// See the [module-level documentation](crate::chaos) for the details.
+    pub fn verify_chaos<Conc>(
+        &self,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ChaosRet<ConcRet<Conc>>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        let strategy = (self.scenarios(), any::<Index>(), 0..MAX_CHAOS_POINTS).prop_map(
+            |(scenario, thread, point)| {
+                let thread = thread.index(scenario.parallel_part.len());
+                (scenario, ChaosTarget { thread, point })
+            },
+        );
+
+        let result = self.runner().run(&strategy, |(scenario, target)| {
+            check_chaos_scenario_with_loom_using::<Conc>(&self.loom, scenario, target)
+                .map_err(|_| TestCaseError::Fail("Chaos mode failure".into()))
+        });
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(TestError::Fail(_, (scenario, target))) => {
+                // rerun the scenario to get the failing execution
+                Err(
+                    check_chaos_scenario_with_loom_using::<Conc>(&self.loom, scenario, target)
+                        .unwrap_err(),
+                )
+            }
+            Err(TestError::Abort(reason)) => {
+                Err(VerificationFailure::GeneratorError(reason.to_string()))
+            }
+        }
+    }
+}
+
+/// Executes the given scenario injecting a panic at `target` and checks the resulting execution inside [loom] model-checker.
+pub fn check_chaos_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    target: ChaosTarget,
+) -> Result<(), VerificationFailure<ConcOp<Conc>, ChaosRet<ConcRet<Conc>>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    check_chaos_scenario_with_loom_using::<Conc>(&LoomConfig::default(), scenario, target)
+}
+
+/// Same as [check_chaos_scenario_with_loom] but configures loom with `loom`.
+pub(crate) fn check_chaos_scenario_with_loom_using<Conc>(
+    loom: &LoomConfig,
+    scenario: Scenario<ConcOp<Conc>>,
+    target: ChaosTarget,
+) -> Result<(), VerificationFailure<ConcOp<Conc>, ChaosRet<ConcRet<Conc>>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    CHAOS_MODE.fetch_add(1, Ordering::Relaxed);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        find_failure_with_loom(loom, move || {
+            let (execution, validated) =
+                execute_chaos_scenario_with_loom::<Conc>(scenario.clone(), target);
+            if !check_chaos_execution::<Conc::Seq>(&execution) {
+                Some(VerificationFailure::NonLinearizable(execution))
+            } else if let Err(message) = validated {
+                Some(VerificationFailure::InvalidState {
+                    scenario: scenario.clone(),
+                    execution,
+                    after: ScenarioPart::Parallel,
+                    message,
+                })
+            } else {
+                None
+            }
+        })
+    }));
+    CHAOS_MODE.fetch_sub(1, Ordering::Relaxed);
+    result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
+
+/// Executes the given scenario with [loom] mock threads injecting a panic at `target`.
+///
+/// Returns the resulting execution and the result of [validate](crate::ConcurrentSpec::validate)
+/// after the parallel part.
+#[allow(clippy::type_complexity)]
+pub fn execute_chaos_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    target: ChaosTarget,
+) -> (
+    Execution<ConcOp<Conc>, ChaosRet<ConcRet<Conc>>>,
+    Result<(), String>,
+)
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + 'static,
+{
+    let conc = Rc::new(Conc::default());
+
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+
+    // init part
+    for op in scenario.init_part {
+        recorder.record(op.clone(), || ChaosRet::Ret(conc.exec(op)));
+    }
+
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
+    let recorder = Rc::new(recorder.record_parallel_part_with_capacity(total_parallel_ops));
+
+    // parallel part
+    let handles: Vec<_> = scenario
+        .parallel_part
+        .into_iter()
+        .enumerate()
+        .map(|(thread_id, thread_ops)| {
+            let conc = conc.clone();
+            let recorder = recorder.clone();
+
+            thread::spawn(move || {
+                if thread_id == target.thread {
+                    COUNTDOWN.with(|countdown| countdown.set(Some(target.point)));
+                }
+
+                let mut recorder = recorder.record_thread_with_capacity(thread_ops.len());
+                for op in thread_ops {
+                    let mut panicked = false;
+                    recorder.record(op.clone(), || {
+                        match panic::catch_unwind(AssertUnwindSafe(|| conc.exec(op))) {
+                            Ok(ret) => ChaosRet::Ret(ret),
+                            Err(payload) if payload.is::<InjectedPanic>() => {
+                                panicked = true;
+                                ChaosRet::Panicked
+                            }
+                            Err(payload) => panic::resume_unwind(payload),
+                        }
+                    });
+                    // the panicking thread stops
+                    if panicked {
+                        break;
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let validated = conc.validate();
+
+    // post part
+    let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+    for op in scenario.post_part {
+        recorder.record(op.clone(), || ChaosRet::Ret(conc.exec(op)));
+    }
+
+    (recorder.finish(), validated)
+}
+
+/// The sequential specification which doesn't check the results of the panicked operations.
+#[derive(Default)]
+struct IgnoringPanicked<Seq>(Seq);
+
+impl<Seq> SequentialSpec for IgnoringPanicked<Seq>
+where
+    Seq: SequentialSpec,
+    Seq::Ret: PartialEq,
+{
+    type Op = (Seq::Op, bool);
+    type Ret = Option<Seq::Ret>;
+
+    fn exec(&mut self, (op, panicked): Self::Op) -> Self::Ret {
+        let ret = self.0.exec(op);
+        (!panicked).then_some(ret)
+    }
+
+    fn ret_matches(expected: &Self::Ret, actual: &Self::Ret) -> bool {
+        match (expected, actual) {
+            (Some(expected), Some(actual)) => Seq::ret_matches(expected, actual),
+            (expected, actual) => expected.is_none() && actual.is_none(),
+        }
+    }
+}
+
+/// Checks that the execution is linearizable whether the panicked operation took effect or not.
+fn check_chaos_execution<Seq>(execution: &Execution<Seq::Op, ChaosRet<Seq::Ret>>) -> bool
+where
+    Seq: SequentialSpec + Default,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq + Clone,
+{
+    let adapt = |op: &Seq::Op, ret: &ChaosRet<Seq::Ret>| match ret {
+        ChaosRet::Ret(ret) => ((op.clone(), false), Some(ret.clone())),
+        ChaosRet::Panicked => ((op.clone(), true), None),
+    };
+    let adapt_history = |history: &History<Seq::Op, ChaosRet<Seq::Ret>>| -> History<_, _> {
+        history
+            .iter()
+            .map(|inv| {
+                let (op, ret) = adapt(&inv.op, &inv.ret);
+                Invocation { op, ret }
+            })
+            .collect::<Vec<_>>()
+            .into()
+    };
+    let adapt_parallel_history = |skip_panicked: bool| -> ParallelHistory<_, _> {
+        execution
+            .parallel_part
+            .iter()
+            .filter(|inv| !(skip_panicked && matches!(inv.ret, ChaosRet::Panicked)))
+            .map(|inv| {
+                let (op, ret) = adapt(&inv.op, &inv.ret);
+                ParallelInvocation {
+                    thread_id: inv.thread_id,
+                    call_timestamp: inv.call_timestamp,
+                    return_timestamp: inv.return_timestamp,
+                    op,
+                    ret,
+                }
+            })
+            .collect::<Vec<_>>()
+            .into()
+    };
+
+    [false, true].into_iter().any(|skip_panicked| {
+        let adapted = Execution {
+            init_part: adapt_history(&execution.init_part),
+            parallel_part: adapt_parallel_history(skip_panicked),
+            post_part: adapt_history(&execution.post_part),
+            pending: Vec::new(),
+        };
+        LinearizabilityChecker::<IgnoringPanicked<Seq>>::check(&adapted)
+    })
+}
@@ -1,6 +1,13 @@
 //! The module with the linearizability checker implementation.
 
-use std::collections::HashSet;
+use std::any::Any;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::{self, Debug, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::execution::*;
 use crate::spec::*;
@@ -13,13 +20,27 @@ use crate::spec::*;
 ///
 /// The checker rebuilds the sequential specification for each considered linearization. But it does it
 /// lazily: it only rebuilds the sequential specification when it needs to backtrack and check the other branch.
+///
+/// Pending invocations follow the parallel part in the graph. They happen-before nothing,
+/// may return anything and may be left out of the linearization, as in the Herlihy–Wing definition.
+/// A cancelled invocation may be left out too, but if it takes effect, it does so before it is cancelled.
+///
+/// Most linearizable executions linearize in a trivial order, e.g. by the call timestamps,
+/// so the checker tries a handful of such orders before the search. See [fast_path_stats] for how often it helps.
 pub struct LinearizabilityChecker<'e, Seq: SequentialSpec> {
     execution: &'e Execution<Seq::Op, Seq::Ret>,
     hb: Vec<Vec<InvocationId>>, // for each invocation in the parallel part, a list of invocations which it happens-before
     in_degree: Vec<usize>, // for each invocation in the parallel part, number of invocations that happen-before
     minimal_invocations: HashSet<InvocationId>, // invocations in the parallel part that have in_degree == 0
     linearized: Vec<InvocationId>,              // current linearization of the parallel part
+    remaining_completed: usize, // number of completed invocations that are not linearized yet
     seq_spec: Seq,
+    new_seq: &'e dyn Fn() -> Seq, // creates a fresh sequential specification
+    deadline: Option<Instant>,    // when the search gives up
+    nodes_left: Option<usize>,    // how many more nodes the search may visit
+    gave_up: bool,                // whether the search gave up
+    #[cfg(feature = "tracing")]
+    backtracks: usize, // how many times the search backtracked
 }
 
 impl<'e, Seq> LinearizabilityChecker<'e, Seq>
@@ -29,19 +50,105 @@ where
     Seq::Ret: PartialEq,
 {
     /// Checks if the given execution is linearizable with respect to the given sequential specification `Seq`.
-    pub fn check(execution: &'e Execution<Seq::Op, Seq::Ret>) -> bool {
-        let parallel_part = &execution.parallel_part;
-        let mut hb_parallel = vec![vec![]; parallel_part.len()];
+    pub fn check(execution: &'e Execution<Seq::Op, Seq::Ret>) -> bool
+    where
+        Seq: Default,
+    {
+        Self::check_with(execution, &Seq::default)
+    }
 
-        for (inv_id_a, inv_a) in parallel_part.iter().enumerate() {
-            for (inv_id_b, inv_b) in parallel_part.iter().enumerate() {
-                if inv_a.return_timestamp < inv_b.call_timestamp {
-                    hb_parallel[inv_id_a].push(inv_id_b);
-                }
-            }
+    /// Same as [check](LinearizabilityChecker::check) but creates the sequential specification with `new_seq`.
+    pub fn check_with(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+    ) -> bool {
+        let mut checker = Self::new(execution, new_seq);
+        let linearizable = checker.linearizes_trivially() || checker.check_init_part();
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            linearizable,
+            backtracks = checker.backtracks,
+            "checked the linearizability"
+        );
+        linearizable
+    }
+
+    /// Same as [check_with](LinearizabilityChecker::check_with) but gives up once `timeout` elapses,
+    /// e.g. on an adversarial history taking exponential time. Returns [None] if it did.
+    pub fn check_with_timeout(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+        timeout: Duration,
+    ) -> Option<bool> {
+        let checker = Self::new(execution, new_seq);
+        if checker.linearizes_trivially() {
+            return Some(true);
         }
+        checker.check_within(timeout)
+    }
+
+    /// Same as [check](LinearizabilityChecker::check) but returns the found linearization of the parallel part,
+    /// or [None] if the execution is not linearizable.
+    ///
+    /// The linearization lists the invocations of the [parallel part](Execution::parallel_part) by their indices,
+    /// followed by the [pending](Execution::pending) ones that took effect, whose indices continue after the completed ones.
+    /// The init and the post parts are left out, as they are linearized in their order.
+    pub fn check_with_witness(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+    ) -> Option<Vec<InvocationId>>
+    where
+        Seq: Default,
+    {
+        let mut checker = Self::new(execution, &Seq::default);
+        if let Some(linearization) = checker.trivial_linearization() {
+            return Some(linearization);
+        }
+        checker.check_init_part().then_some(checker.linearized)
+    }
+
+    /// Counts the distinct linearizations of the given execution, stopping at `max`.
+    ///
+    /// Linearizations are distinct if they order the parallel part differently.
+    /// A scenario whose executions have few linearizations despite heavy concurrency is a discriminating one.
+    pub fn count_linearizations(execution: &'e Execution<Seq::Op, Seq::Ret>, max: usize) -> usize
+    where
+        Seq: Default,
+    {
+        let mut checker = Self::new(execution, &Seq::default);
+        let init_part_matches = execution.init_part.iter().all(|inv| {
+            let ret = checker.seq_spec.exec(inv.op.clone());
+            Seq::ret_matches(&ret, &inv.ret)
+        });
+        if !init_part_matches || max == 0 {
+            return 0;
+        }
+        checker.count_parallel_part(max)
+    }
+
+    fn new(execution: &'e Execution<Seq::Op, Seq::Ret>, new_seq: &'e dyn Fn() -> Seq) -> Self {
+        let intervals = execution.intervals();
+        Self::with_order(execution, new_seq, |a, b| {
+            intervals[a].return_timestamp < intervals[b].call_timestamp
+        })
+    }
 
-        let mut in_degree = vec![0; parallel_part.len()];
+    /// Creates a checker which must preserve the order of the invocations `a` and `b` of the parallel part
+    /// if `precedes(a, b)` holds. The ids of the pending invocations follow the ones of the parallel part.
+    fn with_order(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+        precedes: impl Fn(InvocationId, InvocationId) -> bool,
+    ) -> Self {
+        let num_invocations = execution.parallel_part.len() + execution.pending.len();
+        let hb_parallel: Vec<Vec<_>> = (0..num_invocations)
+            .map(|inv_id_a| {
+                (0..num_invocations)
+                    .filter(|&inv_id_b| precedes(inv_id_a, inv_id_b))
+                    .collect()
+            })
+            .collect();
+
+        let mut in_degree = vec![0; num_invocations];
         for hb_per_inv in hb_parallel.iter() {
             for &inv_id in hb_per_inv {
                 in_degree[inv_id] += 1;
@@ -55,54 +162,167 @@ where
             }
         }
 
-        let mut checker = LinearizabilityChecker {
+        LinearizabilityChecker {
             execution,
             hb: hb_parallel,
             in_degree,
             minimal_invocations,
             linearized: Vec::new(),
-            seq_spec: Seq::default(),
-        };
+            remaining_completed: execution.parallel_part.len(),
+            seq_spec: new_seq(),
+            new_seq,
+            deadline: None,
+            nodes_left: None,
+            gave_up: false,
+            #[cfg(feature = "tracing")]
+            backtracks: 0,
+        }
+    }
+
+    /// Runs [check_init_part](LinearizabilityChecker::check_init_part) giving up once `timeout` elapses.
+    fn check_within(mut self, timeout: Duration) -> Option<bool> {
+        // a timeout too long to represent never elapses
+        self.deadline = Instant::now().checked_add(timeout);
+        self.check_giving_up()
+    }
 
-        checker.check_init_part()
+    /// Same as [check_with_timeout](LinearizabilityChecker::check_with_timeout) but gives up at `deadline`, if any.
+    fn check_until(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+        deadline: Option<Instant>,
+    ) -> Option<bool> {
+        let mut checker = Self::new(execution, new_seq);
+        if checker.linearizes_trivially() {
+            return Some(true);
+        }
+        checker.deadline = deadline;
+        checker.check_giving_up()
+    }
+
+    /// Runs [check_init_part](LinearizabilityChecker::check_init_part) and returns [None]
+    /// if the search gave up before finding a linearization.
+    fn check_giving_up(&mut self) -> Option<bool> {
+        let linearizable = self.check_init_part();
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            linearizable,
+            gave_up = self.gave_up,
+            backtracks = self.backtracks,
+            "checked the linearizability"
+        );
+        (linearizable || !self.gave_up).then_some(linearizable)
+    }
+
+    /// Whether the search exceeded its time or its node budget. Each call visits a node.
+    fn out_of_budget(&mut self) -> bool {
+        if let Some(nodes_left) = &mut self.nodes_left {
+            if *nodes_left == 0 {
+                return true;
+            }
+            *nodes_left -= 1;
+        }
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
     }
 
     fn check_init_part(&mut self) -> bool {
         self.execution.init_part.iter().all(|inv| {
             let ret = self.seq_spec.exec(inv.op.clone());
-            ret == inv.ret
+            Seq::ret_matches(&ret, &inv.ret)
         }) && self.check_parallel_part()
     }
 
     fn check_parallel_part(&mut self) -> bool {
-        if self.minimal_invocations.is_empty() {
-            return self.check_post_part();
+        if self.out_of_budget() {
+            self.gave_up = true;
+            return false;
+        }
+        if self.remaining_completed == 0 {
+            if self.check_post_part() {
+                return true;
+            }
+            // the remaining pending invocations may still take effect before the post part
+            if self.minimal_invocations.is_empty() {
+                return false;
+            }
+            self.rebuild_seq_spec();
         };
 
         self.minimal_invocations.clone().into_iter().any(|inv_id| {
             self.call(inv_id);
 
-            let inv = &self.execution.parallel_part[inv_id];
-            let ret = self.seq_spec.exec(inv.op.clone());
-            if ret == inv.ret && self.check_parallel_part() {
+            if self.exec_matches(inv_id) && self.check_parallel_part() {
                 return true;
             }
 
             self.undo(inv_id);
             self.rebuild_seq_spec();
-            false
+            self.check_skipping(inv_id, Self::check_parallel_part)
         })
     }
 
+    /// Counts the linearizations where the pending invocations that are not linearized
+    /// before the last completed one are left out.
+    fn count_parallel_part(&mut self, max: usize) -> usize {
+        if self.remaining_completed == 0 {
+            return self.check_post_part() as usize;
+        };
+
+        let mut count = 0;
+        for inv_id in self.minimal_invocations.clone() {
+            if count == max {
+                break;
+            }
+            self.call(inv_id);
+
+            if self.exec_matches(inv_id) {
+                count += self.count_parallel_part(max - count);
+            }
+
+            self.undo(inv_id);
+            self.rebuild_seq_spec();
+
+            if self.execution.is_cancelled(inv_id) && count < max {
+                self.release(inv_id);
+                count += self.count_parallel_part(max - count);
+                self.unrelease(inv_id);
+            }
+        }
+        count
+    }
+
     fn check_post_part(&mut self) -> bool {
         self.execution.post_part.iter().all(|inv| {
             let ret = self.seq_spec.exec(inv.op.clone());
-            ret == inv.ret
+            Seq::ret_matches(&ret, &inv.ret)
         })
     }
 
+    /// Executes the invocation on the sequential specification and checks its result.
+    /// Pending invocations match any result.
+    fn exec_matches(&mut self, inv_id: InvocationId) -> bool {
+        let parallel_part = &self.execution.parallel_part;
+        match parallel_part.get(inv_id) {
+            Some(inv) => Seq::ret_matches(&self.seq_spec.exec(inv.op.clone()), &inv.ret),
+            None => {
+                let inv = &self.execution.pending[inv_id - parallel_part.len()];
+                self.seq_spec.exec(inv.op.clone());
+                true
+            }
+        }
+    }
+
     fn call(&mut self, inv_id: usize) {
+        if inv_id < self.execution.parallel_part.len() {
+            self.remaining_completed -= 1;
+        }
         self.linearized.push(inv_id);
+        self.release(inv_id);
+    }
+
+    /// Removes the invocation from the happens-before graph, making the invocations after it minimal.
+    fn release(&mut self, inv_id: usize) {
         self.minimal_invocations.remove(&inv_id);
         for &next_inv_id in self.hb[inv_id].iter() {
             self.in_degree[next_inv_id] -= 1;
@@ -113,18 +333,35 @@ where
     }
 
     fn rebuild_seq_spec(&mut self) {
-        self.seq_spec = Seq::default();
+        #[cfg(feature = "tracing")]
+        {
+            self.backtracks += 1;
+        }
+        self.seq_spec = (self.new_seq)();
 
         for inv in self.execution.init_part.iter() {
             self.seq_spec.exec(inv.op.clone());
         }
+        let parallel_part = &self.execution.parallel_part;
         for &inv_id in self.linearized.iter() {
-            let inv = &self.execution.parallel_part[inv_id];
-            self.seq_spec.exec(inv.op.clone());
+            let op = match parallel_part.get(inv_id) {
+                Some(inv) => &inv.op,
+                None => &self.execution.pending[inv_id - parallel_part.len()].op,
+            };
+            self.seq_spec.exec(op.clone());
         }
     }
 
     fn undo(&mut self, inv_id: usize) {
+        if inv_id < self.execution.parallel_part.len() {
+            self.remaining_completed += 1;
+        }
+        self.unrelease(inv_id);
+        self.linearized.pop();
+    }
+
+    /// Reverts [release](LinearizabilityChecker::release).
+    fn unrelease(&mut self, inv_id: usize) {
         for &next_inv_id in self.hb[inv_id].iter() {
             if self.in_degree[next_inv_id] == 0 {
                 self.minimal_invocations.remove(&next_inv_id);
@@ -132,125 +369,2687 @@ where
             self.in_degree[next_inv_id] += 1;
         }
         self.minimal_invocations.insert(inv_id);
-        self.linearized.pop();
+    }
+
+    /// If the invocation is cancelled, checks the rest of the execution with `check`
+    /// assuming that it never took effect. Returns `false` otherwise.
+    fn check_skipping(&mut self, inv_id: usize, check: impl FnOnce(&mut Self) -> bool) -> bool {
+        if !self.execution.is_cancelled(inv_id) {
+            return false;
+        }
+        self.release(inv_id);
+        if check(self) {
+            return true;
+        }
+        self.unrelease(inv_id);
+        false
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::recorder::*;
+/// Why an execution is not linearizable, as found by [diagnose](LinearizabilityChecker::diagnose).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnosis<Op, Ret> {
+    /// The longest prefix of a linearization that matches the execution, with the returns of the sequential specification.
+    pub prefix: Vec<(Op, Ret)>,
+    /// The operations that may follow the prefix but returned something else in the execution.
+    pub rejected: Vec<Rejection<Op, Ret>>,
+}
 
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    enum Op<T> {
-        Push(T),
-        Pop,
+/// An operation that can't extend the linearization prefix of a [Diagnosis].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rejection<Op, Ret> {
+    /// The rejected operation.
+    pub op: Op,
+    /// The thread of the parallel part that called the operation, or [None] for the init and post parts.
+    pub thread_id: Option<usize>,
+    /// The return of the sequential specification.
+    pub expected: Ret,
+    /// The return recorded in the execution.
+    pub actual: Ret,
+}
+
+/// The steps of the longest linearization prefix of a non-linearizable execution, continued with the rest of the execution,
+/// as found by [explain](Execution::explain).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation<Op, Ret> {
+    /// The init part, the linearized invocations of the parallel part, the remaining completed ones in the order of their calls,
+    /// and the post part. The pending invocations that aren't linearized are left out.
+    pub steps: Vec<ExplainedStep<Op, Ret>>,
+    /// The number of the first steps that form the longest linearizable prefix.
+    pub matched: usize,
+    /// The operations that may follow the prefix but returned something else in the execution.
+    pub rejected: Vec<Rejection<Op, Ret>>,
+}
+
+/// A step of an [Explanation].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExplainedStep<Op, Ret> {
+    /// The operation.
+    pub op: Op,
+    /// The thread of the parallel part that called the operation, or [None] for the init and post parts.
+    pub thread_id: Option<usize>,
+    /// The return of the sequential specification after the previous steps.
+    pub expected: Ret,
+    /// The return recorded in the execution, or [None] for a pending invocation.
+    pub actual: Option<Ret>,
+}
+
+impl<Op, Ret> Execution<Op, Ret> {
+    /// Annotates the longest linearization prefix and the rest of the execution with what the sequential specification `Seq`
+    /// would have returned at each step, or returns [None] if the execution is linearizable.
+    pub fn explain<Seq>(&self) -> Option<Explanation<Op, Ret>>
+    where
+        Seq: SequentialSpec<Op = Op, Ret = Ret> + Default,
+        Op: Clone,
+        Ret: PartialEq + Clone,
+    {
+        LinearizabilityChecker::<Seq>::explain_with(self, &Seq::default)
     }
+}
 
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    enum Ret<T> {
-        Push,
-        Pop(Option<T>),
+/// The deepest point reached by the search of [diagnose](LinearizabilityChecker::diagnose).
+struct Deepest<Op, Ret> {
+    diagnosis: Diagnosis<Op, Ret>,
+    /// The linearized invocations of the parallel part.
+    linearized: Vec<InvocationId>,
+}
+
+impl<Op, Ret> Deepest<Op, Ret> {
+    /// The linearized invocations of the parallel part followed by the remaining completed ones in the order of their calls.
+    fn parallel_order(&self, execution: &Execution<Op, Ret>) -> Vec<InvocationId> {
+        let parallel_part = &execution.parallel_part;
+        let mut remaining: Vec<_> = (0..parallel_part.len())
+            .filter(|inv_id| !self.linearized.contains(inv_id))
+            .collect();
+        remaining.sort_by_key(|&inv_id| parallel_part[inv_id].call_timestamp);
+
+        let mut order = self.linearized.clone();
+        order.extend(remaining);
+        order
     }
+}
 
-    #[derive(Debug, Clone)]
-    struct SequentialStack<T> {
-        stack: Vec<T>,
+impl<'e, Seq> LinearizabilityChecker<'e, Seq>
+where
+    Seq: SequentialSpec,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq + Clone,
+{
+    /// Explains why the given execution is not linearizable with respect to `Seq`,
+    /// or returns [None] if it is linearizable.
+    ///
+    /// The search is the same as in [check](LinearizabilityChecker::check), but it remembers the deepest point it reached:
+    /// the longest linearization prefix and the candidates for the next operation that were rejected there.
+    pub fn diagnose(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+    ) -> Option<Diagnosis<Seq::Op, Seq::Ret>>
+    where
+        Seq: Default,
+    {
+        Self::diagnose_with(execution, &Seq::default)
     }
 
-    impl<T> Default for SequentialStack<T> {
-        fn default() -> Self {
-            Self { stack: Vec::new() }
+    /// Same as [diagnose](LinearizabilityChecker::diagnose) but creates the sequential specification with `new_seq`.
+    pub fn diagnose_with(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+    ) -> Option<Diagnosis<Seq::Op, Seq::Ret>> {
+        Self::deepest(execution, new_seq).map(|deepest| deepest.diagnosis)
+    }
+
+    /// Same as [explain](Execution::explain) but creates the sequential specification with `new_seq`.
+    pub fn explain_with(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+    ) -> Option<Explanation<Seq::Op, Seq::Ret>> {
+        let deepest = Self::deepest(execution, new_seq)?;
+
+        let parallel_part = &execution.parallel_part;
+        let init_part = (execution.init_part.iter()).map(|inv| (&inv.op, None, Some(&inv.ret)));
+        let linearized = (deepest.parallel_order(execution).into_iter()).map(|inv_id| {
+            match parallel_part.get(inv_id) {
+                Some(inv) => (&inv.op, Some(inv.thread_id), Some(&inv.ret)),
+                None => {
+                    let inv = &execution.pending[inv_id - parallel_part.len()];
+                    (&inv.op, Some(inv.thread_id), None)
+                }
+            }
+        });
+        let post_part = (execution.post_part.iter()).map(|inv| (&inv.op, None, Some(&inv.ret)));
+
+        let mut seq_spec = new_seq();
+        let steps = init_part
+            .chain(linearized)
+            .chain(post_part)
+            .map(|(op, thread_id, actual)| ExplainedStep {
+                op: op.clone(),
+                thread_id,
+                expected: seq_spec.exec(op.clone()),
+                actual: actual.cloned(),
+            })
+            .collect();
+
+        Some(Explanation {
+            steps,
+            matched: deepest.diagnosis.prefix.len(),
+            rejected: deepest.diagnosis.rejected,
+        })
+    }
+
+    /// The invocation of the first step of the [explanation](Execution::explain) that doesn't match the sequential specification,
+    /// or [None] if the execution is linearizable.
+    #[cfg(feature = "color")]
+    pub(crate) fn first_mismatch(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+    ) -> Option<InvocationRef> {
+        let deepest = Self::deepest(execution, new_seq)?;
+
+        let matched = deepest.diagnosis.prefix.len();
+        let Some(matched) = matched.checked_sub(execution.init_part.len()) else {
+            return Some(InvocationRef::Init(matched));
+        };
+        let order = deepest.parallel_order(execution);
+        Some(match matched.checked_sub(order.len()) {
+            Some(post_id) => InvocationRef::Post(post_id),
+            None => InvocationRef::Parallel(order[matched]),
+        })
+    }
+
+    /// Searches for a linearization remembering the deepest point it reached, or returns [None] if there is a linearization.
+    fn deepest(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+    ) -> Option<Deepest<Seq::Op, Seq::Ret>> {
+        let mut checker = Self::new(execution, new_seq);
+
+        let mut prefix = Vec::new();
+        for inv in execution.init_part.iter() {
+            let ret = checker.seq_spec.exec(inv.op.clone());
+            if !Seq::ret_matches(&ret, &inv.ret) {
+                let rejection = Rejection {
+                    op: inv.op.clone(),
+                    thread_id: None,
+                    expected: ret,
+                    actual: inv.ret.clone(),
+                };
+                return Some(Deepest {
+                    diagnosis: Diagnosis {
+                        prefix,
+                        rejected: vec![rejection],
+                    },
+                    linearized: Vec::new(),
+                });
+            }
+            prefix.push((inv.op.clone(), ret));
+        }
+
+        let mut deepest = None;
+        if checker.diagnose_parallel_part(&mut deepest) {
+            None
+        } else {
+            deepest
         }
     }
 
-    impl<T> SequentialSpec for SequentialStack<T> {
-        type Op = Op<T>;
-        type Ret = Ret<T>;
+    /// The same as [check_parallel_part](LinearizabilityChecker::check_parallel_part)
+    /// but keeps the deepest point of the search in `deepest`.
+    fn diagnose_parallel_part(&mut self, deepest: &mut Option<Deepest<Seq::Op, Seq::Ret>>) -> bool {
+        if self.remaining_completed == 0 {
+            match self.diagnose_post_part() {
+                Ok(()) => return true,
+                Err((post_prefix, rejection)) => {
+                    self.record_deepest(deepest, post_prefix, vec![rejection])
+                }
+            }
+            if self.minimal_invocations.is_empty() {
+                return false;
+            }
+            self.rebuild_seq_spec();
+        }
 
-        fn exec(&mut self, op: Self::Op) -> Self::Ret {
-            match op {
-                Op::Push(value) => {
-                    self.stack.push(value);
-                    Ret::Push
+        // sorted to make the diagnosis deterministic
+        let mut candidates: Vec<_> = self.minimal_invocations.iter().copied().collect();
+        candidates.sort_unstable();
+
+        let mut rejected = Vec::new();
+        for inv_id in candidates {
+            self.call(inv_id);
+
+            match self.exec_diagnosed(inv_id) {
+                Ok(()) if self.diagnose_parallel_part(deepest) => return true,
+                Ok(()) => {}
+                Err(rejection) => rejected.push(rejection),
+            }
+
+            self.undo(inv_id);
+            self.rebuild_seq_spec();
+
+            if self.check_skipping(inv_id, |checker| checker.diagnose_parallel_part(deepest)) {
+                return true;
+            }
+        }
+
+        self.record_deepest(deepest, Vec::new(), rejected);
+        false
+    }
+
+    /// Checks the post part, returning the matched prefix of it and the first mismatch.
+    #[allow(clippy::type_complexity)]
+    fn diagnose_post_part(
+        &mut self,
+    ) -> Result<(), (Vec<(Seq::Op, Seq::Ret)>, Rejection<Seq::Op, Seq::Ret>)> {
+        let mut prefix = Vec::new();
+        for inv in self.execution.post_part.iter() {
+            let ret = self.seq_spec.exec(inv.op.clone());
+            if !Seq::ret_matches(&ret, &inv.ret) {
+                let rejection = Rejection {
+                    op: inv.op.clone(),
+                    thread_id: None,
+                    expected: ret,
+                    actual: inv.ret.clone(),
+                };
+                return Err((prefix, rejection));
+            }
+            prefix.push((inv.op.clone(), ret));
+        }
+        Ok(())
+    }
+
+    /// The same as [exec_matches](LinearizabilityChecker::exec_matches) but describes the mismatch.
+    fn exec_diagnosed(&mut self, inv_id: InvocationId) -> Result<(), Rejection<Seq::Op, Seq::Ret>> {
+        let parallel_part = &self.execution.parallel_part;
+        match parallel_part.get(inv_id) {
+            Some(inv) => {
+                let ret = self.seq_spec.exec(inv.op.clone());
+                if Seq::ret_matches(&ret, &inv.ret) {
+                    return Ok(());
                 }
-                Op::Pop => Ret::Pop(self.stack.pop()),
+                Err(Rejection {
+                    op: inv.op.clone(),
+                    thread_id: Some(inv.thread_id),
+                    expected: ret,
+                    actual: inv.ret.clone(),
+                })
+            }
+            None => {
+                let inv = &self.execution.pending[inv_id - parallel_part.len()];
+                self.seq_spec.exec(inv.op.clone());
+                Ok(())
             }
         }
     }
 
-    #[test]
-    fn init_and_post_parts_are_sequentional() {
-        let mut recorder = record_init_part();
-        recorder.record(Op::Push(1), || Ret::Push);
-        recorder.record(Op::Push(2), || Ret::Push);
+    /// Replaces the `deepest` diagnosis if the current linearization followed by `post_prefix` is longer.
+    fn record_deepest(
+        &self,
+        deepest: &mut Option<Deepest<Seq::Op, Seq::Ret>>,
+        post_prefix: Vec<(Seq::Op, Seq::Ret)>,
+        rejected: Vec<Rejection<Seq::Op, Seq::Ret>>,
+    ) {
+        let len = self.execution.init_part.len() + self.linearized.len() + post_prefix.len();
+        if deepest
+            .as_ref()
+            .is_some_and(|deepest| deepest.diagnosis.prefix.len() >= len)
+        {
+            return;
+        }
 
-        let mut recorder = recorder.record_post_part();
-        recorder.record(Op::Pop, || Ret::Pop(Some(2)));
-        recorder.record(Op::Pop, || Ret::Pop(Some(1)));
+        // the returns are replayed on a fresh specification, since the current one may be ahead
+        let mut seq_spec = (self.new_seq)();
+        let parallel_part = &self.execution.parallel_part;
+        let linearized_ops =
+            self.linearized
+                .iter()
+                .map(|&inv_id| match parallel_part.get(inv_id) {
+                    Some(inv) => &inv.op,
+                    None => &self.execution.pending[inv_id - parallel_part.len()].op,
+                });
+        let mut prefix: Vec<_> = (self.execution.init_part.iter().map(|inv| &inv.op))
+            .chain(linearized_ops)
+            .map(|op| (op.clone(), seq_spec.exec(op.clone())))
+            .collect();
+        prefix.extend(post_prefix);
 
-        let execution = recorder.finish();
+        *deepest = Some(Deepest {
+            diagnosis: Diagnosis { prefix, rejected },
+            linearized: self.linearized.clone(),
+        });
+    }
+}
 
-        assert!(LinearizabilityChecker::<SequentialStack<i32>>::check(
-            &execution
-        ));
+impl<'e, Seq> LinearizabilityChecker<'e, Seq>
+where
+    Seq: UndoableSequentialSpec + Default,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// Same as [check](LinearizabilityChecker::check) but backtracks by [undoing](UndoableSequentialSpec::undo)
+    /// the operations instead of rebuilding the sequential specification.
+    pub fn check_undoable(execution: &'e Execution<Seq::Op, Seq::Ret>) -> bool {
+        Self::check_undoable_until(execution, &Seq::default, None) == Some(true)
     }
 
-    #[test]
-    fn parallel_part_overlapping() {
-        let mut init_recorder = record_init_part();
-        init_recorder.record(Op::Push(1), || Ret::Push);
-        init_recorder.record(Op::Push(2), || Ret::Push);
-        let init_part = init_recorder.finish().init_part;
-
-        let mut recorder_a = InternalRecorder::new(0);
-        let mut recorder_b = InternalRecorder::new(1);
-        recorder_a.add_call(Op::Pop, 4);
-        recorder_b.add_call(Op::Pop, 5);
-        recorder_a.add_return(Ret::Pop(Some(2)), 6);
-        recorder_b.add_return(Ret::Pop(Some(1)), 7);
-
-        let execution = Execution {
-            init_part,
-            parallel_part: [
-                recorder_a.history().into_inner(),
-                recorder_b.history().into_inner(),
-            ]
-            .concat()
-            .into(),
-            post_part: History::new(),
-        };
+    /// Same as [check_undoable](LinearizabilityChecker::check_undoable) but creates the sequential specification
+    /// with `new_seq` and gives up at `deadline`, if any. Returns [None] if it did.
+    fn check_undoable_until(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+        deadline: Option<Instant>,
+    ) -> Option<bool> {
+        let mut checker = Self::new(execution, new_seq);
+        checker.deadline = deadline;
+        let linearizable = execution.init_part.iter().all(|inv| {
+            let ret = checker.seq_spec.exec(inv.op.clone());
+            Seq::ret_matches(&ret, &inv.ret)
+        }) && checker.check_parallel_part_undoable();
+        (linearizable || !checker.gave_up).then_some(linearizable)
+    }
 
-        assert!(LinearizabilityChecker::<SequentialStack<i32>>::check(
-            &execution
-        ));
+    fn check_parallel_part_undoable(&mut self) -> bool {
+        if self.out_of_budget() {
+            self.gave_up = true;
+            return false;
+        }
+        if self.remaining_completed == 0 && self.check_post_part_undoable() {
+            return true;
+        }
+
+        self.minimal_invocations.clone().into_iter().any(|inv_id| {
+            self.call(inv_id);
+
+            let parallel_part = &self.execution.parallel_part;
+            let (matches, token) = match parallel_part.get(inv_id) {
+                Some(inv) => {
+                    let (ret, token) = self.seq_spec.exec_undoable(inv.op.clone());
+                    (Seq::ret_matches(&ret, &inv.ret), token)
+                }
+                None => {
+                    let inv = &self.execution.pending[inv_id - parallel_part.len()];
+                    (true, self.seq_spec.exec_undoable(inv.op.clone()).1)
+                }
+            };
+            if matches && self.check_parallel_part_undoable() {
+                return true;
+            }
+
+            self.seq_spec.undo(token);
+            self.undo(inv_id);
+            self.check_skipping(inv_id, Self::check_parallel_part_undoable)
+        })
     }
 
-    #[test]
-    fn parallel_part_does_not_violate_happens_before() {
-        let mut recorder_a = InternalRecorder::new(0);
-        let mut recorder_b = InternalRecorder::new(1);
-        recorder_a.add_call(Op::Pop, 4);
-        recorder_b.add_call(Op::Pop, 5);
-        recorder_a.add_return(Ret::Pop(Some(1)), 6);
-
-        recorder_a.add_call(Op::Push(1), 7);
-        recorder_b.add_return(Ret::Pop(None), 8);
-        recorder_a.add_return(Ret::Push, 9);
-
-        let execution = Execution {
-            init_part: History::new(),
-            parallel_part: [
-                recorder_a.history().into_inner(),
-                recorder_b.history().into_inner(),
-            ]
-            .concat()
-            .into(),
-            post_part: History::new(),
-        };
+    /// Checks the post part and undoes it if it doesn't match.
+    fn check_post_part_undoable(&mut self) -> bool {
+        let mut tokens = Vec::with_capacity(self.execution.post_part.len());
+        for inv in self.execution.post_part.iter() {
+            let (ret, token) = self.seq_spec.exec_undoable(inv.op.clone());
+            tokens.push(token);
+            if !Seq::ret_matches(&ret, &inv.ret) {
+                for token in tokens.into_iter().rev() {
+                    self.seq_spec.undo(token);
+                }
+                return false;
+            }
+        }
+        true
+    }
+}
 
-        assert!(!LinearizabilityChecker::<SequentialStack<i32>>::check(
-            &execution
-        ));
+impl<'e, Seq> LinearizabilityChecker<'e, Seq>
+where
+    Seq: SequentialSpec + Default + Clone + PartialEq,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// Same as [check](LinearizabilityChecker::check) but splits the parallel part at quiescent points
+    /// (timestamps at which no invocation is pending) and checks the segments one by one.
+    ///
+    /// Instead of backtracking across the whole history, it computes the set of sequential states
+    /// reachable after each segment and checks the next segment starting from each of them.
+    /// This makes long histories with many quiescent points tractable.
+    /// Executions with pending invocations are checked with [check](LinearizabilityChecker::check) instead.
+    pub fn check_decomposed(execution: &'e Execution<Seq::Op, Seq::Ret>) -> bool {
+        if !execution.pending.is_empty() {
+            return Self::check(execution);
+        }
+
+        let mut init_state = Seq::default();
+        let init_part_matches = execution
+            .init_part
+            .iter()
+            .all(|inv| Seq::ret_matches(&init_state.exec(inv.op.clone()), &inv.ret));
+        if !init_part_matches {
+            return false;
+        }
+
+        let mut states = vec![init_state];
+        for segment in execution.parallel_part.quiescent_segments() {
+            let segment: Vec<_> = segment
+                .into_iter()
+                .map(|inv_id| &execution.parallel_part[inv_id])
+                .collect();
+
+            let mut next_states = Vec::new();
+            for state in &states {
+                let mut linearized = vec![false; segment.len()];
+                collect_reachable_states(
+                    &segment,
+                    &mut linearized,
+                    state.clone(),
+                    &mut next_states,
+                );
+            }
+
+            if next_states.is_empty() {
+                return false;
+            }
+            states = next_states;
+        }
+
+        states.into_iter().any(|mut state| {
+            execution
+                .post_part
+                .iter()
+                .all(|inv| Seq::ret_matches(&state.exec(inv.op.clone()), &inv.ret))
+        })
+    }
+}
+
+impl<'e, Seq> LinearizabilityChecker<'e, Seq>
+where
+    Seq: SequentialSpec + Default + Clone + Hash + Eq,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// Same as [check](LinearizabilityChecker::check) but implements the Wing–Gong–Lowe algorithm.
+    ///
+    /// Instead of rebuilding the sequential specification on backtracking, it keeps a copy of the state
+    /// for each step of the search. It also caches the visited configurations, i.e. the sets of linearized
+    /// invocations along with the reached states, and never explores a configuration twice.
+    /// Different orders of commuting invocations lead to the same configuration,
+    /// so histories with much concurrency are checked in time exponential in the number of invocations
+    /// instead of factorial.
+    pub fn check_memoized(execution: &'e Execution<Seq::Op, Seq::Ret>) -> bool {
+        Self::check_memoized_until(execution, &Seq::default, None) == Some(true)
+    }
+
+    /// Same as [check_memoized](LinearizabilityChecker::check_memoized) but creates the sequential specification
+    /// with `new_seq` and gives up at `deadline`, if any. Returns [None] if it did.
+    fn check_memoized_until(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &dyn Fn() -> Seq,
+        deadline: Option<Instant>,
+    ) -> Option<bool> {
+        let mut state = new_seq();
+        let init_part_matches = execution
+            .init_part
+            .iter()
+            .all(|inv| Seq::ret_matches(&state.exec(inv.op.clone()), &inv.ret));
+        if !init_part_matches {
+            return Some(false);
+        }
+
+        MemoizedSearch::check_execution(execution, state, HashSet::new(), deadline)
+    }
+}
+
+impl<'e, Seq> LinearizabilityChecker<'e, Seq>
+where
+    Seq: StateEquivalence + Default,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// Same as [check_memoized](LinearizabilityChecker::check_memoized) but considers the configurations
+    /// with [equivalent](StateEquivalence::equivalent) states the same.
+    ///
+    /// It prunes the orders of the invocations that lead to different representations of the same abstract state,
+    /// e.g. a set storing its elements in the insertion order, and doesn't require the state to be hashable.
+    pub fn check_pruned(execution: &'e Execution<Seq::Op, Seq::Ret>) -> bool {
+        Self::check_pruned_until(execution, &Seq::default, None) == Some(true)
+    }
+
+    /// Same as [check_pruned](LinearizabilityChecker::check_pruned) but creates the sequential specification
+    /// with `new_seq` and gives up at `deadline`, if any. Returns [None] if it did.
+    fn check_pruned_until(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &dyn Fn() -> Seq,
+        deadline: Option<Instant>,
+    ) -> Option<bool> {
+        let mut state = new_seq();
+        let init_part_matches = execution
+            .init_part
+            .iter()
+            .all(|inv| Seq::ret_matches(&state.exec(inv.op.clone()), &inv.ret));
+        if !init_part_matches {
+            return Some(false);
+        }
+
+        MemoizedSearch::check_execution(
+            execution,
+            state,
+            EquivalentStates(HashMap::new()),
+            deadline,
+        )
+    }
+}
+
+impl<'e, Seq> LinearizabilityChecker<'e, Seq>
+where
+    Seq: SequentialSpec + Default + Clone + Hash + Eq,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// Same as [check](LinearizabilityChecker::check) but implements the just-in-time linearization of Lowe,
+    /// as in Porcupine and Knossos, for histories with thousands of invocations.
+    ///
+    /// It walks through the calls and the returns of the parallel part in the order of their timestamps
+    /// and linearizes the called invocations only when one of them returns, so it never branches on the orders
+    /// that the returns don't force. Like [check_memoized](LinearizabilityChecker::check_memoized),
+    /// it caches the visited configurations. Combine it with [PartitionableSpec] by checking the sub-histories
+    /// of the keys one by one, as [check_partitioned](LinearizabilityChecker::check_partitioned) does.
+    pub fn check_jit(execution: &'e Execution<Seq::Op, Seq::Ret>) -> bool {
+        Self::check_jit_until(execution, &Seq::default, None) == Some(true)
+    }
+
+    /// Same as [check_jit](LinearizabilityChecker::check_jit) but creates the sequential specification
+    /// with `new_seq` and gives up at `deadline`, if any. Returns [None] if it did.
+    fn check_jit_until(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &dyn Fn() -> Seq,
+        deadline: Option<Instant>,
+    ) -> Option<bool> {
+        let mut state = new_seq();
+        let init_part_matches = execution
+            .init_part
+            .iter()
+            .all(|inv| Seq::ret_matches(&state.exec(inv.op.clone()), &inv.ret));
+        if !init_part_matches {
+            return Some(false);
+        }
+
+        let mut search = JitSearch::new(execution);
+        search.deadline = deadline;
+        let linearizable = search.check(0, state);
+        (linearizable || !search.gave_up).then_some(linearizable)
+    }
+
+    /// Checks the execution with the given search algorithm.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the backend requires another trait than [Hash] and [Eq] of `Seq`,
+    /// i.e. [Undoable](CheckerBackend::Undoable), [Pruned](CheckerBackend::Pruned)
+    /// or [Partitioned](CheckerBackend::Partitioned). Use [SpecChecker] or their own checks for them.
+    pub fn check_using(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        backend: CheckerBackend,
+    ) -> bool {
+        match backend {
+            CheckerBackend::Backtracking => Self::check(execution),
+            CheckerBackend::Memoized => Self::check_memoized(execution),
+            CheckerBackend::Jit => Self::check_jit(execution),
+            CheckerBackend::Undoable | CheckerBackend::Pruned | CheckerBackend::Partitioned => {
+                panic!(
+                    "the {:?} backend requires another trait of the sequential specification",
+                    backend
+                )
+            }
+        }
+    }
+}
+
+/// The search algorithms of [check_using](LinearizabilityChecker::check_using) and [SpecChecker].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CheckerBackend {
+    /// Backtracking over the orders of the happens-before graph, as [check](LinearizabilityChecker::check) does.
+    #[default]
+    Backtracking,
+    /// The Wing–Gong–Lowe algorithm of [check_memoized](LinearizabilityChecker::check_memoized).
+    Memoized,
+    /// The just-in-time linearization of [check_jit](LinearizabilityChecker::check_jit).
+    Jit,
+    /// Backtracking by undoing the operations, as [check_undoable](LinearizabilityChecker::check_undoable) does.
+    Undoable,
+    /// The memoized search over the equivalent states of [check_pruned](LinearizabilityChecker::check_pruned).
+    Pruned,
+    /// Checking the partitions separately, as [check_partitioned](LinearizabilityChecker::check_partitioned) does.
+    Partitioned,
+}
+
+/// A [CheckerBackend] bound to the sequential specification `Seq` it checks the executions against,
+/// e.g. to select the backend of [verify](crate::Lincheck::verify) with [checker](crate::Lincheck::checker).
+///
+/// The backends require different traits of `Seq`, so each one has its own constructor.
+#[derive(Clone)]
+pub struct SpecChecker {
+    backend: CheckerBackend,
+    check: Arc<dyn Any + Send + Sync>, // the CheckFn of the sequential specification
+}
+
+/// The check of [SpecChecker] for the sequential specification `Seq`, creating it with the given constructor
+/// and giving up at the given deadline, if any.
+struct CheckFn<Seq: SequentialSpec>(CheckUntil<Seq>);
+
+type CheckUntil<Seq> = fn(
+    &Execution<<Seq as SequentialSpec>::Op, <Seq as SequentialSpec>::Ret>,
+    &dyn Fn() -> Seq,
+    Option<Instant>,
+) -> Option<bool>;
+
+impl SpecChecker {
+    fn new<Seq>(backend: CheckerBackend, check: CheckUntil<Seq>) -> Self
+    where
+        Seq: SequentialSpec + 'static,
+    {
+        SpecChecker {
+            backend,
+            check: Arc::new(CheckFn::<Seq>(check)),
+        }
+    }
+
+    /// [check](LinearizabilityChecker::check) against `Seq`.
+    pub fn backtracking<Seq>() -> Self
+    where
+        Seq: SequentialSpec + Default + 'static,
+        Seq::Op: Clone,
+        Seq::Ret: PartialEq,
+    {
+        Self::new::<Seq>(
+            CheckerBackend::Backtracking,
+            |execution, new_seq, deadline| {
+                LinearizabilityChecker::<Seq>::check_until(execution, new_seq, deadline)
+            },
+        )
+    }
+
+    /// [check_memoized](LinearizabilityChecker::check_memoized) against `Seq`.
+    pub fn memoized<Seq>() -> Self
+    where
+        Seq: SequentialSpec + Default + Clone + Hash + Eq + 'static,
+        Seq::Op: Clone,
+        Seq::Ret: PartialEq,
+    {
+        Self::new::<Seq>(CheckerBackend::Memoized, |execution, new_seq, deadline| {
+            LinearizabilityChecker::<Seq>::check_memoized_until(execution, new_seq, deadline)
+        })
+    }
+
+    /// [check_jit](LinearizabilityChecker::check_jit) against `Seq`.
+    pub fn jit<Seq>() -> Self
+    where
+        Seq: SequentialSpec + Default + Clone + Hash + Eq + 'static,
+        Seq::Op: Clone,
+        Seq::Ret: PartialEq,
+    {
+        Self::new::<Seq>(CheckerBackend::Jit, |execution, new_seq, deadline| {
+            LinearizabilityChecker::<Seq>::check_jit_until(execution, new_seq, deadline)
+        })
+    }
+
+    /// [check_undoable](LinearizabilityChecker::check_undoable) against `Seq`.
+    pub fn undoable<Seq>() -> Self
+    where
+        Seq: UndoableSequentialSpec + Default + 'static,
+        Seq::Op: Clone,
+        Seq::Ret: PartialEq,
+    {
+        Self::new::<Seq>(CheckerBackend::Undoable, |execution, new_seq, deadline| {
+            LinearizabilityChecker::<Seq>::check_undoable_until(execution, new_seq, deadline)
+        })
+    }
+
+    /// [check_pruned](LinearizabilityChecker::check_pruned) against `Seq`.
+    pub fn pruned<Seq>() -> Self
+    where
+        Seq: StateEquivalence + Default + 'static,
+        Seq::Op: Clone,
+        Seq::Ret: PartialEq,
+    {
+        Self::new::<Seq>(CheckerBackend::Pruned, |execution, new_seq, deadline| {
+            LinearizabilityChecker::<Seq>::check_pruned_until(execution, new_seq, deadline)
+        })
+    }
+
+    /// [check_partitioned](LinearizabilityChecker::check_partitioned) against `Seq`.
+    pub fn partitioned<Seq>() -> Self
+    where
+        Seq: PartitionableSpec + Default + 'static,
+        Seq::Op: Clone,
+        Seq::Ret: PartialEq + Clone,
+    {
+        Self::new::<Seq>(
+            CheckerBackend::Partitioned,
+            |execution, new_seq, deadline| {
+                LinearizabilityChecker::<Seq>::check_partitioned_until(execution, new_seq, deadline)
+            },
+        )
+    }
+
+    /// The backend of the checker.
+    pub fn backend(&self) -> CheckerBackend {
+        self.backend
+    }
+
+    /// Checks the execution for linearizability with the backend.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checker is bound to another sequential specification than `Seq`.
+    pub fn check<Seq>(&self, execution: &Execution<Seq::Op, Seq::Ret>) -> bool
+    where
+        Seq: SequentialSpec + Default + 'static,
+    {
+        self.check_with::<Seq>(execution, &Seq::default, None) == Some(true)
+    }
+
+    /// Same as [check](SpecChecker::check) but creates the sequential specification with `new_seq`
+    /// and gives up once `timeout` elapses, if any. Returns [None] if it did.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checker is bound to another sequential specification than `Seq`.
+    pub fn check_with<Seq>(
+        &self,
+        execution: &Execution<Seq::Op, Seq::Ret>,
+        new_seq: &dyn Fn() -> Seq,
+        timeout: Option<Duration>,
+    ) -> Option<bool>
+    where
+        Seq: SequentialSpec + 'static,
+    {
+        // a timeout too long to represent never elapses
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        let CheckFn(check) = self
+            .check
+            .downcast_ref::<CheckFn<Seq>>()
+            .unwrap_or_else(|| {
+                panic!(
+                    "the checker is bound to another sequential specification than {}",
+                    std::any::type_name::<Seq>()
+                )
+            });
+        check(execution, new_seq, deadline)
+    }
+}
+
+impl Debug for SpecChecker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpecChecker")
+            .field("backend", &self.backend)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A call or a return of an invocation of the parallel part.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Event {
+    Call(InvocationId),
+    Return(InvocationId),
+}
+
+/// The state of [check_jit](LinearizabilityChecker::check_jit).
+struct JitSearch<'e, Seq: SequentialSpec> {
+    execution: &'e Execution<Seq::Op, Seq::Ret>,
+    events: Vec<Event>, // the calls and the returns in the order of their timestamps, the calls first on ties
+    called_at: Vec<usize>, // for each invocation in the parallel part and the pending ones, the index of its call
+    linearized: Vec<bool>, // for each invocation in the parallel part and the pending ones, whether it is linearized or left out
+    visited: HashSet<(usize, Vec<bool>, Seq)>, // configurations that are already explored
+    deadline: Option<Instant>, // when the search gives up
+    gave_up: bool,         // whether the search gave up
+}
+
+impl<'e, Seq> JitSearch<'e, Seq>
+where
+    Seq: SequentialSpec + Clone + Hash + Eq,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    fn new(execution: &'e Execution<Seq::Op, Seq::Ret>) -> Self {
+        let intervals = execution.intervals();
+        let mut events: Vec<_> = intervals
+            .iter()
+            .enumerate()
+            .flat_map(|(inv_id, inv)| {
+                let call = Some((inv.call_timestamp, false, Event::Call(inv_id)));
+                // a cancellation forces the cancelled invocation like a return
+                let ret = (inv.return_timestamp != Timestamp::MAX).then_some((
+                    inv.return_timestamp,
+                    true,
+                    Event::Return(inv_id),
+                ));
+                call.into_iter().chain(ret)
+            })
+            .collect();
+        events.sort_by_key(|&(timestamp, is_return, _)| (timestamp, is_return));
+        let events: Vec<_> = events.into_iter().map(|(_, _, event)| event).collect();
+
+        let mut called_at = vec![0; intervals.len()];
+        for (index, event) in events.iter().enumerate() {
+            if let Event::Call(inv_id) = *event {
+                called_at[inv_id] = index;
+            }
+        }
+
+        JitSearch {
+            execution,
+            events,
+            called_at,
+            linearized: vec![false; intervals.len()],
+            visited: HashSet::new(),
+            deadline: None,
+            gave_up: false,
+        }
+    }
+
+    /// Checks the rest of the execution from the event at `index` in the given state.
+    fn check(&mut self, mut index: usize, state: Seq) -> bool {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.gave_up = true;
+            return false;
+        }
+        // the calls and the returns of the linearized invocations don't force anything
+        while let Some(&event) = self.events.get(index) {
+            match event {
+                Event::Return(inv_id) if !self.linearized[inv_id] => break,
+                _ => index += 1,
+            }
+        }
+
+        if index == self.events.len() {
+            let mut post_state = state.clone();
+            let post_part_matches = self
+                .execution
+                .post_part
+                .iter()
+                .all(|inv| Seq::ret_matches(&post_state.exec(inv.op.clone()), &inv.ret));
+            if post_part_matches {
+                return true;
+            }
+        }
+
+        if !self
+            .visited
+            .insert((index, self.linearized.clone(), state.clone()))
+        {
+            return false;
+        }
+
+        // a cancelled invocation may never take effect
+        if let Some(&Event::Return(inv_id)) = self.events.get(index) {
+            if self.execution.is_cancelled(inv_id) {
+                self.linearized[inv_id] = true;
+                let result = self.check(index, state.clone());
+                self.linearized[inv_id] = false;
+                if result {
+                    return true;
+                }
+            }
+        }
+
+        // an invocation returns, so it or another called one takes effect now;
+        // after the last return, only the pending invocations are left
+        let parallel_part = &self.execution.parallel_part;
+        (0..self.linearized.len()).any(|inv_id| {
+            if self.linearized[inv_id] || self.called_at[inv_id] >= index {
+                return false;
+            }
+
+            let mut next_state = state.clone();
+            let matches = match parallel_part.get(inv_id) {
+                Some(inv) => Seq::ret_matches(&next_state.exec(inv.op.clone()), &inv.ret),
+                None => {
+                    let inv = &self.execution.pending[inv_id - parallel_part.len()];
+                    next_state.exec(inv.op.clone());
+                    true
+                }
+            };
+            if !matches {
+                return false;
+            }
+
+            self.linearized[inv_id] = true;
+            let result = self.check(index, next_state);
+            self.linearized[inv_id] = false;
+            result
+        })
+    }
+}
+
+/// The explored configurations of [MemoizedSearch].
+trait Visited<Seq> {
+    /// Adds the configuration and returns whether it is explored for the first time.
+    fn insert(&mut self, linearized: &[bool], state: &Seq) -> bool;
+}
+
+impl<Seq: Clone + Hash + Eq> Visited<Seq> for HashSet<(Vec<bool>, Seq)> {
+    fn insert(&mut self, linearized: &[bool], state: &Seq) -> bool {
+        HashSet::insert(self, (linearized.to_vec(), state.clone()))
+    }
+}
+
+/// The representatives of the equivalence classes of the states reached for each set of linearized invocations.
+struct EquivalentStates<Seq>(HashMap<Vec<bool>, Vec<Seq>>);
+
+impl<Seq: StateEquivalence> Visited<Seq> for EquivalentStates<Seq> {
+    fn insert(&mut self, linearized: &[bool], state: &Seq) -> bool {
+        let states = self.0.entry(linearized.to_vec()).or_default();
+        if states.iter().any(|other| other.equivalent(state)) {
+            return false;
+        }
+        states.push(state.clone());
+        true
+    }
+}
+
+/// The state of [check_memoized](LinearizabilityChecker::check_memoized) and [check_pruned](LinearizabilityChecker::check_pruned).
+struct MemoizedSearch<'e, Seq: SequentialSpec, V> {
+    execution: &'e Execution<Seq::Op, Seq::Ret>,
+    intervals: Vec<Interval>,
+    linearized: Vec<bool>, // for each invocation in the parallel part and the pending ones, whether it is linearized or left out
+    remaining_completed: usize, // number of completed invocations that are not linearized yet
+    visited: V,            // configurations that are already explored
+    deadline: Option<Instant>, // when the search gives up
+    gave_up: bool,         // whether the search gave up
+}
+
+impl<'e, Seq, V> MemoizedSearch<'e, Seq, V>
+where
+    Seq: SequentialSpec + Clone,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+    V: Visited<Seq>,
+{
+    /// Checks the parallel and post parts of the execution starting from the state after the init part.
+    /// Returns [None] if the search gave up at `deadline`.
+    fn check_execution(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        state: Seq,
+        visited: V,
+        deadline: Option<Instant>,
+    ) -> Option<bool> {
+        let mut search = MemoizedSearch {
+            execution,
+            intervals: execution.intervals(),
+            linearized: vec![false; execution.parallel_part.len() + execution.pending.len()],
+            remaining_completed: execution.parallel_part.len(),
+            visited,
+            deadline,
+            gave_up: false,
+        };
+        let linearizable = search.check(state);
+        (linearizable || !search.gave_up).then_some(linearizable)
+    }
+
+    fn check(&mut self, state: Seq) -> bool {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.gave_up = true;
+            return false;
+        }
+        if self.remaining_completed == 0 {
+            let mut post_state = state.clone();
+            let post_part_matches = self
+                .execution
+                .post_part
+                .iter()
+                .all(|inv| Seq::ret_matches(&post_state.exec(inv.op.clone()), &inv.ret));
+            if post_part_matches {
+                return true;
+            }
+        }
+
+        if !self.visited.insert(&self.linearized, &state) {
+            return false;
+        }
+
+        (0..self.linearized.len()).any(|inv_id| {
+            self.is_minimal(inv_id)
+                && (self.check_linearizing(inv_id, &state) || self.check_skipping(inv_id, &state))
+        })
+    }
+
+    /// Checks the rest of the execution after the minimal invocation takes effect in the given state.
+    fn check_linearizing(&mut self, inv_id: InvocationId, state: &Seq) -> bool {
+        let parallel_part = &self.execution.parallel_part;
+        let mut next_state = state.clone();
+        let matches = match parallel_part.get(inv_id) {
+            Some(inv) => Seq::ret_matches(&next_state.exec(inv.op.clone()), &inv.ret),
+            None => {
+                let inv = &self.execution.pending[inv_id - parallel_part.len()];
+                next_state.exec(inv.op.clone());
+                true
+            }
+        };
+        if !matches {
+            return false;
+        }
+
+        let completed = inv_id < parallel_part.len();
+        self.linearized[inv_id] = true;
+        self.remaining_completed -= usize::from(completed);
+        let result = self.check(next_state);
+        self.linearized[inv_id] = false;
+        self.remaining_completed += usize::from(completed);
+        result
+    }
+
+    /// If the minimal invocation is cancelled, checks the rest of the execution assuming that it never took effect.
+    fn check_skipping(&mut self, inv_id: InvocationId, state: &Seq) -> bool {
+        if !self.execution.is_cancelled(inv_id) {
+            return false;
+        }
+        self.linearized[inv_id] = true;
+        let result = self.check(state.clone());
+        self.linearized[inv_id] = false;
+        result
+    }
+
+    /// Whether the invocation is not linearized yet and every invocation that happens-before it is.
+    fn is_minimal(&self, inv_id: InvocationId) -> bool {
+        let call_timestamp = self.intervals[inv_id].call_timestamp;
+        !self.linearized[inv_id]
+            && self
+                .intervals
+                .iter()
+                .zip(self.linearized.iter())
+                .all(|(other, &linearized)| linearized || other.return_timestamp >= call_timestamp)
+    }
+}
+
+/// The sequential consistency checker.
+///
+/// Sequential consistency is weaker than linearizability: the invocations of each thread must keep their order,
+/// but the invocations of different threads may be reordered regardless of the real time.
+/// The init and post parts still run before and after the parallel part.
+/// It reuses the search of [LinearizabilityChecker] with the happens-before graph restricted to the program order.
+pub struct SequentialConsistencyChecker<Seq> {
+    _seq: PhantomData<Seq>,
+}
+
+impl<Seq> SequentialConsistencyChecker<Seq>
+where
+    Seq: SequentialSpec,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// Checks if the given execution is sequentially consistent with respect to the given sequential specification `Seq`.
+    pub fn check(execution: &Execution<Seq::Op, Seq::Ret>) -> bool
+    where
+        Seq: Default,
+    {
+        Self::check_with(execution, &Seq::default)
+    }
+
+    /// Same as [check](SequentialConsistencyChecker::check) but creates the sequential specification with `new_seq`.
+    pub fn check_with(execution: &Execution<Seq::Op, Seq::Ret>, new_seq: &dyn Fn() -> Seq) -> bool {
+        Self::checker(execution, new_seq).check_init_part()
+    }
+
+    /// Same as [check_with](SequentialConsistencyChecker::check_with) but gives up once `timeout` elapses.
+    /// Returns [None] if it did.
+    pub fn check_with_timeout(
+        execution: &Execution<Seq::Op, Seq::Ret>,
+        new_seq: &dyn Fn() -> Seq,
+        timeout: Duration,
+    ) -> Option<bool> {
+        Self::checker(execution, new_seq).check_within(timeout)
+    }
+
+    fn checker<'e>(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+    ) -> LinearizabilityChecker<'e, Seq> {
+        let intervals = execution.intervals();
+        LinearizabilityChecker::<Seq>::with_order(execution, new_seq, |a, b| {
+            let (inv_a, inv_b) = (&intervals[a], &intervals[b]);
+            inv_a.thread_id == inv_b.thread_id && inv_a.return_timestamp < inv_b.call_timestamp
+        })
+    }
+}
+
+/// The quiescent consistency checker.
+///
+/// Quiescent consistency is weaker than linearizability: the parallel part is split into segments
+/// at quiescent points (timestamps at which no invocation is pending), and only the order of the segments
+/// must be preserved. The invocations within a segment may be reordered arbitrarily, even the ones of the same thread.
+/// The init and post parts still run before and after the parallel part.
+pub struct QuiescentConsistencyChecker<Seq> {
+    _seq: PhantomData<Seq>,
+}
+
+impl<Seq> QuiescentConsistencyChecker<Seq>
+where
+    Seq: SequentialSpec,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// Checks if the given execution is quiescently consistent with respect to the given sequential specification `Seq`.
+    pub fn check(execution: &Execution<Seq::Op, Seq::Ret>) -> bool
+    where
+        Seq: Default,
+    {
+        Self::check_with(execution, &Seq::default)
+    }
+
+    /// Same as [check](QuiescentConsistencyChecker::check) but creates the sequential specification with `new_seq`.
+    pub fn check_with(execution: &Execution<Seq::Op, Seq::Ret>, new_seq: &dyn Fn() -> Seq) -> bool {
+        Self::checker(execution, new_seq).check_init_part()
+    }
+
+    /// Same as [check_with](QuiescentConsistencyChecker::check_with) but gives up once `timeout` elapses.
+    /// Returns [None] if it did.
+    pub fn check_with_timeout(
+        execution: &Execution<Seq::Op, Seq::Ret>,
+        new_seq: &dyn Fn() -> Seq,
+        timeout: Duration,
+    ) -> Option<bool> {
+        Self::checker(execution, new_seq).check_within(timeout)
+    }
+
+    fn checker<'e>(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+    ) -> LinearizabilityChecker<'e, Seq> {
+        let intervals: Vec<_> = execution
+            .intervals()
+            .iter()
+            .map(|inv| (inv.call_timestamp, inv.return_timestamp))
+            .collect();
+        let mut segment_of = vec![0; intervals.len()];
+        for (segment_id, segment) in quiescent_segments(&intervals).iter().enumerate() {
+            for &inv_id in segment {
+                segment_of[inv_id] = segment_id;
+            }
+        }
+
+        LinearizabilityChecker::<Seq>::with_order(execution, new_seq, |a, b| {
+            segment_of[a] < segment_of[b]
+        })
+    }
+}
+
+/// The result of [check_components](LinearizabilityChecker::check_components).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalityReport<K> {
+    /// Whether the sub-history of each component is linearizable, ordered by the component.
+    pub components: Vec<(K, bool)>,
+    /// Whether the whole history is linearizable.
+    pub combined: bool,
+}
+
+impl<K> LocalityReport<K> {
+    /// The components whose sub-histories are not linearizable.
+    pub fn failed_components(&self) -> impl Iterator<Item = &K> {
+        self.components
+            .iter()
+            .filter(|(_, linearizable)| !linearizable)
+            .map(|(component, _)| component)
+    }
+
+    /// Whether every component is linearizable on its own but the whole history is not.
+    ///
+    /// Linearizability is local, so this means that the components are not independent:
+    /// the sequential specification relates the operations of different components.
+    pub fn is_interference(&self) -> bool {
+        !self.combined && self.failed_components().next().is_none()
+    }
+}
+
+impl<'e, Seq> LinearizabilityChecker<'e, Seq>
+where
+    Seq: SequentialSpec + Default,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq + Clone,
+{
+    /// Checks the sub-history of each component of a composed data structure independently
+    /// and then the whole history. `component` maps an operation to the component it acts on.
+    ///
+    /// Each sub-history is checked against a fresh `Seq` which only receives the operations of the component.
+    /// It is useful for debugging layered data structures: a failing component points at the broken layer.
+    pub fn check_components<K: Ord + Clone>(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        component: impl Fn(&Seq::Op) -> K,
+    ) -> LocalityReport<K> {
+        let keys: BTreeSet<K> = execution
+            .init_part
+            .iter()
+            .map(|inv| component(&inv.op))
+            .chain(execution.parallel_part.iter().map(|inv| component(&inv.op)))
+            .chain(execution.post_part.iter().map(|inv| component(&inv.op)))
+            .collect();
+
+        let components = keys
+            .into_iter()
+            .map(|key| {
+                let sub_execution = execution.filter(|op| component(op) == key);
+                let linearizable = LinearizabilityChecker::<Seq>::check(&sub_execution);
+                (key, linearizable)
+            })
+            .collect();
+
+        LocalityReport {
+            components,
+            combined: Self::check(execution),
+        }
+    }
+}
+
+impl<'e, Seq> LinearizabilityChecker<'e, Seq>
+where
+    Seq: PartitionableSpec + Default,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq + Clone,
+{
+    /// Same as [check](LinearizabilityChecker::check) but checks the sub-history of each
+    /// [partition](PartitionableSpec) separately against a fresh `Seq`.
+    ///
+    /// The search is exponential in the length of the history, so checking many short sub-histories
+    /// is much faster than checking the whole one, e.g. for maps with many keys.
+    pub fn check_partitioned(execution: &'e Execution<Seq::Op, Seq::Ret>) -> bool {
+        Self::check_partitioned_until(execution, &Seq::default, None) == Some(true)
+    }
+
+    /// Same as [check_partitioned](LinearizabilityChecker::check_partitioned) but creates the sequential specifications
+    /// with `new_seq` and gives up at `deadline`, if any. Returns [None] if it did.
+    fn check_partitioned_until(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &dyn Fn() -> Seq,
+        deadline: Option<Instant>,
+    ) -> Option<bool> {
+        let keys: BTreeSet<Seq::Key> = execution
+            .init_part
+            .iter()
+            .map(|inv| Seq::partition_key(&inv.op))
+            .chain(
+                execution
+                    .parallel_part
+                    .iter()
+                    .map(|inv| Seq::partition_key(&inv.op)),
+            )
+            .chain(
+                execution
+                    .pending
+                    .iter()
+                    .map(|inv| Seq::partition_key(&inv.op)),
+            )
+            .chain(
+                execution
+                    .post_part
+                    .iter()
+                    .map(|inv| Seq::partition_key(&inv.op)),
+            )
+            .collect();
+
+        let mut verdict = Some(true);
+        for key in keys {
+            let sub_execution = execution.filter(|op| Seq::partition_key(op) == key);
+            match LinearizabilityChecker::<Seq>::check_until(&sub_execution, new_seq, deadline) {
+                Some(true) => {}
+                Some(false) => return Some(false),
+                None => verdict = None, // another partition may still be not linearizable
+            }
+        }
+        verdict
+    }
+}
+
+static FAST_PATH_HITS: AtomicUsize = AtomicUsize::new(0);
+static FAST_PATH_MISSES: AtomicUsize = AtomicUsize::new(0);
+
+/// How often the executions checked so far by this process linearized in a trivial order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FastPathStats {
+    /// The number of executions linearized in a trivial order.
+    pub hits: usize,
+    /// The number of executions that needed the search.
+    pub misses: usize,
+}
+
+/// Returns how often the [LinearizabilityChecker] found a linearization without the search.
+pub fn fast_path_stats() -> FastPathStats {
+    FastPathStats {
+        hits: FAST_PATH_HITS.load(Ordering::Relaxed),
+        misses: FAST_PATH_MISSES.load(Ordering::Relaxed),
+    }
+}
+
+/// The result of [check_bounded](LinearizabilityChecker::check_bounded).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoundedVerdict {
+    /// A linearization was found.
+    Linearizable,
+    /// The search finished without finding a linearization.
+    NotLinearizable,
+    /// The search gave up before finding a linearization.
+    Unknown,
+}
+
+impl<'e, Seq> LinearizabilityChecker<'e, Seq>
+where
+    Seq: SequentialSpec,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// A bounded version of [check](LinearizabilityChecker::check) for histories too long for the exhaustive search.
+    ///
+    /// It first tries the cheap orders of the parallel part: by the call timestamps, by the return timestamps,
+    /// thread by thread and round-robin between the threads. If none of them matches, it backtracks over all the orders, but gives up
+    /// after visiting `max_nodes` nodes of the search tree. Pending invocations are left out of the cheap orders.
+    pub fn check_bounded(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        max_nodes: usize,
+    ) -> BoundedVerdict
+    where
+        Seq: Default,
+    {
+        Self::check_bounded_with(execution, &Seq::default, max_nodes)
+    }
+
+    /// Same as [check_bounded](LinearizabilityChecker::check_bounded) but creates the sequential specification with `new_seq`.
+    pub fn check_bounded_with(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        new_seq: &'e dyn Fn() -> Seq,
+        max_nodes: usize,
+    ) -> BoundedVerdict {
+        let mut checker = Self::new(execution, new_seq);
+        if checker.linearizes_trivially() {
+            return BoundedVerdict::Linearizable;
+        }
+
+        checker.nodes_left = Some(max_nodes);
+        match checker.check_giving_up() {
+            Some(true) => BoundedVerdict::Linearizable,
+            Some(false) => BoundedVerdict::NotLinearizable,
+            None => BoundedVerdict::Unknown,
+        }
+    }
+
+    /// Whether one of the [cheap orders](LinearizabilityChecker::cheap_orders) matches the sequential specification.
+    /// Counts the [fast path](fast_path_stats) hits and misses.
+    fn linearizes_trivially(&self) -> bool {
+        self.trivial_linearization().is_some()
+    }
+
+    /// The first of the [cheap orders](LinearizabilityChecker::cheap_orders) that is a linearization, if any.
+    fn trivial_linearization(&self) -> Option<Vec<InvocationId>> {
+        let linearization =
+            (self.cheap_orders().into_iter()).find(|order| self.order_matches(order));
+        let hit = linearization.is_some();
+        let counter = if hit {
+            &FAST_PATH_HITS
+        } else {
+            &FAST_PATH_MISSES
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        linearization
+    }
+
+    /// The orders of the completed invocations of the parallel part by the call timestamps, by the return timestamps,
+    /// thread by thread and round-robin between the threads, leaving out the latter two if they violate
+    /// the happens-before relation.
+    fn cheap_orders(&self) -> Vec<Vec<InvocationId>> {
+        let parallel_part = &self.execution.parallel_part;
+        let mut by_call: Vec<_> = (0..parallel_part.len()).collect();
+        by_call.sort_by_key(|&inv_id| parallel_part[inv_id].call_timestamp);
+        let mut by_return = by_call.clone();
+        by_return.sort_by_key(|&inv_id| parallel_part[inv_id].return_timestamp);
+        let mut by_thread = by_call.clone();
+        by_thread.sort_by_key(|&inv_id| parallel_part[inv_id].thread_id);
+
+        // the i-th invocation of every thread, then the (i + 1)-th one and so on
+        let mut index_in_thread = HashMap::new();
+        let mut round_robin: Vec<_> = by_thread
+            .iter()
+            .map(|&inv_id| {
+                let index = index_in_thread
+                    .entry(parallel_part[inv_id].thread_id)
+                    .or_insert(0);
+                *index += 1;
+                (*index, inv_id)
+            })
+            .collect();
+        round_robin.sort();
+        let round_robin = round_robin.into_iter().map(|(_, inv_id)| inv_id).collect();
+
+        let mut orders = vec![by_call, by_return];
+        orders.extend(
+            [by_thread, round_robin]
+                .into_iter()
+                .filter(|order| self.respects_hb(order)),
+        );
+        orders
+    }
+
+    /// Whether the `order` of the completed invocations of the parallel part respects the happens-before relation.
+    fn respects_hb(&self, order: &[InvocationId]) -> bool {
+        let mut position = vec![0; self.execution.parallel_part.len()];
+        for (index, &inv_id) in order.iter().enumerate() {
+            position[inv_id] = index;
+        }
+        order.iter().all(|&inv_id| {
+            self.hb[inv_id]
+                .iter()
+                .filter(|&&next| next < position.len())
+                .all(|&next| position[inv_id] < position[next])
+        })
+    }
+
+    /// Whether the execution matches the sequential specification when the parallel part is linearized in the `order`.
+    fn order_matches(&self, order: &[InvocationId]) -> bool {
+        let mut seq_spec = (self.new_seq)();
+        let parallel_part = &self.execution.parallel_part;
+        self.execution
+            .init_part
+            .iter()
+            .map(|inv| (&inv.op, &inv.ret))
+            .chain(order.iter().map(|&inv_id| {
+                let inv = &parallel_part[inv_id];
+                (&inv.op, &inv.ret)
+            }))
+            .chain(
+                self.execution
+                    .post_part
+                    .iter()
+                    .map(|inv| (&inv.op, &inv.ret)),
+            )
+            .all(|(op, ret)| Seq::ret_matches(&seq_spec.exec(op.clone()), ret))
+    }
+}
+
+/// The result of [check_sampled](LinearizabilityChecker::check_sampled).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampledVerdict {
+    /// A linearization was found.
+    Linearizable,
+    /// A sampled order had a single possible continuation at every step and still failed,
+    /// so no linearization exists.
+    NotLinearizable,
+    /// No linearization was found within the effort.
+    ProbablyNotLinearizable {
+        /// The number of invocations of the parallel part in the longest linearizable prefix that was sampled.
+        longest_prefix: usize,
+    },
+}
+
+impl<'e, Seq> LinearizabilityChecker<'e, Seq>
+where
+    Seq: SequentialSpec + Default + Clone,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// An approximate version of [check](LinearizabilityChecker::check) for histories too long for the exhaustive search.
+    ///
+    /// It samples up to `samples` random linearization orders respecting the happens-before relation.
+    /// Each order is built greedily: the next invocation is chosen at random among the minimal ones
+    /// whose result matches the sequential specification. `samples` is the effort.
+    /// Unlike the exhaustive search, failing to find a linearization is not a proof unless the order was forced.
+    /// Executions with pending invocations are checked with [check](LinearizabilityChecker::check) instead.
+    pub fn check_sampled(
+        execution: &'e Execution<Seq::Op, Seq::Ret>,
+        samples: usize,
+    ) -> SampledVerdict {
+        if !execution.pending.is_empty() {
+            return if Self::check(execution) {
+                SampledVerdict::Linearizable
+            } else {
+                SampledVerdict::NotLinearizable
+            };
+        }
+
+        let mut init_state = Seq::default();
+        let init_part_matches = execution
+            .init_part
+            .iter()
+            .all(|inv| Seq::ret_matches(&init_state.exec(inv.op.clone()), &inv.ret));
+        if !init_part_matches {
+            return SampledVerdict::NotLinearizable;
+        }
+
+        let mut rng = SplitMix64(0);
+        let mut longest_prefix = 0;
+        for _ in 0..samples {
+            match sample_linearization(execution, init_state.clone(), &mut rng) {
+                Ok(()) => return SampledVerdict::Linearizable,
+                Err((_, true)) => return SampledVerdict::NotLinearizable,
+                Err((prefix, false)) => longest_prefix = longest_prefix.max(prefix),
+            }
+        }
+        SampledVerdict::ProbablyNotLinearizable { longest_prefix }
+    }
+}
+
+/// A small deterministic generator choosing the sampled linearizations.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Builds a random linearization of the parallel part and checks the post part after it.
+///
+/// On failure, returns the length of the linearized prefix and whether every step was forced.
+fn sample_linearization<Seq>(
+    execution: &Execution<Seq::Op, Seq::Ret>,
+    mut state: Seq,
+    rng: &mut SplitMix64,
+) -> Result<(), (usize, bool)>
+where
+    Seq: SequentialSpec + Clone,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    let parallel_part = &execution.parallel_part;
+
+    // an invocation is minimal iff it is called before every pending invocation returns
+    let mut by_call: BTreeSet<_> = parallel_part
+        .iter()
+        .enumerate()
+        .map(|(inv_id, inv)| (inv.call_timestamp, inv_id))
+        .collect();
+    let mut by_return: BTreeSet<_> = parallel_part
+        .iter()
+        .enumerate()
+        .map(|(inv_id, inv)| (inv.return_timestamp, inv_id))
+        .collect();
+
+    let mut forced = true;
+    let mut prefix = 0;
+    while let Some(&(min_return, _)) = by_return.first() {
+        let candidates: Vec<_> = by_call
+            .iter()
+            .take_while(|&&(call, _)| call <= min_return)
+            .map(|&(_, inv_id)| inv_id)
+            .collect();
+
+        let mut matching: Vec<_> = candidates
+            .into_iter()
+            .filter_map(|inv_id| {
+                let inv = &parallel_part[inv_id];
+                let mut next_state = state.clone();
+                Seq::ret_matches(&next_state.exec(inv.op.clone()), &inv.ret)
+                    .then_some((inv_id, next_state))
+            })
+            .collect();
+        // the order stays forced as long as there is at most one matching candidate
+        forced &= matching.len() <= 1;
+
+        if matching.is_empty() {
+            return Err((prefix, forced));
+        }
+        let (inv_id, next_state) = matching.swap_remove(rng.next_u64() as usize % matching.len());
+        let inv = &parallel_part[inv_id];
+        by_call.remove(&(inv.call_timestamp, inv_id));
+        by_return.remove(&(inv.return_timestamp, inv_id));
+        state = next_state;
+        prefix += 1;
+    }
+
+    let post_part_matches = execution
+        .post_part
+        .iter()
+        .all(|inv| Seq::ret_matches(&state.exec(inv.op.clone()), &inv.ret));
+    if post_part_matches {
+        Ok(())
+    } else {
+        Err((prefix, forced))
+    }
+}
+
+/// Collects all the distinct states reachable by linearizing the rest of the segment starting from `state`.
+fn collect_reachable_states<Seq>(
+    segment: &[&ParallelInvocation<Seq::Op, Seq::Ret>],
+    linearized: &mut [bool],
+    state: Seq,
+    reachable_states: &mut Vec<Seq>,
+) where
+    Seq: SequentialSpec + Clone + PartialEq,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    if linearized.iter().all(|&linearized| linearized) {
+        if !reachable_states.contains(&state) {
+            reachable_states.push(state);
+        }
+        return;
+    }
+
+    for (i, inv) in segment.iter().enumerate() {
+        let is_minimal = !linearized[i]
+            && segment
+                .iter()
+                .zip(linearized.iter())
+                .all(|(other, &linearized)| {
+                    linearized || other.return_timestamp > inv.call_timestamp
+                });
+        if !is_minimal {
+            continue;
+        }
+
+        let mut next_state = state.clone();
+        if Seq::ret_matches(&next_state.exec(inv.op.clone()), &inv.ret) {
+            linearized[i] = true;
+            collect_reachable_states(segment, linearized, next_state, reachable_states);
+            linearized[i] = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution;
+    use crate::recorder::*;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Op<T> {
+        Push(T),
+        Pop,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Ret<T> {
+        Push,
+        Pop(Option<T>),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct SequentialStack<T> {
+        stack: Vec<T>,
+    }
+
+    impl<T> Default for SequentialStack<T> {
+        fn default() -> Self {
+            Self { stack: Vec::new() }
+        }
+    }
+
+    impl<T> SequentialSpec for SequentialStack<T> {
+        type Op = Op<T>;
+        type Ret = Ret<T>;
+
+        fn exec(&mut self, op: Self::Op) -> Self::Ret {
+            match op {
+                Op::Push(value) => {
+                    self.stack.push(value);
+                    Ret::Push
+                }
+                Op::Pop => Ret::Pop(self.stack.pop()),
+            }
+        }
+    }
+
+    impl<T: Clone> UndoableSequentialSpec for SequentialStack<T> {
+        /// The operation reverting the executed one, if any.
+        type UndoToken = Option<Op<T>>;
+
+        fn exec_undoable(&mut self, op: Self::Op) -> (Self::Ret, Self::UndoToken) {
+            match op {
+                Op::Push(value) => {
+                    self.stack.push(value);
+                    (Ret::Push, Some(Op::Pop))
+                }
+                Op::Pop => {
+                    let value = self.stack.pop();
+                    (Ret::Pop(value.clone()), value.map(Op::Push))
+                }
+            }
+        }
+
+        fn undo(&mut self, token: Self::UndoToken) {
+            if let Some(op) = token {
+                self.exec(op);
+            }
+        }
+    }
+
+    #[test]
+    fn init_and_post_parts_are_sequentional() {
+        let mut recorder = record_init_part();
+        recorder.record(Op::Push(1), || Ret::Push);
+        recorder.record(Op::Push(2), || Ret::Push);
+
+        let mut recorder = recorder.record_post_part();
+        recorder.record(Op::Pop, || Ret::Pop(Some(2)));
+        recorder.record(Op::Pop, || Ret::Pop(Some(1)));
+
+        let execution = recorder.finish();
+
+        assert!(LinearizabilityChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+    }
+
+    #[test]
+    fn parallel_part_overlapping() {
+        let execution = execution! {
+            init: [Op::Push(1) => Ret::Push, Op::Push(2) => Ret::Push],
+            parallel: [
+                0 @ 4..6: Op::Pop => Ret::Pop(Some(2)),
+                1 @ 5..7: Op::Pop => Ret::Pop(Some(1)),
+            ],
+        };
+
+        assert!(LinearizabilityChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+    }
+
+    #[test]
+    fn parallel_part_does_not_violate_happens_before() {
+        let execution = execution! {
+            parallel: [
+                0 @ 4..6: Op::Pop => Ret::Pop(Some(1)),
+                0 @ 7..9: Op::Push(1) => Ret::Push,
+                1 @ 5..8: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        assert!(!LinearizabilityChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+    }
+
+    #[test]
+    fn witness_is_a_linearization() {
+        // only the pending pop of 2 empties the stack for the post part, so no cheap order matches
+        let execution = execution! {
+            parallel: [
+                0 @ 0..10: Op::Push(1) => Ret::Push,
+                1 @ 1..2: Op::Push(2) => Ret::Push,
+            ],
+            post: [Op::Pop => Ret::Pop(Some(1)), Op::Pop => Ret::Pop(None)],
+        }
+        .with_pending(vec![(1, 3, Op::Pop)]);
+
+        let witness =
+            LinearizabilityChecker::<SequentialStack<i32>>::check_with_witness(&execution).unwrap();
+        assert!(witness == vec![0, 1, 2] || witness == vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn no_witness_for_non_linearizable() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: Op::Push(1) => Ret::Push,
+                1 @ 2..3: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        let witness =
+            LinearizabilityChecker::<SequentialStack<i32>>::check_with_witness(&execution);
+        assert_eq!(witness, None);
+    }
+
+    #[test]
+    fn check_gives_up_after_timeout() {
+        // only pushing 2 before 1 matches, which no cheap order does
+        let execution = execution! {
+            parallel: [
+                0 @ 0..10: Op::Push(1) => Ret::Push,
+                1 @ 1..2: Op::Push(2) => Ret::Push,
+                1 @ 3..4: Op::Pop => Ret::Pop(Some(1)),
+            ],
+        };
+        let new_seq = SequentialStack::default;
+
+        assert_eq!(
+            LinearizabilityChecker::check_with_timeout(&execution, &new_seq, Duration::ZERO),
+            None
+        );
+        assert_eq!(
+            LinearizabilityChecker::check_with_timeout(&execution, &new_seq, Duration::MAX),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn bounded_check_tries_cheap_orders_first() {
+        let execution = execution! {
+            init: [Op::Push(1) => Ret::Push],
+            parallel: [
+                0 @ 0..2: Op::Push(2) => Ret::Push,
+                1 @ 1..3: Op::Pop => Ret::Pop(Some(2)),
+            ],
+        };
+
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<i32>>::check_bounded(&execution, 0),
+            BoundedVerdict::Linearizable
+        );
+    }
+
+    #[test]
+    fn round_robin_order_is_tried() {
+        // neither the call nor the return order pops after the right push
+        let execution = execution! {
+            parallel: [
+                0 @ 1..3: Op::Push(1) => Ret::Push,
+                0 @ 4..9: Op::Push(2) => Ret::Push,
+                1 @ 0..5: Op::Pop => Ret::Pop(Some(1)),
+                1 @ 6..8: Op::Pop => Ret::Pop(Some(2)),
+            ],
+        };
+        let checker = LinearizabilityChecker::new(&execution, &SequentialStack::<i32>::default);
+
+        let before = fast_path_stats();
+        assert!(checker.linearizes_trivially());
+        assert!(fast_path_stats().hits > before.hits);
+    }
+
+    #[test]
+    fn bounded_check_gives_up_after_max_nodes() {
+        // only pushing 2 before 1 matches, which no cheap order does
+        let execution = execution! {
+            parallel: [
+                0 @ 0..10: Op::Push(1) => Ret::Push,
+                1 @ 1..2: Op::Push(2) => Ret::Push,
+                1 @ 3..4: Op::Pop => Ret::Pop(Some(1)),
+            ],
+        };
+
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<i32>>::check_bounded(&execution, 2),
+            BoundedVerdict::Unknown
+        );
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<i32>>::check_bounded(&execution, 100),
+            BoundedVerdict::Linearizable
+        );
+    }
+
+    #[test]
+    fn bounded_check_rejects_non_linearizable() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: Op::Push(1) => Ret::Push,
+                1 @ 2..3: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<i32>>::check_bounded(&execution, 100),
+            BoundedVerdict::NotLinearizable
+        );
+    }
+
+    #[test]
+    fn diagnose_reports_longest_prefix() {
+        let execution = execution! {
+            parallel: [
+                0 @ 4..6: Op::Pop => Ret::Pop(Some(1)),
+                0 @ 7..9: Op::Push(1) => Ret::Push,
+                1 @ 5..8: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        let diagnosis = LinearizabilityChecker::<SequentialStack<i32>>::diagnose(&execution);
+        assert_eq!(
+            diagnosis,
+            Some(Diagnosis {
+                prefix: vec![(Op::Pop, Ret::Pop(None))],
+                rejected: vec![Rejection {
+                    op: Op::Pop,
+                    thread_id: Some(0),
+                    expected: Ret::Pop(None),
+                    actual: Ret::Pop(Some(1)),
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn diagnose_reports_post_part_mismatch() {
+        let execution = execution! {
+            init: [Op::Push(1) => Ret::Push],
+            parallel: [],
+            post: [Op::Pop => Ret::Pop(Some(2))],
+        };
+
+        let diagnosis = LinearizabilityChecker::<SequentialStack<i32>>::diagnose(&execution);
+        assert_eq!(
+            diagnosis,
+            Some(Diagnosis {
+                prefix: vec![(Op::Push(1), Ret::Push)],
+                rejected: vec![Rejection {
+                    op: Op::Pop,
+                    thread_id: None,
+                    expected: Ret::Pop(Some(1)),
+                    actual: Ret::Pop(Some(2)),
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn explain_continues_after_the_prefix() {
+        let execution = execution! {
+            parallel: [
+                0 @ 4..6: Op::Pop => Ret::Pop(Some(1)),
+                0 @ 7..9: Op::Push(1) => Ret::Push,
+                1 @ 5..8: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        let explanation = execution.explain::<SequentialStack<i32>>().unwrap();
+        assert_eq!(explanation.matched, 1);
+        assert_eq!(
+            explanation.steps,
+            vec![
+                ExplainedStep {
+                    op: Op::Pop,
+                    thread_id: Some(1),
+                    expected: Ret::Pop(None),
+                    actual: Some(Ret::Pop(None)),
+                },
+                ExplainedStep {
+                    op: Op::Pop,
+                    thread_id: Some(0),
+                    expected: Ret::Pop(None),
+                    actual: Some(Ret::Pop(Some(1))),
+                },
+                ExplainedStep {
+                    op: Op::Push(1),
+                    thread_id: Some(0),
+                    expected: Ret::Push,
+                    actual: Some(Ret::Push),
+                },
+            ]
+        );
+        assert_eq!(explanation.rejected.len(), 1);
+    }
+
+    #[test]
+    fn diagnose_accepts_linearizable() {
+        let execution = execution! {
+            init: [Op::Push(1) => Ret::Push, Op::Push(2) => Ret::Push],
+            parallel: [
+                0 @ 4..6: Op::Pop => Ret::Pop(Some(2)),
+                1 @ 5..7: Op::Pop => Ret::Pop(Some(1)),
+            ],
+        };
+
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<i32>>::diagnose(&execution),
+            None
+        );
+    }
+
+    #[test]
+    fn pending_invocation_may_take_effect() {
+        let execution = execution! {
+            parallel: [0 @ 0..1: Op::Push(1) => Ret::Push],
+            pending: [1 @ 2..: Op::Pop],
+            post: [Op::Pop => Ret::Pop(None)],
+        };
+
+        assert!(LinearizabilityChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+    }
+
+    #[test]
+    fn pending_invocation_may_be_left_out() {
+        let execution = execution! {
+            parallel: [0 @ 0..1: Op::Push(1) => Ret::Push],
+            pending: [1 @ 2..: Op::Pop],
+            post: [Op::Pop => Ret::Pop(Some(1))],
+        };
+
+        assert!(LinearizabilityChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+    }
+
+    #[test]
+    fn pending_invocation_does_not_violate_happens_before() {
+        let execution = execution! {
+            parallel: [0 @ 0..1: Op::Pop => Ret::Pop(Some(1))],
+            pending: [1 @ 2..: Op::Push(1)],
+        };
+
+        assert!(!LinearizabilityChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+        assert!(SequentialConsistencyChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+    }
+
+    #[test]
+    fn cancelled_invocation_takes_effect_before_cancellation_or_never() {
+        let took_effect = execution! {
+            parallel: [0 @ 2..3: Op::Pop => Ret::Pop(Some(1))],
+            cancelled: [0 @ 0..1: Op::Push(1)],
+        };
+        let left_out = execution! {
+            parallel: [0 @ 2..3: Op::Pop => Ret::Pop(None)],
+            cancelled: [0 @ 0..1: Op::Push(1)],
+        };
+        // unlike a pending pop, the cancelled one can't take the value pushed after the cancellation
+        let took_effect_late = execution! {
+            parallel: [1 @ 2..3: Op::Push(1) => Ret::Push],
+            cancelled: [0 @ 0..1: Op::Pop],
+            post: [Op::Pop => Ret::Pop(None)],
+        };
+
+        for backend in [
+            CheckerBackend::Backtracking,
+            CheckerBackend::Memoized,
+            CheckerBackend::Jit,
+        ] {
+            let check = |execution| {
+                LinearizabilityChecker::<SequentialStack<i32>>::check_using(execution, backend)
+            };
+            assert!(check(&took_effect));
+            assert!(check(&left_out));
+            assert!(!check(&took_effect_late));
+        }
+        assert!(LinearizabilityChecker::<SequentialStack<i32>>::check_undoable(&left_out));
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<i32>>::check_with_witness(&left_out),
+            Some(vec![0])
+        );
+    }
+
+    #[test]
+    fn decomposed_check_keeps_every_reachable_state() {
+        let execution = execution! {
+            parallel: [
+                // the first segment: pushes overlap, so both orders are possible
+                0 @ 0..2: Op::Push(1) => Ret::Push,
+                1 @ 1..3: Op::Push(2) => Ret::Push,
+                // the second segment: only one of the orders is consistent with the pops
+                0 @ 4..5: Op::Pop => Ret::Pop(Some(2)),
+                1 @ 6..7: Op::Pop => Ret::Pop(Some(1)),
+            ],
+        };
+
+        assert_eq!(execution.parallel_part.quiescent_segments().len(), 3);
+        assert!(LinearizabilityChecker::<SequentialStack<i32>>::check_decomposed(&execution));
+    }
+
+    #[test]
+    fn decomposed_check_rejects_non_linearizable() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: Op::Push(1) => Ret::Push,
+                1 @ 2..3: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        assert!(!LinearizabilityChecker::<SequentialStack<i32>>::check_decomposed(&execution));
+    }
+
+    #[test]
+    fn memoized_check_agrees_with_check() {
+        let linearizable = execution! {
+            parallel: [
+                0 @ 0..2: Op::Push(1) => Ret::Push,
+                1 @ 1..3: Op::Push(2) => Ret::Push,
+                0 @ 4..5: Op::Pop => Ret::Pop(Some(2)),
+            ],
+            pending: [1 @ 6..: Op::Pop],
+            post: [Op::Pop => Ret::Pop(None)],
+        };
+        let non_linearizable = execution! {
+            parallel: [
+                0 @ 4..6: Op::Pop => Ret::Pop(Some(1)),
+                0 @ 7..9: Op::Push(1) => Ret::Push,
+                1 @ 5..8: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        assert!(LinearizabilityChecker::<SequentialStack<i32>>::check_memoized(&linearizable));
+        assert!(!LinearizabilityChecker::<SequentialStack<i32>>::check_memoized(&non_linearizable));
+    }
+
+    #[test]
+    fn memoized_check_prunes_commuting_orders() {
+        // the plain search would try all 16! orders of the pushes
+        let execution = Execution::from_intervals(
+            vec![],
+            (0..16)
+                .map(|thread_id| (thread_id, thread_id, 16 + thread_id, Op::Push(1), Ret::Push))
+                .collect(),
+            vec![(Op::Pop, Ret::Pop(Some(2)))],
+        );
+
+        assert!(!LinearizabilityChecker::<SequentialStack<i32>>::check_memoized(&execution));
+    }
+
+    #[test]
+    fn jit_check_agrees_with_check() {
+        let linearizable = execution! {
+            parallel: [
+                0 @ 0..2: Op::Push(1) => Ret::Push,
+                1 @ 1..3: Op::Push(2) => Ret::Push,
+                0 @ 4..5: Op::Pop => Ret::Pop(Some(2)),
+            ],
+            pending: [1 @ 6..: Op::Pop],
+            post: [Op::Pop => Ret::Pop(None)],
+        };
+        let non_linearizable = execution! {
+            parallel: [
+                0 @ 4..6: Op::Pop => Ret::Pop(Some(1)),
+                0 @ 7..9: Op::Push(1) => Ret::Push,
+                1 @ 5..8: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        for backend in [
+            CheckerBackend::Backtracking,
+            CheckerBackend::Memoized,
+            CheckerBackend::Jit,
+        ] {
+            assert!(LinearizabilityChecker::<SequentialStack<i32>>::check_using(
+                &linearizable,
+                backend
+            ));
+            assert!(
+                !LinearizabilityChecker::<SequentialStack<i32>>::check_using(
+                    &non_linearizable,
+                    backend
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn jit_check_handles_long_histories() {
+        // each thread pushes and pops its own value, overlapping the neighbouring threads
+        let execution = Execution::from_intervals(
+            vec![],
+            (0..200)
+                .flat_map(|i| {
+                    [
+                        (i % 4, 4 * i, 4 * i + 5, Op::Push(i), Ret::Push),
+                        (i % 4, 4 * i + 6, 4 * i + 7, Op::Pop, Ret::Pop(Some(i))),
+                    ]
+                })
+                .collect(),
+            vec![(Op::Pop, Ret::Pop(None))],
+        );
+
+        assert!(LinearizabilityChecker::<SequentialStack<usize>>::check_jit(
+            &execution
+        ));
+    }
+
+    /// A set of the pushed values, where pop removes the largest one.
+    /// It stores the values in the order of the pushes, so commuting pushes lead to different states.
+    #[derive(Debug, Clone, Default)]
+    struct InsertionOrderSet {
+        values: Vec<u8>,
+    }
+
+    impl SequentialSpec for InsertionOrderSet {
+        type Op = Op<u8>;
+        type Ret = Ret<u8>;
+
+        fn exec(&mut self, op: Self::Op) -> Self::Ret {
+            match op {
+                Op::Push(value) => {
+                    if !self.values.contains(&value) {
+                        self.values.push(value);
+                    }
+                    Ret::Push
+                }
+                Op::Pop => {
+                    let max = self.values.iter().copied().max();
+                    self.values.retain(|&value| Some(value) != max);
+                    Ret::Pop(max)
+                }
+            }
+        }
+    }
+
+    impl StateEquivalence for InsertionOrderSet {
+        fn equivalent(&self, other: &Self) -> bool {
+            let sorted = |values: &[u8]| values.iter().copied().collect::<BTreeSet<_>>();
+            sorted(&self.values) == sorted(&other.values)
+        }
+    }
+
+    #[test]
+    fn pruned_check_prunes_equivalent_states() {
+        // every order of the pushes leads to a different representation of the same set
+        let execution = |max| {
+            Execution::from_intervals(
+                vec![],
+                (0..16)
+                    .map(|value| (value, value, 16 + value, Op::Push(value as u8), Ret::Push))
+                    .collect(),
+                vec![(Op::Pop, Ret::Pop(Some(max)))],
+            )
+        };
+
+        assert!(LinearizabilityChecker::<InsertionOrderSet>::check_pruned(
+            &execution(15)
+        ));
+        assert!(!LinearizabilityChecker::<InsertionOrderSet>::check_pruned(
+            &execution(16)
+        ));
+    }
+
+    #[test]
+    fn undoable_check_agrees_with_check() {
+        let linearizable = execution! {
+            init: [Op::Push(1) => Ret::Push],
+            parallel: [
+                0 @ 0..2: Op::Pop => Ret::Pop(Some(1)),
+                1 @ 1..3: Op::Pop => Ret::Pop(None),
+                0 @ 4..5: Op::Push(2) => Ret::Push,
+            ],
+            pending: [1 @ 6..: Op::Pop],
+            post: [Op::Pop => Ret::Pop(Some(2))],
+        };
+        let non_linearizable = execution! {
+            parallel: [
+                0 @ 4..6: Op::Pop => Ret::Pop(Some(1)),
+                0 @ 7..9: Op::Push(1) => Ret::Push,
+                1 @ 5..8: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        assert!(LinearizabilityChecker::<SequentialStack<i32>>::check_undoable(&linearizable));
+        assert!(!LinearizabilityChecker::<SequentialStack<i32>>::check_undoable(&non_linearizable));
+    }
+
+    #[test]
+    fn sampled_check_finds_linearization() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..2: Op::Push(1) => Ret::Push,
+                1 @ 1..3: Op::Push(2) => Ret::Push,
+                0 @ 4..5: Op::Pop => Ret::Pop(Some(2)),
+                1 @ 6..7: Op::Pop => Ret::Pop(Some(1)),
+            ],
+        };
+
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<i32>>::check_sampled(&execution, 100),
+            SampledVerdict::Linearizable
+        );
+    }
+
+    #[test]
+    fn sampled_check_rejects_forced_order() {
+        let execution = execution! {
+            parallel: [
+                0 @ 4..6: Op::Pop => Ret::Pop(Some(1)),
+                0 @ 7..9: Op::Push(1) => Ret::Push,
+                1 @ 5..8: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<i32>>::check_sampled(&execution, 100),
+            SampledVerdict::NotLinearizable
+        );
+    }
+
+    #[test]
+    fn sampled_check_is_inconclusive_for_unforced_orders() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..2: Op::Push(1) => Ret::Push,
+                1 @ 1..3: Op::Push(2) => Ret::Push,
+            ],
+            post: [Op::Pop => Ret::Pop(Some(3))],
+        };
+
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<i32>>::check_sampled(&execution, 100),
+            SampledVerdict::ProbablyNotLinearizable { longest_prefix: 2 }
+        );
+    }
+
+    #[test]
+    fn counts_linearizations() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..3: Op::Push(0) => Ret::Push,
+                1 @ 1..4: Op::Push(1) => Ret::Push,
+                2 @ 2..5: Op::Push(2) => Ret::Push,
+            ],
+        };
+
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<usize>>::count_linearizations(&execution, 10),
+            6
+        );
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<usize>>::count_linearizations(&execution, 4),
+            4
+        );
+    }
+
+    #[test]
+    fn counts_only_valid_linearizations() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..2: Op::Push(1) => Ret::Push,
+                1 @ 1..3: Op::Push(2) => Ret::Push,
+            ],
+            post: [Op::Pop => Ret::Pop(Some(2))],
+        };
+
+        assert_eq!(
+            LinearizabilityChecker::<SequentialStack<i32>>::count_linearizations(&execution, 10),
+            1
+        );
+    }
+
+    #[test]
+    fn check_components_reports_failed_component() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: Op::Push(1) => Ret::Push,
+                0 @ 4..5: Op::Pop => Ret::Pop(Some(3)),
+                1 @ 2..3: Op::Push(2) => Ret::Push,
+            ],
+        };
+
+        // the pushes of 2 form their own component
+        let report =
+            LinearizabilityChecker::<SequentialStack<i32>>::check_components(&execution, |op| {
+                matches!(op, Op::Push(2))
+            });
+        assert_eq!(report.components, vec![(false, false), (true, true)]);
+        assert!(!report.combined);
+        assert!(!report.is_interference());
+    }
+
+    #[test]
+    fn check_components_reports_interference() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: Op::Push(1) => Ret::Push,
+                1 @ 2..3: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        // a stack is not a composition of its pushes and pops
+        let report =
+            LinearizabilityChecker::<SequentialStack<i32>>::check_components(&execution, |op| {
+                matches!(op, Op::Pop)
+            });
+        assert_eq!(report.failed_components().count(), 0);
+        assert!(report.is_interference());
+    }
+
+    /// Registers indexed by the first component of the operations.
+    #[derive(Debug, Clone, Default)]
+    struct Registers {
+        values: HashMap<u8, u8>,
+    }
+
+    impl SequentialSpec for Registers {
+        /// Writes the value if any, reads otherwise.
+        type Op = (u8, Option<u8>);
+        /// The value before the operation.
+        type Ret = Option<u8>;
+
+        fn exec(&mut self, (key, value): Self::Op) -> Self::Ret {
+            match value {
+                Some(value) => self.values.insert(key, value),
+                None => self.values.get(&key).copied(),
+            }
+        }
+    }
+
+    impl PartitionableSpec for Registers {
+        type Key = u8;
+
+        fn partition_key(&(key, _): &Self::Op) -> u8 {
+            key
+        }
+    }
+
+    #[test]
+    fn partitioned_check_checks_each_key() {
+        let linearizable = execution! {
+            init: [(0, Some(1)) => None],
+            parallel: [
+                0 @ 0..3: (0, Some(2)) => Some(1),
+                1 @ 1..2: (0, None) => Some(2),
+                1 @ 4..5: (1, None) => None,
+            ],
+            post: [(1, Some(3)) => None],
+        };
+        let non_linearizable = execution! {
+            parallel: [
+                0 @ 0..1: (0, Some(2)) => None,
+                1 @ 2..3: (1, Some(3)) => None,
+                1 @ 4..5: (0, None) => None,
+            ],
+        };
+
+        assert!(LinearizabilityChecker::<Registers>::check_partitioned(
+            &linearizable
+        ));
+        assert!(!LinearizabilityChecker::<Registers>::check_partitioned(
+            &non_linearizable
+        ));
+    }
+
+    #[test]
+    fn spec_checker_checks_with_its_backend() {
+        let linearizable = execution! {
+            parallel: [
+                0 @ 0..3: (0, Some(2)) => None,
+                1 @ 1..2: (0, None) => Some(2),
+            ],
+        };
+        let non_linearizable = execution! {
+            parallel: [
+                0 @ 0..1: (0, Some(2)) => None,
+                1 @ 2..3: (0, None) => None,
+            ],
+        };
+
+        let checker = SpecChecker::partitioned::<Registers>();
+        assert_eq!(checker.backend(), CheckerBackend::Partitioned);
+        assert!(checker.check::<Registers>(&linearizable));
+        assert!(!checker.check::<Registers>(&non_linearizable));
+    }
+
+    #[test]
+    #[should_panic(expected = "the checker is bound to another sequential specification")]
+    fn spec_checker_rejects_another_spec() {
+        let execution = execution! {
+            parallel: [0 @ 0..1: Op::Push(1) => Ret::Push],
+        };
+        SpecChecker::partitioned::<Registers>().check::<SequentialStack<i32>>(&execution);
+    }
+
+    #[test]
+    fn spec_checker_uses_constructor_and_timeout() {
+        let popping = execution! {
+            parallel: [0 @ 0..1: Op::Pop => Ret::Pop(Some(7))],
+        };
+        // only pushing 2 before 1 matches, which no cheap order does
+        let adversarial = execution! {
+            parallel: [
+                0 @ 0..10: Op::Push(1) => Ret::Push,
+                1 @ 1..2: Op::Push(2) => Ret::Push,
+                1 @ 3..4: Op::Pop => Ret::Pop(Some(1)),
+            ],
+        };
+        let new_seq = || SequentialStack { stack: vec![7] };
+
+        for checker in [
+            SpecChecker::backtracking::<SequentialStack<i32>>(),
+            SpecChecker::memoized::<SequentialStack<i32>>(),
+            SpecChecker::jit::<SequentialStack<i32>>(),
+            SpecChecker::undoable::<SequentialStack<i32>>(),
+        ] {
+            let backend = checker.backend();
+            assert!(
+                !checker.check::<SequentialStack<i32>>(&popping),
+                "{backend:?}"
+            );
+            assert_eq!(
+                checker.check_with(&popping, &new_seq, None),
+                Some(true),
+                "{backend:?}"
+            );
+            assert_eq!(
+                checker.check_with(
+                    &adversarial,
+                    &SequentialStack::default,
+                    Some(Duration::ZERO)
+                ),
+                None,
+                "{backend:?}"
+            );
+            assert_eq!(
+                checker.check_with(&adversarial, &SequentialStack::default, Some(Duration::MAX)),
+                Some(true),
+                "{backend:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn sequential_consistency_ignores_real_time_across_threads() {
+        let execution = execution! {
+            parallel: [
+                0 @ 1..2: Op::Push(1) => Ret::Push,
+                1 @ 3..4: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        assert!(!LinearizabilityChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+        assert!(SequentialConsistencyChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+    }
+
+    #[test]
+    fn sequential_consistency_keeps_program_order() {
+        let execution = execution! {
+            parallel: [
+                0 @ 1..2: Op::Push(1) => Ret::Push,
+                0 @ 3..4: Op::Pop => Ret::Pop(None),
+            ],
+        };
+
+        assert!(!SequentialConsistencyChecker::<SequentialStack<i32>>::check(&execution));
+    }
+
+    #[test]
+    fn quiescent_consistency_reorders_within_segment() {
+        let execution = execution! {
+            parallel: [
+                // the pop of the first thread returns before its push is called
+                0 @ 1..2: Op::Pop => Ret::Pop(Some(1)),
+                0 @ 3..5: Op::Push(1) => Ret::Push,
+                1 @ 1..4: Op::Push(2) => Ret::Push,
+                // after the quiescent point
+                1 @ 6..7: Op::Pop => Ret::Pop(Some(2)),
+            ],
+        };
+
+        assert!(!SequentialConsistencyChecker::<SequentialStack<i32>>::check(&execution));
+        assert!(QuiescentConsistencyChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+    }
+
+    #[test]
+    fn quiescent_consistency_keeps_segment_order() {
+        let execution = execution! {
+            parallel: [
+                0 @ 1..2: Op::Pop => Ret::Pop(Some(1)),
+                1 @ 3..4: Op::Push(1) => Ret::Push,
+            ],
+        };
+
+        assert!(!QuiescentConsistencyChecker::<SequentialStack<i32>>::check(
+            &execution
+        ));
+    }
+
+    /// A set whose snapshot may list the values in any order.
+    #[derive(Debug, Clone, Default)]
+    struct SequentialSet {
+        values: Vec<i32>,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum SetOp {
+        Insert(i32),
+        Snapshot,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum SetRet {
+        Insert,
+        Snapshot(Vec<i32>),
+    }
+
+    impl SequentialSpec for SequentialSet {
+        type Op = SetOp;
+        type Ret = SetRet;
+
+        fn exec(&mut self, op: Self::Op) -> Self::Ret {
+            match op {
+                SetOp::Insert(value) => {
+                    self.values.push(value);
+                    SetRet::Insert
+                }
+                SetOp::Snapshot => SetRet::Snapshot(self.values.clone()),
+            }
+        }
+
+        fn ret_matches(expected: &Self::Ret, actual: &Self::Ret) -> bool {
+            match (expected, actual) {
+                (SetRet::Snapshot(expected), SetRet::Snapshot(actual)) => {
+                    let mut expected = expected.clone();
+                    let mut actual = actual.clone();
+                    expected.sort();
+                    actual.sort();
+                    expected == actual
+                }
+                (expected, actual) => expected == actual,
+            }
+        }
+    }
+
+    #[test]
+    fn results_are_compared_with_ret_matches() {
+        let execution = execution! {
+            init: [SetOp::Insert(1) => SetRet::Insert],
+            parallel: [
+                0 @ 0..1: SetOp::Insert(2) => SetRet::Insert,
+                1 @ 2..3: SetOp::Snapshot => SetRet::Snapshot(vec![2, 1]),
+            ],
+        };
+        assert!(LinearizabilityChecker::<SequentialSet>::check(&execution));
+        assert!(LinearizabilityChecker::<SequentialSet>::diagnose(&execution).is_none());
+
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: SetOp::Insert(2) => SetRet::Insert,
+                1 @ 2..3: SetOp::Snapshot => SetRet::Snapshot(vec![]),
+            ],
+        };
+        assert!(!LinearizabilityChecker::<SequentialSet>::check(&execution));
     }
 }
@@ -0,0 +1,110 @@
+//! Displaying executions with ANSI colors in the terminal.
+//!
+//! The plain tables of [Execution] are meant for CI logs, where the escape codes would only get in the way.
+//! In a terminal, [Execution::colored] gives each thread its own color,
+//! highlights the first operation that has no legal linearization, i.e. the first one after
+//! the longest linearizable prefix of the [explanation](Execution::explain), and dims the spans when a thread is idle.
+//!
+//! ```
+//! # use lincheck::{execution, specs::{Register, RegisterOp, RegisterRet}};
+//! let execution = execution! {
+//!     parallel: [
+//!         0 @ 0..1: RegisterOp::Write(1) => RegisterRet::Write,
+//!         1 @ 2..3: RegisterOp::Read => RegisterRet::Read(0),
+//!     ],
+//! };
+//! println!("{}", execution.colored::<Register<i32>>());
+//! ```
+
+use std::fmt::Debug;
+
+use crate::checker::LinearizabilityChecker;
+use crate::execution::*;
+use crate::spec::*;
+
+/// The styles of the threads of the parallel part, repeated if there are more threads.
+pub(crate) const THREAD_STYLES: [&str; 6] = ["36", "33", "35", "32", "34", "91"];
+/// The style of the first operation without a legal linearization.
+pub(crate) const HIGHLIGHT_STYLE: &str = "1;97;41";
+/// The style of the spans when a thread is idle.
+pub(crate) const DIM_STYLE: &str = "2";
+
+/// An [Execution] displayed with ANSI colors, created by [Execution::colored].
+///
+/// Formatting it with a width, e.g. `{:100}`, fits the tables into the width like the plain display.
+pub struct Colored<'e, Op, Ret> {
+    pub(crate) execution: &'e Execution<Op, Ret>,
+    /// The first operation without a legal linearization, if the execution isn't linearizable.
+    pub(crate) mismatch: Option<InvocationRef>,
+}
+
+impl<Op, Ret> Execution<Op, Ret> {
+    /// Displays the execution with a color per thread, highlighting the first operation
+    /// that has no legal linearization with respect to `Seq`.
+    pub fn colored<Seq>(&self) -> Colored<'_, Op, Ret>
+    where
+        Seq: SequentialSpec<Op = Op, Ret = Ret> + Default,
+        Op: Clone + Debug,
+        Ret: PartialEq + Clone + Debug,
+    {
+        Colored {
+            execution: self,
+            mismatch: LinearizabilityChecker::<Seq>::first_mismatch(self, &Seq::default),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execution;
+    use crate::specs::{Register, RegisterOp as Op, RegisterRet as Ret};
+
+    /// Removes the ANSI escape codes.
+    fn strip(colored: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = colored.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn highlights_first_mismatch() {
+        let execution = execution! {
+            init: [Op::Write(1) => Ret::Write],
+            parallel: [
+                0 @ 0..1: Op::Write(2) => Ret::Write,
+                1 @ 2..3: Op::Read => Ret::Read(1),
+            ],
+            post: [Op::Read => Ret::Read(2)],
+        };
+
+        let colored = execution.colored::<Register<i32>>().to_string();
+        assert_eq!(strip(&colored), execution.to_string());
+        assert!(colored.contains("\x1b[1;97;41m Read : Read(1) \x1b[0m"));
+        assert!(colored.contains("\x1b[36m Write(2) : Write \x1b[0m"));
+        // the second thread is idle while the first one writes
+        assert!(
+            colored.contains("|\x1b[36m------------------\x1b[0m|\x1b[2m----------------\x1b[0m|")
+        );
+    }
+
+    #[test]
+    fn linearizable_execution_is_not_highlighted() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: Op::Write(2) => Ret::Write,
+                1 @ 2..3: Op::Read => Ret::Read(2),
+            ],
+        };
+
+        let colored = format!("{:30}", execution.colored::<Register<i32>>());
+        assert_eq!(strip(&colored), format!("{:30}", execution));
+        assert!(!colored.contains("\x1b[1;97;41m"));
+    }
+}
@@ -0,0 +1,441 @@
+//! Estimation of the interleaving coverage of native-thread runs and coverage-guided generation.
+//!
+//! Unlike [loom], native threads observe a single interleaving per run chosen by the OS scheduler,
+//! so running a scenario many times doesn't mean that many interleavings were explored.
+//! [CoverageTracker] counts the behaviorally distinct executions, i.e. the ones that differ in the order
+//! of the calls and returns or in the returned values, and estimates which fraction of the behaviors
+//! has been observed.
+//!
+//! The estimate is the Good-Turing sample coverage `1 - f1 / n`, where `n` is the number of runs
+//! and `f1` is the number of behaviors observed exactly once. It approaches `1` as the runs stop
+//! revealing new behaviors. The raw saturation curve is reported along with it.
+//!
+//! The same signal guides the generation in [verify_guided](crate::Lincheck::verify_guided):
+//! the distinct executions explored by [loom] and the distinct states of the sequential specification
+//! they reach are accumulated across the scenarios, and the kinds of operations of the scenarios
+//! that increased them are favored in the following ones.
+
+#[cfg(feature = "proptest")]
+use proptest::prelude::*;
+#[cfg(feature = "proptest")]
+use proptest::strategy::{NewTree, ValueTree};
+#[cfg(feature = "proptest")]
+use proptest::test_runner::TestRunner;
+use std::collections::hash_map::{DefaultHasher, HashMap};
+#[cfg(feature = "proptest")]
+use std::collections::HashSet;
+#[cfg(feature = "proptest")]
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "proptest")]
+use std::mem::{self, Discriminant};
+#[cfg(feature = "proptest")]
+use std::panic::UnwindSafe;
+#[cfg(feature = "proptest")]
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "proptest")]
+use crate::backend::{ExecutionBackend, LoomBackend};
+#[cfg(feature = "proptest")]
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::scenario::*;
+use crate::spec::*;
+#[cfg(feature = "proptest")]
+use crate::Lincheck;
+
+/// Counts the distinct behaviors of the recorded executions.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageTracker {
+    /// How many times each behavior was observed, by its hash.
+    counts: HashMap<u64, usize>,
+    /// The number of distinct behaviors after each run.
+    curve: Vec<usize>,
+}
+
+/// The estimated interleaving coverage.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageEstimate {
+    /// The number of runs.
+    pub runs: usize,
+    /// The number of distinct behaviors observed.
+    pub distinct: usize,
+    /// The estimated fraction of the behaviors that has been observed, from `0` to `1`.
+    pub coverage: f64,
+    /// The number of distinct behaviors observed after each run.
+    pub curve: Vec<usize>,
+}
+
+impl CoverageTracker {
+    /// Creates an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the behavior of an execution.
+    pub fn record<Op, Ret: Hash>(&mut self, execution: &Execution<Op, Ret>) {
+        *self.counts.entry(behavior(execution)).or_default() += 1;
+        self.curve.push(self.counts.len());
+    }
+
+    /// Estimates the coverage of the recorded executions.
+    pub fn estimate(&self) -> CoverageEstimate {
+        let runs = self.curve.len();
+        let singletons = self.counts.values().filter(|&&count| count == 1).count();
+        let coverage = if runs == 0 {
+            0.0
+        } else {
+            1.0 - singletons as f64 / runs as f64
+        };
+
+        CoverageEstimate {
+            runs,
+            distinct: self.counts.len(),
+            coverage,
+            curve: self.curve.clone(),
+        }
+    }
+}
+
+/// The hash of the order of the calls and returns and of the returned values of an execution.
+fn behavior<Op, Ret: Hash>(execution: &Execution<Op, Ret>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for inv in execution.init_part.iter() {
+        inv.ret.hash(&mut hasher);
+    }
+    // the timestamps come from a global counter, so they capture the interleaving
+    for inv in execution.parallel_part.iter() {
+        (inv.thread_id, inv.call_timestamp, inv.return_timestamp).hash(&mut hasher);
+        inv.ret.hash(&mut hasher);
+    }
+    for inv in execution.pending.iter() {
+        (inv.thread_id, inv.call_timestamp).hash(&mut hasher);
+    }
+    for inv in execution.post_part.iter() {
+        inv.ret.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Executes the given scenario `runs` times on native threads and estimates the interleaving coverage.
+pub fn estimate_coverage_with_threads<Conc>(
+    scenario: &Scenario<ConcOp<Conc>>,
+    runs: usize,
+) -> CoverageEstimate
+where
+    Conc: ConcurrentSpec + Default + Sync,
+    ConcOp<Conc>: Send + Clone,
+    ConcRet<Conc>: Send + Hash,
+{
+    let mut tracker = CoverageTracker::new();
+    for _ in 0..runs {
+        tracker.record(&execute_scenario_with_threads::<Conc>(scenario.clone()));
+    }
+    tracker.estimate()
+}
+
+/// Configuration of [verify_guided](Lincheck::verify_guided).
+#[cfg(feature = "proptest")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuidedConfig {
+    /// The number of scenarios generated for each case, of which the one with the most favored operations is checked.
+    /// The generation isn't guided if it is `1`.
+    pub candidates: usize,
+}
+
+#[cfg(feature = "proptest")]
+impl Default for GuidedConfig {
+    fn default() -> Self {
+        Self { candidates: 4 }
+    }
+}
+
+/// The coverage accumulated across the scenarios and the weights of the kinds of operations it has learned.
+///
+/// The kind of an operation is its enum variant, so the operations that aren't enums aren't told apart.
+#[cfg(feature = "proptest")]
+pub struct CoverageFeedback<Op> {
+    executions: HashSet<u64>,
+    states: HashSet<u64>,
+    weights: HashMap<Discriminant<Op>, f64>,
+}
+
+#[cfg(feature = "proptest")]
+impl<Op> Default for CoverageFeedback<Op> {
+    fn default() -> Self {
+        Self {
+            executions: HashSet::new(),
+            states: HashSet::new(),
+            weights: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<Op> CoverageFeedback<Op> {
+    /// Creates an empty accumulator, in which every kind of operation has the weight `1`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of distinct executions recorded.
+    pub fn distinct_executions(&self) -> usize {
+        self.executions.len()
+    }
+
+    /// The number of distinct sequential states reached by the recorded executions.
+    pub fn distinct_states(&self) -> usize {
+        self.states.len()
+    }
+
+    /// The current coverage, i.e. the number of distinct executions and states.
+    pub fn coverage(&self) -> usize {
+        self.distinct_executions() + self.distinct_states()
+    }
+
+    /// Records an execution and the states the sequential specification `Seq` goes through
+    /// when it runs the operations of the execution in the order they were called.
+    pub fn record<Seq>(&mut self, execution: &Execution<Op, Seq::Ret>)
+    where
+        Seq: SequentialSpec<Op = Op> + Default + Hash,
+        Op: Clone,
+        Seq::Ret: Hash,
+    {
+        self.executions.insert(behavior(execution));
+
+        let mut seq = Seq::default();
+        let ops = execution
+            .init_part
+            .iter()
+            .map(|inv| &inv.op)
+            .chain(execution.parallel_part.iter().map(|inv| &inv.op))
+            .chain(execution.post_part.iter().map(|inv| &inv.op));
+        for op in ops {
+            seq.exec(op.clone());
+            let mut hasher = DefaultHasher::new();
+            seq.hash(&mut hasher);
+            self.states.insert(hasher.finish());
+        }
+    }
+
+    /// Rewards the kinds of the operations of the scenario that increased the coverage by `gain`.
+    pub fn reward(&mut self, scenario: &Scenario<Op>, gain: usize) {
+        let num_ops = scenario.num_ops();
+        if gain == 0 || num_ops == 0 {
+            return;
+        }
+        for op in scenario_ops(scenario) {
+            *self.weights.entry(mem::discriminant(op)).or_insert(1.0) +=
+                gain as f64 / num_ops as f64;
+        }
+    }
+
+    /// The mean weight of the operations of the scenario.
+    pub fn score(&self, scenario: &Scenario<Op>) -> f64 {
+        let num_ops = scenario.num_ops();
+        if num_ops == 0 {
+            return 1.0;
+        }
+        let total: f64 = scenario_ops(scenario)
+            .map(|op| {
+                self.weights
+                    .get(&mem::discriminant(op))
+                    .copied()
+                    .unwrap_or(1.0)
+            })
+            .sum();
+        total / num_ops as f64
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<Op> Debug for CoverageFeedback<Op> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoverageFeedback")
+            .field("distinct_executions", &self.distinct_executions())
+            .field("distinct_states", &self.distinct_states())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "proptest")]
+fn scenario_ops<Op>(scenario: &Scenario<Op>) -> impl Iterator<Item = &Op> {
+    scenario
+        .init_part
+        .iter()
+        .chain(scenario.parallel_part.iter().flatten())
+        .chain(scenario.post_part.iter())
+}
+
+/// Generates [GuidedConfig::candidates] scenarios with `base` and picks the one
+/// with the highest [score](CoverageFeedback::score), so it shrinks like the scenarios of `base`.
+#[cfg(feature = "proptest")]
+pub(crate) struct GuidedStrategy<Op> {
+    base: BoxedStrategy<Scenario<Op>>,
+    feedback: Arc<Mutex<CoverageFeedback<Op>>>,
+    candidates: usize,
+}
+
+#[cfg(feature = "proptest")]
+impl<Op> Debug for GuidedStrategy<Op> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GuidedStrategy")
+            .field("candidates", &self.candidates)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<Op: Debug> Strategy for GuidedStrategy<Op> {
+    type Tree = Box<dyn ValueTree<Value = Scenario<Op>>>;
+    type Value = Scenario<Op>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let mut best: Option<(f64, Self::Tree)> = None;
+        for _ in 0..self.candidates.max(1) {
+            let tree = self.base.new_tree(runner)?;
+            let score = self.feedback.lock().unwrap().score(&tree.current());
+            if best
+                .as_ref()
+                .is_none_or(|(best_score, _)| score > *best_score)
+            {
+                best = Some((score, tree));
+            }
+        }
+        Ok(best.expect("at least one candidate is generated").1)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl Lincheck {
+    /// The same as [verify](Lincheck::verify) but guides the generation by the coverage of the explored executions,
+    /// as described in the [module-level documentation](crate::coverage).
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. the last failing execution of the shrunk scenario
+    /// or a panic.
+    pub fn verify_guided<Conc>(
+        &self,
+        config: &GuidedConfig,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Hash + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Clone + Debug + Send + Hash,
+    {
+        let feedback = Arc::new(Mutex::new(CoverageFeedback::new()));
+        let strategy = GuidedStrategy {
+            base: self.scenarios(),
+            feedback: feedback.clone(),
+            candidates: config.candidates,
+        };
+        let backend = LoomBackend {
+            config: self.loom.clone(),
+        };
+        let (correctness, timeout) = (self.correctness, self.check_timeout);
+        let checker = self.checker.clone();
+
+        self.run_cases(&strategy, Scenario::clone, |scenario| {
+            let coverage = feedback.lock().unwrap().coverage();
+            let model_feedback = feedback.clone();
+            let checker = checker.clone();
+            let check = move |execution: &Execution<_, _>| {
+                model_feedback
+                    .lock()
+                    .unwrap()
+                    .record::<Conc::Seq>(execution);
+                // a timed out check passes, as the scenario may not fail again anyway
+                correctness.check_within(execution, &Conc::Seq::default, checker.as_ref(), timeout)
+                    != Some(false)
+            };
+            let result = backend.find_failure::<Conc>(scenario.clone(), check);
+
+            let mut feedback = feedback.lock().unwrap();
+            let gain = feedback.coverage() - coverage;
+            feedback.reward(&scenario, gain);
+            result.map_err(VerificationFailure::NonLinearizable)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::*;
+
+    fn execution(ret: usize) -> Execution<(), usize> {
+        let mut recorder = record_init_part();
+        recorder.record((), || ret);
+        recorder.finish()
+    }
+
+    #[test]
+    fn saturated_coverage() {
+        let mut tracker = CoverageTracker::new();
+        for ret in [0, 1, 0, 1, 1] {
+            tracker.record(&execution(ret));
+        }
+
+        let estimate = tracker.estimate();
+        assert_eq!(estimate.runs, 5);
+        assert_eq!(estimate.distinct, 2);
+        assert_eq!(estimate.coverage, 1.0);
+        assert_eq!(estimate.curve, vec![1, 2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn unsaturated_coverage() {
+        let mut tracker = CoverageTracker::new();
+        for ret in [0, 1, 2, 0] {
+            tracker.record(&execution(ret));
+        }
+
+        let estimate = tracker.estimate();
+        assert_eq!(estimate.distinct, 3);
+        assert_eq!(estimate.coverage, 0.5);
+        assert_eq!(estimate.curve, vec![1, 2, 3, 3]);
+    }
+
+    #[cfg(feature = "proptest")]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Op {
+        Read,
+        Write,
+    }
+
+    #[cfg(feature = "proptest")]
+    fn scenario(op: Op) -> Scenario<Op> {
+        ScenarioBuilder::new().thread([op, op]).thread([op]).build()
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn rewarded_operations() {
+        let mut feedback = CoverageFeedback::new();
+        feedback.reward(&scenario(Op::Write), 3);
+        feedback.reward(&scenario(Op::Read), 0);
+
+        assert_eq!(feedback.score(&scenario(Op::Read)), 1.0);
+        assert_eq!(feedback.score(&scenario(Op::Write)), 4.0);
+        let mixed = ScenarioBuilder::new().thread([Op::Read, Op::Write]).build();
+        assert_eq!(feedback.score(&mixed), 2.5);
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn guided_candidates() {
+        let mut feedback = CoverageFeedback::new();
+        feedback.reward(&scenario(Op::Write), 1);
+        let strategy = GuidedStrategy {
+            base: prop_oneof![Just(scenario(Op::Read)), Just(scenario(Op::Write))].boxed(),
+            feedback: Arc::new(Mutex::new(feedback)),
+            candidates: 16,
+        };
+
+        let mut runner = TestRunner::deterministic();
+        for _ in 0..10 {
+            let tree = strategy.new_tree(&mut runner).unwrap();
+            assert_eq!(tree.current(), scenario(Op::Write));
+        }
+    }
+}
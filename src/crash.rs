@@ -0,0 +1,349 @@
+//! Thread crashes (kill mode).
+//!
+//! The data structure marks the points where a thread may be killed with [crash_point],
+//! e.g. between the steps of a lock-free update. [verify_crash](crate::Lincheck::verify_crash)
+//! generates along with each scenario one thread of the parallel part and one of its crash points,
+//! and kills the thread there in every interleaving explored by [loom].
+//!
+//! Unlike a panic in [chaos mode](crate::chaos), a crash doesn't unwind: the killed thread never runs again,
+//! so its locks stay locked and its half-done update stays as it is. Its operation never returns and is checked
+//! as pending, i.e. it may or may not have taken effect, and the thread executes none of its remaining operations.
+//! The other threads and the post part must still complete and be linearizable. If they get stuck instead,
+//! e.g. waiting for a lock the killed thread held, the failure is a [Deadlock](VerificationFailure::Deadlock)
+//! or a [Livelock](VerificationFailure::Livelock).
+//!
+//! [loom] requires every thread to finish, so the killed thread is in fact parked at the crash point until the post part
+//! is recorded and only then unwinds. Its destructors can't affect the checked execution.
+//!
+//! Outside of crash mode [crash_point] does nothing.
+//! The yield points of [loom] itself can't be hooked, so only explicit crash points are considered.
+
+use proptest::prelude::*;
+use proptest::sample::Index;
+use proptest::test_runner::TestError;
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe, UnwindSafe};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use loom::sync::{Condvar, Mutex};
+use loom::thread::{self, JoinHandle};
+
+use crate::checker::*;
+use crate::deadlock::{self, LiveTrace};
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{Lincheck, LoomConfig};
+
+/// The number of the first crash points of the chosen thread that may kill it.
+const MAX_CRASH_POINTS: usize = 4;
+
+/// The number of the checks running in crash mode. Guards the access to the [loom] thread locals
+/// so that [crash_point] can be called outside of [loom] too.
+static CRASH_MODE: AtomicUsize = AtomicUsize::new(0);
+
+loom::thread_local! {
+    /// Decides whether the current thread is killed at a crash point, and its kill switch.
+    static KILLER: RefCell<Option<(Killer, Rc<KillSwitch>)>> = RefCell::new(None);
+}
+
+/// Called at each crash point of a thread, returns whether the thread is killed there.
+pub(crate) type Killer = Box<dyn FnMut() -> bool>;
+
+/// Parks the killed thread until the execution is recorded.
+#[derive(Default)]
+struct KillSwitch {
+    /// Set when the thread is killed or finishes its operations.
+    stopped: Signal,
+    /// Set when the killed thread may unwind.
+    released: Signal,
+}
+
+/// A flag that a thread waits for.
+///
+/// Unlike a [Notify](loom::sync::Notify), a condition variable doesn't wake up spuriously,
+/// which would double the interleavings to explore at every wait.
+#[derive(Default)]
+struct Signal {
+    set: Mutex<bool>,
+    changed: Condvar,
+}
+
+impl Signal {
+    fn set(&self) {
+        *self.set.lock().unwrap() = true;
+        self.changed.notify_one();
+    }
+
+    fn wait(&self) {
+        let mut set = self.set.lock().unwrap();
+        while !*set {
+            set = self.changed.wait(set).unwrap();
+        }
+    }
+}
+
+/// The payload the killed thread unwinds with once released.
+struct Killed;
+
+/// Marks a point where crash mode may kill the current thread.
+pub fn crash_point() {
+    if CRASH_MODE.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+
+    let switch = KILLER.with(|killer| {
+        let mut killer = killer.borrow_mut();
+        let killed = killer.as_mut().is_some_and(|(kill, _)| kill());
+        killer.take_if(|_| killed).map(|(_, switch)| switch)
+    });
+    if let Some(switch) = switch {
+        switch.stopped.set();
+        switch.released.wait();
+        panic::resume_unwind(Box::new(Killed));
+    }
+}
+
+/// Enables [crash_point] while `f` runs.
+pub(crate) fn with_crash_mode<T>(f: impl FnOnce() -> T) -> T {
+    CRASH_MODE.fetch_add(1, Ordering::Relaxed);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CRASH_MODE.fetch_sub(1, Ordering::Relaxed);
+    result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
+
+/// The threads of the parallel part, some of which may be killed.
+///
+/// The threads aren't scoped, so that a model stuck after a crash is torn down without joining the killed threads.
+pub(crate) struct KillableThreads {
+    /// The kill switches of the threads that may be killed.
+    switches: Vec<Option<Rc<KillSwitch>>>,
+    /// The threads that aren't joined yet.
+    handles: Vec<Option<JoinHandle<()>>>,
+}
+
+impl KillableThreads {
+    /// Spawns the threads of the parallel part executing the operations on `conc` and recording them into `trace`.
+    ///
+    /// A thread with a `killer` is killed at the crash point where the killer decides so, and leaves the operation pending.
+    /// Every thread also stops before an operation if `go_on` returns false.
+    pub(crate) fn spawn<Conc>(
+        conc: &Arc<Conc>,
+        parallel_part: &[Vec<ConcOp<Conc>>],
+        trace: &Arc<LiveTrace<ConcOp<Conc>, ConcRet<Conc>>>,
+        killer: impl Fn(ThreadId) -> Option<Killer>,
+        go_on: impl Fn() -> bool + Clone + 'static,
+    ) -> Self
+    where
+        Conc: ConcurrentSpec + 'static,
+        ConcOp<Conc>: Clone + 'static,
+        ConcRet<Conc>: Clone + 'static,
+    {
+        let (switches, handles) = parallel_part
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(thread_id, thread_ops)| {
+                let (conc, trace, go_on) = (conc.clone(), trace.clone(), go_on.clone());
+                let killer =
+                    killer(thread_id).map(|killer| (killer, Rc::new(KillSwitch::default())));
+                let switch = killer.as_ref().map(|(_, switch)| switch.clone());
+                let stopped = switch.clone();
+                let handle = thread::spawn(move || {
+                    KILLER.with(|current| *current.borrow_mut() = killer);
+
+                    for op in thread_ops {
+                        if !go_on() {
+                            break;
+                        }
+                        trace.call(thread_id, op.clone());
+                        let ret = deadlock::exec_traced(Some(thread_id), &op, &trace, || {
+                            match panic::catch_unwind(AssertUnwindSafe(|| conc.exec(op.clone()))) {
+                                Ok(ret) => Some(ret),
+                                Err(payload) if payload.is::<Killed>() => None,
+                                Err(payload) => panic::resume_unwind(payload),
+                            }
+                        });
+                        match ret {
+                            Some(ret) => trace.ret(thread_id, ret),
+                            // the killed thread stops, and its operation stays pending
+                            None => return,
+                        }
+                    }
+
+                    if let Some(switch) = stopped {
+                        switch.stopped.set();
+                    }
+                });
+                (switch, Some(handle))
+            })
+            .unzip();
+        Self { switches, handles }
+    }
+
+    /// Waits until every thread is killed or finishes its operations.
+    /// The threads that can't be killed are joined.
+    pub(crate) fn wait(&mut self) {
+        for (switch, handle) in self.switches.iter().zip(self.handles.iter_mut()) {
+            match switch {
+                Some(switch) => switch.stopped.wait(),
+                None => handle.take().unwrap().join().unwrap(),
+            }
+        }
+    }
+
+    /// Lets the killed threads unwind and joins the remaining threads.
+    pub(crate) fn release(self) {
+        for (switch, handle) in self.switches.into_iter().zip(self.handles) {
+            if let Some(switch) = switch {
+                switch.released.set();
+            }
+            if let Some(handle) = handle {
+                handle.join().unwrap();
+            }
+        }
+    }
+}
+
+/// Where to kill the thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CrashTarget {
+    /// The thread of the parallel part.
+    pub thread: usize,
+    /// The number of crash points the thread passes before it is killed.
+    pub point: usize,
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` stays linearizable and doesn't get stuck
+    /// after a thread is killed in the middle of an operation.
+    ///
+    /// See the [module-level documentation](crate::crash) for the details.
+    pub fn verify_crash<Conc>(&self) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        let strategy = (self.scenarios(), any::<Index>(), 0..MAX_CRASH_POINTS).prop_map(
+            |(scenario, thread, point)| {
+                let thread = thread.index(scenario.parallel_part.len());
+                (scenario, CrashTarget { thread, point })
+            },
+        );
+
+        let result = self.runner().run(&strategy, |(scenario, target)| {
+            check_crash_scenario_with_loom_using::<Conc>(&self.loom, scenario, target)
+                .map_err(|_| TestCaseError::Fail("Crash mode failure".into()))
+        });
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(TestError::Fail(_, (scenario, target))) => {
+                // rerun the scenario to get the failing execution
+                Err(
+                    check_crash_scenario_with_loom_using::<Conc>(&self.loom, scenario, target)
+                        .unwrap_err(),
+                )
+            }
+            Err(TestError::Abort(reason)) => {
+                Err(VerificationFailure::GeneratorError(reason.to_string()))
+            }
+        }
+    }
+}
+
+/// Executes the given scenario killing the thread at `target` and checks the resulting execution inside [loom] model-checker.
+pub fn check_crash_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    target: CrashTarget,
+) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    check_crash_scenario_with_loom_using::<Conc>(&LoomConfig::default(), scenario, target)
+}
+
+/// Same as [check_crash_scenario_with_loom] but configures loom with `loom`.
+pub(crate) fn check_crash_scenario_with_loom_using<Conc>(
+    loom: &LoomConfig,
+    scenario: Scenario<ConcOp<Conc>>,
+    target: CrashTarget,
+) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    let model_scenario = scenario.clone();
+    with_crash_mode(|| {
+        deadlock::check_traced_with_loom(
+            loom,
+            scenario,
+            move |trace| execute_crash_scenario_with_loom::<Conc>(&model_scenario, target, trace),
+            |execution| LinearizabilityChecker::<Conc::Seq>::check(execution),
+        )
+    })
+}
+
+/// Executes the given scenario with [loom] mock threads killing the thread at `target`
+/// and records the execution into `trace`. The operation of the killed thread is left pending.
+fn execute_crash_scenario_with_loom<Conc>(
+    scenario: &Scenario<ConcOp<Conc>>,
+    target: CrashTarget,
+    trace: &Arc<LiveTrace<ConcOp<Conc>, ConcRet<Conc>>>,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + UnwindSafe + 'static,
+    ConcRet<Conc>: Send + Clone + 'static,
+{
+    trace.reset(scenario.parallel_part.len());
+    let conc = Arc::new(Conc::default());
+
+    // init part
+    for op in scenario.init_part.iter().cloned() {
+        let ret = deadlock::exec_traced(None, &op, trace, || conc.exec(op.clone()));
+        trace.push_init(op, ret);
+    }
+
+    // parallel part
+    let mut threads = KillableThreads::spawn(
+        &conc,
+        &scenario.parallel_part,
+        trace,
+        |thread_id| {
+            (thread_id == target.thread).then(|| {
+                let mut left = target.point;
+                Box::new(move || match left.checked_sub(1) {
+                    Some(next) => {
+                        left = next;
+                        false
+                    }
+                    None => true,
+                }) as Killer
+            })
+        },
+        || true,
+    );
+    threads.wait();
+
+    // post part
+    for op in scenario.post_part.iter().cloned() {
+        let ret = deadlock::exec_traced(None, &op, trace, || conc.exec(op.clone()));
+        trace.push_post(op, ret);
+    }
+
+    let execution = trace.snapshot().0;
+    threads.release();
+    execution
+}
@@ -0,0 +1,270 @@
+//! Convergence checking for CRDT-like data structures.
+//!
+//! Replicated data structures are not linearizable: each replica executes operations locally
+//! and replicas are eventually merged. Instead, they must converge:
+//! - each replica must behave like the sequential specification when observed alone,
+//! - merging must be commutative and idempotent,
+//! - the merged state must be equal to the state of the sequential specification
+//!   after some interleaving of the operations of all the replicas.
+//!
+//! The parallel part of a [Scenario] is run on independent replicas, one per thread.
+//! The replicas are then merged and the post part is run on the merged replica.
+//! Because replicas share no memory, no model-checking is involved.
+
+use proptest::prelude::*;
+use proptest::test_runner::TestError;
+use std::fmt::Debug;
+
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// A replica of a replicated data structure.
+pub trait ReplicatedSpec: Default + Clone {
+    /// The sequential specification for the data structure.
+    type Seq: SequentialSpec + Default;
+
+    /// The observable state which is compared between the replicas and the sequential specification.
+    type State: PartialEq + Debug;
+
+    /// Creates a new replica with the given id from this one.
+    fn fork(&self, replica_id: usize) -> Self;
+
+    /// Executes an operation on the replica.
+    fn exec(&mut self, op: ReplOp<Self>) -> ReplRet<Self>;
+
+    /// Merges the other replica into this one.
+    fn merge(&mut self, other: &Self);
+
+    /// The observable state of the replica.
+    fn state(&self) -> Self::State;
+
+    /// The observable state of the sequential specification.
+    fn seq_state(seq: &Self::Seq) -> Self::State;
+}
+
+/// Type alias not to have always write down FQP.
+pub type ReplOp<T> = <<T as ReplicatedSpec>::Seq as SequentialSpec>::Op;
+
+/// Type alias not to have always write down FQP.
+pub type ReplRet<T> = <<T as ReplicatedSpec>::Seq as SequentialSpec>::Ret;
+
+/// The way a replicated data structure fails to converge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConvergenceViolation<State> {
+    /// The replica running the given thread of the parallel part
+    /// (or the init part if `None`) doesn't behave like the sequential specification.
+    LocalHistory {
+        /// The thread of the parallel part.
+        thread: Option<usize>,
+    },
+    /// Merging the replicas in the reverse order results in a different state.
+    NotCommutative {
+        /// The state after merging in the thread order.
+        forward: State,
+        /// The state after merging in the reverse order.
+        backward: State,
+    },
+    /// Merging a replica that is already merged changes the state.
+    NotIdempotent {
+        /// The state before merging again.
+        before: State,
+        /// The state after merging again.
+        after: State,
+    },
+    /// No interleaving of the operations results in the merged state
+    /// followed by the post part results.
+    Diverged {
+        /// The merged state.
+        merged: State,
+    },
+}
+
+/// A convergence failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConvergenceFailure<Op, State> {
+    /// The replicas don't converge on the scenario.
+    Violation {
+        /// The failing scenario.
+        scenario: Scenario<Op>,
+        /// What went wrong.
+        violation: ConvergenceViolation<State>,
+    },
+    /// The scenarios couldn't be generated, e.g. the strategies rejected too many of them.
+    GeneratorError(String),
+}
+
+impl Lincheck {
+    /// Verifies that the replicated data structure `Repl` converges to a state of its sequential specification.
+    ///
+    /// See the [module-level documentation](crate::crdt) for the details.
+    pub fn verify_convergence<Repl>(
+        &self,
+    ) -> Result<(), ConvergenceFailure<ReplOp<Repl>, Repl::State>>
+    where
+        Repl: ReplicatedSpec,
+        ReplOp<Repl>: Clone + Arbitrary + Debug + 'static,
+        ReplRet<Repl>: PartialEq,
+    {
+        let result = self.runner().run(&self.scenarios(), |scenario| {
+            check_convergence::<Repl>(&scenario)
+                .map_err(|_| TestCaseError::Fail("Replicas don't converge".into()))
+        });
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(TestError::Fail(_, scenario)) => {
+                // rerun the scenario to get the violation
+                let violation = check_convergence::<Repl>(&scenario).unwrap_err();
+                Err(ConvergenceFailure::Violation {
+                    scenario,
+                    violation,
+                })
+            }
+            Err(TestError::Abort(reason)) => {
+                Err(ConvergenceFailure::GeneratorError(reason.to_string()))
+            }
+        }
+    }
+}
+
+/// Runs the scenario on independent replicas and checks that they converge.
+pub fn check_convergence<Repl>(
+    scenario: &Scenario<ReplOp<Repl>>,
+) -> Result<(), ConvergenceViolation<Repl::State>>
+where
+    Repl: ReplicatedSpec,
+    ReplOp<Repl>: Clone,
+    ReplRet<Repl>: PartialEq,
+{
+    let mut origin = Repl::default();
+    if !behaves_sequentially(&mut origin, &[], &scenario.init_part) {
+        return Err(ConvergenceViolation::LocalHistory { thread: None });
+    }
+
+    let mut replicas = Vec::with_capacity(scenario.parallel_part.len());
+    for (thread, thread_ops) in scenario.parallel_part.iter().enumerate() {
+        let mut replica = origin.fork(thread);
+        if !behaves_sequentially(&mut replica, &scenario.init_part, thread_ops) {
+            return Err(ConvergenceViolation::LocalHistory {
+                thread: Some(thread),
+            });
+        }
+        replicas.push(replica);
+    }
+
+    let merge_all = |replicas: &mut dyn Iterator<Item = &Repl>| {
+        let mut merged = origin.clone();
+        for replica in replicas {
+            merged.merge(replica);
+        }
+        merged
+    };
+    let mut merged = merge_all(&mut replicas.iter());
+
+    let backward = merge_all(&mut replicas.iter().rev());
+    if merged.state() != backward.state() {
+        return Err(ConvergenceViolation::NotCommutative {
+            forward: merged.state(),
+            backward: backward.state(),
+        });
+    }
+
+    let mut merged_twice = merged.clone();
+    merged_twice.merge(&merged);
+    for replica in &replicas {
+        merged_twice.merge(replica);
+    }
+    if merged.state() != merged_twice.state() {
+        return Err(ConvergenceViolation::NotIdempotent {
+            before: merged.state(),
+            after: merged_twice.state(),
+        });
+    }
+
+    let merged_state = merged.state();
+    let post_rets: Vec<_> = scenario
+        .post_part
+        .iter()
+        .map(|op| merged.exec(op.clone()))
+        .collect();
+
+    let mut interleaving = Vec::new();
+    let mut positions = vec![0; scenario.parallel_part.len()];
+    let converges = any_interleaving(
+        &scenario.parallel_part,
+        &mut positions,
+        &mut interleaving,
+        &mut |interleaving| {
+            let mut seq = Repl::Seq::default();
+            for op in scenario
+                .init_part
+                .iter()
+                .chain(interleaving.iter().copied())
+            {
+                seq.exec(op.clone());
+            }
+            Repl::seq_state(&seq) == merged_state
+                && scenario
+                    .post_part
+                    .iter()
+                    .zip(post_rets.iter())
+                    .all(|(op, ret)| Repl::Seq::ret_matches(&seq.exec(op.clone()), ret))
+        },
+    );
+
+    if converges {
+        Ok(())
+    } else {
+        Err(ConvergenceViolation::Diverged {
+            merged: merged_state,
+        })
+    }
+}
+
+/// Checks that executing `ops` on the replica returns the same results
+/// as the sequential specification after executing `prefix`.
+fn behaves_sequentially<Repl>(
+    replica: &mut Repl,
+    prefix: &[ReplOp<Repl>],
+    ops: &[ReplOp<Repl>],
+) -> bool
+where
+    Repl: ReplicatedSpec,
+    ReplOp<Repl>: Clone,
+    ReplRet<Repl>: PartialEq,
+{
+    let mut seq = Repl::Seq::default();
+    for op in prefix {
+        seq.exec(op.clone());
+    }
+    ops.iter()
+        .all(|op| Repl::Seq::ret_matches(&seq.exec(op.clone()), &replica.exec(op.clone())))
+}
+
+/// Checks whether `pred` holds for any interleaving of the threads' operations that preserves the per-thread order.
+fn any_interleaving<'a, Op>(
+    threads: &'a [Vec<Op>],
+    positions: &mut [usize],
+    interleaving: &mut Vec<&'a Op>,
+    pred: &mut impl FnMut(&[&'a Op]) -> bool,
+) -> bool {
+    let mut finished = true;
+    for thread in 0..threads.len() {
+        let Some(op) = threads[thread].get(positions[thread]) else {
+            continue;
+        };
+        finished = false;
+
+        positions[thread] += 1;
+        interleaving.push(op);
+        let found = any_interleaving(threads, positions, interleaving, pred);
+        interleaving.pop();
+        positions[thread] -= 1;
+
+        if found {
+            return true;
+        }
+    }
+    finished && pred(interleaving)
+}
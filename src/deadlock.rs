@@ -0,0 +1,357 @@
+//! Detecting the scenarios whose threads stop making progress.
+//!
+//! If every thread is blocked, e.g. on a lock it already holds, [loom] panics with a deadlock.
+//! If the threads keep spinning instead, [loom] panics after exceeding its [branch limit](crate::LoomConfig::max_branches).
+//! Either way the model is torn down before the recorder has an execution to return.
+//!
+//! [check_scenario_for_deadlocks_with_loom] records the operations into a trace that lives outside of the model
+//! and turns such a panic into a [Deadlock](VerificationFailure::Deadlock) or a [Livelock](VerificationFailure::Livelock)
+//! with the execution so far, where the operations that never returned are pending.
+//! Since [loom] gives up in the thread that was running when it exceeded the branch limit,
+//! the operation of that thread is reported as the one spinning forever.
+//! A panic of an operation is reported the same way as [Panicked](VerificationFailure::Panicked).
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe, UnwindSafe};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use loom::sync::atomic::AtomicBool;
+use loom::thread;
+
+use crate::checker::*;
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{leaks, LoomConfig};
+
+/// How [loom] reports the threads that can't make progress.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Stuck {
+    /// Every thread was blocked.
+    Deadlock,
+    /// The model exceeded the branch limit.
+    Livelock,
+}
+
+/// Recognizes the panics of [loom] caused by threads that can't make progress and returns their message.
+pub(crate) fn stuck(payload: &(dyn Any + Send)) -> Option<(Stuck, &str)> {
+    let message = panic_message(payload)?;
+    if message.starts_with("deadlock") {
+        Some((Stuck::Deadlock, message))
+    } else if message.contains("maximum number of branches") {
+        Some((Stuck::Livelock, message))
+    } else {
+        None
+    }
+}
+
+/// The message of a panic raised with a string, as [panic!] does.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    payload
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| payload.downcast_ref::<&str>().copied())
+}
+
+/// The execution recorded so far, kept outside of the model so that it survives the panic.
+pub(crate) struct LiveTrace<Op, Ret> {
+    state: Mutex<TraceState<Op, Ret>>,
+}
+
+struct TraceState<Op, Ret> {
+    execution: Execution<Op, Ret>,
+    /// The operation each thread of the parallel part is executing and its call timestamp.
+    calls: Vec<Option<(Timestamp, Op)>>,
+    /// The thread whose operation panicked, [None] for the main thread, and the message of the panic.
+    panic: Option<(Option<ThreadId>, String)>,
+    /// The thread that was running when [loom] exceeded the branch limit, [None] for the main thread, and its operation.
+    spinning: Option<(Option<ThreadId>, Op)>,
+    timer: Timestamp,
+}
+
+impl<Op: Clone, Ret: Clone> LiveTrace<Op, Ret> {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(TraceState {
+                execution: Execution::default(),
+                calls: Vec::new(),
+                panic: None,
+                spinning: None,
+                timer: 0,
+            }),
+        }
+    }
+
+    /// Forgets the previous iteration of the model.
+    pub(crate) fn reset(&self, num_threads: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.execution = Execution::default();
+        state.calls = vec![None; num_threads];
+        state.panic = None;
+        state.spinning = None;
+        state.timer = 0;
+    }
+
+    fn panicked(&self, thread_id: Option<ThreadId>, payload: &(dyn Any + Send)) {
+        let message = panic_message(payload).unwrap_or("Box<dyn Any>");
+        self.state.lock().unwrap().panic = Some((thread_id, message.to_string()));
+    }
+
+    fn panic(&self) -> Option<(Option<ThreadId>, String)> {
+        self.state.lock().unwrap().panic.clone()
+    }
+
+    fn spun(&self, thread_id: Option<ThreadId>, op: &Op) {
+        self.state
+            .lock()
+            .unwrap()
+            .spinning
+            .get_or_insert_with(|| (thread_id, op.clone()));
+    }
+
+    fn spinning(&self) -> Option<(Option<ThreadId>, Op)> {
+        self.state.lock().unwrap().spinning.clone()
+    }
+
+    pub(crate) fn push_init(&self, op: Op, ret: Ret) {
+        let mut state = self.state.lock().unwrap();
+        state.execution.init_part.push(Invocation { op, ret });
+    }
+
+    pub(crate) fn push_post(&self, op: Op, ret: Ret) {
+        let mut state = self.state.lock().unwrap();
+        state.execution.post_part.push(Invocation { op, ret });
+    }
+
+    pub(crate) fn call(&self, thread_id: ThreadId, op: Op) {
+        let mut state = self.state.lock().unwrap();
+        let call_timestamp = state.timer;
+        state.timer += 1;
+        state.calls[thread_id] = Some((call_timestamp, op));
+    }
+
+    pub(crate) fn ret(&self, thread_id: ThreadId, ret: Ret) {
+        let mut state = self.state.lock().unwrap();
+        let return_timestamp = state.timer;
+        state.timer += 1;
+        let (call_timestamp, op) = state.calls[thread_id].take().unwrap();
+        state.execution.parallel_part.push(ParallelInvocation {
+            thread_id,
+            call_timestamp,
+            return_timestamp,
+            op,
+            ret,
+        });
+    }
+
+    /// Returns the execution so far, where the operations being executed are pending,
+    /// and the threads that execute them.
+    pub(crate) fn snapshot(&self) -> (Execution<Op, Ret>, Vec<ThreadId>) {
+        let state = self.state.lock().unwrap();
+        let mut execution = state.execution.clone();
+        let mut threads = Vec::new();
+        for (thread_id, call) in state.calls.iter().enumerate() {
+            if let Some((call_timestamp, op)) = call {
+                execution.pending.push(PendingInvocation::new(
+                    thread_id,
+                    *call_timestamp,
+                    op.clone(),
+                ));
+                threads.push(thread_id);
+            }
+        }
+        (execution, threads)
+    }
+}
+
+/// Executes the given scenario and checks the resulting execution for linearizability inside [loom] model-checker.
+/// Unlike [check_scenario_with_loom], a deadlock, a livelock or a panic of an operation is returned as a failure too.
+pub fn check_scenario_for_deadlocks_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    check_scenario_traced_with_loom(
+        &LoomConfig::default(),
+        scenario,
+        Arc::new(Conc::default),
+        |execution| LinearizabilityChecker::<Conc::Seq>::check(execution),
+    )
+}
+
+/// Same as [check_scenario_for_deadlocks_with_loom] but configures loom with `loom`,
+/// creates the data structure with `new_conc` and checks the executions with `check`.
+pub(crate) fn check_scenario_traced_with_loom<Conc>(
+    loom: &LoomConfig,
+    scenario: Scenario<ConcOp<Conc>>,
+    new_conc: Arc<dyn Fn() -> Conc + Send + Sync>,
+    check: impl Fn(&Execution<ConcOp<Conc>, ConcRet<Conc>>) -> bool + Send + Sync + 'static,
+) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: Debug + Send + Clone,
+{
+    let model_scenario = scenario.clone();
+    check_traced_with_loom(
+        loom,
+        scenario,
+        move |trace| execute_traced_scenario_with_loom(new_conc(), &model_scenario, trace),
+        check,
+    )
+}
+
+/// Same as [check_scenario_traced_with_loom] but executes the scenario with `execute`,
+/// which records the execution into the given trace.
+pub(crate) fn check_traced_with_loom<Op, Ret>(
+    loom: &LoomConfig,
+    scenario: Scenario<Op>,
+    execute: impl Fn(&Arc<LiveTrace<Op, Ret>>) -> Execution<Op, Ret> + Send + Sync + 'static,
+    check: impl Fn(&Execution<Op, Ret>) -> bool + Send + Sync + 'static,
+) -> Result<(), VerificationFailure<Op, Ret>>
+where
+    Op: Send + Sync + Clone + Debug + 'static,
+    Ret: Debug + Send + Clone + 'static,
+{
+    let trace = Arc::new(LiveTrace::new());
+    let check_leaks = loom.check_leaks;
+
+    let model_trace = trace.clone();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        find_failure_with_loom(loom, move || {
+            let execute = || execute(&model_trace);
+            // the memory errors are reported when the scenario is rerun untraced,
+            // but the tracking keeps the memory from being freed twice
+            let execution = if check_leaks {
+                leaks::track(execute).0
+            } else {
+                execute()
+            };
+            (!check(&execution)).then_some(execution)
+        })
+    }));
+
+    match result {
+        Ok(result) => result.map_err(VerificationFailure::NonLinearizable),
+        Err(payload) => match stuck(&*payload) {
+            Some((Stuck::Deadlock, _)) => {
+                let (execution_so_far, blocked_threads) = trace.snapshot();
+                Err(VerificationFailure::Deadlock {
+                    execution_so_far,
+                    blocked_threads,
+                })
+            }
+            Some((Stuck::Livelock, _)) => {
+                let (execution_so_far, spinning_threads) = trace.snapshot();
+                let (spinning_thread, spinning_op) = match trace.spinning() {
+                    Some((thread_id, op)) => (thread_id, Some(op)),
+                    None => (None, None),
+                };
+                Err(VerificationFailure::Livelock {
+                    execution_so_far,
+                    spinning_threads,
+                    spinning_thread,
+                    spinning_op,
+                })
+            }
+            None => match trace.panic() {
+                Some((thread, payload)) => Err(VerificationFailure::Panicked {
+                    scenario,
+                    thread,
+                    payload,
+                    partial_execution: trace.snapshot().0,
+                }),
+                // the panic didn't come from an operation
+                None => panic::resume_unwind(payload),
+            },
+        },
+    }
+}
+
+/// Executes the given scenario on the given instance of the data structure with [loom] mock threads
+/// recording every call, return and panic into `trace`, and returns the resulting execution.
+/// The init and post parts are executed with [exec_mut](ConcurrentSpec::exec_mut).
+fn execute_traced_scenario_with_loom<Conc>(
+    mut conc: Conc,
+    scenario: &Scenario<ConcOp<Conc>>,
+    trace: &Arc<LiveTrace<ConcOp<Conc>, ConcRet<Conc>>>,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone,
+    ConcRet<Conc>: Send + Clone,
+{
+    trace.reset(scenario.parallel_part.len());
+
+    // init part
+    for op in scenario.init_part.iter().cloned() {
+        let ret = exec_traced(None, &op, trace, || conc.exec_mut(op.clone()));
+        trace.push_init(op, ret);
+    }
+
+    let conc = Rc::new(conc);
+    let stop_maintenance = Rc::new(AtomicBool::new(false));
+    let maintenance = spawn_maintenance(&conc, &stop_maintenance);
+
+    // parallel part
+    let handles: Vec<_> = scenario
+        .parallel_part
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(thread_id, thread_ops)| {
+            let (conc, trace) = (conc.clone(), trace.clone());
+
+            thread::spawn(move || {
+                for op in thread_ops {
+                    trace.call(thread_id, op.clone());
+                    let ret = exec_traced(Some(thread_id), &op, &trace, || conc.exec(op.clone()));
+                    trace.ret(thread_id, ret);
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    stop_and_join_maintenance(maintenance, &stop_maintenance);
+    let mut conc = unshare(conc);
+
+    // post part
+    for op in scenario.post_part.iter().cloned() {
+        let ret = exec_traced(None, &op, trace, || conc.exec_mut(op.clone()));
+        trace.push_post(op, ret);
+    }
+
+    trace.snapshot().0
+}
+
+/// Executes the operation with `exec` and records its panic into `trace` in place of the return, unless it is how [loom]
+/// stops a stuck model. The panicked operation stays pending, since it never returned.
+/// If [loom] gave up on a livelock, `op` is recorded as the spinning operation.
+pub(crate) fn exec_traced<Op: Clone, Ret: Clone, T>(
+    thread_id: Option<ThreadId>,
+    op: &Op,
+    trace: &LiveTrace<Op, Ret>,
+    exec: impl FnOnce() -> T,
+) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(exec)) {
+        Ok(ret) => ret,
+        Err(payload) => {
+            match stuck(&*payload) {
+                None => trace.panicked(thread_id, &*payload),
+                Some((Stuck::Livelock, _)) => trace.spun(thread_id, op),
+                Some((Stuck::Deadlock, _)) => {}
+            }
+            panic::resume_unwind(payload)
+        }
+    }
+}
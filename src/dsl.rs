@@ -0,0 +1,171 @@
+//! Declarative scenario families loaded at runtime.
+//!
+//! A [ScenarioFamily] describes the shape of the generated scenarios: which operations each thread runs,
+//! how often and how many of them. It is written in [RON](https://github.com/ron-rs/ron),
+//! so new workload shapes can be added without writing [proptest] strategies:
+//! ```ron
+//! (
+//!     init_part: (ops: [(op: Push(1))], max_ops: 2),
+//!     parallel_part: [
+//!         (ops: [(op: Push(1), weight: 3), (op: Pop)], min_ops: 1, max_ops: 3),
+//!         (ops: [(op: Pop)], min_ops: 1, max_ops: 1),
+//!     ],
+//!     post_part: (ops: [(op: Pop)], max_ops: 2),
+//! )
+//! ```
+//! The operations are deserialized with [serde], so `Op` must implement [Deserialize].
+//! The init and post parts are empty if omitted.
+//!
+//! This module requires the `dsl` feature.
+
+use proptest::prelude::*;
+use proptest::strategy::Union;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::panic::UnwindSafe;
+use std::path::Path;
+
+use crate::error::VerificationFailure;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{Constructors, Lincheck};
+
+/// A family of scenarios.
+///
+/// See the [module-level documentation](crate::dsl) for the format.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(bound(deserialize = "Op: Deserialize<'de>"))]
+pub struct ScenarioFamily<Op> {
+    /// The operations of the init part.
+    #[serde(default)]
+    pub init_part: ThreadTemplate<Op>,
+    /// The operations of each thread of the parallel part.
+    pub parallel_part: Vec<ThreadTemplate<Op>>,
+    /// The operations of the post part.
+    #[serde(default)]
+    pub post_part: ThreadTemplate<Op>,
+}
+
+/// The operations of a single thread.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ThreadTemplate<Op> {
+    /// The operations to choose from.
+    pub ops: Vec<OpTemplate<Op>>,
+    /// The minimum number of operations.
+    #[serde(default)]
+    pub min_ops: usize,
+    /// The maximum number of operations.
+    pub max_ops: usize,
+}
+
+/// An operation to choose from.
+#[derive(Clone, Debug, Deserialize)]
+pub struct OpTemplate<Op> {
+    /// The operation.
+    pub op: Op,
+    /// How often the operation is chosen relative to the other ones of the thread.
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl<Op> Default for ThreadTemplate<Op> {
+    fn default() -> Self {
+        Self {
+            ops: Vec::new(),
+            min_ops: 0,
+            max_ops: 0,
+        }
+    }
+}
+
+impl<Op: DeserializeOwned> ScenarioFamily<Op> {
+    /// Parses a scenario family from a RON string.
+    pub fn from_ron(s: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(s)
+    }
+
+    /// Loads a scenario family from a RON file.
+    /// Parsing errors are reported as [InvalidData](io::ErrorKind::InvalidData).
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let s = fs::read_to_string(path)?;
+        Self::from_ron(&s).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl<Op: Clone + Debug + 'static> ScenarioFamily<Op> {
+    /// Generates the scenarios of the family.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_ops` is greater than `max_ops` in some thread
+    /// or if a thread that must run operations has none to choose from.
+    pub fn strategy(&self) -> BoxedStrategy<Scenario<Op>> {
+        let parallel_strategy: Vec<_> = self
+            .parallel_part
+            .iter()
+            .map(ThreadTemplate::strategy)
+            .collect();
+
+        (
+            self.init_part.strategy(),
+            parallel_strategy,
+            self.post_part.strategy(),
+        )
+            .prop_map(|(init_part, parallel_part, post_part)| Scenario {
+                init_part,
+                parallel_part,
+                post_part,
+            })
+            .boxed()
+    }
+}
+
+impl<Op: Clone + Debug + 'static> ThreadTemplate<Op> {
+    fn strategy(&self) -> BoxedStrategy<Vec<Op>> {
+        assert!(
+            self.min_ops <= self.max_ops,
+            "min_ops must not be greater than max_ops"
+        );
+        if self.ops.is_empty() {
+            assert_eq!(self.min_ops, 0, "a thread with min_ops > 0 must have ops");
+            return Just(Vec::new()).boxed();
+        }
+
+        let op_strategy = Union::new_weighted(
+            self.ops
+                .iter()
+                .map(|template| (template.weight, Just(template.op.clone()).boxed()))
+                .collect(),
+        );
+        prop::collection::vec(op_strategy, self.min_ops..=self.max_ops).boxed()
+    }
+}
+
+impl Lincheck {
+    /// The same as [verify](Lincheck::verify) but generates the scenarios of the given family.
+    /// [num_threads](Lincheck::num_threads) and [num_ops](Lincheck::num_ops) are ignored.
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    pub fn verify_family<Conc>(
+        &self,
+        family: &ScenarioFamily<ConcOp<Conc>>,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let constructors = Constructors::new(Conc::default, Conc::Seq::default);
+        self.run_cases(&family.strategy(), Scenario::clone, |scenario| {
+            self.check_scenario_with(scenario, &constructors)
+        })
+    }
+}
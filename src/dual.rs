@@ -0,0 +1,367 @@
+//! Dual data structures.
+//!
+//! In a dual data structure, e.g. a synchronous queue, an operation that can't complete right away,
+//! like taking from an empty queue, doesn't fail or spin but registers a request that other operations fulfill.
+//! Following Scherer and Scott, such an operation is split into two halves:
+//! - the request, which either completes the operation or registers it,
+//! - the follow-up, which returns the result once the request has been fulfilled.
+//!
+//! Each half is linearized on its own. The recorder logs the halves as separate invocations
+//! and the checker validates them against a [DualSequentialSpec] wrapped into [Dual].
+//! A follow-up that is still waiting when the parallel part finishes is recorded as pending.
+
+use proptest::prelude::*;
+use std::fmt::Debug;
+use std::sync::Barrier;
+use std::thread;
+
+use crate::checker::*;
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::recorder::{self, *};
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// Identifies a request, so that its follow-up can find it.
+pub type Ticket = usize;
+
+/// A half of an operation on a dual data structure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DualOp<Op> {
+    /// The request of the operation with the given ticket.
+    Request(Op, Ticket),
+    /// The follow-up of the request with the given ticket.
+    FollowUp(Ticket),
+}
+
+/// The result of a [DualOp].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DualRet<Ret> {
+    /// The operation completed with the result.
+    Completed(Ret),
+    /// The request was registered, or its follow-up found it unfulfilled.
+    Waiting,
+}
+
+/// The sequential specification of a dual data structure.
+pub trait DualSequentialSpec {
+    /// The type of operations.
+    type Op;
+
+    /// The type of return values.
+    type Ret;
+
+    /// Executes the request of an operation.
+    /// Returns the result if the operation completes right away, otherwise registers the request under `ticket`.
+    fn request(&mut self, op: Self::Op, ticket: Ticket) -> Option<Self::Ret>;
+
+    /// Executes the follow-up of the request registered under `ticket`.
+    /// Returns the result if the request has been fulfilled.
+    fn follow_up(&mut self, ticket: Ticket) -> Option<Self::Ret>;
+}
+
+/// Adapts a [DualSequentialSpec] to a [SequentialSpec] of the halves of the operations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Dual<Seq>(pub Seq);
+
+impl<Seq: DualSequentialSpec> SequentialSpec for Dual<Seq> {
+    type Op = DualOp<Seq::Op>;
+    type Ret = DualRet<Seq::Ret>;
+
+    fn exec(&mut self, op: Self::Op) -> Self::Ret {
+        let ret = match op {
+            DualOp::Request(op, ticket) => self.0.request(op, ticket),
+            DualOp::FollowUp(ticket) => self.0.follow_up(ticket),
+        };
+        ret.map_or(DualRet::Waiting, DualRet::Completed)
+    }
+}
+
+/// The concurrent implementation of a dual data structure.
+pub trait DualConcurrentSpec {
+    /// The sequential specification for the data structure.
+    type Seq: DualSequentialSpec;
+
+    /// The handle of a registered request.
+    type Waiter;
+
+    /// Starts an operation. Returns the result if the operation completes right away,
+    /// otherwise registers the request and returns the handle to wait on.
+    fn request(&self, op: DualConcOp<Self>) -> Result<DualConcRet<Self>, Self::Waiter>;
+
+    /// Waits until the request is fulfilled and returns the result.
+    ///
+    /// Returns `None` if it gave up waiting, e.g. on a timeout. The request must stay registered then,
+    /// since the sequential specification can't tell an abandoned request from a slow one.
+    fn follow_up(&self, waiter: Self::Waiter) -> Option<DualConcRet<Self>>;
+}
+
+/// Type alias not to have always write down FQP.
+pub type DualConcOp<T> = <<T as DualConcurrentSpec>::Seq as DualSequentialSpec>::Op;
+
+/// Type alias not to have always write down FQP.
+pub type DualConcRet<T> = <<T as DualConcurrentSpec>::Seq as DualSequentialSpec>::Ret;
+
+impl Lincheck {
+    /// Verifies that the dual data structure `Conc` is linearizable with respect to
+    /// [Dual] of its sequential specification, executing the scenarios on native threads.
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    /// The executions are not reproducible, so the last failing one is returned.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_dual<Conc>(
+        &self,
+    ) -> Result<(), VerificationFailure<DualOp<DualConcOp<Conc>>, DualRet<DualConcRet<Conc>>>>
+    where
+        Conc: DualConcurrentSpec + Default + Sync,
+        Conc::Seq: Default,
+        DualConcOp<Conc>: Send + Clone + Arbitrary + Debug + 'static,
+        DualConcRet<Conc>: Send + PartialEq + Debug,
+    {
+        self.run_cases(
+            &self.scenarios(),
+            |scenario| {
+                // the tickets are the positions of the operations in the scenario
+                let mut next_ticket = 0;
+                scenario.map(|op: &DualConcOp<Conc>| {
+                    next_ticket += 1;
+                    DualOp::Request(op.clone(), next_ticket - 1)
+                })
+            },
+            |scenario| {
+                let execution = execute_dual_scenario_with_threads::<Conc>(scenario);
+                if !LinearizabilityChecker::<Dual<Conc::Seq>>::check(&execution) {
+                    return Err(VerificationFailure::NonLinearizable(execution));
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Executes the given scenario on native threads recording the halves of the operations separately.
+///
+/// The tickets are the positions of the operations in the scenario.
+/// A thread stops after a follow-up that gave up. In the init and post parts, such a follow-up is left out.
+#[allow(clippy::type_complexity)]
+pub fn execute_dual_scenario_with_threads<Conc>(
+    scenario: Scenario<DualConcOp<Conc>>,
+) -> Execution<DualOp<DualConcOp<Conc>>, DualRet<DualConcRet<Conc>>>
+where
+    Conc: DualConcurrentSpec + Default + Sync,
+    DualConcOp<Conc>: Send + Clone,
+    DualConcRet<Conc>: Send,
+{
+    let conc = Conc::default();
+    let mut next_ticket = 0;
+
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+
+    // init part
+    for op in scenario.init_part {
+        record_sequential(&mut recorder, &conc, op, next_ticket);
+        next_ticket += 1;
+    }
+
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
+    let recorder = recorder.record_parallel_part_with_capacity(total_parallel_ops);
+
+    // parallel part
+    // the threads wait on the barrier to start executing the operations at the same time
+    let barrier = Barrier::new(scenario.parallel_part.len());
+    thread::scope(|s| {
+        for thread_ops in scenario.parallel_part {
+            let first_ticket = next_ticket;
+            next_ticket += thread_ops.len();
+
+            let (conc, recorder, barrier) = (&conc, &recorder, &barrier);
+            s.spawn(move || {
+                let mut recorder = recorder.record_thread_with_capacity(2 * thread_ops.len());
+                barrier.wait();
+                for (ticket, op) in (first_ticket..).zip(thread_ops) {
+                    if !record_parallel(&mut recorder, conc, op, ticket) {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    // post part
+    let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+    for op in scenario.post_part {
+        record_sequential(&mut recorder, &conc, op, next_ticket);
+        next_ticket += 1;
+    }
+
+    recorder.finish()
+}
+
+/// Records the request of `op` and waits for its follow-up if needed.
+/// Returns the waiter if the request was registered.
+fn record_request<Conc>(
+    recorder: &mut impl Recorder<Op = DualOp<DualConcOp<Conc>>, Ret = DualRet<DualConcRet<Conc>>>,
+    conc: &Conc,
+    op: DualConcOp<Conc>,
+    ticket: Ticket,
+) -> Option<Conc::Waiter>
+where
+    Conc: DualConcurrentSpec,
+    DualConcOp<Conc>: Clone,
+{
+    let mut waiter = None;
+    recorder.record(DualOp::Request(op.clone(), ticket), || {
+        match conc.request(op) {
+            Ok(ret) => DualRet::Completed(ret),
+            Err(registered) => {
+                waiter = Some(registered);
+                DualRet::Waiting
+            }
+        }
+    });
+    waiter
+}
+
+/// Executes `op` in the init or post part. A follow-up that gave up is left out.
+fn record_sequential<Conc>(
+    recorder: &mut impl Recorder<Op = DualOp<DualConcOp<Conc>>, Ret = DualRet<DualConcRet<Conc>>>,
+    conc: &Conc,
+    op: DualConcOp<Conc>,
+    ticket: Ticket,
+) where
+    Conc: DualConcurrentSpec,
+    DualConcOp<Conc>: Clone,
+{
+    if let Some(waiter) = record_request(recorder, conc, op, ticket) {
+        if let Some(ret) = conc.follow_up(waiter) {
+            recorder.record(DualOp::FollowUp(ticket), || DualRet::Completed(ret));
+        }
+    }
+}
+
+/// Executes `op` in the parallel part. Returns `false` if the follow-up gave up and is left pending.
+fn record_parallel<Conc>(
+    recorder: &mut PerThreadRecorder<'_, DualOp<DualConcOp<Conc>>, DualRet<DualConcRet<Conc>>>,
+    conc: &Conc,
+    op: DualConcOp<Conc>,
+    ticket: Ticket,
+) -> bool
+where
+    Conc: DualConcurrentSpec,
+    DualConcOp<Conc>: Clone,
+{
+    let Some(waiter) = record_request(recorder, conc, op, ticket) else {
+        return true;
+    };
+
+    let mut completed = false;
+    recorder.record_blocking(DualOp::FollowUp(ticket), || {
+        let ret = conc.follow_up(waiter);
+        completed = ret.is_some();
+        ret.map(DualRet::Completed)
+    });
+    completed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution;
+    use std::collections::{HashMap, VecDeque};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Op {
+        Put(u8),
+        Take,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Ret {
+        Put,
+        Take(u8),
+    }
+
+    /// A queue where the takes wait for the puts.
+    #[derive(Default)]
+    struct DualQueue {
+        values: VecDeque<u8>,
+        requests: VecDeque<Ticket>,
+        fulfilled: HashMap<Ticket, u8>,
+    }
+
+    impl DualSequentialSpec for DualQueue {
+        type Op = Op;
+        type Ret = Ret;
+
+        fn request(&mut self, op: Op, ticket: Ticket) -> Option<Ret> {
+            match op {
+                Op::Put(value) => {
+                    match self.requests.pop_front() {
+                        Some(request) => {
+                            self.fulfilled.insert(request, value);
+                        }
+                        None => self.values.push_back(value),
+                    }
+                    Some(Ret::Put)
+                }
+                Op::Take => {
+                    let value = self.values.pop_front();
+                    if value.is_none() {
+                        self.requests.push_back(ticket);
+                    }
+                    value.map(Ret::Take)
+                }
+            }
+        }
+
+        fn follow_up(&mut self, ticket: Ticket) -> Option<Ret> {
+            self.fulfilled.remove(&ticket).map(Ret::Take)
+        }
+    }
+
+    #[test]
+    fn follow_up_after_fulfillment() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: DualOp::Request(Op::Take, 0) => DualRet::Waiting,
+                1 @ 2..3: DualOp::Request(Op::Put(1), 1) => DualRet::Completed(Ret::Put),
+                0 @ 4..5: DualOp::FollowUp(0) => DualRet::Completed(Ret::Take(1)),
+            ],
+        };
+
+        assert!(LinearizabilityChecker::<Dual<DualQueue>>::check(&execution));
+    }
+
+    #[test]
+    fn follow_up_before_fulfillment() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: DualOp::Request(Op::Take, 0) => DualRet::Waiting,
+                0 @ 2..3: DualOp::FollowUp(0) => DualRet::Completed(Ret::Take(1)),
+                1 @ 4..5: DualOp::Request(Op::Put(1), 1) => DualRet::Completed(Ret::Put),
+            ],
+        };
+
+        assert!(!LinearizabilityChecker::<Dual<DualQueue>>::check(
+            &execution
+        ));
+    }
+
+    #[test]
+    fn requests_are_fulfilled_in_order() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: DualOp::Request(Op::Take, 0) => DualRet::Waiting,
+                1 @ 2..3: DualOp::Request(Op::Take, 1) => DualRet::Waiting,
+                2 @ 4..5: DualOp::Request(Op::Put(1), 2) => DualRet::Completed(Ret::Put),
+                1 @ 6..7: DualOp::FollowUp(1) => DualRet::Completed(Ret::Take(1)),
+            ],
+            pending: [0 @ 8..: DualOp::FollowUp(0)],
+        };
+
+        assert!(!LinearizabilityChecker::<Dual<DualQueue>>::check(
+            &execution
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+//! Durable linearizability of persistent data structures.
+//!
+//! A data structure that persists its state, e.g. in persistent memory, must survive a crash of the whole system:
+//! the operations completed before the crash must be recovered, and the operations in flight may be recovered or lost,
+//! but never half-done. [verify_durable](crate::Lincheck::verify_durable) generates along with each scenario a crash point
+//! at which the system crashes. The crash points are marked with [crash_point](crate::crash::crash_point) as in [crash mode](crate::crash)
+//! and counted across the threads in the order [loom] executes them.
+//!
+//! Once the chosen crash point is reached, every thread of the parallel part is killed at its next crash point
+//! and starts no more operations, so the steps between two crash points are atomic with respect to the crash.
+//! If the parallel part passes fewer crash points, the system crashes after it.
+//! The data structure is then rebuilt with [recover](RecoverableSpec::recover), and the post part runs on the recovered instance.
+//!
+//! The operations of the killed threads never return and are pending. The execution is durably linearizable
+//! if it is linearizable with every pending operation either taking effect before the crash or not at all.
+//!
+//! Every operation must reach a crash point without waiting for another thread.
+//! Otherwise a thread waiting for a killed one is never killed itself, and the check fails with a deadlock.
+
+use proptest::prelude::*;
+use proptest::test_runner::TestError;
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::checker::*;
+use crate::crash::{self, KillableThreads, Killer};
+use crate::deadlock::{self, LiveTrace};
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{Lincheck, LoomConfig};
+
+/// The number of the first crash points of the parallel part at which the system may crash.
+const MAX_SYSTEM_CRASH_POINTS: usize = 8;
+
+/// The crash of every thread at once.
+struct SystemCrash {
+    /// The number of crash points left before the crash.
+    left: Cell<usize>,
+    crashed: Cell<bool>,
+}
+
+impl SystemCrash {
+    /// Called at a crash point of any thread, returns whether the system has crashed.
+    fn reached(&self) -> bool {
+        if !self.crashed.get() {
+            match self.left.get().checked_sub(1) {
+                Some(left) => self.left.set(left),
+                None => self.crashed.set(true),
+            }
+        }
+        self.crashed.get()
+    }
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` is durably linearizable,
+    /// i.e. it recovers a linearizable state after the system crashes in the middle of the parallel part.
+    ///
+    /// See the [module-level documentation](crate::durable) for the details.
+    pub fn verify_durable<Conc>(
+        &self,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: RecoverableSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        let strategy = (self.scenarios(), 0..MAX_SYSTEM_CRASH_POINTS);
+
+        let result = self.runner().run(&strategy, |(scenario, crash_at)| {
+            check_durable_scenario_with_loom_using::<Conc>(&self.loom, scenario, crash_at)
+                .map_err(|_| TestCaseError::Fail("Durability failure".into()))
+        });
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(TestError::Fail(_, (scenario, crash_at))) => {
+                // rerun the scenario to get the failing execution
+                Err(
+                    check_durable_scenario_with_loom_using::<Conc>(&self.loom, scenario, crash_at)
+                        .unwrap_err(),
+                )
+            }
+            Err(TestError::Abort(reason)) => {
+                Err(VerificationFailure::GeneratorError(reason.to_string()))
+            }
+        }
+    }
+}
+
+/// Executes the given scenario crashing the system at the crash point `crash_at` of the parallel part,
+/// runs the post part on the recovered instance and checks the resulting execution inside [loom] model-checker.
+pub fn check_durable_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    crash_at: usize,
+) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: RecoverableSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    check_durable_scenario_with_loom_using::<Conc>(&LoomConfig::default(), scenario, crash_at)
+}
+
+/// Same as [check_durable_scenario_with_loom] but configures loom with `loom`.
+pub(crate) fn check_durable_scenario_with_loom_using<Conc>(
+    loom: &LoomConfig,
+    scenario: Scenario<ConcOp<Conc>>,
+    crash_at: usize,
+) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: RecoverableSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    let model_scenario = scenario.clone();
+    crash::with_crash_mode(|| {
+        deadlock::check_traced_with_loom(
+            loom,
+            scenario,
+            move |trace| {
+                execute_durable_scenario_with_loom::<Conc>(&model_scenario, crash_at, trace)
+            },
+            |execution| LinearizabilityChecker::<Conc::Seq>::check(execution),
+        )
+    })
+}
+
+/// Executes the given scenario with [loom] mock threads crashing the system at `crash_at`,
+/// executes the post part on the recovered instance and records the execution into `trace`.
+/// The operations of the killed threads are left pending.
+fn execute_durable_scenario_with_loom<Conc>(
+    scenario: &Scenario<ConcOp<Conc>>,
+    crash_at: usize,
+    trace: &Arc<LiveTrace<ConcOp<Conc>, ConcRet<Conc>>>,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: RecoverableSpec + Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + UnwindSafe + 'static,
+    ConcRet<Conc>: Send + Clone + 'static,
+{
+    trace.reset(scenario.parallel_part.len());
+    let mut conc = Conc::default();
+
+    // init part
+    for op in scenario.init_part.iter().cloned() {
+        let ret = deadlock::exec_traced(None, &op, trace, || conc.exec_mut(op.clone()));
+        trace.push_init(op, ret);
+    }
+
+    // parallel part
+    let conc = Arc::new(conc);
+    let crash = Rc::new(SystemCrash {
+        left: Cell::new(crash_at),
+        crashed: Cell::new(false),
+    });
+    let mut threads = KillableThreads::spawn(
+        &conc,
+        &scenario.parallel_part,
+        trace,
+        |_| {
+            let crash = crash.clone();
+            Some(Box::new(move || crash.reached()) as Killer)
+        },
+        {
+            let crash = crash.clone();
+            move || !crash.crashed.get()
+        },
+    );
+    threads.wait();
+
+    // post part
+    let mut recovered = conc.recover();
+    for op in scenario.post_part.iter().cloned() {
+        let ret = deadlock::exec_traced(None, &op, trace, || recovered.exec_mut(op.clone()));
+        trace.push_post(op, ret);
+    }
+
+    let execution = trace.snapshot().0;
+    threads.release();
+    execution
+}
@@ -0,0 +1,100 @@
+use crate::execution::Execution;
+use crate::scenario::{Scenario, ScenarioPart};
+
+/// Why the verification failed, so that the callers can tell a violation of the correctness condition
+/// from a crashed, a stuck or a misconfigured test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationFailure<Op, Ret> {
+    /// The execution violates the correctness condition, e.g. it is not linearizable.
+    NonLinearizable(Execution<Op, Ret>),
+    /// An operation panicked.
+    Panicked {
+        /// The minimized scenario that triggered the panic.
+        scenario: Scenario<Op>,
+        /// The thread of the parallel part that panicked, or [None] for the init and the post parts.
+        thread: Option<usize>,
+        /// The message of the panic.
+        payload: String,
+        /// The execution up to the panic. The operations of the parallel part that never returned,
+        /// including the panicked one, are pending.
+        partial_execution: Execution<Op, Ret>,
+    },
+    /// Every thread was blocked, e.g. on a lock it already held or on locks taken in the opposite orders.
+    Deadlock {
+        /// The execution up to the deadlock. The operations that never returned are pending.
+        execution_so_far: Execution<Op, Ret>,
+        /// The threads of the parallel part that were blocked in an operation.
+        /// It is empty if the main thread was blocked in the init or the post part.
+        blocked_threads: Vec<usize>,
+    },
+    /// The threads kept running without finishing their operations until [loom] exceeded
+    /// its [branch limit](crate::LoomConfig::max_branches), e.g. spinning on a flag that is never set.
+    Livelock {
+        /// The execution up to the point where [loom] gave up. The operations that never returned are pending.
+        execution_so_far: Execution<Op, Ret>,
+        /// The threads of the parallel part that were still in an operation.
+        /// It is empty if the main thread was stuck in the init or the post part.
+        spinning_threads: Vec<usize>,
+        /// The thread of the parallel part that was running when [loom] gave up, or [None] for the main thread.
+        spinning_thread: Option<usize>,
+        /// The operation that was running when [loom] gave up, i.e. most likely the one spinning forever.
+        /// It is [None] if [loom] gave up outside of an operation.
+        spinning_op: Option<Op>,
+    },
+    /// Checking an execution took longer than the [check_timeout](crate::Lincheck::check_timeout),
+    /// so it is unknown whether the execution is correct.
+    CheckerTimeout {
+        /// The scenario whose execution took too long to check.
+        scenario: Scenario<Op>,
+        /// The execution that took too long to check.
+        execution: Execution<Op, Ret>,
+    },
+    /// [validate](crate::ConcurrentSpec::validate) found an invalid state of the data structure,
+    /// even though the execution may be linearizable.
+    InvalidState {
+        /// The scenario that left the data structure in the invalid state.
+        scenario: Scenario<Op>,
+        /// The execution of the instance in the invalid state.
+        execution: Execution<Op, Ret>,
+        /// The part of the scenario after which the state was invalid.
+        after: ScenarioPart,
+        /// The description of the violated invariant.
+        message: String,
+    },
+    /// The memory [tracked](crate::leaks) while executing the scenario leaked or was freed twice,
+    /// even though the execution may be linearizable.
+    MemoryError {
+        /// The scenario that leaked or double-freed the memory.
+        scenario: Scenario<Op>,
+        /// The execution of the scenario, on the first instance if there are several.
+        execution: Execution<Op, Ret>,
+        /// The number of allocations that weren't freed after the instances were dropped.
+        leaked: usize,
+        /// The number of times the memory was freed twice.
+        double_frees: usize,
+    },
+    /// The scenarios couldn't be generated, e.g. the strategies rejected too many of them.
+    GeneratorError(String),
+}
+
+impl<Op, Ret> VerificationFailure<Op, Ret> {
+    /// The execution that shows the failure, complete or up to the point where it stopped.
+    pub fn execution(&self) -> Option<&Execution<Op, Ret>> {
+        match self {
+            VerificationFailure::NonLinearizable(execution) => Some(execution),
+            VerificationFailure::Panicked {
+                partial_execution, ..
+            } => Some(partial_execution),
+            VerificationFailure::Deadlock {
+                execution_so_far, ..
+            }
+            | VerificationFailure::Livelock {
+                execution_so_far, ..
+            } => Some(execution_so_far),
+            VerificationFailure::CheckerTimeout { execution, .. }
+            | VerificationFailure::InvalidState { execution, .. }
+            | VerificationFailure::MemoryError { execution, .. } => Some(execution),
+            VerificationFailure::GeneratorError(_) => None,
+        }
+    }
+}
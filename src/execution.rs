@@ -1,17 +1,24 @@
 use std::ops::{Deref, DerefMut};
 
-pub(crate) type Timestamp = usize;
-pub(crate) type ThreadId = usize;
-pub(crate) type InvocationId = usize;
+/// A point in time of an [Execution]. Only the order of the timestamps matters.
+pub type Timestamp = usize;
+/// The index of a thread of the parallel part.
+pub type ThreadId = usize;
+/// The index of an invocation of the parallel part. The pending invocations are indexed after the completed ones.
+pub type InvocationId = usize;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) struct Invocation<Op, Ret> {
+/// An invocation of the init or the post part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Invocation<Op, Ret> {
     pub(crate) op: Op,
     pub(crate) ret: Ret,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) struct ParallelInvocation<Op, Ret> {
+/// A completed invocation of the parallel part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParallelInvocation<Op, Ret> {
     pub(crate) thread_id: ThreadId,
     pub(crate) call_timestamp: Timestamp,
     pub(crate) return_timestamp: Timestamp,
@@ -20,13 +27,55 @@ pub(crate) struct ParallelInvocation<Op, Ret> {
     pub(crate) ret: Ret,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct History<Op, Ret> {
+/// An invocation of the parallel part that was called but never returned, e.g. a blocking operation.
+///
+/// A cancelled invocation, e.g. a dropped future, was abandoned by its thread at the cancel timestamp.
+/// It may have taken effect before it was cancelled or not at all, and the thread may call more operations after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingInvocation<Op> {
+    pub(crate) thread_id: ThreadId,
+    pub(crate) call_timestamp: Timestamp,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub(crate) cancel_timestamp: Option<Timestamp>,
+
+    pub(crate) op: Op,
+}
+
+/// The interval of an invocation of the parallel part.
+/// Pending invocations return at [Timestamp::MAX], the cancelled ones at their cancel timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Interval {
+    pub(crate) thread_id: ThreadId,
+    pub(crate) call_timestamp: Timestamp,
+    pub(crate) return_timestamp: Timestamp,
+}
+
+/// The position of an invocation in an [Execution].
+#[cfg(feature = "color")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InvocationRef {
+    Init(usize),
+    Parallel(InvocationId),
+    Post(usize),
+}
+
+/// The invocations of the init or the post part in the order of execution.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct History<Op, Ret> {
     inner: Vec<Invocation<Op, Ret>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct ParallelHistory<Op, Ret> {
+/// The completed invocations of the parallel part. The invocations of each thread are in the program order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct ParallelHistory<Op, Ret> {
     inner: Vec<ParallelInvocation<Op, Ret>>,
 }
 
@@ -35,60 +84,750 @@ pub(crate) struct ParallelHistory<Op, Ret> {
 /// - The parallel part, which consists of invocations that happen concurrently
 /// - The post part, which consists of invocations that happen after all other invocations
 ///
+/// Besides, the parallel part may end with pending invocations, which were called but never returned,
+/// e.g. blocking operations that were still blocked when the parallel part finished.
+/// Each of them may take effect at any point after its call and before the post part, or not take effect at all.
+///
 /// It is usually the result of recording the execution of a [Scenario](crate::scenario::Scenario).
 ///
-/// There are two ways to obtain the execution trace:
+/// There are three ways to obtain the execution trace:
 /// - By recording the execution of a [Scenario](crate::scenario::Scenario) using [execute_scenario_with_loom](crate::scenario::execute_scenario_with_loom).
 /// - By explicitly using the [recorder](crate::recorder) module.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// - By building it from a history captured elsewhere with [Execution::builder].
+///
+/// With the `serde` feature, an execution can be saved with [to_ron](Execution::to_ron),
+/// e.g. to attach a counterexample to a bug report, and loaded back with [from_ron](Execution::from_ron).
+/// See also [to_html](Execution::to_html) for a report that can be viewed in a browser.
+/// [to_dot](Execution::to_dot) and [to_mermaid](Execution::to_mermaid) export the happens-before graph of the invocations.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Execution<Op, Ret> {
     pub(crate) init_part: History<Op, Ret>,
     pub(crate) parallel_part: ParallelHistory<Op, Ret>,
     pub(crate) post_part: History<Op, Ret>,
+    pub(crate) pending: Vec<PendingInvocation<Op>>,
+}
+
+#[cfg(feature = "serde")]
+impl<Op: serde::Serialize, Ret: serde::Serialize> Execution<Op, Ret> {
+    /// Serializes the execution to a RON string.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Op: serde::de::DeserializeOwned, Ret: serde::de::DeserializeOwned> Execution<Op, Ret> {
+    /// Parses an execution from a RON string.
+    pub fn from_ron(s: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(s)
+    }
+}
+
+/// Builds an [Execution] with explicit invocation intervals, e.g. to test specifications against hand-crafted histories.
+///
+/// ```
+/// # use lincheck::execution;
+/// let execution = execution! {
+///     init: [("push", 1) => None],
+///     parallel: [
+///         0 @ 4..6: ("pop", 0) => Some(1),
+///         1 @ 5..7: ("pop", 0) => None,
+///     ],
+///     post: [("pop", 0) => None],
+/// };
+/// ```
+///
+/// Each invocation of the parallel part is written as `thread @ call..return: op => ret`,
+/// where `call` and `return` are the timestamps of the call and the return.
+/// Pending invocations are written as `thread @ call..: op`:
+///
+/// ```
+/// # use lincheck::execution;
+/// let execution = execution! {
+///     parallel: [0 @ 0..1: ("push", 1) => ()],
+///     pending: [1 @ 2..: ("pop", 0)],
+/// };
+/// ```
+///
+/// Cancelled invocations are written as `thread @ call..cancel: op`:
+///
+/// ```
+/// # use lincheck::execution;
+/// let execution = execution! {
+///     parallel: [1 @ 3..4: ("pop", 0) => None::<i32>],
+///     cancelled: [0 @ 0..1: ("push", 1)],
+/// };
+/// ```
+///
+/// The init, post, pending and cancelled parts are empty if omitted.
+#[macro_export]
+macro_rules! execution {
+    (
+        $(init: [$($init_op:expr => $init_ret:expr),* $(,)?],)?
+        parallel: [$($thread:literal @ $call:literal .. $return:literal : $op:expr => $ret:expr),* $(,)?]
+        $(, pending: [$($pending_thread:literal @ $pending_call:literal .. : $pending_op:expr),* $(,)?])?
+        $(, cancelled: [$($cancelled_thread:literal @ $cancelled_call:literal .. $cancel:literal : $cancelled_op:expr),* $(,)?])?
+        $(, post: [$($post_op:expr => $post_ret:expr),* $(,)?])?
+        $(,)?
+    ) => {
+        $crate::Execution::from_intervals(
+            vec![$($(($init_op, $init_ret)),*)?],
+            vec![$(($thread, $call, $return, $op, $ret)),*],
+            vec![$($(($post_op, $post_ret)),*)?],
+        )
+        .with_pending(vec![$($(($pending_thread, $pending_call, $pending_op)),*)?])
+        .with_cancelled(vec![$($(($cancelled_thread, $cancelled_call, $cancel, $cancelled_op)),*)?])
+    };
+}
+
+impl<Op, Ret> Execution<Op, Ret> {
+    /// The function behind [execution!](crate::execution!).
+    ///
+    /// # Panics
+    ///
+    /// Panics if an invocation returns before it is called
+    /// or if the invocations of a thread overlap.
+    #[doc(hidden)]
+    #[allow(clippy::type_complexity)]
+    pub fn from_intervals(
+        init_part: Vec<(Op, Ret)>,
+        parallel_part: Vec<(ThreadId, Timestamp, Timestamp, Op, Ret)>,
+        post_part: Vec<(Op, Ret)>,
+    ) -> Self {
+        let history = |invocations: Vec<(Op, Ret)>| -> History<Op, Ret> {
+            invocations
+                .into_iter()
+                .map(|(op, ret)| Invocation { op, ret })
+                .collect::<Vec<_>>()
+                .into()
+        };
+
+        let parallel_part: ParallelHistory<Op, Ret> = parallel_part
+            .into_iter()
+            .map(
+                |(thread_id, call_timestamp, return_timestamp, op, ret)| ParallelInvocation {
+                    thread_id,
+                    call_timestamp,
+                    return_timestamp,
+                    op,
+                    ret,
+                },
+            )
+            .collect::<Vec<_>>()
+            .into();
+
+        let execution = Execution {
+            init_part: history(init_part),
+            parallel_part,
+            post_part: history(post_part),
+            pending: Vec::new(),
+        };
+        if let Err(error) = execution.validate() {
+            panic!("{}", error);
+        }
+        execution
+    }
+
+    /// Adds the pending invocations for [execution!](crate::execution!).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pending invocation is called before the previous invocation of its thread returns
+    /// or if a thread has several pending invocations.
+    #[doc(hidden)]
+    pub fn with_pending(mut self, pending: Vec<(ThreadId, Timestamp, Op)>) -> Self {
+        self.pending
+            .extend(pending.into_iter().map(|(thread_id, call_timestamp, op)| {
+                PendingInvocation::new(thread_id, call_timestamp, op)
+            }));
+        if let Err(error) = self.validate() {
+            panic!("{}", error);
+        }
+        self
+    }
+
+    /// Adds the cancelled invocations for [execution!](crate::execution!).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a cancelled invocation is cancelled before it is called or overlaps another invocation of its thread.
+    #[doc(hidden)]
+    pub fn with_cancelled(mut self, cancelled: Vec<(ThreadId, Timestamp, Timestamp, Op)>) -> Self {
+        self.pending.extend(cancelled.into_iter().map(
+            |(thread_id, call_timestamp, cancel_timestamp, op)| {
+                PendingInvocation::cancelled(thread_id, call_timestamp, cancel_timestamp, op)
+            },
+        ));
+        if let Err(error) = self.validate() {
+            panic!("{}", error);
+        }
+        self
+    }
+
+    /// Starts building an execution, e.g. from a history captured by another tracing system.
+    pub fn builder() -> ExecutionBuilder<Op, Ret> {
+        ExecutionBuilder::default()
+    }
+
+    /// The invocations of the init part.
+    pub fn init_part(&self) -> &History<Op, Ret> {
+        &self.init_part
+    }
+
+    /// The completed invocations of the parallel part.
+    pub fn parallel_part(&self) -> &ParallelHistory<Op, Ret> {
+        &self.parallel_part
+    }
+
+    /// The invocations of the post part.
+    pub fn post_part(&self) -> &History<Op, Ret> {
+        &self.post_part
+    }
+
+    /// The invocations of the parallel part that were called but never returned.
+    pub fn pending(&self) -> &[PendingInvocation<Op>] {
+        &self.pending
+    }
+
+    /// Checks that every invocation returns or is cancelled after it is called, the invocations of a thread don't overlap
+    /// and a pending invocation that isn't cancelled is the only one and the last one of its thread.
+    fn validate(&self) -> Result<(), InvalidExecution> {
+        for inv in self.parallel_part.iter() {
+            if inv.call_timestamp >= inv.return_timestamp {
+                return Err(InvalidExecution::ReturnBeforeCall {
+                    thread_id: inv.thread_id,
+                });
+            }
+        }
+
+        for thread_id in 0..self.parallel_part.num_threads() {
+            let mut prev_return_timestamp = None;
+            for inv in self.parallel_part.thread(thread_id) {
+                if prev_return_timestamp >= Some(inv.call_timestamp) {
+                    return Err(InvalidExecution::OverlappingInvocations { thread_id });
+                }
+                prev_return_timestamp = Some(inv.return_timestamp);
+            }
+        }
+
+        let intervals = self.intervals();
+        let (cancelled, pending): (Vec<_>, Vec<_>) = (self.pending.iter().enumerate())
+            .map(|(index, inv)| (self.parallel_part.len() + index, inv))
+            .partition(|(_, inv)| inv.cancel_timestamp.is_some());
+
+        // a cancelled invocation is checked like a completed one returning at its cancel timestamp
+        for &(inv_id, inv) in cancelled.iter() {
+            let thread_id = inv.thread_id;
+            let interval = intervals[inv_id];
+            if interval.call_timestamp >= interval.return_timestamp {
+                return Err(InvalidExecution::ReturnBeforeCall { thread_id });
+            }
+            let overlaps = (0..self.parallel_part.len())
+                .chain(cancelled.iter().map(|&(other_id, _)| other_id))
+                .filter(|&other_id| {
+                    other_id != inv_id && intervals[other_id].thread_id == thread_id
+                })
+                .map(|other_id| intervals[other_id])
+                .any(|other| {
+                    other.call_timestamp < interval.return_timestamp
+                        && interval.call_timestamp < other.return_timestamp
+                });
+            if overlaps {
+                return Err(InvalidExecution::OverlappingInvocations { thread_id });
+            }
+        }
+
+        for (index, &(_, inv)) in pending.iter().enumerate() {
+            let thread_id = inv.thread_id;
+            if pending[..index]
+                .iter()
+                .any(|(_, other)| other.thread_id == thread_id)
+            {
+                return Err(InvalidExecution::SeveralPendingInvocations { thread_id });
+            }
+            let returns_later = (intervals.iter())
+                .filter(|other| other.thread_id == thread_id)
+                .any(|other| {
+                    other.return_timestamp != Timestamp::MAX
+                        && other.return_timestamp >= inv.call_timestamp
+                });
+            if returns_later {
+                return Err(InvalidExecution::PendingInvocationNotLast { thread_id });
+            }
+        }
+        Ok(())
+    }
+
+    /// The intervals of the parallel part followed by the ones of the pending invocations,
+    /// indexed by the invocation ids.
+    pub(crate) fn intervals(&self) -> Vec<Interval> {
+        let completed = self.parallel_part.iter().map(|inv| Interval {
+            thread_id: inv.thread_id,
+            call_timestamp: inv.call_timestamp,
+            return_timestamp: inv.return_timestamp,
+        });
+        let pending = self.pending.iter().map(|inv| Interval {
+            thread_id: inv.thread_id,
+            call_timestamp: inv.call_timestamp,
+            return_timestamp: inv.cancel_timestamp.unwrap_or(Timestamp::MAX),
+        });
+        completed.chain(pending).collect()
+    }
+
+    /// Whether the invocation is a [cancelled](PendingInvocation::cancel_timestamp) pending one.
+    pub(crate) fn is_cancelled(&self, inv_id: InvocationId) -> bool {
+        (inv_id.checked_sub(self.parallel_part.len()))
+            .and_then(|index| self.pending.get(index))
+            .is_some_and(|inv| inv.cancel_timestamp.is_some())
+    }
+}
+
+/// Builds an [Execution] invocation by invocation.
+///
+/// ```
+/// # use lincheck::Execution;
+/// let execution = Execution::builder()
+///     .init(("push", 1), None)
+///     .parallel(0, 4, 6, ("pop", 0), Some(1))
+///     .parallel(1, 5, 7, ("pop", 0), None)
+///     .pending(0, 8, ("pop", 0))
+///     .post(("pop", 0), None)
+///     .build()
+///     .unwrap();
+/// assert_eq!(execution.parallel_part().len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExecutionBuilder<Op, Ret> {
+    execution: Execution<Op, Ret>,
+}
+
+impl<Op, Ret> Default for ExecutionBuilder<Op, Ret> {
+    fn default() -> Self {
+        Self {
+            execution: Execution::default(),
+        }
+    }
+}
+
+impl<Op, Ret> ExecutionBuilder<Op, Ret> {
+    /// Appends an invocation to the init part.
+    pub fn init(mut self, op: Op, ret: Ret) -> Self {
+        self.execution.init_part.push(Invocation::new(op, ret));
+        self
+    }
+
+    /// Adds a completed invocation of the parallel part called by the thread at `call_timestamp`
+    /// and returned at `return_timestamp`. The invocations may be added in any order.
+    pub fn parallel(
+        mut self,
+        thread_id: ThreadId,
+        call_timestamp: Timestamp,
+        return_timestamp: Timestamp,
+        op: Op,
+        ret: Ret,
+    ) -> Self {
+        self.execution.parallel_part.push(ParallelInvocation::new(
+            thread_id,
+            call_timestamp,
+            return_timestamp,
+            op,
+            ret,
+        ));
+        self
+    }
+
+    /// Adds an invocation of the parallel part called by the thread at `call_timestamp` that never returned.
+    pub fn pending(mut self, thread_id: ThreadId, call_timestamp: Timestamp, op: Op) -> Self {
+        self.execution
+            .pending
+            .push(PendingInvocation::new(thread_id, call_timestamp, op));
+        self
+    }
+
+    /// Adds an invocation of the parallel part called by the thread at `call_timestamp`
+    /// and cancelled at `cancel_timestamp` without returning.
+    pub fn cancelled(
+        mut self,
+        thread_id: ThreadId,
+        call_timestamp: Timestamp,
+        cancel_timestamp: Timestamp,
+        op: Op,
+    ) -> Self {
+        self.execution.pending.push(PendingInvocation::cancelled(
+            thread_id,
+            call_timestamp,
+            cancel_timestamp,
+            op,
+        ));
+        self
+    }
+
+    /// Appends an invocation to the post part.
+    pub fn post(mut self, op: Op, ret: Ret) -> Self {
+        self.execution.post_part.push(Invocation::new(op, ret));
+        self
+    }
+
+    /// Sorts the parallel part by the call timestamps and checks that the execution is well-formed.
+    pub fn build(mut self) -> Result<Execution<Op, Ret>, InvalidExecution> {
+        self.execution
+            .parallel_part
+            .sort_by_key(|inv| inv.call_timestamp);
+        self.execution.validate()?;
+        Ok(self.execution)
+    }
+}
+
+/// Why an [ExecutionBuilder] couldn't build an execution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InvalidExecution {
+    /// An invocation of the thread returns before it is called.
+    ReturnBeforeCall {
+        /// The thread of the invocation.
+        thread_id: ThreadId,
+    },
+    /// An invocation of the thread is called before the previous one returns.
+    OverlappingInvocations {
+        /// The thread of the invocations.
+        thread_id: ThreadId,
+    },
+    /// The thread has several pending invocations.
+    SeveralPendingInvocations {
+        /// The thread of the invocations.
+        thread_id: ThreadId,
+    },
+    /// The pending invocation of the thread is called before another invocation of the thread returns.
+    PendingInvocationNotLast {
+        /// The thread of the invocations.
+        thread_id: ThreadId,
+    },
+}
+
+impl std::error::Error for InvalidExecution {}
+
+impl<Op, Ret> Invocation<Op, Ret> {
+    /// Creates an invocation of the init or the post part.
+    pub fn new(op: Op, ret: Ret) -> Self {
+        Self { op, ret }
+    }
+
+    /// The invoked operation.
+    pub fn op(&self) -> &Op {
+        &self.op
+    }
+
+    /// The result of the operation.
+    pub fn ret(&self) -> &Ret {
+        &self.ret
+    }
+}
+
+impl<Op, Ret> ParallelInvocation<Op, Ret> {
+    /// Creates a completed invocation of the parallel part.
+    pub fn new(
+        thread_id: ThreadId,
+        call_timestamp: Timestamp,
+        return_timestamp: Timestamp,
+        op: Op,
+        ret: Ret,
+    ) -> Self {
+        Self {
+            thread_id,
+            call_timestamp,
+            return_timestamp,
+            op,
+            ret,
+        }
+    }
+
+    /// The thread that called the operation.
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+
+    /// When the operation was called.
+    pub fn call_timestamp(&self) -> Timestamp {
+        self.call_timestamp
+    }
+
+    /// When the operation returned.
+    pub fn return_timestamp(&self) -> Timestamp {
+        self.return_timestamp
+    }
+
+    /// The invoked operation.
+    pub fn op(&self) -> &Op {
+        &self.op
+    }
+
+    /// The result of the operation.
+    pub fn ret(&self) -> &Ret {
+        &self.ret
+    }
+}
+
+impl<Op> PendingInvocation<Op> {
+    /// Creates an invocation of the parallel part that never returned.
+    pub fn new(thread_id: ThreadId, call_timestamp: Timestamp, op: Op) -> Self {
+        Self {
+            thread_id,
+            call_timestamp,
+            cancel_timestamp: None,
+            op,
+        }
+    }
+
+    /// Creates an invocation of the parallel part that was cancelled at `cancel_timestamp` without returning.
+    pub fn cancelled(
+        thread_id: ThreadId,
+        call_timestamp: Timestamp,
+        cancel_timestamp: Timestamp,
+        op: Op,
+    ) -> Self {
+        Self {
+            thread_id,
+            call_timestamp,
+            cancel_timestamp: Some(cancel_timestamp),
+            op,
+        }
+    }
+
+    /// The thread that called the operation.
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+
+    /// When the operation was called.
+    pub fn call_timestamp(&self) -> Timestamp {
+        self.call_timestamp
+    }
+
+    /// When the operation was cancelled, or [None] if it is still pending.
+    pub fn cancel_timestamp(&self) -> Option<Timestamp> {
+        self.cancel_timestamp
+    }
+
+    /// The invoked operation.
+    pub fn op(&self) -> &Op {
+        &self.op
+    }
+}
+
+impl<Op, Ret> ParallelHistory<Op, Ret> {
+    /// The number of threads, i.e. one more than the largest thread id.
+    pub fn num_threads(&self) -> usize {
+        self.inner
+            .iter()
+            .map(|inv| inv.thread_id + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Iterates over the invocations of the given thread in the program order.
+    pub fn thread(
+        &self,
+        thread_id: ThreadId,
+    ) -> impl Iterator<Item = &ParallelInvocation<Op, Ret>> + '_ {
+        self.inner
+            .iter()
+            .filter(move |inv| inv.thread_id == thread_id)
+    }
+
+    /// Iterates over the invocations in the order of call timestamps.
+    pub fn by_call(&self) -> impl Iterator<Item = &ParallelInvocation<Op, Ret>> + '_ {
+        self.sorted_by_key(|inv| inv.call_timestamp)
+    }
+
+    /// Iterates over the invocations in the order of return timestamps.
+    pub fn by_return(&self) -> impl Iterator<Item = &ParallelInvocation<Op, Ret>> + '_ {
+        self.sorted_by_key(|inv| inv.return_timestamp)
+    }
+
+    /// Iterates over the invocations pending at some point of the interval `[from, to)`
+    /// in the order of call timestamps.
+    pub fn during(
+        &self,
+        from: Timestamp,
+        to: Timestamp,
+    ) -> impl Iterator<Item = &ParallelInvocation<Op, Ret>> + '_ {
+        self.by_call()
+            .take_while(move |inv| inv.call_timestamp < to)
+            .filter(move |inv| inv.return_timestamp >= from)
+    }
+
+    fn sorted_by_key(
+        &self,
+        key: impl Fn(&ParallelInvocation<Op, Ret>) -> Timestamp,
+    ) -> impl Iterator<Item = &ParallelInvocation<Op, Ret>> + '_ {
+        let mut inv_ids: Vec<InvocationId> = (0..self.inner.len()).collect();
+        inv_ids.sort_by_key(|&inv_id| key(&self.inner[inv_id]));
+        inv_ids.into_iter().map(move |inv_id| &self.inner[inv_id])
+    }
 }
 
 impl<Op, Ret> ParallelHistory<Op, Ret> {
-    pub fn get_thread_parts(&self) -> Vec<Vec<&ParallelInvocation<Op, Ret>>> {
-        let mut thread_parts = Vec::new();
-        for inv in &self.inner {
-            if thread_parts.len() <= inv.thread_id {
-                thread_parts.resize_with(inv.thread_id + 1, Vec::new);
+    /// Splits the history at quiescent points, i.e. timestamps at which no invocation is pending.
+    /// Returns the invocation ids of each segment in the order of call timestamps.
+    ///
+    /// Every invocation of a segment happens-before every invocation of the next segments.
+    pub fn quiescent_segments(&self) -> Vec<Vec<InvocationId>> {
+        let intervals: Vec<_> = self
+            .inner
+            .iter()
+            .map(|inv| (inv.call_timestamp, inv.return_timestamp))
+            .collect();
+        quiescent_segments(&intervals)
+    }
+}
+
+/// Splits the invocations with the given `(call, return)` intervals at quiescent points.
+/// Returns the invocation ids of each segment in the order of call timestamps.
+pub(crate) fn quiescent_segments(intervals: &[(Timestamp, Timestamp)]) -> Vec<Vec<InvocationId>> {
+    let mut inv_ids: Vec<_> = (0..intervals.len()).collect();
+    inv_ids.sort_by_key(|&inv_id| intervals[inv_id].0);
+
+    let mut segments: Vec<Vec<InvocationId>> = Vec::new();
+    let mut max_return_timestamp = None;
+    for inv_id in inv_ids {
+        let (call_timestamp, return_timestamp) = intervals[inv_id];
+        match max_return_timestamp {
+            Some(timestamp) if timestamp > call_timestamp => {
+                segments.last_mut().unwrap().push(inv_id);
             }
-            thread_parts[inv.thread_id].push(inv);
+            _ => segments.push(vec![inv_id]),
+        }
+        max_return_timestamp = max_return_timestamp.max(Some(return_timestamp));
+    }
+    segments
+}
+
+impl<Op: Clone, Ret: Clone> Execution<Op, Ret> {
+    /// Keeps only the invocations whose operations satisfy the predicate, keeping the timestamps.
+    pub(crate) fn filter(&self, mut predicate: impl FnMut(&Op) -> bool) -> Self {
+        let mut filter_history = |history: &History<Op, Ret>| -> History<Op, Ret> {
+            history
+                .iter()
+                .filter(|inv| predicate(&inv.op))
+                .cloned()
+                .collect::<Vec<_>>()
+                .into()
+        };
+        let init_part = filter_history(&self.init_part);
+        let post_part = filter_history(&self.post_part);
+
+        let parallel_part = self
+            .parallel_part
+            .iter()
+            .filter(|inv| predicate(&inv.op))
+            .cloned()
+            .collect::<Vec<_>>()
+            .into();
+
+        let pending = self
+            .pending
+            .iter()
+            .filter(|inv| predicate(&inv.op))
+            .cloned()
+            .collect();
+
+        Execution {
+            init_part,
+            parallel_part,
+            post_part,
+            pending,
+        }
+    }
+}
+
+impl<Op, Ret> Execution<Op, Ret> {
+    /// Maps the operation and the result of every invocation keeping the timestamps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the execution has pending invocations since they have no result to map.
+    #[cfg(feature = "proptest")]
+    pub(crate) fn map<Op2, Ret2>(
+        self,
+        mut f: impl FnMut(Op, Ret) -> (Op2, Ret2),
+    ) -> Execution<Op2, Ret2> {
+        assert!(
+            self.pending.is_empty(),
+            "pending invocations can't be mapped"
+        );
+        let mut map_history = |history: History<Op, Ret>| -> History<Op2, Ret2> {
+            history
+                .into_iter()
+                .map(|inv| {
+                    let (op, ret) = f(inv.op, inv.ret);
+                    Invocation { op, ret }
+                })
+                .collect::<Vec<_>>()
+                .into()
+        };
+        let init_part = map_history(self.init_part);
+        let post_part = map_history(self.post_part);
+
+        let parallel_part = self
+            .parallel_part
+            .into_iter()
+            .map(|inv| {
+                let (op, ret) = f(inv.op, inv.ret);
+                ParallelInvocation {
+                    thread_id: inv.thread_id,
+                    call_timestamp: inv.call_timestamp,
+                    return_timestamp: inv.return_timestamp,
+                    op,
+                    ret,
+                }
+            })
+            .collect::<Vec<_>>()
+            .into();
+
+        Execution {
+            init_part,
+            parallel_part,
+            post_part,
+            pending: Vec::new(),
         }
-        thread_parts
     }
 }
 
 // The rest of the file consists of boilerplate trait implementations
 
 impl<Op, Ret> History<Op, Ret> {
+    /// Creates an empty history.
     pub fn new() -> Self {
         Self { inner: Vec::new() }
     }
 
+    /// Creates an empty history with space for `cap` invocations.
     pub fn with_capacity(cap: usize) -> Self {
         Self {
             inner: Vec::with_capacity(cap),
         }
     }
 
+    /// Returns the invocations.
     pub fn into_inner(self) -> Vec<Invocation<Op, Ret>> {
         self.inner
     }
 }
 
 impl<Op, Ret> ParallelHistory<Op, Ret> {
+    /// Creates an empty history.
     pub fn new() -> Self {
         Self { inner: Vec::new() }
     }
 
+    /// Creates an empty history with space for `cap` invocations.
     pub fn with_capacity(cap: usize) -> Self {
         Self {
             inner: Vec::with_capacity(cap),
         }
     }
 
+    /// Returns the invocations.
     pub fn into_inner(self) -> Vec<ParallelInvocation<Op, Ret>> {
         self.inner
     }
@@ -218,6 +957,131 @@ impl<Op, Ret> Default for Execution<Op, Ret> {
             init_part: History::default(),
             parallel_part: ParallelHistory::default(),
             post_part: History::default(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invocation(
+        thread_id: ThreadId,
+        call_timestamp: Timestamp,
+        return_timestamp: Timestamp,
+    ) -> ParallelInvocation<usize, ()> {
+        ParallelInvocation {
+            thread_id,
+            call_timestamp,
+            return_timestamp,
+            op: call_timestamp,
+            ret: (),
         }
     }
+
+    fn ops<'a>(invs: impl Iterator<Item = &'a ParallelInvocation<usize, ()>>) -> Vec<usize> {
+        invs.map(|inv| inv.op).collect()
+    }
+
+    #[test]
+    fn parallel_history_views() {
+        let history: ParallelHistory<_, _> = vec![
+            invocation(0, 0, 3),
+            invocation(0, 4, 5),
+            invocation(2, 1, 2),
+            invocation(2, 6, 8),
+        ]
+        .into();
+
+        assert_eq!(history.num_threads(), 3);
+        assert_eq!(ops(history.thread(0)), vec![0, 4]);
+        assert_eq!(ops(history.thread(1)), vec![]);
+        assert_eq!(ops(history.by_call()), vec![0, 1, 4, 6]);
+        assert_eq!(ops(history.by_return()), vec![1, 0, 4, 6]);
+        assert_eq!(ops(history.during(3, 6)), vec![0, 4]);
+        assert_eq!(ops(history.during(9, 10)), vec![]);
+    }
+
+    #[test]
+    fn builder_sorts_the_parallel_part() {
+        let execution = Execution::builder()
+            .init(0, ())
+            .parallel(1, 2, 4, 2, ())
+            .parallel(0, 0, 3, 0, ())
+            .pending(1, 5, 5)
+            .post(9, ())
+            .build()
+            .unwrap();
+
+        assert_eq!(execution.init_part().first().map(Invocation::op), Some(&0));
+        assert_eq!(ops(execution.parallel_part().iter()), vec![0, 2]);
+        assert_eq!(execution.pending()[0].thread_id(), 1);
+        assert_eq!(execution.post_part().len(), 1);
+    }
+
+    #[test]
+    fn builder_rejects_invalid_executions() {
+        let build = |builder: ExecutionBuilder<usize, ()>| builder.build().unwrap_err();
+
+        assert_eq!(
+            build(Execution::builder().parallel(0, 2, 2, 0, ())),
+            InvalidExecution::ReturnBeforeCall { thread_id: 0 }
+        );
+        assert_eq!(
+            build(
+                Execution::builder()
+                    .parallel(1, 0, 2, 0, ())
+                    .parallel(1, 1, 3, 1, ())
+            ),
+            InvalidExecution::OverlappingInvocations { thread_id: 1 }
+        );
+        assert_eq!(
+            build(Execution::builder().pending(0, 0, 0).pending(0, 1, 1)),
+            InvalidExecution::SeveralPendingInvocations { thread_id: 0 }
+        );
+        assert_eq!(
+            build(
+                Execution::builder()
+                    .pending(0, 0, 0)
+                    .parallel(0, 1, 2, 1, ())
+            ),
+            InvalidExecution::PendingInvocationNotLast { thread_id: 0 }
+        );
+        assert_eq!(
+            build(Execution::builder().cancelled(0, 2, 2, 0)),
+            InvalidExecution::ReturnBeforeCall { thread_id: 0 }
+        );
+        assert_eq!(
+            build(
+                Execution::builder()
+                    .cancelled(0, 0, 2, 0)
+                    .parallel(0, 1, 3, 1, ())
+            ),
+            InvalidExecution::OverlappingInvocations { thread_id: 0 }
+        );
+        assert!(Execution::builder()
+            .cancelled(0, 0, 1, 0)
+            .parallel(0, 2, 3, 1, ())
+            .pending(0, 4, 2)
+            .build()
+            .is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn ron_round_trip() {
+        let execution = crate::execution! {
+            init: [Some(1) => true],
+            parallel: [
+                0 @ 0..2: None => false,
+                1 @ 1..3: Some(2) => true,
+            ],
+            pending: [1 @ 4..: None],
+            post: [None => false],
+        };
+
+        let ron = execution.to_ron().unwrap();
+        assert_eq!(Execution::from_ron(&ron).unwrap(), execution);
+    }
 }
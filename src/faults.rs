@@ -0,0 +1,302 @@
+//! User-defined fault injection.
+//!
+//! The data structure marks the points where an operation may fail with [fail_point!](crate::fail_point),
+//! e.g. an allocation or an I/O call, and handles the failure as it would in production.
+//! [verify_faults](crate::Lincheck::verify_faults) picks one thread of the parallel part and triggers
+//! one of the fail points it reaches in every interleaving explored by [loom].
+//!
+//! The sequential specification decides what a faulted operation returns with [FaultySpec::exec_faulted].
+//! The triggered fail points are recorded next to the operations, so they are displayed with the execution.
+//!
+//! Outside of fault injection the fail points are never triggered.
+
+use proptest::prelude::*;
+use proptest::sample::Index;
+use std::cell::RefCell;
+use std::fmt::{self, Debug, Formatter};
+use std::panic::{self, AssertUnwindSafe, UnwindSafe};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use loom::thread;
+
+use crate::checker::*;
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::recorder::{self, *};
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{Lincheck, LoomConfig};
+
+/// The number of the first fail points reached by the chosen thread that may be triggered.
+const MAX_FAIL_POINTS: usize = 4;
+
+/// The number of the checks injecting faults. Guards the access to the [loom] thread locals
+/// so that the fail points can be reached outside of [loom] too.
+static FAULT_INJECTION: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Default)]
+struct FaultState {
+    /// The number of fail points left before triggering one in the current thread.
+    countdown: Option<usize>,
+    /// The fail points triggered during the current operation.
+    triggered: Vec<&'static str>,
+}
+
+loom::thread_local! {
+    static STATE: RefCell<FaultState> = RefCell::new(FaultState::default());
+}
+
+/// Marks a fail point and returns whether it is triggered.
+///
+/// ```
+/// # use lincheck::fail_point;
+/// fn reserve(capacity: usize) -> Result<Vec<u8>, ()> {
+///     fail_point!("reserve", Err(()));
+///     Ok(Vec::with_capacity(capacity))
+/// }
+/// ```
+///
+/// With one argument it evaluates to whether the fail point is triggered.
+/// With two arguments it returns the second one from the enclosing function if the fail point is triggered.
+#[macro_export]
+macro_rules! fail_point {
+    ($name:expr) => {
+        $crate::faults::fail_point($name)
+    };
+    ($name:expr, $ret:expr) => {
+        if $crate::faults::fail_point($name) {
+            return $ret;
+        }
+    };
+}
+
+/// The function behind [fail_point!](crate::fail_point).
+pub fn fail_point(name: &'static str) -> bool {
+    if FAULT_INJECTION.load(Ordering::Relaxed) == 0 {
+        return false;
+    }
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let triggered = match state.countdown {
+            Some(0) => {
+                state.countdown = None;
+                true
+            }
+            Some(left) => {
+                state.countdown = Some(left - 1);
+                false
+            }
+            None => false,
+        };
+        if triggered {
+            state.triggered.push(name);
+        }
+        triggered
+    })
+}
+
+/// The sequential specification of a data structure with fail points.
+pub trait FaultySpec: SequentialSpec {
+    /// Executes an operation during which the given fail points were triggered.
+    fn exec_faulted(&mut self, op: Self::Op, faults: &[&'static str]) -> Self::Ret;
+}
+
+/// Which fail point to trigger.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FaultTarget {
+    /// The thread of the parallel part.
+    pub thread: usize,
+    /// The number of fail points the thread reaches before the triggered one.
+    pub point: usize,
+}
+
+/// An operation along with the fail points triggered during it.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Faulted<Op> {
+    /// The operation.
+    pub op: Op,
+    /// The triggered fail points.
+    pub faults: Vec<&'static str>,
+}
+
+impl<Op: Debug> Debug for Faulted<Op> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.op.fmt(f)?;
+        if !self.faults.is_empty() {
+            write!(f, " !{}", self.faults.join(" !"))?;
+        }
+        Ok(())
+    }
+}
+
+/// The sequential specification of the operations with faults.
+#[derive(Default)]
+pub(crate) struct Faulty<Seq>(Seq);
+
+impl<Seq: FaultySpec> SequentialSpec for Faulty<Seq> {
+    type Op = Faulted<Seq::Op>;
+    type Ret = Seq::Ret;
+
+    fn exec(&mut self, faulted: Self::Op) -> Self::Ret {
+        if faulted.faults.is_empty() {
+            self.0.exec(faulted.op)
+        } else {
+            self.0.exec_faulted(faulted.op, &faulted.faults)
+        }
+    }
+
+    fn ret_matches(expected: &Self::Ret, actual: &Self::Ret) -> bool
+    where
+        Self::Ret: PartialEq,
+    {
+        Seq::ret_matches(expected, actual)
+    }
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` stays linearizable when its fail points are triggered.
+    ///
+    /// See the [module-level documentation](crate::faults) for the details.
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_faults<Conc>(
+        &self,
+    ) -> Result<(), VerificationFailure<Faulted<ConcOp<Conc>>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + FaultySpec + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        let strategy = (self.scenarios(), any::<Index>(), 0..MAX_FAIL_POINTS).prop_map(
+            |(scenario, thread, point)| {
+                let thread = thread.index(scenario.parallel_part.len());
+                (scenario, FaultTarget { thread, point })
+            },
+        );
+
+        self.run_cases(
+            &strategy,
+            |(scenario, _)| {
+                scenario.map(|op: &ConcOp<Conc>| Faulted {
+                    op: op.clone(),
+                    faults: Vec::new(),
+                })
+            },
+            |(scenario, target)| {
+                check_faulty_scenario_with_loom_using::<Conc>(&self.loom, scenario, target)
+                    .map_err(VerificationFailure::NonLinearizable)
+            },
+        )
+    }
+}
+
+/// Executes the given scenario triggering the fail point at `target`
+/// and checks the resulting execution for linearizability inside [loom] model-checker.
+#[allow(clippy::type_complexity)]
+pub fn check_faulty_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    target: FaultTarget,
+) -> Result<(), Execution<Faulted<ConcOp<Conc>>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + FaultySpec + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    check_faulty_scenario_with_loom_using::<Conc>(&LoomConfig::default(), scenario, target)
+}
+
+/// Same as [check_faulty_scenario_with_loom] but configures loom with `loom`.
+#[allow(clippy::type_complexity)]
+pub(crate) fn check_faulty_scenario_with_loom_using<Conc>(
+    loom: &LoomConfig,
+    scenario: Scenario<ConcOp<Conc>>,
+    target: FaultTarget,
+) -> Result<(), Execution<Faulted<ConcOp<Conc>>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + FaultySpec + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    FAULT_INJECTION.fetch_add(1, Ordering::Relaxed);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        find_failure_with_loom(loom, move || {
+            let execution = execute_faulty_scenario_with_loom::<Conc>(scenario.clone(), target);
+            (!LinearizabilityChecker::<Faulty<Conc::Seq>>::check(&execution)).then_some(execution)
+        })
+    }));
+    FAULT_INJECTION.fetch_sub(1, Ordering::Relaxed);
+    result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
+
+/// Executes the given scenario with [loom] mock threads triggering the fail point at `target`
+/// and returns the resulting execution.
+pub fn execute_faulty_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    target: FaultTarget,
+) -> Execution<Faulted<ConcOp<Conc>>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + 'static,
+{
+    let conc = Rc::new(Conc::default());
+
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+
+    // init part
+    for op in scenario.init_part {
+        recorder.record(op.clone(), || exec(&*conc, op));
+    }
+
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
+    let recorder = Rc::new(recorder.record_parallel_part_with_capacity(total_parallel_ops));
+
+    // parallel part
+    let handles: Vec<_> = scenario
+        .parallel_part
+        .into_iter()
+        .enumerate()
+        .map(|(thread_id, thread_ops)| {
+            let conc = conc.clone();
+            let recorder = recorder.clone();
+
+            thread::spawn(move || {
+                if thread_id == target.thread {
+                    STATE.with(|state| state.borrow_mut().countdown = Some(target.point));
+                }
+
+                let mut recorder = recorder.record_thread_with_capacity(thread_ops.len());
+                for op in thread_ops {
+                    recorder.record(op.clone(), || exec(&*conc, op));
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    // post part
+    let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+    for op in scenario.post_part {
+        recorder.record(op.clone(), || exec(&*conc, op));
+    }
+
+    // the triggered fail points are recorded along with the results and moved to the operations afterwards
+    recorder
+        .finish()
+        .map(|op, (ret, faults)| (Faulted { op, faults }, ret))
+}
+
+/// Executes the operation and takes the fail points triggered during it.
+fn exec<Conc: ConcurrentSpec>(conc: &Conc, op: ConcOp<Conc>) -> (ConcRet<Conc>, Vec<&'static str>) {
+    let ret = conc.exec(op);
+    let faults = STATE.with(|state| std::mem::take(&mut state.borrow_mut().triggered));
+    (ret, faults)
+}
@@ -1,29 +1,381 @@
 use std::fmt::{self, Debug, Display, Formatter};
 
+use crate::checker::{Diagnosis, Explanation};
+#[cfg(feature = "color")]
+use crate::color::*;
+use crate::error::VerificationFailure;
 use crate::execution::*;
+use crate::import::ImportError;
+#[cfg(feature = "proptest")]
+use crate::prefill::PrefilledFailure;
+#[cfg(feature = "proptest")]
+use crate::repro::ReproToken;
+use crate::scenario::{Scenario, ScenarioPart};
+#[cfg(feature = "proptest")]
+use crate::suite::SuiteReport;
 
 impl<Op, Ret> Display for Execution<Op, Ret>
 where
     Op: Debug,
     Ret: Debug,
 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let tables = [
+            self.init_part.table(),
+            self.parallel_part.table(),
+            self.post_part.table(),
+        ];
+        write_execution(f, tables, &self.pending, |_| None)
+    }
+}
+
+#[cfg(feature = "color")]
+impl<Op: Debug, Ret: Debug> Display for Colored<'_, Op, Ret> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let execution = self.execution;
+        let thread_style = |thread_id: ThreadId| THREAD_STYLES[thread_id % THREAD_STYLES.len()];
+
+        let mut init_part = execution.init_part.table();
+        let mut post_part = execution.post_part.table();
+        match self.mismatch {
+            Some(InvocationRef::Init(id)) => {
+                init_part.columns[0].spans[id].style = Some(HIGHLIGHT_STYLE)
+            }
+            Some(InvocationRef::Post(id)) => {
+                post_part.columns[0].spans[id].style = Some(HIGHLIGHT_STYLE)
+            }
+            _ => {}
+        }
+
+        // the invocations of a thread are identified by their calls
+        let highlighted = match self.mismatch {
+            Some(InvocationRef::Parallel(inv_id)) => {
+                let inv = &execution.parallel_part[inv_id];
+                Some((inv.thread_id, inv.call_timestamp))
+            }
+            _ => None,
+        };
+        let mut parallel_part = execution.parallel_part.table();
+        for (thread_id, column) in parallel_part.columns.iter_mut().enumerate() {
+            column.style = Some(thread_style(thread_id));
+            let mut invs = execution.parallel_part.thread(thread_id);
+            for span in column.spans.iter_mut() {
+                if span.content.is_none() {
+                    span.style = Some(DIM_STYLE);
+                } else if let Some(inv) = invs.next() {
+                    if Some((thread_id, inv.call_timestamp)) == highlighted {
+                        span.style = Some(HIGHLIGHT_STYLE);
+                    }
+                }
+            }
+        }
+
+        let tables = [init_part, parallel_part, post_part];
+        write_execution(f, tables, &execution.pending, |thread_id| {
+            Some(thread_style(thread_id))
+        })
+    }
+}
+
+/// Writes the tables of the init, the parallel and the post parts, and the pending invocations
+/// with the styles of their threads.
+fn write_execution<Op: Debug>(
+    f: &mut Formatter<'_>,
+    [init_part, parallel_part, post_part]: [Table; 3],
+    pending: &[PendingInvocation<Op>],
+    thread_style: impl Fn(ThreadId) -> Option<&'static str>,
+) -> fmt::Result {
+    writeln!(f, "INIT PART:")?;
+    writeln!(f, "{}", fit(&init_part, f))?;
+
+    writeln!(f, "PARALLEL PART:")?;
+    writeln!(f, "{}", fit(&parallel_part, f))?;
+
+    if !pending.is_empty() {
+        writeln!(f, "PENDING:")?;
+        for inv in pending.iter() {
+            let style = thread_style(inv.thread_id);
+            match inv.cancel_timestamp {
+                Some(cancel_timestamp) => styled(
+                    f,
+                    style,
+                    format_args!(
+                        "THREAD {}: {:?} (cancelled at {})",
+                        inv.thread_id, inv.op, cancel_timestamp
+                    ),
+                )?,
+                None => styled(
+                    f,
+                    style,
+                    format_args!("THREAD {}: {:?}", inv.thread_id, inv.op),
+                )?,
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)?;
+    }
+
+    writeln!(f, "POST PART:")?;
+    writeln!(f, "{}", fit(&post_part, f))?;
+
+    Ok(())
+}
+
+impl<Op: Debug> Display for Scenario<Op> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         writeln!(f, "INIT PART:")?;
-        writeln!(f, "{}", &self.init_part)?;
+        writeln!(
+            f,
+            "{}",
+            fit(
+                &ops_table(["MAIN THREAD".to_string()], [&self.init_part]),
+                f
+            )
+        )?;
 
         writeln!(f, "PARALLEL PART:")?;
-        writeln!(f, "{}", self.parallel_part)?;
+        let headers =
+            (0..self.parallel_part.len()).map(|thread_id| format!("THREAD {}", thread_id));
+        writeln!(f, "{}", fit(&ops_table(headers, &self.parallel_part), f))?;
 
         writeln!(f, "POST PART:")?;
-        writeln!(f, "{}", &self.post_part)?;
+        writeln!(
+            f,
+            "{}",
+            fit(
+                &ops_table(["MAIN THREAD".to_string()], [&self.post_part]),
+                f
+            )
+        )?;
 
         Ok(())
     }
 }
 
+impl Display for ScenarioPart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioPart::Init => write!(f, "init"),
+            ScenarioPart::Parallel => write!(f, "parallel"),
+            ScenarioPart::Post => write!(f, "post"),
+        }
+    }
+}
+
+impl<Op: Debug, Ret: Debug> Display for VerificationFailure<Op, Ret> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationFailure::NonLinearizable(execution) => {
+                write!(f, "Non-linearizable execution: \n\n {}", fit(execution, f))
+            }
+            VerificationFailure::Panicked {
+                scenario,
+                thread,
+                payload,
+                partial_execution,
+            } => {
+                match thread {
+                    Some(thread_id) => write!(f, "THREAD {}", thread_id)?,
+                    None => write!(f, "MAIN THREAD")?,
+                }
+                write!(
+                    f,
+                    " panicked: {}\n\nSCENARIO:\n{}\nEXECUTION SO FAR:\n{}",
+                    payload,
+                    fit(scenario, f),
+                    fit(partial_execution, f)
+                )
+            }
+            VerificationFailure::Deadlock {
+                execution_so_far,
+                blocked_threads,
+            } => write!(
+                f,
+                "Deadlock, blocked threads {:?}: \n\n {}",
+                blocked_threads,
+                fit(execution_so_far, f)
+            ),
+            VerificationFailure::Livelock {
+                execution_so_far,
+                spinning_threads,
+                spinning_thread,
+                spinning_op,
+            } => {
+                write!(f, "Livelock, spinning threads {:?}", spinning_threads)?;
+                if let Some(op) = spinning_op {
+                    match spinning_thread {
+                        Some(thread_id) => {
+                            write!(f, ", {:?} of thread {} never returns", op, thread_id)?
+                        }
+                        None => write!(f, ", {:?} of the main thread never returns", op)?,
+                    }
+                }
+                write!(f, ": \n\n {}", fit(execution_so_far, f))
+            }
+            VerificationFailure::CheckerTimeout {
+                scenario,
+                execution,
+            } => write!(
+                f,
+                "Checker timed out\n\nSCENARIO:\n{}\nEXECUTION:\n{}",
+                fit(scenario, f),
+                fit(execution, f)
+            ),
+            VerificationFailure::InvalidState {
+                scenario,
+                execution,
+                after,
+                message,
+            } => write!(
+                f,
+                "Invalid state after the {} part: {}\n\nSCENARIO:\n{}\nEXECUTION:\n{}",
+                after,
+                message,
+                fit(scenario, f),
+                fit(execution, f)
+            ),
+            VerificationFailure::MemoryError {
+                scenario,
+                execution,
+                leaked,
+                double_frees,
+            } => write!(
+                f,
+                "Memory error: {} allocations leaked, {} double frees\n\nSCENARIO:\n{}\nEXECUTION:\n{}",
+                leaked,
+                double_frees,
+                fit(scenario, f),
+                fit(execution, f)
+            ),
+            VerificationFailure::GeneratorError(reason) => {
+                write!(f, "Failed to generate scenarios: {}", reason)
+            }
+        }
+    }
+}
+
+impl<Op: Debug, Ret: Debug> Display for Diagnosis<Op, Ret> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "LONGEST LINEARIZABLE PREFIX:")?;
+        let spans = self
+            .prefix
+            .iter()
+            .map(|(op, ret)| CellsSpan::new(1, Some(format!("{:?} : {:?}", op, ret))))
+            .collect();
+        let table = Table {
+            cell_height: 2,
+            columns: vec![Column::new("LINEARIZATION".to_string(), spans)],
+        };
+        writeln!(f, "{}", fit(&table, f))?;
+
+        writeln!(f, "REJECTED NEXT OPERATIONS:")?;
+        for rejection in self.rejected.iter() {
+            match rejection.thread_id {
+                Some(thread_id) => write!(f, "THREAD {}: ", thread_id)?,
+                None => write!(f, "MAIN THREAD: ")?,
+            }
+            writeln!(
+                f,
+                "{:?} : expected {:?}, actual {:?}",
+                rejection.op, rejection.expected, rejection.actual
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<Op: Debug, Ret: Debug + PartialEq> Display for Explanation<Op, Ret> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "LONGEST LINEARIZABLE PREFIX:")?;
+        for (i, step) in self.steps.iter().enumerate() {
+            if i == self.matched {
+                writeln!(f, "\nREST OF THE EXECUTION BY THE CALLS:")?;
+            }
+            match step.thread_id {
+                Some(thread_id) => write!(f, "THREAD {}: ", thread_id)?,
+                None => write!(f, "MAIN THREAD: ")?,
+            }
+            match &step.actual {
+                Some(actual) if *actual == step.expected => {
+                    writeln!(f, "{:?} : {:?}", step.op, actual)?
+                }
+                Some(actual) => writeln!(
+                    f,
+                    "{:?} : expected {:?}, actual {:?}",
+                    step.op, step.expected, actual
+                )?,
+                None => writeln!(f, "{:?} : expected {:?}, pending", step.op, step.expected)?,
+            }
+        }
+
+        writeln!(f, "\nREJECTED NEXT OPERATIONS:")?;
+        for rejection in self.rejected.iter() {
+            match rejection.thread_id {
+                Some(thread_id) => write!(f, "THREAD {}: ", thread_id)?,
+                None => write!(f, "MAIN THREAD: ")?,
+            }
+            writeln!(
+                f,
+                "{:?} : expected {:?}, actual {:?}",
+                rejection.op, rejection.expected, rejection.actual
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Displays the value with the width of the formatter it is written to, so the nested tables fit into it too.
+struct Fit<'a, T>(&'a T, Option<usize>);
+
+fn fit<'a, T>(value: &'a T, f: &Formatter<'_>) -> Fit<'a, T> {
+    Fit(value, f.width())
+}
+
+impl<T: Display> Display for Fit<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(width) => write!(f, "{:width$}", self.0, width = width),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// Lays out the operations of each thread in a column, one operation per row.
+fn ops_table<'a, Op: Debug + 'a>(
+    headers: impl IntoIterator<Item = String>,
+    threads: impl IntoIterator<Item = &'a Vec<Op>>,
+) -> Table {
+    let threads: Vec<_> = threads.into_iter().collect();
+    let num_rows = threads.iter().map(|ops| ops.len()).max().unwrap_or(0);
+
+    let columns = headers
+        .into_iter()
+        .zip(threads)
+        .map(|(header, ops)| {
+            let mut spans: Vec<_> = ops
+                .iter()
+                .map(|op| CellsSpan::new(1, Some(format!("{:?}", op))))
+                .collect();
+            if ops.len() < num_rows {
+                spans.push(CellsSpan::new(num_rows - ops.len(), None));
+            }
+            Column::new(header, spans)
+        })
+        .collect();
+
+    Table {
+        cell_height: 2,
+        columns,
+    }
+}
+
 struct Column {
     header: String,
     spans: Vec<CellsSpan>,
+    /// The ANSI style of the header and the spans without their own.
+    style: Option<&'static str>,
 }
 
 struct Table {
@@ -34,6 +386,117 @@ struct Table {
 struct CellsSpan {
     len_in_cells: usize,
     content: Option<String>,
+    /// The ANSI style of the span, e.g. `"1;31"` for bold red.
+    style: Option<&'static str>,
+}
+
+impl Column {
+    fn new(header: String, spans: Vec<CellsSpan>) -> Self {
+        Self {
+            header,
+            spans,
+            style: None,
+        }
+    }
+}
+
+impl Display for InvalidExecution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidExecution::ReturnBeforeCall { thread_id } => write!(
+                f,
+                "an invocation must return after it is called, but one of thread {} doesn't",
+                thread_id
+            ),
+            InvalidExecution::OverlappingInvocations { thread_id } => write!(
+                f,
+                "the invocations of a thread must not overlap, but the ones of thread {} do",
+                thread_id
+            ),
+            InvalidExecution::SeveralPendingInvocations { thread_id } => write!(
+                f,
+                "a thread can have at most one pending invocation, but thread {} has several",
+                thread_id
+            ),
+            InvalidExecution::PendingInvocationNotLast { thread_id } => write!(
+                f,
+                "a pending invocation must be the last one of its thread, but the one of thread {} isn't",
+                thread_id
+            ),
+        }
+    }
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Syntax { offset, message } => {
+                write!(f, "syntax error at byte {}: {}", offset, message)
+            }
+            ImportError::Malformed { index, message } => {
+                write!(f, "malformed operation #{}: {}", index, message)
+            }
+            ImportError::Decode { index, message } => {
+                write!(f, "couldn't decode operation #{}: {}", index, message)
+            }
+            ImportError::Invalid(error) => write!(f, "invalid execution: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl Display for SuiteReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let failed = self.failures().count();
+        writeln!(
+            f,
+            "lincheck suite: {} passed, {} failed in {:.2?}",
+            self.results.len() - failed,
+            failed,
+            self.elapsed
+        )?;
+        for result in self.results.iter() {
+            let status = if result.failure.is_some() {
+                "FAILED"
+            } else {
+                "ok"
+            };
+            writeln!(
+                f,
+                "{:<6} {} ({} scenarios, {} interleavings, {:.2?})",
+                status, result.name, result.scenarios, result.interleavings, result.elapsed
+            )?;
+        }
+
+        for result in self.failures() {
+            writeln!(f, "\n---- {} ----", result.name)?;
+            writeln!(f, "{}", result.failure.as_deref().unwrap_or_default())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<Op: Debug, Ret: Debug> Display for PrefilledFailure<Op, Ret> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "After the prefill {:?}:\n{}",
+            self.prefill,
+            fit(&self.failure, f)
+        )
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl Display for ReproToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}-{}", self.seed, self.case)?;
+        if let Some(scenario) = &self.scenario {
+            write!(f, "-{}", scenario)?;
+        }
+        Ok(())
+    }
 }
 
 impl<Op, Ret> Display for Invocation<Op, Ret>
@@ -62,20 +525,22 @@ where
     Ret: Debug,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.table().fmt(f)
+    }
+}
+
+impl<Op: Debug, Ret: Debug> History<Op, Ret> {
+    /// Lays out the invocations in a column, one invocation per row.
+    fn table(&self) -> Table {
         let spans: Vec<_> = self
             .iter()
             .map(|inv| CellsSpan::new(2, Some(format!("{}", inv))))
             .collect();
 
-        let table = Table {
+        Table {
             cell_height: 2,
-            columns: vec![Column {
-                header: "MAIN THREAD".to_string(),
-                spans,
-            }],
-        };
-
-        table.fmt(f)
+            columns: vec![Column::new("MAIN THREAD".to_string(), spans)],
+        }
     }
 }
 
@@ -86,23 +551,26 @@ where
     Ret: Debug,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let thread_parts = self.get_thread_parts();
-        let max_return_timestamp = thread_parts
+        self.table().fmt(f)
+    }
+}
+
+impl<Op: Debug, Ret: Debug> ParallelHistory<Op, Ret> {
+    /// Lays out the invocations of each thread in a column from their call to their return.
+    fn table(&self) -> Table {
+        let max_return_timestamp = self
             .iter()
-            .filter_map(|thread_part| thread_part.last())
-            .map(|&inv| inv.return_timestamp)
+            .map(|inv| inv.return_timestamp)
             .max()
             .unwrap_or(0);
         let max_return_timestamp = max_return_timestamp as isize;
 
-        let columns: Vec<_> = thread_parts
-            .into_iter()
-            .enumerate()
-            .map(|(thread_id, thread_part)| {
+        let columns: Vec<_> = (0..self.num_threads())
+            .map(|thread_id| {
                 let mut spans = Vec::new();
                 let mut prev_inv_return_timestamp: isize = -1;
 
-                for inv in thread_part {
+                for inv in self.thread(thread_id) {
                     let call_timestamp = inv.call_timestamp as isize;
 
                     if call_timestamp > prev_inv_return_timestamp + 1 {
@@ -127,34 +595,24 @@ where
                     ));
                 }
 
-                Column {
-                    header: format!("THREAD {}", thread_id),
-                    spans,
-                }
+                Column::new(format!("THREAD {}", thread_id), spans)
             })
             .collect();
 
-        let table = Table {
+        Table {
             cell_height: 2,
             columns,
-        };
-
-        table.fmt(f)
+        }
     }
 }
 
 impl CellsSpan {
     fn new(len_in_cells: usize, content: Option<String>) -> Self {
         assert!(len_in_cells > 0);
-        if let Some(ref content) = content {
-            assert!(
-                content.lines().count() <= 1,
-                "multi-line content not supported"
-            );
-        }
         Self {
             len_in_cells,
             content,
+            style: None,
         }
     }
     fn len_in_lines(&self, cell_height: usize) -> usize {
@@ -162,6 +620,13 @@ impl CellsSpan {
     }
 }
 
+/// The maximum width of the contents of a column, the longer lines are wrapped.
+const MAX_CONTENT_WIDTH: usize = 80;
+/// The maximum number of lines of the contents of a span, the rest is cut off with an ellipsis.
+const MAX_CONTENT_LINES: usize = 4;
+/// The minimum width of a column, including the padding, when the table is squeezed into the maximum width.
+const MIN_COLUMN_WIDTH: usize = 5;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SpanState {
     NextSpan,
@@ -176,26 +641,44 @@ struct ColumnState {
     span_state: SpanState,
 }
 
+/// Renders the table, fitting it into the width of the formatter if it is set, e.g. `{:100}`.
+///
+/// Each column is as wide as its widest contents, but no wider than [MAX_CONTENT_WIDTH],
+/// and the widest columns are narrowed first to fit into the width.
+/// The contents are wrapped within the column and the cells grow higher to fit them.
 impl Display for Table {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let column_widths: Vec<_> = self
+        let natural_widths: Vec<_> = self
             .columns
             .iter()
             .map(|col| {
                 col.spans
                     .iter()
-                    .map(|span: &CellsSpan| {
-                        if let Some(ref str) = span.content {
-                            str.len() + 2
-                        } else {
-                            0
-                        }
-                    })
+                    .flat_map(|span| span.content.iter().flat_map(|content| content.lines()))
+                    .map(|line| line.chars().count().min(MAX_CONTENT_WIDTH) + 2)
                     .max()
                     .unwrap_or(0)
-                    .max(col.header.len() + 2)
+                    .max(col.header.chars().count() + 2)
+            })
+            .collect();
+        let column_widths = fit_widths(natural_widths, f.width());
+
+        // the lines of the contents of each span of each column
+        let span_lines: Vec<Vec<_>> = (self.columns.iter().zip(&column_widths))
+            .map(|(column, &width)| {
+                (column.spans.iter())
+                    .map(|span| match &span.content {
+                        Some(content) => wrap(content, width.saturating_sub(2).max(1)),
+                        None => Vec::new(),
+                    })
+                    .collect()
             })
             .collect();
+        // the cells grow higher if a span doesn't fit its contents
+        let cell_height = (self.columns.iter().zip(&span_lines))
+            .flat_map(|(column, lines)| column.spans.iter().zip(lines))
+            .map(|(span, lines)| (lines.len() + 1).div_ceil(span.len_in_cells))
+            .fold(self.cell_height, usize::max);
 
         let mut column_states: Vec<_> = self
             .columns
@@ -222,9 +705,14 @@ impl Display for Table {
             write!(f, "|")?;
             for (col, column) in self.columns.iter().enumerate() {
                 let width = column_widths[col];
-                let header = &column.header;
+                let header = truncate(&column.header, width.saturating_sub(2));
 
-                write!(f, "{:^width$}|", header, width = width)?;
+                styled(
+                    f,
+                    column.style,
+                    format_args!("{:^width$}", header, width = width),
+                )?;
+                write!(f, "|")?;
             }
             writeln!(f)
         };
@@ -248,11 +736,13 @@ impl Display for Table {
                         SpanState::NextSpan => {
                             let span = &self.columns[col].spans[column_state.current_span];
                             column_state.span_state = SpanState::Span {
-                                remaining: span.len_in_lines(self.cell_height),
+                                remaining: span.len_in_lines(cell_height),
                             };
                         }
                         SpanState::Separator => {
-                            write!(f, "{:-^width$}", "", width = width)?;
+                            let span = &column.spans[column_state.current_span];
+                            let style = span.style.or(column.style);
+                            styled(f, style, format_args!("{:-^width$}", "", width = width))?;
                             column_state.current_span += 1;
                             if column_state.current_span == self.columns[col].spans.len() {
                                 column_state.span_state = SpanState::Finished;
@@ -267,14 +757,17 @@ impl Display for Table {
                                 continue;
                             }
 
+                            // the contents are centered vertically, leaning to the bottom
                             let span = &column.spans[column_state.current_span];
-                            let content =
-                                if *remaining == (span.len_in_lines(self.cell_height) + 1) / 2 {
-                                    span.content.as_deref().unwrap_or("")
-                                } else {
-                                    ""
-                                };
-                            write!(f, "{:^width$}", content, width = width)?;
+                            let lines = &span_lines[col][column_state.current_span];
+                            let len_in_lines = span.len_in_lines(cell_height);
+                            let first_line = (len_in_lines + 1).saturating_sub(lines.len()) / 2;
+                            let line = len_in_lines - *remaining;
+                            let content = (line.checked_sub(first_line))
+                                .and_then(|i| lines.get(i))
+                                .map_or("", String::as_str);
+                            let style = span.style.or(column.style);
+                            styled(f, style, format_args!("{:^width$}", content, width = width))?;
 
                             *remaining -= 1;
                             break;
@@ -292,3 +785,150 @@ impl Display for Table {
         Ok(())
     }
 }
+
+/// Writes the text with the ANSI style, if any.
+fn styled(f: &mut Formatter<'_>, style: Option<&str>, text: fmt::Arguments<'_>) -> fmt::Result {
+    match style {
+        Some(style) => write!(f, "\x1b[{}m{}\x1b[0m", style, text),
+        None => f.write_fmt(text),
+    }
+}
+
+/// Narrows the widest columns until the table, including the borders, fits into `max_width`
+/// or all of them are narrowed to [MIN_COLUMN_WIDTH].
+fn fit_widths(widths: Vec<usize>, max_width: Option<usize>) -> Vec<usize> {
+    let Some(max_width) = max_width else {
+        return widths;
+    };
+    let available = max_width.saturating_sub(widths.len() + 1);
+    let total = |cap: usize| widths.iter().map(|&width| width.min(cap)).sum::<usize>();
+
+    let mut cap = widths.iter().copied().max().unwrap_or(0);
+    while cap > MIN_COLUMN_WIDTH && total(cap) > available {
+        cap -= 1;
+    }
+    widths.into_iter().map(|width| width.min(cap)).collect()
+}
+
+/// Wraps the lines of the contents at the spaces, or anywhere if a word is longer than `width`,
+/// keeping at most [MAX_CONTENT_LINES] lines.
+fn wrap(content: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        let mut current = String::new();
+        let mut current_len = 0;
+        for word in line.split_inclusive(' ') {
+            if current_len > 0 && current_len + word.trim_end().chars().count() > width {
+                lines.push(current.trim_end().to_string());
+                current.clear();
+                current_len = 0;
+            }
+            for c in word.chars() {
+                if current_len == width {
+                    if c == ' ' {
+                        continue;
+                    }
+                    lines.push(std::mem::take(&mut current));
+                    current_len = 0;
+                }
+                current.push(c);
+                current_len += 1;
+            }
+        }
+        lines.push(current.trim_end().to_string());
+    }
+
+    if lines.len() > MAX_CONTENT_LINES {
+        lines.truncate(MAX_CONTENT_LINES);
+        let last = lines.last_mut().unwrap();
+        let kept: String = last.chars().take(width - 1).collect();
+        *last = kept + "…";
+    }
+    lines
+}
+
+/// Cuts off the end of the text with an ellipsis if it is longer than `width`.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+        kept + "…"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(header: &str, contents: &[&str]) -> Table {
+        let spans = (contents.iter())
+            .map(|content| CellsSpan::new(1, Some(content.to_string())))
+            .collect();
+        Table {
+            cell_height: 2,
+            columns: vec![Column::new(header.to_string(), spans)],
+        }
+    }
+
+    #[test]
+    fn multi_line_contents() {
+        let table = column("MAIN THREAD", &["first\nsecond", "third"]);
+
+        let expected = "\
+|=============|
+| MAIN THREAD |
+|=============|
+|    first    |
+|   second    |
+|-------------|
+|             |
+|    third    |
+|-------------|
+";
+        assert_eq!(table.to_string(), expected);
+    }
+
+    #[test]
+    fn wraps_to_max_width() {
+        let scenario = Scenario {
+            init_part: vec![],
+            parallel_part: vec![vec!["a very long operation with arguments"], vec!["short"]],
+            post_part: vec![],
+        };
+
+        let table = format!("{:30}", scenario);
+        let parallel_part: Vec<_> = (table.lines())
+            .skip_while(|line| *line != "PARALLEL PART:")
+            .skip(1)
+            .take_while(|line| !line.is_empty())
+            .collect();
+        let expected = vec![
+            "|=================|==========|",
+            "|    THREAD 0     | THREAD 1 |",
+            "|=================|==========|",
+            "|  \"a very long   |          |",
+            "| operation with  | \"short\"  |",
+            "|   arguments\"    |          |",
+            "|-----------------|----------|",
+        ];
+        assert_eq!(parallel_part, expected);
+    }
+
+    #[test]
+    fn cuts_off_with_ellipsis() {
+        let table = column("LINEARIZATION", &["one two three four five six"]);
+
+        let expected = "\
+|=======|
+| LINE… |
+|=======|
+|  one  |
+|  two  |
+| three |
+| four… |
+|-------|
+";
+        assert_eq!(format!("{:9}", table), expected);
+    }
+}
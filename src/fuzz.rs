@@ -0,0 +1,293 @@
+//! Fuzzing on native threads.
+//!
+//! [loom] can't model all the weak memory effects of the real hardware, so the data structures should be
+//! additionally fuzzed. [fuzz](crate::Lincheck::fuzz) executes the generated scenarios on OS threads
+//! many times each and checks every execution for linearizability.
+//! Before each operation, the threads spin for a random number of iterations and sometimes yield
+//! to shake up the interleavings the OS scheduler would otherwise repeat.
+//!
+//! The executions are not reproducible, so the last failing one is reported as is, without a rerun.
+//! Since native threads are used, the data structure must not use [loom] primitives.
+//!
+//! [fuzz_report](crate::Lincheck::fuzz_report) also measures the latencies of the operations
+//! into [LatencyHistogram](crate::bench::LatencyHistogram)s, so a single run catches both
+//! the linearizability regressions and the latency cliffs.
+
+use proptest::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::fmt::Debug;
+use std::hint;
+use std::mem;
+use std::panic;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Barrier;
+use std::thread;
+use std::time::Instant;
+
+use crate::bench::{LatencyRecorder, OpLatencies};
+use crate::checker::*;
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::recorder::{self, *};
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// Seeds the random number generators of the threads.
+static NEXT_SEED: AtomicU64 = AtomicU64::new(0x9e37_79b9_7f4a_7c15);
+
+/// The outcome of [fuzz_report](Lincheck::fuzz_report) together with the latencies of the operations.
+#[derive(Clone, Debug)]
+pub struct FuzzReport<Op, Ret> {
+    /// The same as the result of [fuzz_with](Lincheck::fuzz_with).
+    pub result: Result<(), VerificationFailure<Op, Ret>>,
+    /// The number of executions, including the ones of the shrinking steps.
+    pub executions: usize,
+    /// The latency distributions of the operations of the parallel parts per operation kind, sorted by the kind.
+    pub latencies: Vec<OpLatencies>,
+}
+
+/// What [fuzz_report](Lincheck::fuzz_report) records across the scenarios.
+struct Recording<Op> {
+    latencies: RefCell<LatencyRecorder<Op>>,
+    executions: Cell<usize>,
+}
+
+/// Configuration of [fuzz_with](crate::Lincheck::fuzz_with).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzConfig {
+    /// The number of times each scenario is executed.
+    pub runs_per_scenario: usize,
+    /// The maximum number of spin iterations before each operation. No jitter is added if `0`.
+    pub max_jitter: u32,
+    /// The probability `1 / n` to yield to the OS scheduler before each operation. Never yields if `0`.
+    pub yield_one_in: u64,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self {
+            runs_per_scenario: 100,
+            max_jitter: 1000,
+            yield_one_in: 4,
+        }
+    }
+}
+
+impl Lincheck {
+    /// Fuzzes the concurrent implementation `Conc` on native threads with the default [FuzzConfig].
+    ///
+    /// See the [module-level documentation](crate::fuzz) for the details.
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    pub fn fuzz<Conc>(&self) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Sync,
+        Conc::Seq: Default,
+        ConcOp<Conc>: Send + Clone + Arbitrary + Debug + 'static,
+        ConcRet<Conc>: Send + PartialEq + Debug,
+    {
+        self.fuzz_with::<Conc>(&FuzzConfig::default())
+    }
+
+    /// The same as [fuzz](Lincheck::fuzz) but with the given configuration.
+    pub fn fuzz_with<Conc>(
+        &self,
+        config: &FuzzConfig,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Sync,
+        Conc::Seq: Default,
+        ConcOp<Conc>: Send + Clone + Arbitrary + Debug + 'static,
+        ConcRet<Conc>: Send + PartialEq + Debug,
+    {
+        self.fuzz_recording::<Conc>(config, None)
+    }
+
+    /// The same as [fuzz_with](Lincheck::fuzz_with) but also measures the latency of every operation
+    /// of the parallel parts and reports them per operation kind, i.e. its enum variant.
+    pub fn fuzz_report<Conc>(&self, config: &FuzzConfig) -> FuzzReport<ConcOp<Conc>, ConcRet<Conc>>
+    where
+        Conc: ConcurrentSpec + Default + Sync,
+        Conc::Seq: Default,
+        ConcOp<Conc>: Send + Clone + Arbitrary + Debug + 'static,
+        ConcRet<Conc>: Send + PartialEq + Debug,
+    {
+        let recording = Recording {
+            latencies: RefCell::new(LatencyRecorder::default()),
+            executions: Cell::new(0),
+        };
+        let result = self.fuzz_recording::<Conc>(config, Some(&recording));
+        FuzzReport {
+            result,
+            executions: recording.executions.get(),
+            latencies: recording.latencies.into_inner().finish(),
+        }
+    }
+
+    /// Same as [fuzz_with](Lincheck::fuzz_with) but records the latencies of the operations
+    /// and counts the executions in `recording`, if it is given.
+    fn fuzz_recording<Conc>(
+        &self,
+        config: &FuzzConfig,
+        recording: Option<&Recording<ConcOp<Conc>>>,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Sync,
+        Conc::Seq: Default,
+        ConcOp<Conc>: Send + Clone + Arbitrary + Debug + 'static,
+        ConcRet<Conc>: Send + PartialEq + Debug,
+    {
+        self.run_cases(&self.scenarios(), Scenario::clone, |scenario| {
+            for _ in 0..config.runs_per_scenario {
+                let execution = match recording {
+                    Some(recording) => {
+                        let executions = &recording.executions;
+                        executions.set(executions.get() + 1);
+                        let latencies = &mut recording.latencies.borrow_mut();
+                        // the names are rendered before the threads start
+                        latencies.name_kinds(scenario.parallel_part.iter().flatten());
+                        execute_scenario_timed::<Conc>(scenario.clone(), config, Some(latencies))
+                    }
+                    None => execute_scenario_with_jitter::<Conc>(scenario.clone(), config),
+                };
+                if !LinearizabilityChecker::<Conc::Seq>::check(&execution) {
+                    return Err(VerificationFailure::NonLinearizable(execution));
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Executes the given scenario on native threads adding random jitter before each operation of the parallel part
+/// and returns the resulting execution.
+pub fn execute_scenario_with_jitter<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    config: &FuzzConfig,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Default + Sync,
+    ConcOp<Conc>: Send + Clone,
+    ConcRet<Conc>: Send,
+{
+    execute_scenario_timed::<Conc>(scenario, config, None)
+}
+
+/// Same as [execute_scenario_with_jitter] but records the latency of every operation of the parallel part,
+/// leaving out the jitter, into `latencies` if it is given.
+fn execute_scenario_timed<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    config: &FuzzConfig,
+    latencies: Option<&mut LatencyRecorder<ConcOp<Conc>>>,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Default + Sync,
+    ConcOp<Conc>: Send + Clone,
+    ConcRet<Conc>: Send,
+{
+    let record_latencies = latencies.is_some();
+    let conc = Conc::default();
+
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+
+    // init part
+    for op in scenario.init_part {
+        recorder.record(op.clone(), || conc.exec(op));
+    }
+
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
+    let recorder = recorder.record_parallel_part_with_capacity(total_parallel_ops);
+
+    // parallel part
+    // the threads wait on the barrier to start executing the operations at the same time
+    let barrier = Barrier::new(scenario.parallel_part.len());
+    let thread_latencies: Vec<_> = thread::scope(|s| {
+        let handles: Vec<_> = scenario
+            .parallel_part
+            .into_iter()
+            .map(|thread_ops| {
+                let (conc, recorder, barrier) = (&conc, &recorder, &barrier);
+                s.spawn(move || {
+                    let mut jitter = Jitter::new(config);
+                    let mut latencies = LatencyRecorder::default();
+                    let mut recorder = recorder.record_thread_with_capacity(thread_ops.len());
+                    barrier.wait();
+                    for op in thread_ops {
+                        jitter.wait();
+                        if record_latencies {
+                            let kind = mem::discriminant(&op);
+                            recorder.record(op.clone(), || {
+                                let start = Instant::now();
+                                let ret = conc.exec(op);
+                                latencies.record(kind, start.elapsed());
+                                ret
+                            });
+                        } else {
+                            recorder.record(op.clone(), || conc.exec(op));
+                        }
+                    }
+                    latencies
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload))
+            })
+            .collect()
+    });
+    if let Some(latencies) = latencies {
+        for thread_latencies in thread_latencies {
+            latencies.merge(thread_latencies);
+        }
+    }
+
+    // post part
+    let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+    for op in scenario.post_part {
+        recorder.record(op.clone(), || conc.exec(op));
+    }
+
+    recorder.finish()
+}
+
+/// Random delays of a thread.
+struct Jitter<'a> {
+    config: &'a FuzzConfig,
+    /// The state of the xorshift generator.
+    state: u64,
+}
+
+impl<'a> Jitter<'a> {
+    fn new(config: &'a FuzzConfig) -> Self {
+        Self {
+            config,
+            state: NEXT_SEED.fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed) | 1,
+        }
+    }
+
+    fn next_random(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    fn wait(&mut self) {
+        if self.config.yield_one_in > 0
+            && self.next_random().is_multiple_of(self.config.yield_one_in)
+        {
+            thread::yield_now();
+        }
+        if self.config.max_jitter > 0 {
+            let spins = self.next_random() % (u64::from(self.config.max_jitter) + 1);
+            for _ in 0..spins {
+                hint::spin_loop();
+            }
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! Exports of the happens-before graph of executions, e.g. to see why no linearization respects it.
+//!
+//! The nodes are the invocations and an edge goes from an invocation to each one called after it returned.
+//! The init part precedes the whole parallel part and the post part follows it.
+//! Only the edges that don't follow from the others are drawn, so the graph stays readable.
+//! The invocations of the parallel part are grouped by thread, and the pending ones are dashed,
+//! since they may take effect at any point after their call or not at all.
+
+use std::fmt::{Debug, Write};
+
+use crate::execution::*;
+
+/// An invocation of the happens-before graph.
+struct Node {
+    id: String,
+    label: String,
+    thread_id: Option<ThreadId>,
+    pending: bool,
+}
+
+impl<Op: Debug, Ret: Debug> Execution<Op, Ret> {
+    /// Renders the happens-before graph of the execution in the DOT language of Graphviz.
+    ///
+    /// ```
+    /// # use lincheck::execution;
+    /// let execution = execution! {
+    ///     parallel: [
+    ///         0 @ 0..1: ("push", 1) => None::<i32>,
+    ///         1 @ 2..3: ("pop", 0) => Some(1),
+    ///     ],
+    /// };
+    /// assert!(execution.to_dot().contains("inv0 -> inv1;"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let (nodes, edges) = self.happens_before_graph();
+
+        let mut dot = String::from("digraph execution {\n");
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        let write_node = |dot: &mut String, indent: &str, node: &Node| {
+            let style = if node.pending { ", style=dashed" } else { "" };
+            writeln!(
+                dot,
+                "{}{} [label=\"{}\"{}];",
+                indent,
+                node.id,
+                escape_dot(&node.label),
+                style
+            )
+            .unwrap();
+        };
+
+        for node in nodes.iter().filter(|node| node.thread_id.is_none()) {
+            write_node(&mut dot, "    ", node);
+        }
+        for thread_id in 0..self.num_threads() {
+            writeln!(dot, "    subgraph cluster_thread{} {{", thread_id).unwrap();
+            writeln!(dot, "        label=\"THREAD {}\";", thread_id).unwrap();
+            for node in nodes
+                .iter()
+                .filter(|node| node.thread_id == Some(thread_id))
+            {
+                write_node(&mut dot, "        ", node);
+            }
+            dot.push_str("    }\n");
+        }
+        for (from, to) in edges {
+            writeln!(dot, "    {} -> {};", nodes[from].id, nodes[to].id).unwrap();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the happens-before graph of the execution as a Mermaid flowchart,
+    /// e.g. to embed it into a Markdown issue.
+    pub fn to_mermaid(&self) -> String {
+        let (nodes, edges) = self.happens_before_graph();
+
+        let mut mermaid = String::from("flowchart TD\n");
+        let write_node = |mermaid: &mut String, indent: &str, node: &Node| {
+            let (open, close) = if node.pending {
+                ("([", "])")
+            } else {
+                ("[", "]")
+            };
+            writeln!(
+                mermaid,
+                "{}{}{}\"{}\"{}",
+                indent,
+                node.id,
+                open,
+                escape_mermaid(&node.label),
+                close
+            )
+            .unwrap();
+        };
+
+        for node in nodes.iter().filter(|node| node.thread_id.is_none()) {
+            write_node(&mut mermaid, "    ", node);
+        }
+        for thread_id in 0..self.num_threads() {
+            writeln!(
+                mermaid,
+                "    subgraph thread{}[\"THREAD {}\"]",
+                thread_id, thread_id
+            )
+            .unwrap();
+            for node in nodes
+                .iter()
+                .filter(|node| node.thread_id == Some(thread_id))
+            {
+                write_node(&mut mermaid, "        ", node);
+            }
+            mermaid.push_str("    end\n");
+        }
+        for (from, to) in edges {
+            writeln!(mermaid, "    {} --> {}", nodes[from].id, nodes[to].id).unwrap();
+        }
+        mermaid
+    }
+
+    /// The number of threads of the parallel part, including the ones with only a pending invocation.
+    fn num_threads(&self) -> usize {
+        (self.parallel_part.iter().map(|inv| inv.thread_id + 1))
+            .chain(self.pending.iter().map(|inv| inv.thread_id + 1))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The invocations of the init part, the parallel part, the pending ones and the post part,
+    /// and the edges of the transitive reduction of the happens-before order between them.
+    fn happens_before_graph(&self) -> (Vec<Node>, Vec<(usize, usize)>) {
+        let mut nodes = Vec::new();
+        for (i, inv) in self.init_part.iter().enumerate() {
+            nodes.push(Node {
+                id: format!("init{}", i),
+                label: format!("INIT: {:?} : {:?}", inv.op, inv.ret),
+                thread_id: None,
+                pending: false,
+            });
+        }
+        for (inv_id, inv) in self.parallel_part.iter().enumerate() {
+            nodes.push(Node {
+                id: format!("inv{}", inv_id),
+                label: format!(
+                    "{:?} : {:?}\n{}..{}",
+                    inv.op, inv.ret, inv.call_timestamp, inv.return_timestamp
+                ),
+                thread_id: Some(inv.thread_id),
+                pending: false,
+            });
+        }
+        for (i, inv) in self.pending.iter().enumerate() {
+            let label = match inv.cancel_timestamp {
+                Some(cancel_timestamp) => format!(
+                    "{:?} : cancelled\n{}..{}",
+                    inv.op, inv.call_timestamp, cancel_timestamp
+                ),
+                None => format!("{:?} : pending\n{}..", inv.op, inv.call_timestamp),
+            };
+            nodes.push(Node {
+                id: format!("inv{}", self.parallel_part.len() + i),
+                label,
+                thread_id: Some(inv.thread_id),
+                pending: true,
+            });
+        }
+        for (i, inv) in self.post_part.iter().enumerate() {
+            nodes.push(Node {
+                id: format!("post{}", i),
+                label: format!("POST: {:?} : {:?}", inv.op, inv.ret),
+                thread_id: None,
+                pending: false,
+            });
+        }
+
+        // the parallel part between the init and the post parts, ordered by the intervals
+        let intervals = self.intervals();
+        let (first_parallel, first_post) =
+            (self.init_part.len(), self.init_part.len() + intervals.len());
+        let happens_before = |a: usize, b: usize| {
+            let part = |node: usize| {
+                if node < first_parallel {
+                    0
+                } else if node < first_post {
+                    1
+                } else {
+                    2
+                }
+            };
+            match (part(a), part(b)) {
+                (1, 1) => {
+                    intervals[a - first_parallel].return_timestamp
+                        < intervals[b - first_parallel].call_timestamp
+                }
+                // the pending invocations may take effect after the post part started, or never,
+                // but the cancelled ones are settled by then
+                (1, 2) => intervals[a - first_parallel].return_timestamp != Timestamp::MAX,
+                (part_a, part_b) => part_a < part_b || (part_a == part_b && a < b),
+            }
+        };
+
+        let mut edges = Vec::new();
+        for a in 0..nodes.len() {
+            for b in 0..nodes.len() {
+                if happens_before(a, b)
+                    && !(0..nodes.len()).any(|c| happens_before(a, c) && happens_before(c, b))
+                {
+                    edges.push((a, b));
+                }
+            }
+        }
+        (nodes, edges)
+    }
+}
+
+/// Escapes the label for a quoted DOT string, breaking the lines.
+fn escape_dot(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the label for a quoted Mermaid string, breaking the lines.
+fn escape_mermaid(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => escaped.push_str("#quot;"),
+            '\n' => escaped.push_str("<br/>"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execution;
+    use crate::specs::{RegisterOp as Op, RegisterRet as Ret};
+    use crate::Execution;
+
+    fn stale_read() -> Execution<Op<i32>, Ret<i32>> {
+        execution! {
+            init: [Op::Write(1) => Ret::Write],
+            parallel: [
+                0 @ 0..1: Op::Write(2) => Ret::Write,
+                1 @ 2..4: Op::Read => Ret::Read(1),
+                0 @ 3..5: Op::Write(3) => Ret::Write,
+            ],
+            post: [Op::Read => Ret::Read(3)],
+        }
+    }
+
+    #[test]
+    fn dot() {
+        let expected = r#"digraph execution {
+    node [shape=box, fontname="monospace"];
+    init0 [label="INIT: Write(1) : Write"];
+    post0 [label="POST: Read : Read(3)"];
+    subgraph cluster_thread0 {
+        label="THREAD 0";
+        inv0 [label="Write(2) : Write\n0..1"];
+        inv2 [label="Write(3) : Write\n3..5"];
+    }
+    subgraph cluster_thread1 {
+        label="THREAD 1";
+        inv1 [label="Read : Read(1)\n2..4"];
+    }
+    init0 -> inv0;
+    inv0 -> inv1;
+    inv0 -> inv2;
+    inv1 -> post0;
+    inv2 -> post0;
+}
+"#;
+        assert_eq!(stale_read().to_dot(), expected);
+    }
+
+    #[test]
+    fn mermaid_with_pending() {
+        let execution = stale_read().with_pending(vec![(1, 6, Op::Write(4))]);
+
+        let expected = r#"flowchart TD
+    init0["INIT: Write(1) : Write"]
+    post0["POST: Read : Read(3)"]
+    subgraph thread0["THREAD 0"]
+        inv0["Write(2) : Write<br/>0..1"]
+        inv2["Write(3) : Write<br/>3..5"]
+    end
+    subgraph thread1["THREAD 1"]
+        inv1["Read : Read(1)<br/>2..4"]
+        inv3(["Write(4) : pending<br/>6.."])
+    end
+    init0 --> inv0
+    inv0 --> inv1
+    inv0 --> inv2
+    inv1 --> inv3
+    inv1 --> post0
+    inv2 --> inv3
+    inv2 --> post0
+"#;
+        assert_eq!(execution.to_mermaid(), expected);
+    }
+}
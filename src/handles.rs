@@ -0,0 +1,268 @@
+//! Testing data structures used through per-thread handles.
+//!
+//! Many real-world APIs hand out a handle per thread instead of sharing the data structure itself,
+//! e.g. the cloned senders of a channel or the workers of a work-stealing deque.
+//! The handles often keep an [Arc] to the data structure, so it must be shared as `Arc<Self>`
+//! rather than borrowed by the threads. Here the harness creates the data structure in an [Arc]
+//! and gives every thread of the parallel part its own [handle](HandleSpec::handle), on which the operations are executed.
+//! The init and the post parts are executed on a separate handle of the main thread.
+//!
+//! The handles may also have [roles](RoleSpec), e.g. the senders and the receiver of a channel.
+//! Each thread then gets a handle with its role and only executes the operations allowed for the role.
+
+use proptest::prelude::*;
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+use std::rc::Rc;
+
+use loom::sync::Arc;
+use loom::thread;
+
+use crate::checker::*;
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::recorder::{self, *};
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{scenario_strategy_with_parts, Lincheck, LoomConfig};
+
+/// The concurrent implementation of a data structure used through per-thread handles.
+pub trait HandleSpec: Default {
+    /// The sequential specification for the data structure.
+    type Seq: SequentialSpec + Default;
+
+    /// A handle owned by a single thread, e.g. a sender of a channel.
+    type Handle;
+
+    /// Creates a new handle to the shared data structure.
+    fn handle(this: &Arc<Self>) -> Self::Handle;
+
+    /// Executes an operation on the data structure through the handle.
+    fn exec(handle: &mut Self::Handle, op: HandleOp<Self>) -> HandleRet<Self>;
+}
+
+/// A data structure whose handles have roles, e.g. a channel with a single receiver and many senders.
+pub trait RoleSpec: HandleSpec {
+    /// The role of a handle.
+    type Role;
+
+    /// The role of the handle of the thread of the parallel part.
+    fn role(thread_id: ThreadId) -> Self::Role;
+
+    /// The role of the handle of the main thread executing the init and the post parts.
+    fn main_role() -> Self::Role;
+
+    /// Creates a new handle with the role to the shared data structure.
+    fn handle_with_role(this: &Arc<Self>, role: &Self::Role) -> Self::Handle;
+
+    /// The strategy of the operations that can be executed through a handle with the role.
+    fn ops(role: &Self::Role) -> BoxedStrategy<HandleOp<Self>>;
+}
+
+/// Type alias not to have always write down FQP.
+pub type HandleOp<T> = <<T as HandleSpec>::Seq as SequentialSpec>::Op;
+
+/// Type alias not to have always write down FQP.
+pub type HandleRet<T> = <<T as HandleSpec>::Seq as SequentialSpec>::Ret;
+
+impl Lincheck {
+    /// Verifies that the data structure `H` used through per-thread [handles](HandleSpec) is linearizable.
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    pub fn verify_handles<H>(&self) -> Result<(), VerificationFailure<HandleOp<H>, HandleRet<H>>>
+    where
+        H: HandleSpec + 'static,
+        H::Handle: 'static,
+        HandleOp<H>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        HandleRet<H>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        self.run_cases(&self.scenarios(), Scenario::clone, |scenario| {
+            check_handle_scenario_with_loom_using::<H>(&self.loom, scenario)
+                .map_err(VerificationFailure::NonLinearizable)
+        })
+    }
+
+    /// Verifies that the data structure `R` used through per-thread handles with [roles](RoleSpec) is linearizable.
+    ///
+    /// The operations of each thread are generated with the [strategy](RoleSpec::ops) of its role,
+    /// and the shrunk scenarios keep the operations on the threads with the same roles.
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    pub fn verify_roles<R>(&self) -> Result<(), VerificationFailure<HandleOp<R>, HandleRet<R>>>
+    where
+        R: RoleSpec + 'static,
+        R::Handle: 'static,
+        HandleOp<R>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        HandleRet<R>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        let main_ops = R::ops(&R::main_role());
+        let strategy = scenario_strategy_with_parts(
+            1..=self.num_threads,
+            self.num_ops,
+            &self.thread_distribution,
+            [main_ops.clone(), main_ops.clone(), main_ops],
+            (0..self.num_threads)
+                .map(|thread_id| R::ops(&R::role(thread_id)))
+                .collect(),
+            false,
+        );
+
+        self.run_cases(&strategy, Scenario::clone, |scenario| {
+            check_role_scenario_with_loom_using::<R>(&self.loom, scenario)
+                .map_err(VerificationFailure::NonLinearizable)
+        })
+    }
+}
+
+/// Executes the given scenario through per-thread handles and checks the resulting execution
+/// for linearizability inside [loom] model-checker.
+pub fn check_handle_scenario_with_loom<H>(
+    scenario: Scenario<HandleOp<H>>,
+) -> Result<(), Execution<HandleOp<H>, HandleRet<H>>>
+where
+    H: HandleSpec + 'static,
+    H::Handle: 'static,
+    HandleOp<H>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    HandleRet<H>: PartialEq + Debug + Send + Clone + 'static,
+{
+    check_handle_scenario_with_loom_using::<H>(&LoomConfig::default(), scenario)
+}
+
+/// Same as [check_handle_scenario_with_loom] but configures loom with `loom`.
+pub(crate) fn check_handle_scenario_with_loom_using<H>(
+    loom: &LoomConfig,
+    scenario: Scenario<HandleOp<H>>,
+) -> Result<(), Execution<HandleOp<H>, HandleRet<H>>>
+where
+    H: HandleSpec + 'static,
+    H::Handle: 'static,
+    HandleOp<H>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    HandleRet<H>: PartialEq + Debug + Send + Clone + 'static,
+{
+    find_failure_with_loom(loom, move || {
+        let execution = execute_handle_scenario_with_loom::<H>(scenario.clone());
+        (!LinearizabilityChecker::<H::Seq>::check(&execution)).then_some(execution)
+    })
+}
+
+/// Executes the given scenario with [loom] mock threads, each using its own handle to the data structure,
+/// and returns the resulting execution.
+pub fn execute_handle_scenario_with_loom<H>(
+    scenario: Scenario<HandleOp<H>>,
+) -> Execution<HandleOp<H>, HandleRet<H>>
+where
+    H: HandleSpec + 'static,
+    H::Handle: 'static,
+    HandleOp<H>: Clone + 'static,
+    HandleRet<H>: 'static,
+{
+    execute_with_handles(scenario, H::handle, |shared, _| H::handle(shared))
+}
+
+/// Same as [check_handle_scenario_with_loom] but gives each thread a handle with its [role](RoleSpec).
+pub fn check_role_scenario_with_loom<R>(
+    scenario: Scenario<HandleOp<R>>,
+) -> Result<(), Execution<HandleOp<R>, HandleRet<R>>>
+where
+    R: RoleSpec + 'static,
+    R::Handle: 'static,
+    HandleOp<R>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    HandleRet<R>: PartialEq + Debug + Send + Clone + 'static,
+{
+    check_role_scenario_with_loom_using::<R>(&LoomConfig::default(), scenario)
+}
+
+/// Same as [check_role_scenario_with_loom] but configures loom with `loom`.
+pub(crate) fn check_role_scenario_with_loom_using<R>(
+    loom: &LoomConfig,
+    scenario: Scenario<HandleOp<R>>,
+) -> Result<(), Execution<HandleOp<R>, HandleRet<R>>>
+where
+    R: RoleSpec + 'static,
+    R::Handle: 'static,
+    HandleOp<R>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    HandleRet<R>: PartialEq + Debug + Send + Clone + 'static,
+{
+    find_failure_with_loom(loom, move || {
+        let execution = execute_role_scenario_with_loom::<R>(scenario.clone());
+        (!LinearizabilityChecker::<R::Seq>::check(&execution)).then_some(execution)
+    })
+}
+
+/// Same as [execute_handle_scenario_with_loom] but gives each thread a handle with its [role](RoleSpec).
+pub fn execute_role_scenario_with_loom<R>(
+    scenario: Scenario<HandleOp<R>>,
+) -> Execution<HandleOp<R>, HandleRet<R>>
+where
+    R: RoleSpec + 'static,
+    R::Handle: 'static,
+    HandleOp<R>: Clone + 'static,
+    HandleRet<R>: 'static,
+{
+    execute_with_handles(
+        scenario,
+        |shared| R::handle_with_role(shared, &R::main_role()),
+        |shared, thread_id| R::handle_with_role(shared, &R::role(thread_id)),
+    )
+}
+
+/// Executes the scenario with the handle of the main thread created by `main_handle`
+/// and the handles of the threads of the parallel part created by `thread_handle`.
+fn execute_with_handles<H>(
+    scenario: Scenario<HandleOp<H>>,
+    main_handle: impl FnOnce(&Arc<H>) -> H::Handle,
+    thread_handle: impl Fn(&Arc<H>, ThreadId) -> H::Handle,
+) -> Execution<HandleOp<H>, HandleRet<H>>
+where
+    H: HandleSpec + 'static,
+    H::Handle: 'static,
+    HandleOp<H>: Clone + 'static,
+    HandleRet<H>: 'static,
+{
+    let shared = Arc::new(H::default());
+    let mut main_handle = main_handle(&shared);
+
+    // init part
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+    for op in scenario.init_part {
+        recorder.record(op.clone(), || H::exec(&mut main_handle, op));
+    }
+
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
+    let recorder = Rc::new(recorder.record_parallel_part_with_capacity(total_parallel_ops));
+
+    // parallel part
+    let thread_handles: Vec<_> = scenario
+        .parallel_part
+        .iter()
+        .enumerate()
+        .map(|(thread_id, _)| thread_handle(&shared, thread_id))
+        .collect();
+    drop(shared);
+    let threads: Vec<_> = scenario
+        .parallel_part
+        .into_iter()
+        .zip(thread_handles)
+        .map(|(thread_ops, mut handle)| {
+            let recorder = recorder.clone();
+
+            thread::spawn(move || {
+                let mut recorder = recorder.record_thread_with_capacity(thread_ops.len());
+                for op in thread_ops {
+                    recorder.record(op.clone(), || H::exec(&mut handle, op));
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    // post part
+    let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+    for op in scenario.post_part {
+        recorder.record(op.clone(), || H::exec(&mut main_handle, op));
+    }
+    recorder.finish()
+}
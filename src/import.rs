@@ -0,0 +1,748 @@
+//! Importing histories captured by other tools, so that distributed systems can be checked with lincheck.
+//!
+//! Two formats are supported:
+//! - [Knossos](https://github.com/jepsen-io/knossos) and [Jepsen](https://github.com/jepsen-io/jepsen) EDN histories,
+//!   parsed by [from_edn]. It is a list of operation maps such as `{:process 0, :type :invoke, :f :write, :value 1}`.
+//!   The `:invoke` of a process is completed by its next `:ok`, `:fail` or `:info`.
+//!   Failed operations didn't take effect and are dropped, while the indeterminate `:info` ones and the ones
+//!   that were never completed become pending. The operations of the processes that aren't integers,
+//!   e.g. `:nemesis`, are skipped. The position of an operation in the history is its timestamp.
+//! - [Porcupine](https://github.com/anishathalye/porcupine) JSON operation logs, parsed by [from_porcupine_json].
+//!   It is an array of objects such as `{"ClientId": 0, "Input": 1, "Call": 10, "Output": null, "Return": 20}`.
+//!   The field names are case-insensitive.
+//!
+//! Every process or client becomes a thread of the parallel part, numbered in the order of their first operations.
+//! The operations and their results are decoded by the user-supplied functions from the parsed [Edn] or [Json] values.
+//! ```
+//! # use lincheck::checker::LinearizabilityChecker;
+//! # use lincheck::import::{from_edn, Edn};
+//! # use lincheck::specs::{Register, RegisterOp, RegisterRet};
+//! let history = r#"[
+//!     {:process 0, :type :invoke, :f :write, :value 1}
+//!     {:process 1, :type :invoke, :f :read, :value nil}
+//!     {:process 0, :type :ok, :f :write, :value 1}
+//!     {:process 1, :type :ok, :f :read, :value 1}
+//! ]"#;
+//! let execution = from_edn(
+//!     history,
+//!     |f, value| match (f.as_keyword(), value.as_i64()) {
+//!         (Some("write"), Some(value)) => Ok(RegisterOp::Write(value)),
+//!         (Some("read"), _) => Ok(RegisterOp::Read),
+//!         _ => Err(format!("unknown operation {:?}", f)),
+//!     },
+//!     |f, value| match (f.as_keyword(), value.as_i64()) {
+//!         (Some("write"), _) => Ok(RegisterRet::Write),
+//!         (Some("read"), Some(value)) => Ok(RegisterRet::Read(value)),
+//!         _ => Err(format!("unknown result {:?}", value)),
+//!     },
+//! )
+//! .unwrap();
+//! assert!(LinearizabilityChecker::<Register<i64>>::check(&execution));
+//! ```
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::execution::*;
+
+/// A value of the [EDN](https://github.com/edn-format/edn) format.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Edn {
+    /// `nil`.
+    Nil,
+    /// `true` or `false`.
+    Bool(bool),
+    /// An integer.
+    Int(i64),
+    /// A floating-point number.
+    Float(f64),
+    /// A string.
+    String(String),
+    /// A character, e.g. `\a`.
+    Char(char),
+    /// A keyword without the leading colon, e.g. `write` for `:write`.
+    Keyword(String),
+    /// A symbol.
+    Symbol(String),
+    /// A list, e.g. `(1 2)`.
+    List(Vec<Edn>),
+    /// A vector, e.g. `[1 2]`.
+    Vector(Vec<Edn>),
+    /// A map in the order of its entries.
+    Map(Vec<(Edn, Edn)>),
+    /// A set in the order of its elements.
+    Set(Vec<Edn>),
+    /// A tagged element, e.g. `#inst "2023-07-14"`.
+    Tagged(String, Box<Edn>),
+}
+
+impl Edn {
+    /// Parses a single EDN value. The text around it may only contain whitespace and comments.
+    pub fn parse(input: &str) -> Result<Edn, ImportError> {
+        let mut parser = Parser::new(input);
+        let value = parser.edn()?;
+        parser.skip_edn_whitespace();
+        if !parser.at_end() {
+            return Err(parser.error("unexpected text after the value"));
+        }
+        Ok(value)
+    }
+
+    /// The value of the keyword key of the map, e.g. `map.get("process")` for `:process`.
+    pub fn get(&self, key: &str) -> Option<&Edn> {
+        match self {
+            Edn::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| k.as_keyword() == Some(key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// The name of the keyword without the leading colon.
+    pub fn as_keyword(&self) -> Option<&str> {
+        match self {
+            Edn::Keyword(keyword) => Some(keyword),
+            _ => None,
+        }
+    }
+
+    /// The integer.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Edn::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The elements of the list or the vector.
+    pub fn as_seq(&self) -> Option<&[Edn]> {
+        match self {
+            Edn::List(elements) | Edn::Vector(elements) => Some(elements),
+            _ => None,
+        }
+    }
+}
+
+/// A [JSON](https://www.json.org) value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    /// `null`.
+    Null,
+    /// `true` or `false`.
+    Bool(bool),
+    /// A number without a fraction or an exponent that fits into [i64].
+    Int(i64),
+    /// Any other number.
+    Float(f64),
+    /// A string.
+    String(String),
+    /// An array.
+    Array(Vec<Json>),
+    /// An object in the order of its members.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses a JSON value.
+    pub fn parse(input: &str) -> Result<Json, ImportError> {
+        let mut parser = Parser::new(input);
+        let value = parser.json()?;
+        parser.skip_json_whitespace();
+        if !parser.at_end() {
+            return Err(parser.error("unexpected text after the value"));
+        }
+        Ok(value)
+    }
+
+    /// The member of the object with the given name, compared case-insensitively.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// The integer.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Json::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+}
+
+/// Why a history couldn't be imported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportError {
+    /// The text isn't valid EDN or JSON.
+    Syntax {
+        /// The byte offset of the error in the text.
+        offset: usize,
+        /// What is wrong.
+        message: String,
+    },
+    /// An entry of the history doesn't describe an operation, e.g. a field is missing.
+    Malformed {
+        /// The position of the entry in the history.
+        index: usize,
+        /// What is wrong.
+        message: String,
+    },
+    /// The user-supplied decoder rejected an operation or a result.
+    Decode {
+        /// The position of the entry in the history.
+        index: usize,
+        /// The error returned by the decoder.
+        message: String,
+    },
+    /// The operations don't form a valid execution, e.g. the ones of a client overlap.
+    Invalid(InvalidExecution),
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<InvalidExecution> for ImportError {
+    fn from(error: InvalidExecution) -> Self {
+        ImportError::Invalid(error)
+    }
+}
+
+/// Imports a Knossos EDN history, either a single vector or list of operations or the operations one after another.
+///
+/// `decode_op` receives the `:f` and the `:value` of the invocation,
+/// and `decode_ret` receives the `:f` and the `:value` of the completion.
+pub fn from_edn<Op, Ret>(
+    input: &str,
+    decode_op: impl Fn(&Edn, &Edn) -> Result<Op, String>,
+    decode_ret: impl Fn(&Edn, &Edn) -> Result<Ret, String>,
+) -> Result<Execution<Op, Ret>, ImportError> {
+    let mut parser = Parser::new(input);
+    let mut values = Vec::new();
+    parser.skip_edn_whitespace();
+    while !parser.at_end() {
+        values.push(parser.edn()?);
+        parser.skip_edn_whitespace();
+    }
+    let entries = match values.as_slice() {
+        [Edn::Vector(entries)] | [Edn::List(entries)] => entries.as_slice(),
+        _ => values.as_slice(),
+    };
+
+    let mut threads = Threads::default();
+    // the invocation of each thread that is waiting for its completion
+    let mut invoked: HashMap<ThreadId, (Timestamp, Op)> = HashMap::new();
+    let mut builder = Execution::builder();
+    for (index, entry) in entries.iter().enumerate() {
+        let malformed = |message: &str| ImportError::Malformed {
+            index,
+            message: message.into(),
+        };
+        let decode_error = |message| ImportError::Decode { index, message };
+
+        let Some(process) = entry
+            .get("process")
+            .ok_or(malformed("no :process"))?
+            .as_i64()
+        else {
+            continue;
+        };
+        let thread_id = threads.id(process);
+        let kind = entry
+            .get("type")
+            .and_then(Edn::as_keyword)
+            .ok_or(malformed("no :type"))?;
+        let f = entry.get("f").ok_or(malformed("no :f"))?;
+        let value = entry.get("value").unwrap_or(&Edn::Nil);
+
+        if kind == "invoke" {
+            let op = decode_op(f, value).map_err(decode_error)?;
+            if invoked.insert(thread_id, (index, op)).is_some() {
+                return Err(malformed(
+                    "the process invokes an operation before completing the previous one",
+                ));
+            }
+            continue;
+        }
+        let (call_timestamp, op) = invoked.remove(&thread_id).ok_or(malformed(
+            "the process completes an operation it didn't invoke",
+        ))?;
+        builder = match kind {
+            "ok" => {
+                let ret = decode_ret(f, value).map_err(decode_error)?;
+                builder.parallel(thread_id, call_timestamp, index, op, ret)
+            }
+            "fail" => builder,
+            "info" => builder.pending(thread_id, call_timestamp, op),
+            _ => return Err(malformed("unknown :type")),
+        };
+    }
+    for (thread_id, (call_timestamp, op)) in invoked {
+        builder = builder.pending(thread_id, call_timestamp, op);
+    }
+    Ok(builder.build()?)
+}
+
+/// Imports a Porcupine JSON array of operations with the `ClientId`, `Input`, `Call`, `Output` and `Return` fields.
+///
+/// `decode_op` receives the `Input` of the operation and `decode_ret` receives its `Output`.
+/// The `Call` and `Return` timestamps must be non-negative integers.
+pub fn from_porcupine_json<Op, Ret>(
+    input: &str,
+    decode_op: impl Fn(&Json) -> Result<Op, String>,
+    decode_ret: impl Fn(&Json) -> Result<Ret, String>,
+) -> Result<Execution<Op, Ret>, ImportError> {
+    let Json::Array(entries) = Json::parse(input)? else {
+        return Err(ImportError::Syntax {
+            offset: 0,
+            message: "expected an array of operations".into(),
+        });
+    };
+
+    let mut threads = Threads::default();
+    let mut builder = Execution::builder();
+    for (index, entry) in entries.iter().enumerate() {
+        let field = |name: &str| {
+            entry.get(name).ok_or_else(|| ImportError::Malformed {
+                index,
+                message: format!("no {}", name),
+            })
+        };
+        let timestamp = |name: &str| {
+            field(name)?
+                .as_i64()
+                .and_then(|timestamp| Timestamp::try_from(timestamp).ok())
+                .ok_or_else(|| ImportError::Malformed {
+                    index,
+                    message: format!("{} isn't a non-negative integer", name),
+                })
+        };
+        let decode_error = |message| ImportError::Decode { index, message };
+
+        let client_id = field("ClientId")?.as_i64().ok_or(ImportError::Malformed {
+            index,
+            message: "ClientId isn't an integer".into(),
+        })?;
+        let op = decode_op(field("Input")?).map_err(decode_error)?;
+        let ret = decode_ret(field("Output")?).map_err(decode_error)?;
+        builder = builder.parallel(
+            threads.id(client_id),
+            timestamp("Call")?,
+            timestamp("Return")?,
+            op,
+            ret,
+        );
+    }
+    Ok(builder.build()?)
+}
+
+/// Numbers the processes or the clients in the order of their first operations.
+struct Threads<Id> {
+    ids: HashMap<Id, ThreadId>,
+}
+
+impl<Id> Default for Threads<Id> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl<Id: Eq + Hash> Threads<Id> {
+    fn id(&mut self, id: Id) -> ThreadId {
+        let next = self.ids.len();
+        *self.ids.entry(id).or_insert(next)
+    }
+}
+
+/// A recursive descent parser of EDN and JSON values.
+struct Parser<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, offset: 0 }
+    }
+
+    fn error(&self, message: &str) -> ImportError {
+        ImportError::Syntax {
+            offset: self.offset,
+            message: message.into(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.offset..]
+    }
+
+    fn at_end(&self) -> bool {
+        self.offset == self.input.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        let matches = self.rest().starts_with(prefix);
+        if matches {
+            self.offset += prefix.len();
+        }
+        matches
+    }
+
+    fn expect(&mut self, prefix: &str) -> Result<(), ImportError> {
+        if self.eat(prefix) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", prefix)))
+        }
+    }
+
+    /// Takes the characters while `pred` holds.
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let start = self.offset;
+        while self.peek().is_some_and(&pred) {
+            self.next();
+        }
+        &self.input[start..self.offset]
+    }
+
+    fn skip_edn_whitespace(&mut self) {
+        loop {
+            self.take_while(|c| c.is_whitespace() || c == ',');
+            if self.peek() == Some(';') {
+                self.take_while(|c| c != '\n');
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn skip_json_whitespace(&mut self) {
+        self.take_while(|c| matches!(c, ' ' | '\t' | '\n' | '\r'));
+    }
+
+    fn edn(&mut self) -> Result<Edn, ImportError> {
+        self.skip_edn_whitespace();
+        match self.peek() {
+            None => Err(self.error("unexpected end of the text")),
+            Some('(') => {
+                self.next();
+                Ok(Edn::List(self.edn_elements(')')?))
+            }
+            Some('[') => {
+                self.next();
+                Ok(Edn::Vector(self.edn_elements(']')?))
+            }
+            Some('{') => {
+                self.next();
+                let elements = self.edn_elements('}')?;
+                if elements.len() % 2 != 0 {
+                    return Err(self.error("a map must have an even number of elements"));
+                }
+                let mut elements = elements.into_iter();
+                let mut entries = Vec::new();
+                while let (Some(key), Some(value)) = (elements.next(), elements.next()) {
+                    entries.push((key, value));
+                }
+                Ok(Edn::Map(entries))
+            }
+            Some('#') => {
+                self.next();
+                if self.eat("{") {
+                    Ok(Edn::Set(self.edn_elements('}')?))
+                } else if self.eat("_") {
+                    // the discarded element
+                    self.edn()?;
+                    self.edn()
+                } else {
+                    let tag = self.edn_token();
+                    if tag.is_empty() {
+                        return Err(self.error("expected a tag"));
+                    }
+                    Ok(Edn::Tagged(tag.into(), Box::new(self.edn()?)))
+                }
+            }
+            Some('"') => Ok(Edn::String(self.string()?)),
+            Some(':') => {
+                self.next();
+                let keyword = self.edn_token();
+                if keyword.is_empty() {
+                    return Err(self.error("expected a keyword"));
+                }
+                Ok(Edn::Keyword(keyword.into()))
+            }
+            Some('\\') => {
+                self.next();
+                let name = self.edn_token();
+                let c = match name {
+                    "newline" => '\n',
+                    "space" => ' ',
+                    "tab" => '\t',
+                    "return" => '\r',
+                    _ => {
+                        let mut chars = name.chars();
+                        match (chars.next(), chars.next()) {
+                            (Some(c), None) => c,
+                            // a delimiter, e.g. `\(`
+                            (None, _) => self.next().ok_or(self.error("expected a character"))?,
+                            _ => return Err(self.error("unknown character name")),
+                        }
+                    }
+                };
+                Ok(Edn::Char(c))
+            }
+            Some(_) => {
+                let start = self.offset;
+                let token = self.edn_token();
+                if token.is_empty() {
+                    return Err(self.error("unexpected character"));
+                }
+                match token {
+                    "nil" => Ok(Edn::Nil),
+                    "true" => Ok(Edn::Bool(true)),
+                    "false" => Ok(Edn::Bool(false)),
+                    _ if Self::starts_number(token) => {
+                        let number = token.trim_end_matches(['N', 'M']);
+                        match (number.parse(), number.parse()) {
+                            (Ok(int), _) if !token.ends_with('M') => Ok(Edn::Int(int)),
+                            (_, Ok(float)) => Ok(Edn::Float(float)),
+                            _ => Err(ImportError::Syntax {
+                                offset: start,
+                                message: format!("invalid number `{}`", token),
+                            }),
+                        }
+                    }
+                    _ => Ok(Edn::Symbol(token.into())),
+                }
+            }
+        }
+    }
+
+    fn starts_number(token: &str) -> bool {
+        let digits = token.strip_prefix(['+', '-']).unwrap_or(token);
+        digits.starts_with(|c: char| c.is_ascii_digit())
+    }
+
+    /// The elements up to the closing delimiter.
+    fn edn_elements(&mut self, close: char) -> Result<Vec<Edn>, ImportError> {
+        let mut elements = Vec::new();
+        loop {
+            self.skip_edn_whitespace();
+            if self.peek() == Some(close) {
+                self.next();
+                return Ok(elements);
+            }
+            elements.push(self.edn()?);
+        }
+    }
+
+    /// A symbol, a number or the name of a keyword, a tag or a character.
+    fn edn_token(&mut self) -> &'a str {
+        self.take_while(|c| {
+            !c.is_whitespace() && !matches!(c, ',' | '(' | ')' | '[' | ']' | '{' | '}' | '"' | ';')
+        })
+    }
+
+    fn json(&mut self) -> Result<Json, ImportError> {
+        self.skip_json_whitespace();
+        match self.peek() {
+            None => Err(self.error("unexpected end of the text")),
+            Some('[') => {
+                self.next();
+                let mut elements = Vec::new();
+                self.skip_json_whitespace();
+                if self.eat("]") {
+                    return Ok(Json::Array(elements));
+                }
+                loop {
+                    elements.push(self.json()?);
+                    self.skip_json_whitespace();
+                    if self.eat("]") {
+                        return Ok(Json::Array(elements));
+                    }
+                    self.expect(",")?;
+                }
+            }
+            Some('{') => {
+                self.next();
+                let mut members = Vec::new();
+                self.skip_json_whitespace();
+                if self.eat("}") {
+                    return Ok(Json::Object(members));
+                }
+                loop {
+                    self.skip_json_whitespace();
+                    if self.peek() != Some('"') {
+                        return Err(self.error("expected a member name"));
+                    }
+                    let name = self.string()?;
+                    self.skip_json_whitespace();
+                    self.expect(":")?;
+                    members.push((name, self.json()?));
+                    self.skip_json_whitespace();
+                    if self.eat("}") {
+                        return Ok(Json::Object(members));
+                    }
+                    self.expect(",")?;
+                }
+            }
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some(_) if self.eat("null") => Ok(Json::Null),
+            Some(_) if self.eat("true") => Ok(Json::Bool(true)),
+            Some(_) if self.eat("false") => Ok(Json::Bool(false)),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.offset;
+                let number = self
+                    .take_while(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'));
+                match (number.parse(), number.parse()) {
+                    (Ok(int), _) => Ok(Json::Int(int)),
+                    (_, Ok(float)) => Ok(Json::Float(float)),
+                    _ => Err(ImportError::Syntax {
+                        offset: start,
+                        message: format!("invalid number `{}`", number),
+                    }),
+                }
+            }
+            Some(_) => Err(self.error("unexpected character")),
+        }
+    }
+
+    /// A string in double quotes with the escapes shared by EDN and JSON.
+    fn string(&mut self) -> Result<String, ImportError> {
+        self.expect("\"")?;
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(self.error("unknown escape")),
+                    };
+                    string.push(c);
+                }
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    /// The character of a `\uXXXX` escape, which is followed by another one for a surrogate pair.
+    fn unicode_escape(&mut self) -> Result<char, ImportError> {
+        let high = self.unicode_escape_unit()?;
+        let low = if (0xd800..0xdc00).contains(&high) {
+            self.expect("\\u")?;
+            Some(self.unicode_escape_unit()?)
+        } else {
+            None
+        };
+        char::decode_utf16([high].into_iter().chain(low))
+            .next()
+            .and_then(Result::ok)
+            .ok_or(self.error("invalid surrogate pair"))
+    }
+
+    /// The code unit of a `\uXXXX` escape after the `\u`.
+    fn unicode_escape_unit(&mut self) -> Result<u16, ImportError> {
+        let hex = self
+            .rest()
+            .get(..4)
+            .ok_or(self.error("expected 4 hex digits"))?;
+        let code_unit =
+            u16::from_str_radix(hex, 16).map_err(|_| self.error("expected 4 hex digits"))?;
+        self.offset += 4;
+        Ok(code_unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_edn() {
+        let edn = Edn::parse(
+            r#"{:a [1 -2.5 nil] ; a comment
+                :b #{"x\n" \c} #_ :discarded
+                :c (true sym/bol) :d #inst "2023-07-14"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            edn.get("a"),
+            Some(&Edn::Vector(vec![Edn::Int(1), Edn::Float(-2.5), Edn::Nil]))
+        );
+        assert_eq!(
+            edn.get("b"),
+            Some(&Edn::Set(vec![Edn::String("x\n".into()), Edn::Char('c')]))
+        );
+        assert_eq!(
+            edn.get("c"),
+            Some(&Edn::List(vec![
+                Edn::Bool(true),
+                Edn::Symbol("sym/bol".into())
+            ]))
+        );
+        assert_eq!(
+            edn.get("d"),
+            Some(&Edn::Tagged(
+                "inst".into(),
+                Box::new(Edn::String("2023-07-14".into()))
+            ))
+        );
+        assert!(matches!(
+            Edn::parse("[1 2"),
+            Err(ImportError::Syntax { .. })
+        ));
+    }
+
+    #[test]
+    fn parses_json() {
+        let json = Json::parse(r#" {"a": [1, -2.5e1, null], "B": "\u00e9\ud83d\ude00", "c": {}} "#)
+            .unwrap();
+        assert_eq!(
+            json.get("a"),
+            Some(&Json::Array(vec![
+                Json::Int(1),
+                Json::Float(-25.0),
+                Json::Null
+            ]))
+        );
+        assert_eq!(json.get("b").and_then(Json::as_str), Some("é😀"));
+        assert_eq!(json.get("c"), Some(&Json::Object(vec![])));
+        assert_eq!(
+            Json::parse("[1,]"),
+            Err(ImportError::Syntax {
+                offset: 3,
+                message: "unexpected character".into()
+            })
+        );
+    }
+}
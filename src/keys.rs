@@ -0,0 +1,129 @@
+//! Generation of the keys of map-like data structures from a small pool.
+//!
+//! The keys generated with `any::<K>()` almost never collide, so the operations of the different threads
+//! rarely race on the same key. A [KeySpace] draws the keys from a small pool instead, with a weight per key,
+//! e.g. one hot key and a few cold ones. The operations take it as their [Arbitrary] parameters
+//! and generate the keys with [strategy](KeySpace::strategy):
+//! ```
+//! use lincheck::keys::KeySpace;
+//! use proptest::prelude::*;
+//!
+//! #[derive(Clone, Debug)]
+//! enum MapOp {
+//!     Get(u64),
+//!     Insert(u64, u32),
+//! }
+//!
+//! impl Arbitrary for MapOp {
+//!     type Parameters = KeySpace<u64>;
+//!     type Strategy = BoxedStrategy<Self>;
+//!
+//!     fn arbitrary_with(keys: KeySpace<u64>) -> Self::Strategy {
+//!         let key = keys.strategy();
+//!         prop_oneof![
+//!             key.clone().prop_map(MapOp::Get),
+//!             (key, any::<u32>()).prop_map(|(key, value)| MapOp::Insert(key, value)),
+//!         ]
+//!         .boxed()
+//!     }
+//! }
+//!
+//! // the key 0 is drawn as often as 1 and 2 together
+//! let keys = KeySpace::weighted([(2, 0), (1, 1), (1, 2)]);
+//! ```
+//! [verify_with_keys](Lincheck::verify_with_keys) then generates every part of the scenarios with the same key space.
+
+use proptest::prelude::*;
+use proptest::strategy::Union;
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+
+use crate::error::VerificationFailure;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// A pool of keys with a weight each.
+///
+/// The default key space is empty, and the keys are generated with `any::<K>()` then.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeySpace<K> {
+    keys: Vec<(u32, K)>,
+}
+
+impl<K> Default for KeySpace<K> {
+    fn default() -> Self {
+        Self { keys: Vec::new() }
+    }
+}
+
+impl<K> KeySpace<K> {
+    /// The pool of the given keys, all equally likely.
+    pub fn new(keys: impl IntoIterator<Item = K>) -> Self {
+        Self::weighted(keys.into_iter().map(|key| (1, key)))
+    }
+
+    /// The pool of the given `(weight, key)` pairs. The keys of zero weight are never drawn.
+    pub fn weighted(keys: impl IntoIterator<Item = (u32, K)>) -> Self {
+        Self {
+            keys: keys.into_iter().filter(|&(weight, _)| weight > 0).collect(),
+        }
+    }
+
+    /// The keys of the pool.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.keys.iter().map(|(_, key)| key)
+    }
+
+    /// Whether the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl<K: Arbitrary + Clone + Debug + 'static> KeySpace<K> {
+    /// Draws the keys from the pool according to their weights, or with `any::<K>()` if it is empty.
+    ///
+    /// The keys shrink toward the first ones of the pool.
+    pub fn strategy(&self) -> BoxedStrategy<K> {
+        if self.keys.is_empty() {
+            return any::<K>().boxed();
+        }
+        Union::new_weighted(
+            self.keys
+                .iter()
+                .map(|(weight, key)| (*weight, Just(key.clone())))
+                .collect(),
+        )
+        .boxed()
+    }
+}
+
+impl Lincheck {
+    /// The same as [verify](Lincheck::verify) but generates the keys of the operations of every part from `keys`.
+    ///
+    /// See the [module-level documentation](crate::keys) for the details.
+    pub fn verify_with_keys<Conc, K>(
+        &self,
+        keys: KeySpace<K>,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send
+            + Sync
+            + Clone
+            + Arbitrary<Parameters = KeySpace<K>>
+            + Debug
+            + UnwindSafe
+            + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+        K: Clone,
+    {
+        self.verify_with_params::<Conc>(PartParams {
+            init_part: keys.clone(),
+            parallel_part: keys.clone(),
+            post_part: keys,
+        })
+    }
+}
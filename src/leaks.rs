@@ -0,0 +1,141 @@
+//! Leak and double-free detection inside [loom].
+//!
+//! Lock-free data structures often free their nodes by hand, e.g. with epoch-based reclamation,
+//! so a node may leak or be freed twice even though every execution is linearizable.
+//! When [check_leaks](crate::LoomConfig::check_leaks) is set, the memory allocated with [alloc] or [into_raw]
+//! and freed with [dealloc] or [from_raw] is tracked. [verify](crate::Lincheck::verify) drops the instances
+//! at the end of every execution and reports the memory that is still allocated or was freed twice
+//! as [MemoryError](crate::VerificationFailure::MemoryError), and [run](crate::Lincheck::run) reports
+//! the number of tracked allocations.
+//!
+//! [alloc] and [dealloc] forward to [loom::alloc], so they can only be called inside the [loom] model,
+//! and loom's own leak detection applies to them too. Without [check_leaks](crate::LoomConfig::check_leaks),
+//! it panics on a leak instead of reporting the failing execution.
+//!
+//! ```
+//! use lincheck::leaks;
+//!
+//! struct Node {
+//!     value: usize,
+//! }
+//!
+//! let node = leaks::into_raw(Box::new(Node { value: 1 }));
+//! // ... publish the node and retire it once it is unlinked
+//! let node = unsafe { leaks::from_raw(node) }.expect("the node is freed once");
+//! assert_eq!(node.value, 1);
+//! ```
+
+use std::alloc::Layout;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::mem;
+
+thread_local! {
+    /// The memory tracked on the current thread, which runs every thread of a [loom] model.
+    static LEDGER: RefCell<Option<Ledger>> = const { RefCell::new(None) };
+}
+
+/// The bookkeeping of the tracked memory of an execution.
+#[derive(Debug, Default)]
+pub(crate) struct Ledger {
+    live: HashSet<usize>,
+    pub(crate) allocations: usize,
+    pub(crate) double_frees: usize,
+}
+
+impl Ledger {
+    /// The number of allocations that weren't freed.
+    pub(crate) fn leaked(&self) -> usize {
+        self.live.len()
+    }
+}
+
+/// Tracks the memory allocated and freed by `f`, which must drop everything it allocates.
+pub(crate) fn track<R>(f: impl FnOnce() -> R) -> (R, Ledger) {
+    /// Stops the tracking even if `f` panics, e.g. when loom aborts the execution.
+    struct Tracking;
+
+    impl Drop for Tracking {
+        fn drop(&mut self) {
+            LEDGER.set(None);
+        }
+    }
+
+    LEDGER.set(Some(Ledger::default()));
+    let tracking = Tracking;
+    let result = f();
+    let ledger = LEDGER.take().expect("the ledger is set while tracking");
+    drop(tracking);
+    (result, ledger)
+}
+
+fn record_alloc(ptr: *mut u8) {
+    LEDGER.with_borrow_mut(|ledger| {
+        if let Some(ledger) = ledger {
+            ledger.allocations += 1;
+            ledger.live.insert(ptr as usize);
+        }
+    });
+}
+
+/// Returns whether the memory may be freed, i.e. it isn't freed twice.
+fn record_free(ptr: *mut u8) -> bool {
+    LEDGER.with_borrow_mut(|ledger| match ledger {
+        Some(ledger) => {
+            let live = ledger.live.remove(&(ptr as usize));
+            if !live {
+                ledger.double_frees += 1;
+            }
+            live
+        }
+        None => true,
+    })
+}
+
+/// Allocates memory like [loom::alloc::alloc] and tracks it.
+///
+/// # Safety
+///
+/// See [GlobalAlloc::alloc](std::alloc::GlobalAlloc::alloc).
+pub unsafe fn alloc(layout: Layout) -> *mut u8 {
+    let ptr = loom::alloc::alloc(layout);
+    record_alloc(ptr);
+    ptr
+}
+
+/// Frees the memory allocated with [alloc] like [loom::alloc::dealloc].
+///
+/// The tracked memory that is freed twice is reported instead of being freed again.
+///
+/// # Safety
+///
+/// See [GlobalAlloc::dealloc](std::alloc::GlobalAlloc::dealloc).
+pub unsafe fn dealloc(ptr: *mut u8, layout: Layout) {
+    if record_free(ptr) {
+        loom::alloc::dealloc(ptr, layout);
+    }
+}
+
+/// Converts the box into a raw pointer like [Box::into_raw] and tracks the memory.
+/// The zero-sized values aren't tracked, since they don't allocate.
+pub fn into_raw<T>(value: Box<T>) -> *mut T {
+    let ptr = Box::into_raw(value);
+    if mem::size_of::<T>() != 0 {
+        record_alloc(ptr.cast());
+    }
+    ptr
+}
+
+/// Converts the pointer returned by [into_raw] back into a box like [Box::from_raw].
+///
+/// The tracked memory that is converted back twice is reported, and [None] is returned instead of a second box.
+///
+/// # Safety
+///
+/// See [Box::from_raw].
+pub unsafe fn from_raw<T>(ptr: *mut T) -> Option<Box<T>> {
+    if mem::size_of::<T>() != 0 && !record_free(ptr.cast()) {
+        return None;
+    }
+    Some(Box::from_raw(ptr))
+}
@@ -1,4 +1,5 @@
 #![warn(missing_docs)]
+#![allow(clippy::result_large_err)] // the failures carry executions and are returned once per test
 
 //! Lincheck is a Rust library for testing concurrent data structures for [linearizability](https://en.wikipedia.org/wiki/Linearizability). Simply put, it checks whether a concurrent data structure behaves similarly to a simpler sequential implementation. It is inspired by [Lincheck for Kotlin](https://github.com/JetBrains/lincheck) and is built on top of [loom](https://github.com/tokio-rs/loom), a model-checker for concurrency.
 //!
@@ -11,7 +12,8 @@
 //!
 //! # Tutorial
 //!
-//! ```rust
+#![cfg_attr(feature = "proptest", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "proptest"), doc = "```ignore")]
 //! use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
 //! use loom::sync::atomic::{AtomicBool, Ordering};
 //! use proptest::prelude::*;
@@ -127,6 +129,7 @@
 //!     Lincheck {
 //!         num_threads: 2,
 //!         num_ops: 5,
+//!         ..Default::default()
 //!     }.verify::<TwoSlotsParallel>();
 //! }
 //! ```
@@ -177,32 +180,190 @@
 //! |----------------|
 //! ```
 //!
-//! # Limitations
+//! # Generating the operations
+//!
+//! With the `macros` feature, the `#[operations]` attribute generates the enums of operations and results,
+//! the [Arbitrary] implementation and both `exec` methods from the methods marked with `#[operation]`:
+//! ```ignore
+//! #[derive(Default)]
+//! struct TwoSlotsSequential {
+//!     x: bool,
+//!     y: bool,
+//! }
 //!
-//! - Lincheck runner sets its own panic hook. This doesn't play well with parallel test execution. To fix this, you can run your tests with the `--test-threads=1` flag like this:
-//! ```bash
-//! $ cargo test -- --test-threads=1
+//! // generates `TwoSlotsSequentialOp::{WriteX, ReadX}` and `TwoSlotsSequentialRet::{WriteX, ReadX(bool)}`
+//! #[lincheck::operations]
+//! impl TwoSlotsSequential {
+//!     #[operation]
+//!     fn write_x(&mut self) {
+//!         self.x = true;
+//!     }
+//!
+//!     #[operation]
+//!     fn read_x(&mut self) -> bool {
+//!         self.x
+//!     }
+//! }
+//!
+//! #[lincheck::operations(seq = TwoSlotsSequential)]
+//! impl TwoSlotsParallel {
+//!     #[operation]
+//!     fn write_x(&self) {
+//!         self.x.store(true, Ordering::Relaxed);
+//!     }
+//!
+//!     #[operation]
+//!     fn read_x(&self) -> bool {
+//!         self.x.load(Ordering::Relaxed)
+//!     }
+//! }
 //! ```
-//! - [loom](https://github.com/tokio-rs/loom) can't model all weak memory models effects. This means that some executions that may arise on the real hardware may not be explored by loom. This is why the concurrent data structures should be additionally fuzzed on the real hardware. The support for fuzzing in Lincheck is planned.
+//!
+//! # Without proptest
+//!
+//! The generation and shrinking of the scenarios require the default `proptest` feature.
+//! Without it, the scenarios come from elsewhere, e.g. a custom generator, a corpus of files
+//! or [ScenarioBuilder](scenario::ScenarioBuilder), and are checked with [verify_scenarios](Lincheck::verify_scenarios)
+//! or [check_scenario](Lincheck::check_scenario), so the operations don't have to implement [Arbitrary].
+//!
+//! # Observing long runs
+//!
+//! Set [progress](Lincheck::progress) to be notified of the checked scenarios, the explored interleavings
+//! and the shrinking steps. With the `tracing` feature, the verification is also instrumented with
+//! [tracing](https://docs.rs/tracing) spans and events: a `verify` span with the seed, a `scenario` span per checked scenario
+//! with its case number, a `loom` span reporting the number of explored interleavings and the `minimize` span,
+//! and the linearizability checks report how many times they backtracked.
+//!
+//! # Limitations
+//!
+//! - [loom](https://github.com/tokio-rs/loom) can't model all weak memory models effects. This means that some executions that may arise on the real hardware may not be explored by loom. This is why the concurrent data structures should be additionally fuzzed on the real hardware with [fuzz](Lincheck::fuzz).
 //! - [proptest](https://docs.rs/proptest/latest/proptest/) only explores a random sample of all possible scenarios. This means that some failing executions may not be explored.
 
+#[cfg(feature = "proptest")]
 use proptest::{
     prelude::*,
-    test_runner::{TestError, TestRunner},
+    test_runner::{RngSeed, TestError, TestRunner},
+};
+use std::panic::{AssertUnwindSafe, UnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+#[cfg(feature = "proptest")]
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Write,
+    ops::RangeInclusive,
 };
-use std::panic::UnwindSafe;
 use std::{fmt::Debug, panic};
 
+#[cfg(feature = "proptest")]
+pub mod alloc;
+#[cfg(feature = "proptest")]
+pub mod analysis;
+pub mod backend;
+#[cfg(feature = "proptest")]
+pub mod bench;
+#[cfg(feature = "proptest")]
+pub mod chaos;
 pub mod checker;
+#[cfg(feature = "color")]
+pub mod color;
+pub mod coverage;
+#[cfg(feature = "proptest")]
+pub mod crash;
+#[cfg(feature = "proptest")]
+pub mod crdt;
+pub mod deadlock;
+#[cfg(feature = "dsl")]
+pub mod dsl;
+#[cfg(feature = "proptest")]
+pub mod dual;
+#[cfg(feature = "proptest")]
+pub mod durable;
+mod error;
 mod execution;
+#[cfg(feature = "proptest")]
+pub mod faults;
 mod fmt;
+#[cfg(feature = "proptest")]
+pub mod fuzz;
+mod graph;
+#[cfg(feature = "proptest")]
+pub mod handles;
+pub mod import;
+#[cfg(feature = "proptest")]
+pub mod keys;
+pub mod leaks;
+#[cfg(feature = "arbitrary")]
+pub mod libfuzzer;
+pub mod minimize;
+#[cfg(feature = "proptest")]
+pub mod nondeterminism;
+pub mod objects;
+#[cfg(feature = "proptest")]
+pub mod obstruction;
+#[cfg(feature = "proptest")]
+pub mod parallel;
+#[cfg(feature = "proptest")]
+pub mod prefill;
+#[cfg(feature = "proptest")]
+pub mod program;
+pub mod progress;
 pub mod recorder;
+#[cfg(feature = "proptest")]
+pub mod relaxed;
+mod report;
+#[cfg(feature = "proptest")]
+mod repro;
 pub mod scenario;
+#[cfg(feature = "proptest")]
+pub mod shrink;
+#[cfg(feature = "shuttle")]
+pub mod shuttle_check;
+#[cfg(feature = "proptest")]
+pub mod soak;
 mod spec;
+pub mod specs;
+#[cfg(feature = "proptest")]
+pub mod spurious;
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod stm;
+#[cfg(feature = "proptest")]
+pub mod suite;
+#[cfg(feature = "proptest")]
+pub mod teardown;
+#[cfg(feature = "tokio")]
+pub mod tokio_fuzz;
+#[cfg(feature = "tui")]
+pub mod tui;
 
+pub use error::*;
 pub use execution::*;
+#[cfg(feature = "arbitrary")]
+pub use libfuzzer::fuzz_target;
+#[cfg(feature = "proptest")]
+use parallel::Prefetching;
+#[cfg(feature = "proptest")]
+use progress::{ShrinkStats, VerifyReport};
+#[cfg(feature = "proptest")]
+use repro::ReproToken;
 use scenario::*;
+#[cfg(feature = "proptest")]
+use shrink::{CheckCache, ScenarioStrategy};
 pub use spec::*;
+#[cfg(feature = "proptest")]
+pub use suite::suite;
+
+#[cfg(feature = "macros")]
+pub use lincheck_macros::operations;
+
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod __private {
+    pub use proptest;
+}
 
 /// A test runner for Lincheck.
 /// It is used to configure the test.
@@ -212,6 +373,130 @@ pub struct Lincheck {
     pub num_threads: usize,
     /// The maximum number of operations to run per thread.
     pub num_ops: usize,
+    /// The number of independent instances of the data structure to run the scenario on.
+    /// The threads of the parallel part are distributed between the instances in the round-robin fashion.
+    /// Values greater than `1` help to catch bugs caused by state shared between the instances, e.g. statics.
+    pub num_instances: usize,
+    /// How the number of threads of a scenario is chosen from `1` to [num_threads](Lincheck::num_threads).
+    pub thread_distribution: ThreadDistribution,
+    /// The correctness condition the executions are checked against.
+    pub correctness: Correctness,
+    /// The backend of the [linearizability](Correctness::Linearizability) checks, bound to the sequential specification
+    /// of the verified implementation, e.g. [SpecChecker::partitioned](checker::SpecChecker::partitioned) for [Objects](objects::Objects).
+    /// [None] means [check](checker::LinearizabilityChecker::check).
+    ///
+    /// The backend creates the sequential specification like the other checks, e.g. with the constructor
+    /// of [verify_with](Lincheck::verify_with), and gives up after [check_timeout](Lincheck::check_timeout).
+    pub checker: Option<checker::SpecChecker>,
+    /// The configuration of the [proptest] runner generating the scenarios, e.g. the number of cases,
+    /// the RNG seed, the maximum number of shrinking iterations and the failure persistence.
+    ///
+    /// Fix the seed to reproduce failures deterministically:
+    /// ```
+    /// # use lincheck::Lincheck;
+    /// use proptest::test_runner::{Config, RngSeed};
+    ///
+    /// let lincheck = Lincheck {
+    ///     proptest: Config {
+    ///         cases: 1000,
+    ///         rng_seed: RngSeed::Fixed(42),
+    ///         ..Config::default()
+    ///     },
+    ///     ..Default::default()
+    /// };
+    /// ```
+    #[cfg(feature = "proptest")]
+    pub proptest: ProptestConfig,
+    /// The configuration of the [loom] model checker exploring the interleavings of each scenario.
+    pub loom: LoomConfig,
+    /// The maximum time spent checking a single execution against the [correctness](Lincheck::correctness) condition.
+    /// The search for a linearization may take exponential time on adversarial executions,
+    /// so the executions taking longer are reported as [CheckerTimeout](VerificationFailure::CheckerTimeout).
+    /// [None] means no limit.
+    pub check_timeout: Option<Duration>,
+    /// The maximum width of the tables printed by [verify_or_panic](Lincheck::verify_or_panic).
+    /// The contents of the widest columns are wrapped to fit. [None] means no limit.
+    ///
+    /// The same applies to formatting a failure or an execution with a width, e.g. `format!("{:100}", execution)`.
+    pub output_width: Option<usize>,
+    /// Whether the failing scenario found by [verify](Lincheck::verify) is [minimized](minimize) after shrinking,
+    /// re-checking the candidate reductions with [loom] until none of them fails.
+    /// It finds smaller scenarios than shrinking alone at the cost of more loom runs.
+    pub minimize: bool,
+    /// The observer notified of the progress of the verification, e.g. an `indicatif` progress bar.
+    /// See the [progress] module.
+    pub progress: Option<Arc<dyn progress::ProgressObserver>>,
+    /// The [token](progress::VerifyReport::token) of a failing case, e.g. `"00000000deadbeef-42"`.
+    /// If set, the failing case is rerun instead of the configured cases, so the verification mode
+    /// must be the same as in the failing run, e.g. [verify_with](Lincheck::verify_with) with the same constructors.
+    /// See also [verify_with_seed](Lincheck::verify_with_seed) and `verify_replayable` with the `serde` feature.
+    #[cfg(feature = "proptest")]
+    pub repro: Option<String>,
+}
+
+/// A correctness condition of concurrent executions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Correctness {
+    /// Checked by [LinearizabilityChecker](checker::LinearizabilityChecker).
+    #[default]
+    Linearizability,
+    /// Checked by [SequentialConsistencyChecker](checker::SequentialConsistencyChecker).
+    SequentialConsistency,
+    /// Checked by [QuiescentConsistencyChecker](checker::QuiescentConsistencyChecker).
+    QuiescentConsistency,
+}
+
+/// How the number of threads of the generated scenarios is distributed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ThreadDistribution {
+    /// Every number of threads is equally likely.
+    #[default]
+    Uniform,
+    /// Each additional thread doubles the likelihood, so most scenarios run with the maximum concurrency.
+    TowardMax,
+    /// The weight of each number of threads starting from one. Missing weights are zero.
+    Weights(Vec<u32>),
+}
+
+/// The options of the [loom] model checker.
+///
+/// The options left as [None] keep the defaults of loom, which are read from the `LOOM_*` environment variables.
+/// Large scenarios may exceed the default branch limit, so increase [max_branches](LoomConfig::max_branches)
+/// or bound the preemptions to explore them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoomConfig {
+    /// The maximum number of thread preemptions explored in each interleaving.
+    pub preemption_bound: Option<usize>,
+    /// The maximum number of thread switches in each interleaving.
+    pub max_branches: Option<usize>,
+    /// The maximum time spent exploring the interleavings of a single scenario.
+    pub max_duration: Option<Duration>,
+    /// The file loom stores the progress of the exploration in and resumes it from.
+    /// Requires the `checkpoint` feature of loom.
+    pub checkpoint_file: Option<PathBuf>,
+    /// Whether the memory allocated with [leaks::alloc] and [leaks::into_raw] is checked for leaks and double frees
+    /// at the end of every execution. See the [leaks] module.
+    pub check_leaks: bool,
+}
+
+impl LoomConfig {
+    /// Creates a loom model builder with the configured options.
+    pub(crate) fn builder(&self) -> loom::model::Builder {
+        let mut builder = loom::model::Builder::new();
+        if let Some(preemption_bound) = self.preemption_bound {
+            builder.preemption_bound = Some(preemption_bound);
+        }
+        if let Some(max_branches) = self.max_branches {
+            builder.max_branches = max_branches;
+        }
+        if let Some(max_duration) = self.max_duration {
+            builder.max_duration = Some(max_duration);
+        }
+        if let Some(checkpoint_file) = &self.checkpoint_file {
+            builder.checkpoint_file = Some(checkpoint_file.clone());
+        }
+        builder
+    }
 }
 
 impl Default for Lincheck {
@@ -219,28 +504,160 @@ impl Default for Lincheck {
         Self {
             num_threads: 2,
             num_ops: 5,
+            num_instances: 1,
+            thread_distribution: ThreadDistribution::Uniform,
+            correctness: Correctness::Linearizability,
+            checker: None,
+            #[cfg(feature = "proptest")]
+            proptest: ProptestConfig::default(),
+            loom: LoomConfig::default(),
+            check_timeout: None,
+            output_width: None,
+            minimize: false,
+            progress: None,
+            #[cfg(feature = "proptest")]
+            repro: None,
         }
     }
 }
 
+#[cfg(feature = "proptest")]
+impl Lincheck {
+    /// Creates a test runner with the configured [proptest](Lincheck::proptest) parameters.
+    pub(crate) fn runner(&self) -> TestRunner {
+        TestRunner::new(self.proptest.clone())
+    }
+}
+
+#[cfg(feature = "proptest")]
 impl<Op: Arbitrary + 'static> Arbitrary for Scenario<Op> {
-    type Parameters = Lincheck;
+    type Parameters = (Lincheck, PartParams<Op::Parameters>, ScenarioConfig<Op>);
     type Strategy = BoxedStrategy<Self>;
 
-    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
-        let ops_strategy = || prop::collection::vec(any::<Op>(), 0..=args.num_ops);
-        let init_strategy = ops_strategy();
-        let post_strategy = ops_strategy();
+    fn arbitrary_with((config, params, scenario_config): Self::Parameters) -> Self::Strategy {
+        scenario_strategy(
+            1..=config.num_threads,
+            config.num_ops,
+            &config.thread_distribution,
+            Some(params),
+            scenario_config,
+        )
+    }
+}
 
-        let parallel_strategy = prop::collection::vec(ops_strategy(), 1..=args.num_threads);
+#[cfg(feature = "proptest")]
+impl Lincheck {
+    /// Generates scenarios with the default parameters of the operations in every part.
+    pub(crate) fn scenarios<Op: Arbitrary + 'static>(&self) -> BoxedStrategy<Scenario<Op>> {
+        scenario_strategy(
+            1..=self.num_threads,
+            self.num_ops,
+            &self.thread_distribution,
+            None,
+            ScenarioConfig::default(),
+        )
+    }
+}
 
-        (init_strategy, parallel_strategy, post_strategy)
-            .prop_map(|(init_part, parallel_part, post_part)| Self {
-                init_part,
-                parallel_part,
-                post_part,
-            })
-            .boxed()
+/// Generates scenarios with the number of threads in `num_threads` distributed according to `distribution`
+/// and at most `num_ops` operations per part generated with `params` and the strategies of `config`.
+///
+/// If `params` is [None] and there are neither per-thread strategies nor operation classes,
+/// every part is generated with the default parameters, so shrinking may move the operations between the parts.
+#[cfg(feature = "proptest")]
+pub(crate) fn scenario_strategy<Op: Arbitrary + 'static>(
+    num_threads: RangeInclusive<usize>,
+    num_ops: usize,
+    distribution: &ThreadDistribution,
+    params: Option<PartParams<Op::Parameters>>,
+    config: ScenarioConfig<Op>,
+) -> BoxedStrategy<Scenario<Op>> {
+    let move_ops =
+        params.is_none() && config.per_thread_strategies.is_empty() && config.op_class.is_none();
+    let params = params.unwrap_or_default();
+    let mut parts = [params.init_part, params.parallel_part, params.post_part]
+        .map(|params| any_with::<Op>(params).boxed());
+    let mut per_thread_strategies = config.per_thread_strategies;
+
+    if let Some(class) = config.op_class {
+        let [init_part, parallel_part, post_part] = parts;
+        parts = [
+            restrict_to_part(init_part, class, OpClass::allowed_in_init),
+            restrict_to_part(parallel_part, class, OpClass::allowed_in_parallel),
+            restrict_to_part(post_part, class, OpClass::allowed_in_post),
+        ];
+        per_thread_strategies = per_thread_strategies
+            .into_iter()
+            .map(|strategy| restrict_to_part(strategy, class, OpClass::allowed_in_parallel))
+            .collect();
+    }
+
+    scenario_strategy_with_parts(
+        num_threads,
+        num_ops,
+        distribution,
+        parts,
+        per_thread_strategies,
+        move_ops,
+    )
+}
+
+/// Same as [scenario_strategy] but generates the parts and the threads with the given strategies.
+///
+/// If `move_ops` is set, shrinking may move the operations between the parts, so the strategies must generate the same operations.
+#[cfg(feature = "proptest")]
+pub(crate) fn scenario_strategy_with_parts<Op: Debug + 'static>(
+    num_threads: RangeInclusive<usize>,
+    num_ops: usize,
+    distribution: &ThreadDistribution,
+    parts: [BoxedStrategy<Op>; 3],
+    per_thread_strategies: Vec<BoxedStrategy<Op>>,
+    move_ops: bool,
+) -> BoxedStrategy<Scenario<Op>> {
+    let weighted_threads: Vec<_> = num_threads
+        .map(|threads| (distribution.weight(threads - 1), threads))
+        .filter(|&(weight, _)| weight > 0)
+        .collect();
+    assert!(
+        !weighted_threads.is_empty(),
+        "the thread distribution must allow some number of threads"
+    );
+
+    ScenarioStrategy::new(
+        weighted_threads,
+        num_ops,
+        parts,
+        per_thread_strategies,
+        move_ops,
+    )
+    .boxed()
+}
+
+/// Rejects the operations generated by `strategy` whose class is not `allowed` in the part.
+#[cfg(feature = "proptest")]
+fn restrict_to_part<Op: Debug + 'static>(
+    strategy: BoxedStrategy<Op>,
+    class: fn(&Op) -> OpClass,
+    allowed: fn(OpClass) -> bool,
+) -> BoxedStrategy<Op> {
+    strategy
+        .prop_filter("the operation belongs to another part", move |op| {
+            allowed(class(op))
+        })
+        .boxed()
+}
+
+#[cfg(feature = "proptest")]
+impl ThreadDistribution {
+    /// The weight of `1 + extra_threads` threads.
+    fn weight(&self, extra_threads: usize) -> u32 {
+        match self {
+            ThreadDistribution::Uniform => 1,
+            ThreadDistribution::TowardMax => 1 << extra_threads.min(16),
+            ThreadDistribution::Weights(weights) => {
+                weights.get(extra_threads).copied().unwrap_or(0)
+            }
+        }
     }
 }
 
@@ -251,41 +668,885 @@ impl Lincheck {
     /// The test runner will generate random scenarios and check that regardless of the interleaving of the operations,
     /// the parallel execution is linearizable to some sequential execution.
     ///
-    /// It returns a non-linearizable execution if the test fails.
-    pub fn verify<Conc>(&self) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution
+    /// or the execution up to a panic or a deadlock.
+    #[cfg(feature = "proptest")]
+    pub fn verify<Conc>(&self) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.verify_using(
+            None,
+            ScenarioConfig::default(),
+            &Constructors::new(Conc::default, Conc::Seq::default),
+        )
+    }
+
+    /// The same as [verify](Lincheck::verify) but generates the operations of each part with the given parameters.
+    #[cfg(feature = "proptest")]
+    pub fn verify_with_params<Conc>(
+        &self,
+        params: PartParams<<ConcOp<Conc> as Arbitrary>::Parameters>,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.verify_using(
+            Some(params),
+            ScenarioConfig::default(),
+            &Constructors::new(Conc::default, Conc::Seq::default),
+        )
+    }
+
+    /// The same as [verify](Lincheck::verify) but generates the operations of the threads of the parallel part
+    /// with the strategies of `config`, e.g. one producer and one consumer thread.
+    #[cfg(feature = "proptest")]
+    pub fn verify_with_config<Conc>(
+        &self,
+        config: ScenarioConfig<ConcOp<Conc>>,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.verify_using(
+            None,
+            config,
+            &Constructors::new(Conc::default, Conc::Seq::default),
+        )
+    }
+
+    /// The same as [verify](Lincheck::verify) but creates the concurrent and the sequential implementations
+    /// with the given constructors instead of [Default], e.g. to pass a capacity.
+    #[cfg(feature = "proptest")]
+    pub fn verify_with<Conc>(
+        &self,
+        new_conc: impl Fn() -> Conc + Send + Sync + 'static,
+        new_seq: impl Fn() -> Conc::Seq + Send + Sync + 'static,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
     where
         Conc: ConcurrentSpec + Send + Sync + 'static,
         Conc::Seq: Send + Sync + 'static,
         ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
         ConcRet<Conc>: PartialEq + Debug + Send + Clone,
     {
-        let result = TestRunner::default().run(&any::<Scenario<ConcOp<Conc>>>(), |scenario| {
-            check_scenario_with_loom::<Conc>(scenario)
-                .map_err(|_| TestCaseError::Fail("Non-linearizable execution".into()))
+        self.verify_using(
+            None,
+            ScenarioConfig::default(),
+            &Constructors::new(new_conc, new_seq),
+        )
+    }
+
+    /// The same as [verify](Lincheck::verify) but checks `Conc` against the sequential specification `Seq`
+    /// instead of [Conc::Seq](ConcurrentSpec::Seq). `Seq` must have the same operations and results.
+    ///
+    /// It lets one implementation be checked against several specifications, e.g. a strict one and a relaxed one,
+    /// without implementing [ConcurrentSpec] again.
+    #[cfg(feature = "proptest")]
+    pub fn verify_against<Conc, Seq>(
+        &self,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Seq: SequentialSpec<Op = ConcOp<Conc>, Ret = ConcRet<Conc>>
+            + Default
+            + Send
+            + Sync
+            + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.verify_using(
+            None,
+            ScenarioConfig::default(),
+            &Constructors::new(Conc::default, Seq::default),
+        )
+    }
+
+    #[cfg(feature = "proptest")]
+    fn verify_using<Conc, Seq>(
+        &self,
+        params: Option<PartParams<<ConcOp<Conc> as Arbitrary>::Parameters>>,
+        config: ScenarioConfig<ConcOp<Conc>>,
+        constructors: &Constructors<Conc, Seq>,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Send + Sync + 'static,
+        Seq: SequentialSpec<Op = ConcOp<Conc>, Ret = ConcRet<Conc>> + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.verify_reproducibly(params, config, constructors, 1)
+            .into_result()
+    }
+
+    /// Same as [verify_using](Lincheck::verify_using) but checks the generated scenarios on `workers` threads at once
+    /// and returns the report of the run. If [repro](Lincheck::repro) is set, the scenarios before its case are
+    /// generated with its seed but not checked.
+    ///
+    /// It is the runner of every verification mode checking the generated scenarios with [loom]:
+    /// it notifies the [progress](Lincheck::progress) observer, caches the checked scenarios,
+    /// and [minimizes](Lincheck::minimize) and diagnoses the failing one.
+    #[cfg(feature = "proptest")]
+    fn verify_reproducibly<Conc, Seq>(
+        &self,
+        params: Option<PartParams<<ConcOp<Conc> as Arbitrary>::Parameters>>,
+        config: ScenarioConfig<ConcOp<Conc>>,
+        constructors: &Constructors<Conc, Seq>,
+        workers: usize,
+    ) -> VerifyReport<ConcOp<Conc>, ConcRet<Conc>>
+    where
+        Conc: ConcurrentSpec + Send + Sync + 'static,
+        Seq: SequentialSpec<Op = ConcOp<Conc>, Ret = ConcRet<Conc>> + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let from = self.repro.as_deref().map(|token| {
+            ReproToken::parse(token)
+                .unwrap_or_else(|| panic!("invalid reproducibility token {:?}", token))
+        });
+        let seed = match &from {
+            Some(token) => token.seed,
+            None => self.seed(),
+        };
+        let move_ops = params.is_none()
+            && config.per_thread_strategies.is_empty()
+            && config.op_class.is_none();
+        let mut proptest = self.proptest.clone();
+        proptest.rng_seed = RngSeed::Fixed(seed);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("verify", seed).entered();
+        let (case, failing_case) = (Cell::new(0), Cell::new(None));
+        // shrinking and minimization often produce the same scenario several times
+        let cache = CheckCache::default();
+        let progress = self.progress.as_deref().unwrap_or(&());
+        let shrink = RefCell::new(ShrinkStats::default());
+        // a panicking or deadlocking scenario fails too
+        let check = |scenario: &Scenario<ConcOp<Conc>>| {
+            cache.check(scenario, || {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    self.check_scenario_with(scenario.clone(), constructors)
+                }));
+                matches!(result, Ok(Ok(())))
+            })
+        };
+
+        let test = |scenario: Scenario<ConcOp<Conc>>| {
+            // the cases after the first failure are shrinking steps
+            let shrinking = failing_case.get().is_some();
+            if !shrinking {
+                case.set(case.get() + 1);
+                if from.as_ref().is_some_and(|token| case.get() < token.case) {
+                    return Ok(());
+                }
+                progress.on_scenario_start(case.get(), scenario.num_ops());
+            }
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!(
+                "scenario",
+                case = case.get(),
+                shrinking,
+                threads = scenario.parallel_part.len(),
+                num_ops = scenario.num_ops()
+            )
+            .entered();
+            #[cfg(feature = "tracing")]
+            tracing::debug!(?scenario, "generated the scenario");
+            let passed = check(&scenario);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(passed, "checked the scenario");
+            if shrinking {
+                let mut stats = shrink.borrow_mut();
+                stats.record(scenario.num_ops(), !passed);
+                progress.on_shrink_step(scenario.num_ops(), !passed);
+            } else {
+                progress.on_scenario_end(case.get(), passed);
+                if !passed {
+                    failing_case.set(Some(case.get()));
+                    shrink.replace(ShrinkStats::started(scenario.num_ops()));
+                    progress.on_shrink_start(scenario.num_ops());
+                }
+            }
+            if passed {
+                Ok(())
+            } else {
+                Err(TestCaseError::Fail("Non-linearizable execution".into()))
+            }
+        };
+        let strategy = scenario_strategy(
+            1..=self.num_threads,
+            self.num_ops,
+            &self.thread_distribution,
+            params,
+            config,
+        );
+        let mut runner = TestRunner::new(proptest);
+        let result = if workers > 1 {
+            let cases = runner.config().cases as usize;
+            runner.run(&Prefetching::new(strategy, workers, cases, &check), test)
+        } else {
+            runner.run(&strategy, test)
+        };
+
+        let token = failing_case.get().map(|case| ReproToken {
+            seed,
+            case,
+            scenario: None,
+        });
+        #[cfg(feature = "tracing")]
+        if let Some(token) = &token {
+            tracing::info!(%token, "found a failing case");
+        }
+        let mut report = VerifyReport {
+            scenarios_checked: (case.get())
+                .saturating_sub(from.as_ref().map_or(0, |token| token.case - 1)),
+            failing_scenario: None,
+            failure: None,
+            shrink: ShrinkStats::default(),
+            token: token.map(|token| token.to_string()),
+        };
+        match result {
+            Ok(_) => {}
+            Err(TestError::Fail(_, mut scenario)) => {
+                if self.minimize {
+                    #[cfg(feature = "tracing")]
+                    let _span =
+                        tracing::info_span!("minimize", num_ops = scenario.num_ops()).entered();
+                    scenario = minimize::minimize(scenario, move_ops, |candidate| {
+                        let fails = !check(candidate);
+                        shrink.borrow_mut().record(candidate.num_ops(), fails);
+                        progress.on_shrink_step(candidate.num_ops(), fails);
+                        fails
+                    });
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(
+                        num_ops = scenario.num_ops(),
+                        "minimized the failing scenario"
+                    );
+                }
+                report.shrink = shrink.into_inner();
+                progress.on_shrink_end(&report.shrink);
+                report.failure = Some(self.diagnose_failure(scenario.clone(), constructors));
+                report.failing_scenario = Some(scenario);
+            }
+            Err(TestError::Abort(reason)) => {
+                report.failure = Some(VerificationFailure::GeneratorError(reason.to_string()));
+            }
+        }
+        report
+    }
+
+    /// Runs the cases of `strategy` with the configured [proptest](Lincheck::proptest) runner checking each one with `check`,
+    /// and returns how the minimal failing case failed.
+    ///
+    /// It is the runner of the verification modes whose cases are more than a scenario, e.g. a scenario
+    /// along with the fault to inject, or whose checks are not reproducible. The progress is reported
+    /// like in [verify](Lincheck::verify). The failure of the last failing check is kept, so the failing case
+    /// isn't rerun. A panicking check fails as [Panicked](VerificationFailure::Panicked)
+    /// with the scenario of the case given by `scenario_of`.
+    #[cfg(feature = "proptest")]
+    pub(crate) fn run_cases<V, Op, Ret>(
+        &self,
+        strategy: &impl Strategy<Value = V>,
+        scenario_of: impl Fn(&V) -> Scenario<Op>,
+        check: impl Fn(V) -> Result<(), VerificationFailure<Op, Ret>>,
+    ) -> Result<(), VerificationFailure<Op, Ret>>
+    where
+        V: Clone + Debug,
+    {
+        let progress = self.progress.as_deref().unwrap_or(&());
+        let (case, failure) = (Cell::new(0), RefCell::new(None));
+        // set once the first case fails, the later cases are shrinking steps
+        let shrink: RefCell<Option<ShrinkStats>> = RefCell::new(None);
+
+        let result = self.runner().run(strategy, |value| {
+            let scenario = scenario_of(&value);
+            let num_ops = scenario.num_ops();
+            let shrinking = shrink.borrow().is_some();
+            if !shrinking {
+                case.set(case.get() + 1);
+                progress.on_scenario_start(case.get(), num_ops);
+            }
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| check(value)));
+            let failed = match result {
+                Ok(result) => result.err(),
+                Err(payload) => Some(VerificationFailure::Panicked {
+                    scenario,
+                    thread: None,
+                    payload: deadlock::panic_message(&*payload)
+                        .unwrap_or("Box<dyn Any>")
+                        .to_string(),
+                    partial_execution: Execution::default(),
+                }),
+            };
+            let passed = failed.is_none();
+            if failed.is_some() {
+                failure.replace(failed);
+            }
+
+            if shrinking {
+                (shrink.borrow_mut().as_mut())
+                    .expect("shrinking started")
+                    .record(num_ops, !passed);
+                progress.on_shrink_step(num_ops, !passed);
+            } else {
+                progress.on_scenario_end(case.get(), passed);
+                if !passed {
+                    shrink.replace(Some(ShrinkStats::started(num_ops)));
+                    progress.on_shrink_start(num_ops);
+                }
+            }
+            if passed {
+                Ok(())
+            } else {
+                Err(TestCaseError::Fail("The check failed".into()))
+            }
         });
 
+        if let Some(stats) = shrink.into_inner() {
+            progress.on_shrink_end(&stats);
+        }
         match result {
             Ok(_) => Ok(()),
-            Err(TestError::Fail(_, scenario)) => {
-                // rerun the scenario to get the failing execution
-                Err(check_scenario_with_loom::<Conc>(scenario).unwrap_err())
+            Err(TestError::Fail(_, _)) => Err(failure
+                .into_inner()
+                .expect("the failure of the failing case is kept")),
+            Err(TestError::Abort(reason)) => {
+                Err(VerificationFailure::GeneratorError(reason.to_string()))
             }
-            Err(failure) => panic!("Unexpected failure: {:?}", failure),
         }
     }
 
-    /// The same as [verify](Lincheck::verify) but automatically panics and pretty-prints the execution if the test fails.
-    pub fn verify_or_panic<Conc>(&self)
+    /// The configured seed of the [proptest](Lincheck::proptest) runner, or a random one.
+    #[cfg(feature = "proptest")]
+    fn seed(&self) -> u64 {
+        match self.proptest.rng_seed {
+            RngSeed::Fixed(seed) => seed,
+            RngSeed::Random => self.runner().rng().next_u64(),
+        }
+    }
+
+    /// Reruns the failing scenario to find out how it fails.
+    ///
+    /// The scenario is first rerun on a single instance recording the execution into a trace that survives
+    /// a panic or a deadlock. The failures that need several instances are reproduced on all of them.
+    fn diagnose_failure<Conc, Seq>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+        constructors: &Constructors<Conc, Seq>,
+    ) -> VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>
     where
         Conc: ConcurrentSpec + Send + Sync + 'static,
-        Conc::Seq: Send + Sync + 'static,
+        Seq: SequentialSpec<Op = ConcOp<Conc>, Ret = ConcRet<Conc>> + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let (correctness, check_timeout) = (self.correctness, self.check_timeout);
+        let (new_seq, checker) = (constructors.new_seq.clone(), self.checker.clone());
+        let traced = deadlock::check_scenario_traced_with_loom(
+            &self.loom,
+            scenario.clone(),
+            constructors.new_conc.clone(),
+            // a timed out check is reported when the scenario is rerun below
+            move |execution| {
+                correctness.check_within(execution, &*new_seq, checker.as_ref(), check_timeout)
+                    != Some(false)
+            },
+        );
+        if let Err(failure) = traced {
+            return failure;
+        }
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.check_scenario_with(scenario.clone(), constructors)
+        }));
+        match result {
+            Ok(result) => result.expect_err("the failing scenario passed when rerun"),
+            Err(payload) => VerificationFailure::Panicked {
+                scenario,
+                thread: None,
+                payload: deadlock::panic_message(&*payload)
+                    .unwrap_or("Box<dyn Any>")
+                    .to_string(),
+                partial_execution: Execution::default(),
+            },
+        }
+    }
+
+    /// Checks exactly the given scenario instead of generating them, e.g. a failing one saved by CI.
+    ///
+    /// Like [verify](Lincheck::verify), it explores every interleaving with [loom]
+    /// and returns the first execution violating the configured [correctness](Lincheck::correctness) condition.
+    pub fn replay<Conc>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+    ) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.check_scenario::<Conc>(scenario)
+    }
+
+    /// Verifies `Conc` on the given scenarios instead of generating them with [proptest](https://docs.rs/proptest),
+    /// e.g. the ones of a custom generator or a corpus of files. The operations don't have to implement `Arbitrary`,
+    /// so it is available without the `proptest` feature.
+    ///
+    /// Every scenario is checked like in [verify](Lincheck::verify), but the failing one is reported as is, without shrinking.
+    pub fn verify_scenarios<Conc>(
+        &self,
+        scenarios: impl IntoIterator<Item = Scenario<ConcOp<Conc>>>,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let constructors = Constructors::new(Conc::default, Conc::Seq::default);
+        for scenario in scenarios {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                self.check_scenario_with(scenario.clone(), &constructors)
+            }));
+            // a panicking or deadlocking scenario is diagnosed like a failing one
+            if !matches!(result, Ok(Ok(()))) {
+                return Err(self.diagnose_failure(scenario, &constructors));
+            }
+        }
+        Ok(())
+    }
+
+    /// The same as [verify](Lincheck::verify) but automatically panics and pretty-prints the failure if the test fails.
+    /// For linearizability, the [explanation](Execution::explain) of the execution is printed as well,
+    /// i.e. what the sequential specification would have returned at each step.
+    /// The message ends with the token to rerun the failing case with [verify_with_seed](Lincheck::verify_with_seed).
+    #[cfg(feature = "proptest")]
+    pub fn verify_or_panic<Conc>(&self)
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
         <Conc::Seq as SequentialSpec>::Op:
             Send + Sync + UnwindSafe + Clone + Arbitrary + Debug + 'static,
         <Conc::Seq as SequentialSpec>::Ret: PartialEq + Debug + Send + Clone,
     {
-        let result = self.verify::<Conc>();
-        if let Err(execution) = result {
-            panic!("Non-linearizable execution: \n\n {}", execution);
+        let report = self.verify_reproducibly(
+            None,
+            ScenarioConfig::default(),
+            &Constructors::new(Conc::default, Conc::Seq::default),
+            1,
+        );
+        if let Some(failure) = &report.failure {
+            let explanation = match failure {
+                VerificationFailure::NonLinearizable(execution)
+                    if self.correctness == Correctness::Linearizability =>
+                {
+                    execution.explain::<Conc::Seq>()
+                }
+                _ => None,
+            };
+            let mut message = match self.output_width {
+                Some(width) => format!("{:width$}", failure, width = width),
+                None => failure.to_string(),
+            };
+            if let Some(explanation) = explanation {
+                write!(message, "\n{}", explanation).unwrap();
+            }
+            if let Some(instruction) = report.rerun_instruction() {
+                write!(message, "\n{}", instruction).unwrap();
+            }
+            panic!("{}", message);
+        }
+    }
+
+    /// Checks the given scenario on as many instances of `Conc` as configured against the configured correctness condition,
+    /// exploring every interleaving with [loom].
+    ///
+    /// Unlike [verify](Lincheck::verify), the operations don't have to implement [Arbitrary],
+    /// so the scenarios written with [ScenarioBuilder](scenario::ScenarioBuilder) can pin known-bad interleavings
+    /// as regression tests. It returns the first execution violating the condition.
+    pub fn check_scenario<Conc>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+    ) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.check_scenario_with(
+            scenario,
+            &Constructors::new(Conc::default, Conc::Seq::default),
+        )
+        .map_err(|failure| match failure {
+            VerificationFailure::NonLinearizable(execution)
+            | VerificationFailure::CheckerTimeout { execution, .. }
+            | VerificationFailure::InvalidState { execution, .. }
+            | VerificationFailure::MemoryError { execution, .. } => execution,
+            _ => unreachable!("only the check of an execution fails the scenario"),
+        })
+    }
+
+    /// Same as [check_scenario](Lincheck::check_scenario) but creates the implementations with `constructors`
+    /// and reports the executions that took too long to check as [CheckerTimeout](VerificationFailure::CheckerTimeout).
+    fn check_scenario_with<Conc, Seq>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+        constructors: &Constructors<Conc, Seq>,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Send + Sync + 'static,
+        Seq: SequentialSpec<Op = ConcOp<Conc>, Ret = ConcRet<Conc>> + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.check_scenario_recording(scenario, constructors, None)
+    }
+
+    /// Same as [check_scenario_with](Lincheck::check_scenario_with) but records the explored interleavings
+    /// and the checked executions into `stats`.
+    fn check_scenario_recording<Conc, Seq>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+        constructors: &Constructors<Conc, Seq>,
+        stats: Option<Arc<stats::RunStats>>,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Send + Sync + 'static,
+        Seq: SequentialSpec<Op = ConcOp<Conc>, Ret = ConcRet<Conc>> + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let (num_instances, correctness, check_timeout) =
+            (self.num_instances, self.correctness, self.check_timeout);
+        let check_leaks = self.loom.check_leaks;
+        let (constructors, checker) = (constructors.clone(), self.checker.clone());
+        let progress = self.progress.clone();
+        let explored = Arc::new(AtomicUsize::new(0));
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("loom", instances = num_instances).entered();
+
+        let interleavings = explored.clone();
+        let result = find_failure_with_loom(&self.loom, move || {
+            let Constructors { new_conc, new_seq } = &constructors;
+            let execute = || {
+                let instances = (0..num_instances).map(|_| new_conc()).collect();
+                // the instances are dropped inside, freeing their memory
+                execute_multi_instance_scenario_validating_on(instances, scenario.clone())
+            };
+            let ((executions, invalid), ledger) = if check_leaks {
+                let (result, ledger) = leaks::track(execute);
+                (result, Some(ledger))
+            } else {
+                (execute(), None)
+            };
+            let explored = interleavings.fetch_add(1, Ordering::Relaxed) + 1;
+            #[cfg(feature = "tracing")]
+            tracing::trace!(interleaving = explored, "explored an interleaving");
+            if let Some(progress) = &progress {
+                progress.on_interleavings_explored(explored);
+            }
+            if let Some(stats) = &stats {
+                stats.record_interleaving();
+                if let Some(ledger) = &ledger {
+                    stats.record_allocations(ledger.allocations);
+                }
+            }
+            let failure = executions.iter().find_map(|execution| {
+                let start = Instant::now();
+                let verdict = correctness.check_within(
+                    execution,
+                    &**new_seq,
+                    checker.as_ref(),
+                    check_timeout,
+                );
+                if let Some(stats) = &stats {
+                    stats.record_check(execution, start.elapsed());
+                }
+                match verdict {
+                    Some(true) => None,
+                    Some(false) => Some(VerificationFailure::NonLinearizable(execution.clone())),
+                    None => Some(VerificationFailure::CheckerTimeout {
+                        scenario: scenario.clone(),
+                        execution: execution.clone(),
+                    }),
+                }
+            });
+            // a violation of the correctness condition is reported first
+            failure
+                .or_else(|| {
+                    invalid.map(|invalid| VerificationFailure::InvalidState {
+                        scenario: scenario.clone(),
+                        execution: executions[invalid.instance].clone(),
+                        after: invalid.after,
+                        message: invalid.message,
+                    })
+                })
+                .or_else(|| {
+                    let ledger =
+                        ledger.filter(|ledger| ledger.leaked() + ledger.double_frees > 0)?;
+                    Some(VerificationFailure::MemoryError {
+                        scenario: scenario.clone(),
+                        execution: executions[0].clone(),
+                        leaked: ledger.leaked(),
+                        double_frees: ledger.double_frees,
+                    })
+                })
+        });
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            interleavings = explored.load(Ordering::Relaxed),
+            failed = result.is_err(),
+            "explored the interleavings"
+        );
+        result
+    }
+}
+
+impl Correctness {
+    /// Checks the execution against the correctness condition creating the sequential specification with `new_seq`.
+    /// Returns [None] if the check took longer than `timeout`.
+    ///
+    /// The linearizability is checked with `checker` if it is given.
+    fn check_within<Seq>(
+        self,
+        execution: &Execution<Seq::Op, Seq::Ret>,
+        new_seq: &dyn Fn() -> Seq,
+        checker: Option<&checker::SpecChecker>,
+        timeout: Option<Duration>,
+    ) -> Option<bool>
+    where
+        Seq: SequentialSpec + 'static,
+        Seq::Op: Clone,
+        Seq::Ret: PartialEq,
+    {
+        if let (Correctness::Linearizability, Some(checker)) = (self, checker) {
+            return checker.check_with(execution, new_seq, timeout);
+        }
+        let Some(timeout) = timeout else {
+            return Some(match self {
+                Correctness::Linearizability => {
+                    checker::LinearizabilityChecker::check_with(execution, new_seq)
+                }
+                Correctness::SequentialConsistency => {
+                    checker::SequentialConsistencyChecker::check_with(execution, new_seq)
+                }
+                Correctness::QuiescentConsistency => {
+                    checker::QuiescentConsistencyChecker::check_with(execution, new_seq)
+                }
+            });
+        };
+        match self {
+            Correctness::Linearizability => {
+                checker::LinearizabilityChecker::check_with_timeout(execution, new_seq, timeout)
+            }
+            Correctness::SequentialConsistency => {
+                checker::SequentialConsistencyChecker::check_with_timeout(
+                    execution, new_seq, timeout,
+                )
+            }
+            Correctness::QuiescentConsistency => {
+                checker::QuiescentConsistencyChecker::check_with_timeout(
+                    execution, new_seq, timeout,
+                )
+            }
+        }
+    }
+}
+
+/// The constructors of the concurrent and the sequential implementations.
+/// The sequential one is [Conc::Seq](ConcurrentSpec::Seq) unless verified [against](Lincheck::verify_against) another one.
+struct Constructors<Conc: ConcurrentSpec, Seq = <Conc as ConcurrentSpec>::Seq> {
+    new_conc: Arc<dyn Fn() -> Conc + Send + Sync>,
+    new_seq: Arc<dyn Fn() -> Seq + Send + Sync>,
+}
+
+impl<Conc: ConcurrentSpec, Seq> Constructors<Conc, Seq> {
+    fn new(
+        new_conc: impl Fn() -> Conc + Send + Sync + 'static,
+        new_seq: impl Fn() -> Seq + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            new_conc: Arc::new(new_conc),
+            new_seq: Arc::new(new_seq),
+        }
+    }
+}
+
+impl<Conc: ConcurrentSpec, Seq> Clone for Constructors<Conc, Seq> {
+    fn clone(&self) -> Self {
+        Self {
+            new_conc: self.new_conc.clone(),
+            new_seq: self.new_seq.clone(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "proptest"))]
+mod tests {
+    use super::*;
+    use proptest::strategy::ValueTree;
+
+    fn thread_counts(distribution: ThreadDistribution) -> Vec<usize> {
+        let strategy =
+            scenario_strategy::<u8>(1..=3, 2, &distribution, None, ScenarioConfig::default());
+        let mut runner = TestRunner::deterministic();
+        (0..300)
+            .map(|_| {
+                let tree = strategy.new_tree(&mut runner).unwrap();
+                tree.current().parallel_part.len()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn explicit_thread_weights() {
+        let counts = thread_counts(ThreadDistribution::Weights(vec![0, 1]));
+        assert!(counts.iter().all(|&threads| threads == 2));
+    }
+
+    #[test]
+    fn threads_toward_max() {
+        let counts = thread_counts(ThreadDistribution::TowardMax);
+        let max = counts.iter().filter(|&&threads| threads == 3).count();
+        let min = counts.iter().filter(|&&threads| threads == 1).count();
+        assert!(max > counts.len() / 2);
+        assert!(min < max / 2);
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Op {
+        Insert(u8),
+        Read,
+    }
+
+    impl Arbitrary for Op {
+        /// Whether only inserts are generated.
+        type Parameters = bool;
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(inserts_only: Self::Parameters) -> Self::Strategy {
+            if inserts_only {
+                any::<u8>().prop_map(Op::Insert).boxed()
+            } else {
+                prop_oneof![any::<u8>().prop_map(Op::Insert), Just(Op::Read)].boxed()
+            }
         }
     }
+
+    #[test]
+    fn per_part_params() {
+        let config = Lincheck {
+            num_ops: 4,
+            ..Default::default()
+        };
+        let params = PartParams {
+            init_part: true,
+            parallel_part: false,
+            post_part: true,
+        };
+        let strategy = any_with::<Scenario<Op>>((config, params, ScenarioConfig::default()));
+        let inserts_only = |ops: &[Op]| ops.iter().all(|op| matches!(op, Op::Insert(_)));
+
+        // the shrunk scenario must respect the parameters as well
+        let result = TestRunner::deterministic().run(&strategy, |scenario| {
+            prop_assert!(inserts_only(&scenario.init_part));
+            prop_assert!(inserts_only(&scenario.post_part));
+            prop_assert!(scenario.post_part.len() < 3);
+            Ok(())
+        });
+        let Err(TestError::Fail(_, scenario)) = result else {
+            panic!("a scenario with a long post part must be generated");
+        };
+        assert!(inserts_only(&scenario.post_part));
+        assert_eq!(scenario.post_part, vec![Op::Insert(0); 3]);
+    }
+
+    #[test]
+    fn per_thread_strategies() {
+        let config = Lincheck {
+            num_ops: 4,
+            thread_distribution: ThreadDistribution::Weights(vec![0, 1]),
+            ..Default::default()
+        };
+        let scenario_config = ScenarioConfig {
+            per_thread_strategies: vec![
+                any::<u8>().prop_map(Op::Insert).boxed(),
+                Just(Op::Read).boxed(),
+            ],
+            ..Default::default()
+        };
+        let strategy = any_with::<Scenario<Op>>((config, PartParams::default(), scenario_config));
+        let reads_only = |ops: &[Op]| ops.iter().all(|op| *op == Op::Read);
+
+        let result = TestRunner::deterministic().run(&strategy, |scenario| {
+            prop_assert!(!scenario.parallel_part[0].contains(&Op::Read));
+            prop_assert!(reads_only(&scenario.parallel_part[1]));
+            prop_assert!(scenario.parallel_part[1].len() < 2);
+            Ok(())
+        });
+        let Err(TestError::Fail(_, scenario)) = result else {
+            panic!("a scenario with several reads in the second thread must be generated");
+        };
+        assert_eq!(scenario.parallel_part, vec![vec![], vec![Op::Read; 2]]);
+    }
+
+    #[test]
+    fn op_classes() {
+        let config = Lincheck {
+            num_ops: 4,
+            ..Default::default()
+        };
+        let scenario_config = ScenarioConfig {
+            op_class: Some(|op: &Op| match op {
+                Op::Insert(_) => OpClass::InitOnly,
+                Op::Read => OpClass::Any,
+            }),
+            ..Default::default()
+        };
+        let strategy = any_with::<Scenario<Op>>((config, PartParams::default(), scenario_config));
+        let reads_only = |ops: &[Op]| ops.iter().all(|op| *op == Op::Read);
+
+        // the shrunk scenario must respect the classes as well
+        let result = TestRunner::deterministic().run(&strategy, |scenario| {
+            prop_assert!(scenario.parallel_part.iter().all(|ops| reads_only(ops)));
+            prop_assert!(reads_only(&scenario.post_part));
+            prop_assert!(scenario.init_part.len() < 2);
+            Ok(())
+        });
+        let Err(TestError::Fail(_, scenario)) = result else {
+            panic!("a scenario with several inserts in the init part must be generated");
+        };
+        assert_eq!(scenario.init_part, vec![Op::Insert(0); 2]);
+    }
+
+    #[test]
+    fn proptest_config() {
+        let config = Lincheck {
+            proptest: ProptestConfig {
+                cases: 3,
+                rng_seed: proptest::test_runner::RngSeed::Fixed(42),
+                ..ProptestConfig::default()
+            },
+            ..Default::default()
+        };
+        let occurrences = || -> Vec<usize> {
+            let analysis = config.analyze_spec::<specs::Register<u8>>();
+            assert_eq!(analysis.scenarios, 3);
+            analysis.ops.iter().map(|op| op.occurrences).collect()
+        };
+
+        assert_eq!(occurrences(), occurrences());
+    }
 }
@@ -0,0 +1,101 @@
+//! Coverage-guided generation of the scenarios with [libFuzzer](https://llvm.org/docs/LibFuzzer.html).
+//!
+//! With the `arbitrary` feature, a [Scenario] implements [Arbitrary], so [cargo-fuzz](https://github.com/rust-fuzz/cargo-fuzz)
+//! can build the scenarios from its byte corpus, and the coverage of the data structure guides the search
+//! for the operation sequences that break it. [fuzz_target] checks the scenario of a fuzzer input
+//! like [verify_scenarios](Lincheck::verify_scenarios) and panics on a failure, which libFuzzer reports as a crash:
+//! ```ignore
+//! #![no_main]
+//!
+//! libfuzzer_sys::fuzz_target!(|data: &[u8]| lincheck::fuzz_target::<MyQueue>(data));
+//! ```
+//! The operations must implement [Arbitrary] too, e.g. with `#[derive(arbitrary::Arbitrary)]`.
+//! Every execution of the scenario is still explored with [loom], so the data structure must use its primitives.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+impl<Op> Scenario<Op> {
+    /// Builds a scenario of at most `num_threads` threads with at most `num_ops` operations
+    /// in each of them and in the init and post parts from the fuzzer input.
+    pub fn arbitrary_within<'a>(
+        u: &mut Unstructured<'a>,
+        num_threads: usize,
+        num_ops: usize,
+    ) -> Result<Self>
+    where
+        Op: Arbitrary<'a>,
+    {
+        let ops = |u: &mut Unstructured<'a>| -> Result<Vec<Op>> {
+            let len = u.int_in_range(0..=num_ops)?;
+            (0..len).map(|_| Op::arbitrary(u)).collect()
+        };
+        let init_part = ops(u)?;
+        let threads = u.int_in_range(1..=num_threads.max(1))?;
+        let parallel_part = (0..threads).map(|_| ops(u)).collect::<Result<_>>()?;
+        let post_part = ops(u)?;
+        Ok(Scenario {
+            init_part,
+            parallel_part,
+            post_part,
+        })
+    }
+}
+
+/// Builds the scenarios within the [num_threads](Lincheck::num_threads) and [num_ops](Lincheck::num_ops)
+/// of the default [Lincheck] configuration. Use [arbitrary_within](Scenario::arbitrary_within) for other bounds.
+impl<'a, Op: Arbitrary<'a>> Arbitrary<'a> for Scenario<Op> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let Lincheck {
+            num_threads,
+            num_ops,
+            ..
+        } = Lincheck::default();
+        Self::arbitrary_within(u, num_threads, num_ops)
+    }
+}
+
+impl Lincheck {
+    /// Checks the scenario built from the fuzzer input within the configured [num_threads](Lincheck::num_threads)
+    /// and [num_ops](Lincheck::num_ops) and panics with the failure if the check fails.
+    ///
+    /// The inputs too short to build a scenario from are skipped.
+    /// See the [module-level documentation](crate::libfuzzer) for the details.
+    pub fn fuzz_target<Conc>(&self, data: &[u8])
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: for<'a> Arbitrary<'a> + Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let mut u = Unstructured::new(data);
+        let Ok(scenario) = Scenario::arbitrary_within(&mut u, self.num_threads, self.num_ops)
+        else {
+            return;
+        };
+        if let Err(failure) = self.verify_scenarios::<Conc>([scenario]) {
+            match self.output_width {
+                Some(width) => panic!("{:width$}", failure, width = width),
+                None => panic!("{}", failure),
+            }
+        }
+    }
+}
+
+/// Checks the scenario built from the fuzzer input with the default [Lincheck] configuration.
+///
+/// See [Lincheck::fuzz_target].
+pub fn fuzz_target<Conc>(data: &[u8])
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: for<'a> Arbitrary<'a> + Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+{
+    Lincheck::default().fuzz_target::<Conc>(data)
+}
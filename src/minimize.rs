@@ -0,0 +1,173 @@
+//! Minimization of the failing scenarios after shrinking.
+//!
+//! Shrinking with [proptest](https://docs.rs/proptest) stops as soon as a simplification passes
+//! and never retries it after a later one, so the reported scenario is often not the smallest failing one.
+//! [minimize] re-checks the candidate reductions of a failing scenario until none of them fails anymore:
+//! 1. dropping a whole thread of the parallel part;
+//! 2. dropping chunks of operations across the parts, halving the chunks down to single operations;
+//! 3. merging two threads into one;
+//! 4. moving the first operation of a thread to the end of the init part
+//!    or its last operation to the start of the post part.
+//!
+//! Every reduction makes the scenario smaller in the number of operations, the operations of the parallel part
+//! or the number of threads, so the minimization terminates.
+//!
+//! Set [minimize](crate::Lincheck::minimize) to minimize the failures found by [verify](crate::Lincheck::verify).
+
+use crate::scenario::Scenario;
+
+/// Minimizes the failing `scenario` checking every candidate reduction with `fails`,
+/// e.g. by exploring it with [loom].
+///
+/// The operations are moved between the parts only if `move_ops` is set,
+/// i.e. every part accepts the same operations.
+pub fn minimize<Op: Clone>(
+    mut scenario: Scenario<Op>,
+    move_ops: bool,
+    mut fails: impl FnMut(&Scenario<Op>) -> bool,
+) -> Scenario<Op> {
+    loop {
+        let reduced = reductions(&scenario, move_ops).find(|candidate| fails(candidate));
+        match reduced {
+            Some(reduced) => scenario = reduced,
+            None => return scenario,
+        }
+    }
+}
+
+/// The candidate reductions of `scenario` in the order they are tried.
+fn reductions<Op: Clone>(
+    scenario: &Scenario<Op>,
+    move_ops: bool,
+) -> impl Iterator<Item = Scenario<Op>> + '_ {
+    let threads = scenario.parallel_part.len();
+    let num_ops = scenario.num_ops();
+
+    let drop_threads = (0..threads).map(move |thread| {
+        let mut reduced = scenario.clone();
+        reduced.parallel_part.remove(thread);
+        reduced
+    });
+
+    let chunk_sizes = std::iter::successors(Some(num_ops / 2), |&size| Some(size / 2))
+        .take_while(|&size| size > 0)
+        .chain((num_ops == 1).then_some(1));
+    let drop_chunks = chunk_sizes.flat_map(move |size| {
+        (0..num_ops)
+            .step_by(size)
+            .map(move |start| without(scenario, start..start + size))
+    });
+
+    let merge_threads = (0..threads)
+        .flat_map(move |first| (first + 1..threads).map(move |second| (first, second)))
+        .map(move |(first, second)| {
+            let mut reduced = scenario.clone();
+            let ops = reduced.parallel_part.remove(second);
+            reduced.parallel_part[first].extend(ops);
+            reduced
+        });
+
+    let move_first_ops = (0..threads)
+        .filter(move |&thread| move_ops && !scenario.parallel_part[thread].is_empty())
+        .map(move |thread| {
+            let mut reduced = scenario.clone();
+            let op = reduced.parallel_part[thread].remove(0);
+            reduced.init_part.push(op);
+            reduced
+        });
+
+    let move_last_ops = (0..threads)
+        .filter(move |&thread| move_ops && !scenario.parallel_part[thread].is_empty())
+        .map(move |thread| {
+            let mut reduced = scenario.clone();
+            let op = reduced.parallel_part[thread].pop().unwrap();
+            reduced.post_part.insert(0, op);
+            reduced
+        });
+
+    drop_threads
+        .chain(drop_chunks)
+        .chain(merge_threads)
+        .chain(move_first_ops)
+        .chain(move_last_ops)
+}
+
+/// `scenario` without the operations in `range`, counting the parts in order.
+/// The threads left without operations are dropped.
+fn without<Op: Clone>(scenario: &Scenario<Op>, range: std::ops::Range<usize>) -> Scenario<Op> {
+    let mut index = 0;
+    let mut keep = |ops: &[Op]| -> Vec<Op> {
+        let kept = (ops.iter().enumerate())
+            .filter(|&(i, _)| !range.contains(&(index + i)))
+            .map(|(_, op)| op.clone())
+            .collect();
+        index += ops.len();
+        kept
+    };
+    let init_part = keep(&scenario.init_part);
+    let parallel_part = (scenario.parallel_part.iter())
+        .map(|ops| keep(ops))
+        .filter(|ops| !ops.is_empty())
+        .collect();
+    let post_part = keep(&scenario.post_part);
+    Scenario {
+        init_part,
+        parallel_part,
+        post_part,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(init_part: &[u8], parallel_part: &[&[u8]], post_part: &[u8]) -> Scenario<u8> {
+        Scenario {
+            init_part: init_part.to_vec(),
+            parallel_part: parallel_part.iter().map(|ops| ops.to_vec()).collect(),
+            post_part: post_part.to_vec(),
+        }
+    }
+
+    #[test]
+    fn drops_unrelated_ops() {
+        let minimized = minimize(
+            scenario(&[1, 2], &[&[3, 10, 4], &[5, 6]], &[7, 8]),
+            true,
+            |scenario| scenario.parallel_part.iter().flatten().any(|&op| op == 10),
+        );
+        assert_eq!(minimized, scenario(&[], &[&[10]], &[]));
+    }
+
+    #[test]
+    fn merges_threads() {
+        let minimized = minimize(scenario(&[], &[&[1], &[2]], &[]), true, |scenario| {
+            let ops: Vec<_> = scenario.parallel_part.iter().flatten().collect();
+            ops == [&1, &2]
+        });
+        assert_eq!(minimized, scenario(&[], &[&[1, 2]], &[]));
+    }
+
+    #[test]
+    fn keeps_racing_ops_in_parallel_part() {
+        let minimized = minimize(
+            scenario(&[], &[&[1, 2, 3], &[4, 5, 6]], &[]),
+            true,
+            |scenario| {
+                let busy_threads = scenario.parallel_part.iter().filter(|ops| !ops.is_empty());
+                busy_threads.count() == 2
+            },
+        );
+        assert_eq!(minimized.num_ops(), 2);
+        assert_eq!(minimized.parallel_part.len(), 2);
+    }
+
+    #[test]
+    fn moves_ops_only_if_allowed() {
+        let fails = |scenario: &Scenario<u8>| scenario.num_ops() == 2;
+        let minimized = minimize(scenario(&[], &[&[1, 2]], &[]), false, fails);
+        assert_eq!(minimized, scenario(&[], &[&[1, 2]], &[]));
+        let minimized = minimize(scenario(&[], &[&[1, 2]], &[]), true, fails);
+        assert_eq!(minimized, scenario(&[1, 2], &[], &[]));
+    }
+}
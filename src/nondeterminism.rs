@@ -0,0 +1,269 @@
+//! Linearizability checking against non-deterministic sequential specifications.
+//!
+//! Some operations have several legal results in the same state, e.g. `try_lock` may fail spuriously
+//! or an operation with a random backoff may pick any slot. A [NonDeterministicSequentialSpec] returns
+//! every legal outcome of an operation together with the state it leads to,
+//! and [NonDeterministicChecker] branches over all of them while searching for a linearization.
+
+use proptest::prelude::*;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::panic::UnwindSafe;
+
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// A [SequentialSpec] whose operations may have several legal results.
+///
+/// [exec](SequentialSpec::exec) must return one of the outcomes of [exec_all](NonDeterministicSequentialSpec::exec_all).
+/// It is used where a single result is needed, e.g. by [replay](crate::Replay::replay).
+pub trait NonDeterministicSequentialSpec: SequentialSpec + Sized {
+    /// Returns every legal result of the operation together with the state of the data structure after it.
+    fn exec_all(&self, op: Self::Op) -> Vec<(Self::Ret, Self)>;
+}
+
+/// The linearizability checker for [non-deterministic](NonDeterministicSequentialSpec) specifications.
+///
+/// Like [LinearizabilityChecker](crate::checker::LinearizabilityChecker), it brute-forces the topological orderings
+/// of the happens-before graph. Each linearized operation continues the search from every outcome whose result
+/// matches the recorded one. Pending invocations may take effect with any outcome or be left out.
+pub struct NonDeterministicChecker<Seq> {
+    _seq: PhantomData<Seq>,
+}
+
+impl<Seq> NonDeterministicChecker<Seq>
+where
+    Seq: NonDeterministicSequentialSpec,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// Checks if the given execution is linearizable with respect to the non-deterministic sequential specification `Seq`.
+    pub fn check(execution: &Execution<Seq::Op, Seq::Ret>) -> bool
+    where
+        Seq: Default,
+    {
+        Self::check_with(execution, &Seq::default)
+    }
+
+    /// Same as [check](NonDeterministicChecker::check) but creates the sequential specification with `new_seq`.
+    pub fn check_with(execution: &Execution<Seq::Op, Seq::Ret>, new_seq: &dyn Fn() -> Seq) -> bool {
+        Self::check_init_part(execution, &execution.init_part, &new_seq())
+    }
+
+    fn check_init_part(
+        execution: &Execution<Seq::Op, Seq::Ret>,
+        init_part: &[Invocation<Seq::Op, Seq::Ret>],
+        state: &Seq,
+    ) -> bool {
+        match init_part.split_first() {
+            Some((inv, rest)) => Self::outcomes(state, &inv.op, Some(&inv.ret))
+                .any(|next| Self::check_init_part(execution, rest, &next)),
+            None => {
+                let mut linearized =
+                    vec![false; execution.parallel_part.len() + execution.pending.len()];
+                Self::check_parallel_part(execution, &mut linearized, state)
+            }
+        }
+    }
+
+    fn check_parallel_part(
+        execution: &Execution<Seq::Op, Seq::Ret>,
+        linearized: &mut [bool],
+        state: &Seq,
+    ) -> bool {
+        let parallel_part = &execution.parallel_part;
+        let completed = parallel_part.len();
+        if linearized[..completed].iter().all(|&linearized| linearized)
+            && Self::check_post_part(&execution.post_part, state)
+        {
+            return true;
+        }
+
+        // the remaining pending invocations may still take effect before the post part
+        let intervals = execution.intervals();
+        (0..linearized.len()).any(|inv_id| {
+            if linearized[inv_id] {
+                return false;
+            }
+            let call_timestamp = intervals[inv_id].call_timestamp;
+            let is_minimal = intervals
+                .iter()
+                .zip(linearized.iter())
+                .all(|(other, &linearized)| linearized || other.return_timestamp > call_timestamp);
+            if !is_minimal {
+                return false;
+            }
+            let (op, ret) = match parallel_part.get(inv_id) {
+                Some(inv) => (&inv.op, Some(&inv.ret)),
+                None => (&execution.pending[inv_id - completed].op, None),
+            };
+
+            // a cancelled invocation may also never take effect
+            linearized[inv_id] = true;
+            let result = Self::outcomes(state, op, ret)
+                .any(|next| Self::check_parallel_part(execution, linearized, &next))
+                || (execution.is_cancelled(inv_id)
+                    && Self::check_parallel_part(execution, linearized, state));
+            linearized[inv_id] = false;
+            result
+        })
+    }
+
+    fn check_post_part(post_part: &[Invocation<Seq::Op, Seq::Ret>], state: &Seq) -> bool {
+        match post_part.split_first() {
+            Some((inv, rest)) => Self::outcomes(state, &inv.op, Some(&inv.ret))
+                .any(|next| Self::check_post_part(rest, &next)),
+            None => true,
+        }
+    }
+
+    /// The states after the operation whose results match `ret`. Pending invocations, without `ret`, match any result.
+    fn outcomes<'a>(
+        state: &Seq,
+        op: &Seq::Op,
+        ret: Option<&'a Seq::Ret>,
+    ) -> impl Iterator<Item = Seq> + 'a
+    where
+        Seq: 'a,
+    {
+        state
+            .exec_all(op.clone())
+            .into_iter()
+            .filter(move |(actual, _)| ret.is_none_or(|ret| Seq::ret_matches(actual, ret)))
+            .map(|(_, next)| next)
+    }
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` is linearizable with respect to
+    /// its [non-deterministic](NonDeterministicSequentialSpec) sequential specification.
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    pub fn verify_nondeterministic<Conc>(
+        &self,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: NonDeterministicSequentialSpec + Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        let check =
+            |execution: &Execution<_, _>| NonDeterministicChecker::<Conc::Seq>::check(execution);
+
+        self.run_cases(&self.scenarios(), Scenario::clone, |scenario| {
+            check_scenario_with_loom_using::<Conc>(&self.loom, scenario, check)
+                .map_err(VerificationFailure::NonLinearizable)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::LinearizabilityChecker;
+    use crate::execution;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Op {
+        TryLock,
+        Unlock,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Ret {
+        TryLock(bool),
+        Unlock,
+    }
+
+    /// A lock whose `try_lock` may fail spuriously.
+    #[derive(Debug, Clone, Default)]
+    struct SequentialLock {
+        locked: bool,
+    }
+
+    impl SequentialSpec for SequentialLock {
+        type Op = Op;
+        type Ret = Ret;
+
+        fn exec(&mut self, op: Op) -> Ret {
+            match op {
+                Op::TryLock => {
+                    let acquired = !self.locked;
+                    self.locked = true;
+                    Ret::TryLock(acquired)
+                }
+                Op::Unlock => {
+                    self.locked = false;
+                    Ret::Unlock
+                }
+            }
+        }
+    }
+
+    impl NonDeterministicSequentialSpec for SequentialLock {
+        fn exec_all(&self, op: Op) -> Vec<(Ret, Self)> {
+            let mut outcomes = vec![{
+                let mut next = self.clone();
+                (next.exec(op), next)
+            }];
+            if op == Op::TryLock && !self.locked {
+                outcomes.push((Ret::TryLock(false), self.clone()));
+            }
+            outcomes
+        }
+    }
+
+    #[test]
+    fn accepts_any_legal_result() {
+        let execution = execution! {
+            init: [Op::TryLock => Ret::TryLock(false)],
+            parallel: [
+                0 @ 0..1: Op::TryLock => Ret::TryLock(true),
+            ],
+            post: [Op::TryLock => Ret::TryLock(false)],
+        };
+        assert!(NonDeterministicChecker::<SequentialLock>::check(&execution));
+        assert!(!LinearizabilityChecker::<SequentialLock>::check(&execution));
+    }
+
+    #[test]
+    fn branches_over_the_states() {
+        // only the spurious failure of the first try_lock leaves the lock free for the second one
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: Op::TryLock => Ret::TryLock(false),
+                1 @ 2..3: Op::TryLock => Ret::TryLock(true),
+            ],
+        };
+        assert!(NonDeterministicChecker::<SequentialLock>::check(&execution));
+    }
+
+    #[test]
+    fn rejects_illegal_results() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..2: Op::TryLock => Ret::TryLock(true),
+                1 @ 1..3: Op::TryLock => Ret::TryLock(true),
+            ],
+        };
+        assert!(!NonDeterministicChecker::<SequentialLock>::check(
+            &execution
+        ));
+    }
+
+    #[test]
+    fn pending_invocation_may_take_effect() {
+        let execution = execution! {
+            parallel: [
+                1 @ 1..2: Op::TryLock => Ret::TryLock(false),
+            ],
+            pending: [0 @ 0..: Op::TryLock],
+            post: [Op::Unlock => Ret::Unlock, Op::TryLock => Ret::TryLock(true)],
+        };
+        assert!(NonDeterministicChecker::<SequentialLock>::check(&execution));
+    }
+}
@@ -0,0 +1,192 @@
+//! Scenarios spanning several objects.
+//!
+//! Composed usages of a data structure, e.g. moving the items from one queue to another, are checked
+//! on [Objects]: `N` instances of the same data structure whose operations are [ObjectOp]s naming the instance
+//! they target. The sequential specification is the composed state of `N` sequential instances,
+//! so the generated scenarios mix the operations on every object in every thread, and a history is
+//! linearizable only if the operations on all the objects are linearizable together.
+//!
+//! The objects are independent, so setting [checker](crate::Lincheck::checker) to
+//! [SpecChecker::partitioned](crate::checker::SpecChecker::partitioned) checks the sub-history of each object separately.
+//! An invariant spanning the objects,
+//! e.g. that the items are never lost in between, is checked by wrapping [Objects] into a [ConcurrentSpec]
+//! that forwards [exec](ConcurrentSpec::exec) and implements [validate](ConcurrentSpec::validate)
+//! on top of [objects](Objects::objects):
+//! ```
+//! # use lincheck::objects::{ObjectOp, Objects};
+//! # use lincheck::specs::{Register, RegisterOp, RegisterRet};
+//! # use lincheck::ConcurrentSpec;
+//! # use loom::sync::atomic::{AtomicUsize, Ordering};
+//! #[derive(Default)]
+//! struct AtomicRegister(AtomicUsize);
+//!
+//! impl ConcurrentSpec for AtomicRegister {
+//!     type Seq = Register<usize>;
+//!
+//!     fn exec(&self, op: RegisterOp<usize>) -> RegisterRet<usize> {
+//!         match op {
+//!             RegisterOp::Read => RegisterRet::Read(self.0.load(Ordering::SeqCst)),
+//!             RegisterOp::Write(value) => {
+//!                 self.0.store(value, Ordering::SeqCst);
+//!                 RegisterRet::Write
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! #[derive(Default)]
+//! struct Registers(Objects<AtomicRegister, 2>);
+//!
+//! impl ConcurrentSpec for Registers {
+//!     type Seq = Objects<Register<usize>, 2>;
+//!
+//!     fn exec(&self, op: ObjectOp<RegisterOp<usize>, 2>) -> RegisterRet<usize> {
+//!         self.0.exec(op)
+//!     }
+//!
+//!     fn validate(&self) -> Result<(), String> {
+//!         let [first, second] = self.0.objects();
+//!         let total = first.0.load(Ordering::SeqCst) + second.0.load(Ordering::SeqCst);
+//!         (total < 10).then_some(()).ok_or(format!("the registers hold {}", total))
+//!     }
+//! }
+//! ```
+
+#[cfg(feature = "proptest")]
+use proptest::prelude::*;
+use std::fmt::{self, Debug, Formatter};
+
+use crate::spec::*;
+
+/// An operation on the object [object](ObjectOp::object) of [Objects] of `N` objects.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObjectOp<Op, const N: usize> {
+    /// The index of the object, less than `N`.
+    pub object: usize,
+    /// The operation on the object.
+    pub op: Op,
+}
+
+impl<Op, const N: usize> ObjectOp<Op, N> {
+    /// The operation `op` on the object `object`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `object` is not less than `N`.
+    pub fn new(object: usize, op: Op) -> Self {
+        assert!(object < N, "object {} is out of {} objects", object, N);
+        Self { object, op }
+    }
+}
+
+impl<Op: Debug, const N: usize> Debug for ObjectOp<Op, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {:?}", self.object, self.op)
+    }
+}
+
+/// The operations are generated with the parameters of `Op` and target every object equally likely.
+#[cfg(feature = "proptest")]
+impl<Op: Arbitrary + 'static, const N: usize> Arbitrary for ObjectOp<Op, N> {
+    type Parameters = Op::Parameters;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(params: Self::Parameters) -> Self::Strategy {
+        assert!(N > 0, "there must be some objects");
+        (0..N, any_with::<Op>(params))
+            .prop_map(|(object, op)| ObjectOp { object, op })
+            .boxed()
+    }
+}
+
+/// `N` objects of the same sequential or concurrent implementation `T`,
+/// whose operations are [ObjectOp]s of the operations of `T`.
+///
+/// See the [module-level documentation](crate::objects) for the details.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Objects<T, const N: usize> {
+    objects: [T; N],
+}
+
+impl<T, const N: usize> Objects<T, N> {
+    /// Composes the given objects.
+    pub fn new(objects: [T; N]) -> Self {
+        Self { objects }
+    }
+
+    /// The objects.
+    pub fn objects(&self) -> &[T; N] {
+        &self.objects
+    }
+}
+
+impl<T: Default, const N: usize> Default for Objects<T, N> {
+    fn default() -> Self {
+        Self::new(std::array::from_fn(|_| T::default()))
+    }
+}
+
+impl<Seq: SequentialSpec, const N: usize> SequentialSpec for Objects<Seq, N> {
+    type Op = ObjectOp<Seq::Op, N>;
+    type Ret = Seq::Ret;
+
+    fn exec(&mut self, op: Self::Op) -> Self::Ret {
+        self.objects[op.object].exec(op.op)
+    }
+
+    fn ret_matches(expected: &Self::Ret, actual: &Self::Ret) -> bool
+    where
+        Self::Ret: PartialEq,
+    {
+        Seq::ret_matches(expected, actual)
+    }
+}
+
+impl<Seq: StateEquivalence, const N: usize> StateEquivalence for Objects<Seq, N> {
+    fn equivalent(&self, other: &Self) -> bool {
+        self.objects
+            .iter()
+            .zip(other.objects.iter())
+            .all(|(object, other)| object.equivalent(other))
+    }
+}
+
+impl<Seq: SequentialSpec, const N: usize> PartitionableSpec for Objects<Seq, N> {
+    type Key = usize;
+
+    fn partition_key(op: &Self::Op) -> usize {
+        op.object
+    }
+}
+
+impl<Conc: ConcurrentSpec, const N: usize> ConcurrentSpec for Objects<Conc, N> {
+    type Seq = Objects<Conc::Seq, N>;
+
+    fn exec(&self, op: ConcOp<Self>) -> ConcRet<Self> {
+        self.objects[op.object].exec(op.op)
+    }
+
+    fn exec_mut(&mut self, op: ConcOp<Self>) -> ConcRet<Self> {
+        self.objects[op.object].exec_mut(op.op)
+    }
+
+    const MAINTENANCE_PASSES: usize = Conc::MAINTENANCE_PASSES;
+
+    fn run_maintenance(&self) {
+        for object in self.objects.iter() {
+            object.run_maintenance();
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (index, object) in self.objects.iter().enumerate() {
+            object
+                .validate()
+                .map_err(|message| format!("object #{}: {}", index, message))?;
+        }
+        Ok(())
+    }
+
+    const VALIDATE_BETWEEN_PARTS: bool = Conc::VALIDATE_BETWEEN_PARTS;
+}
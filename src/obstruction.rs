@@ -0,0 +1,283 @@
+//! Obstruction-freedom checking.
+//!
+//! The data structure marks the points where a thread may be suspended with [stall_point],
+//! e.g. between the steps of a multi-step update or inside a critical section.
+//! [verify_obstruction_freedom](crate::Lincheck::verify_obstruction_freedom) picks one thread of the parallel part
+//! and one of its stall points and suspends the thread there until all the other threads finish their operations,
+//! in every interleaving explored by [loom].
+//!
+//! In an obstruction-free data structure the other threads complete regardless of the suspended one.
+//! If they wait for it instead, e.g. on a lock it holds or in a spin loop, [loom] either detects a deadlock
+//! or exceeds its [branch limit](crate::LoomConfig::max_branches). Both are reported as
+//! [Blocked](ObstructionFailure::Blocked), which is distinct from a linearizability violation.
+//! The executions that complete must still be linearizable.
+//!
+//! Outside of obstruction-freedom checking [stall_point] does nothing.
+
+use proptest::prelude::*;
+use proptest::sample::Index;
+use proptest::test_runner::TestError;
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe, UnwindSafe};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use loom::sync::{Condvar, Mutex};
+use loom::thread;
+
+use crate::checker::*;
+use crate::deadlock::stuck;
+use crate::execution::*;
+use crate::recorder::{self, *};
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{Lincheck, LoomConfig};
+
+/// The number of the first stall points of the chosen thread that may suspend it.
+const MAX_STALL_POINTS: usize = 4;
+
+/// The number of the checks for obstruction freedom. Guards the access to the [loom] thread locals
+/// so that [stall_point] can be called outside of [loom] too.
+static STALL_MODE: AtomicUsize = AtomicUsize::new(0);
+
+loom::thread_local! {
+    /// The number of stall points left before the current thread is suspended and the gate it waits at.
+    static STALL: RefCell<Option<(usize, Arc<Gate>)>> = RefCell::new(None);
+}
+
+/// Marks a point where the thread may be suspended until the other threads finish.
+pub fn stall_point() {
+    if STALL_MODE.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+
+    let gate = STALL.with(|stall| {
+        let mut stall = stall.borrow_mut();
+        match stall.as_mut() {
+            Some((0, _)) => stall.take().map(|(_, gate)| gate),
+            Some((left, _)) => {
+                *left -= 1;
+                None
+            }
+            None => None,
+        }
+    });
+    if let Some(gate) = gate {
+        gate.wait();
+    }
+}
+
+/// Where to suspend a thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StallTarget {
+    /// The thread of the parallel part.
+    pub thread: usize,
+    /// The number of stall points the thread passes before being suspended.
+    pub point: usize,
+}
+
+/// An obstruction-freedom failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ObstructionFailure<Op, Ret> {
+    /// The other threads couldn't finish while the thread of `target` was suspended.
+    Blocked {
+        /// The blocked scenario.
+        scenario: Scenario<Op>,
+        /// Where the thread was suspended.
+        target: StallTarget,
+        /// What [loom] reported, a deadlock or the exceeded branch limit.
+        reason: String,
+    },
+    /// The execution is not linearizable.
+    NotLinearizable(Execution<Op, Ret>),
+    /// The scenarios couldn't be generated, e.g. the strategies rejected too many of them.
+    GeneratorError(String),
+}
+
+/// Releases the suspended thread once the other threads finish their operations.
+struct Gate {
+    finished: Mutex<usize>,
+    all_finished: Condvar,
+    other_threads: usize,
+}
+
+impl Gate {
+    fn new(other_threads: usize) -> Self {
+        Self {
+            finished: Mutex::new(0),
+            all_finished: Condvar::new(),
+            other_threads,
+        }
+    }
+
+    fn wait(&self) {
+        let mut finished = self.finished.lock().unwrap();
+        while *finished < self.other_threads {
+            finished = self.all_finished.wait(finished).unwrap();
+        }
+    }
+
+    fn finish(&self) {
+        *self.finished.lock().unwrap() += 1;
+        self.all_finished.notify_all();
+    }
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` stays linearizable and its operations complete
+    /// while another thread is suspended at a stall point.
+    ///
+    /// See the [module-level documentation](crate::obstruction) for the details.
+    pub fn verify_obstruction_freedom<Conc>(
+        &self,
+    ) -> Result<(), ObstructionFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        let strategy = (self.scenarios(), any::<Index>(), 0..MAX_STALL_POINTS).prop_map(
+            |(scenario, thread, point)| {
+                let thread = thread.index(scenario.parallel_part.len());
+                (scenario, StallTarget { thread, point })
+            },
+        );
+
+        let result = self.runner().run(&strategy, |(scenario, target)| {
+            check_obstruction_freedom_with_loom_using::<Conc>(&self.loom, scenario, target)
+                .map_err(|_| TestCaseError::Fail("Obstruction-freedom failure".into()))
+        });
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(TestError::Fail(_, (scenario, target))) => {
+                // rerun the scenario to get the failure
+                Err(
+                    check_obstruction_freedom_with_loom_using::<Conc>(&self.loom, scenario, target)
+                        .unwrap_err(),
+                )
+            }
+            Err(TestError::Abort(reason)) => {
+                Err(ObstructionFailure::GeneratorError(reason.to_string()))
+            }
+        }
+    }
+}
+
+/// Executes the given scenario suspending the thread of `target` at its stall point inside [loom] model-checker
+/// and checks that the other threads finish and the resulting executions are linearizable.
+pub fn check_obstruction_freedom_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    target: StallTarget,
+) -> Result<(), ObstructionFailure<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    check_obstruction_freedom_with_loom_using::<Conc>(&LoomConfig::default(), scenario, target)
+}
+
+/// Same as [check_obstruction_freedom_with_loom] but configures loom with `loom`.
+pub(crate) fn check_obstruction_freedom_with_loom_using<Conc>(
+    loom: &LoomConfig,
+    scenario: Scenario<ConcOp<Conc>>,
+    target: StallTarget,
+) -> Result<(), ObstructionFailure<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    STALL_MODE.fetch_add(1, Ordering::Relaxed);
+    let model_scenario = scenario.clone();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        find_failure_with_loom(loom, move || {
+            let execution =
+                execute_stalled_scenario_with_loom::<Conc>(model_scenario.clone(), target);
+            (!LinearizabilityChecker::<Conc::Seq>::check(&execution)).then_some(execution)
+        })
+    }));
+    STALL_MODE.fetch_sub(1, Ordering::Relaxed);
+
+    match result {
+        Ok(result) => result.map_err(ObstructionFailure::NotLinearizable),
+        Err(payload) => match stuck(&*payload) {
+            Some((_, reason)) => Err(ObstructionFailure::Blocked {
+                scenario,
+                target,
+                reason: reason.to_string(),
+            }),
+            None => panic::resume_unwind(payload),
+        },
+    }
+}
+
+/// Executes the given scenario with [loom] mock threads suspending the thread of `target` at its stall point
+/// until the other threads finish, and returns the resulting execution.
+pub fn execute_stalled_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    target: StallTarget,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + 'static,
+{
+    let conc = Rc::new(Conc::default());
+
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+
+    // init part
+    for op in scenario.init_part {
+        recorder.record(op.clone(), || conc.exec(op));
+    }
+
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
+    let recorder = Rc::new(recorder.record_parallel_part_with_capacity(total_parallel_ops));
+    let gate = Arc::new(Gate::new(scenario.parallel_part.len().saturating_sub(1)));
+
+    // parallel part
+    let handles: Vec<_> = scenario
+        .parallel_part
+        .into_iter()
+        .enumerate()
+        .map(|(thread_id, thread_ops)| {
+            let conc = conc.clone();
+            let recorder = recorder.clone();
+            let gate = gate.clone();
+
+            thread::spawn(move || {
+                let suspended = thread_id == target.thread;
+                if suspended {
+                    STALL.with(|stall| *stall.borrow_mut() = Some((target.point, gate.clone())));
+                }
+
+                let mut recorder = recorder.record_thread_with_capacity(thread_ops.len());
+                for op in thread_ops {
+                    recorder.record(op.clone(), || conc.exec(op));
+                }
+
+                if !suspended {
+                    gate.finish();
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    // post part
+    let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+    for op in scenario.post_part {
+        recorder.record(op.clone(), || conc.exec(op));
+    }
+
+    recorder.finish()
+}
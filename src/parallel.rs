@@ -0,0 +1,127 @@
+//! Checking the scenarios on several threads.
+//!
+//! Each scenario is checked inside its own [loom] model, so independent scenarios may be checked at the same time.
+//! [verify_parallel](crate::Lincheck::verify_parallel) runs like [verify](crate::Lincheck::verify),
+//! but whenever the runner asks for the next scenario, the next batch of one scenario per worker is generated
+//! and checked on scoped threads. The results are remembered, so the runner finds them without checking again,
+//! and the first failing scenario is shrunk on the calling thread as usual.
+//!
+//! The scenarios are generated by the calling thread in the same order as by [verify](crate::Lincheck::verify),
+//! so a fixed [seed](crate::Lincheck::proptest) still makes the run reproducible.
+
+use proptest::prelude::*;
+use proptest::strategy::{NewTree, ValueTree};
+use proptest::test_runner::TestRunner;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt::{self, Debug};
+use std::panic::UnwindSafe;
+use std::thread;
+
+use crate::error::VerificationFailure;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{Constructors, Lincheck};
+
+impl Lincheck {
+    /// The same as [verify](Lincheck::verify) but checks the scenarios on `workers` threads at once.
+    ///
+    /// See the [module-level documentation](crate::parallel) for the details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `workers` is zero.
+    pub fn verify_parallel<Conc>(
+        &self,
+        workers: usize,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        assert!(workers > 0, "at least one worker is required");
+
+        self.verify_reproducibly(
+            None,
+            ScenarioConfig::default(),
+            &Constructors::new(Conc::default, Conc::Seq::default),
+            workers,
+        )
+        .into_result()
+    }
+}
+
+/// Generates the scenarios of `inner` in batches of `workers` and checks each batch on scoped threads with `check`,
+/// which remembers the results for the runner.
+pub(crate) struct Prefetching<'a, S: Strategy> {
+    inner: S,
+    workers: usize,
+    /// The number of cases left to generate.
+    remaining: Cell<usize>,
+    generated: RefCell<VecDeque<S::Tree>>,
+    check: &'a (dyn Fn(&S::Value) -> bool + Sync),
+}
+
+impl<'a, S: Strategy> Prefetching<'a, S> {
+    pub(crate) fn new(
+        inner: S,
+        workers: usize,
+        cases: usize,
+        check: &'a (dyn Fn(&S::Value) -> bool + Sync),
+    ) -> Self {
+        Self {
+            inner,
+            workers,
+            remaining: Cell::new(cases),
+            generated: RefCell::new(VecDeque::new()),
+            check,
+        }
+    }
+}
+
+impl<S: Strategy> Debug for Prefetching<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Prefetching")
+            .field("inner", &self.inner)
+            .field("workers", &self.workers)
+            .field("remaining", &self.remaining)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Strategy for Prefetching<'_, S>
+where
+    S: Strategy,
+    S::Value: Send,
+{
+    type Tree = S::Tree;
+    type Value = S::Value;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let mut generated = self.generated.borrow_mut();
+        if generated.is_empty() {
+            let batch = self.remaining.get().clamp(1, self.workers);
+            self.remaining
+                .set(self.remaining.get().saturating_sub(batch));
+            for _ in 0..batch {
+                match self.inner.new_tree(runner) {
+                    Ok(tree) => generated.push_back(tree),
+                    // the rejection is reported once the generated scenarios run out
+                    Err(_) if !generated.is_empty() => break,
+                    Err(reason) => return Err(reason),
+                }
+            }
+
+            let scenarios: Vec<_> = generated.iter().map(ValueTree::current).collect();
+            thread::scope(|s| {
+                for scenario in scenarios {
+                    let check = self.check;
+                    s.spawn(move || check(&scenario));
+                }
+            });
+        }
+        Ok(generated.pop_front().expect("a batch is generated"))
+    }
+}
@@ -0,0 +1,110 @@
+//! Scenarios starting from a prefilled data structure.
+//!
+//! Some bugs only show up in a nontrivial state, e.g. in a queue spanning several segments.
+//! Reaching such a state with the init part takes many operations, and [loom] explores every one of them
+//! even though they run on a single thread. [verify_prefilled](crate::Lincheck::verify_prefilled) generates
+//! along with each scenario a prefix of operations, runs it on the sequential specification only
+//! and builds the concurrent implementation in the resulting state with [from_seq](PrefillableSpec::from_seq).
+//! The interleaving budget of loom is then spent on the parallel part.
+//!
+//! The prefix shrinks along with the scenario. The prefix of the failing case is returned in the [PrefilledFailure],
+//! and [check_prefilled_scenario](crate::Lincheck::check_prefilled_scenario) reruns it.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::test_runner::TestError;
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+
+use crate::error::VerificationFailure;
+use crate::execution::Execution;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{Constructors, Lincheck};
+
+/// A failure of [verify_prefilled](Lincheck::verify_prefilled).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrefilledFailure<Op, Ret> {
+    /// The operations run on the sequential specification before the failing scenario.
+    pub prefill: Vec<Op>,
+    /// How the scenario failed starting from the prefilled state.
+    pub failure: VerificationFailure<Op, Ret>,
+}
+
+impl Lincheck {
+    /// The same as [verify](Lincheck::verify) but starts each scenario from the state reached
+    /// by at most `max_prefill` random operations of the sequential specification.
+    ///
+    /// See the [module-level documentation](crate::prefill) for the details.
+    pub fn verify_prefilled<Conc>(
+        &self,
+        max_prefill: usize,
+    ) -> Result<(), PrefilledFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: PrefillableSpec + Send + Sync + 'static,
+        Conc::Seq: Clone + Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let strategy = (
+            vec(any::<ConcOp<Conc>>(), 0..=max_prefill),
+            self.scenarios(),
+        );
+
+        let result = self.runner().run(&strategy, |(prefill, scenario)| {
+            self.check_scenario_with(scenario, &prefilled::<Conc>(&prefill))
+                .map_err(|_| TestCaseError::Fail("Non-linearizable execution".into()))
+        });
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(TestError::Fail(_, (prefill, scenario))) => {
+                // rerun the scenario to get the failing execution
+                let failure = self.diagnose_failure(scenario, &prefilled::<Conc>(&prefill));
+                Err(PrefilledFailure { prefill, failure })
+            }
+            Err(TestError::Abort(reason)) => Err(PrefilledFailure {
+                prefill: Vec::new(),
+                failure: VerificationFailure::GeneratorError(reason.to_string()),
+            }),
+        }
+    }
+
+    /// The same as [check_scenario](Lincheck::check_scenario) but starts from the state
+    /// reached by running `prefill` on the sequential specification.
+    pub fn check_prefilled_scenario<Conc>(
+        &self,
+        prefill: &[ConcOp<Conc>],
+        scenario: Scenario<ConcOp<Conc>>,
+    ) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: PrefillableSpec + Send + Sync + 'static,
+        Conc::Seq: Clone + Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.check_scenario_with(scenario, &prefilled::<Conc>(prefill))
+            .map_err(|failure| match failure {
+                VerificationFailure::NonLinearizable(execution)
+                | VerificationFailure::CheckerTimeout { execution, .. }
+                | VerificationFailure::InvalidState { execution, .. }
+                | VerificationFailure::MemoryError { execution, .. } => execution,
+                _ => unreachable!("only the check of an execution fails the scenario"),
+            })
+    }
+}
+
+/// The constructors of both implementations in the state reached by running `prefill` on the sequential specification.
+fn prefilled<Conc>(prefill: &[ConcOp<Conc>]) -> Constructors<Conc>
+where
+    Conc: PrefillableSpec,
+    Conc::Seq: Clone + Default + Send + Sync + 'static,
+    ConcOp<Conc>: Clone,
+{
+    let mut state = Conc::Seq::default();
+    for op in prefill.iter().cloned() {
+        state.exec(op);
+    }
+    let seq_state = state.clone();
+    Constructors::new(move || Conc::from_seq(&state), move || seq_state.clone())
+}
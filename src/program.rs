@@ -0,0 +1,165 @@
+//! Scenarios whose operations depend on the results of the previous ones.
+//!
+//! Real workloads pop what they pushed or compare-and-set the value they read, which a fixed list
+//! of random operations rarely hits. In a [ScenarioProgram], each thread of the parallel part runs
+//! a program instead: a closure built from the generated parameters of the thread that produces
+//! the next operation from the operations the thread has executed so far and their results.
+//! The programs stop by returning [None] or after [num_ops](crate::Lincheck::num_ops) operations.
+//!
+//! Only the parameters are generated and shrunk by proptest, so the programs must be deterministic
+//! for the failures to be reproducible.
+
+use proptest::prelude::*;
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use loom::sync::atomic::AtomicBool;
+use loom::thread;
+
+use crate::checker::*;
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::recorder::{self, *};
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// A scenario with a program of generated parameters `P` in each thread of the parallel part.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScenarioProgram<Op, P> {
+    /// The operations executed sequentially before the parallel part.
+    pub init_part: Vec<Op>,
+    /// The parameters of the program of each thread.
+    pub parallel_part: Vec<P>,
+    /// The operations executed sequentially after the parallel part.
+    pub post_part: Vec<Op>,
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` is linearizable running the programs
+    /// created by `program` from the generated parameters in the threads of the parallel part.
+    ///
+    /// See the [module-level documentation](crate::program) for the details.
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    pub fn verify_program<Conc, P, F>(
+        &self,
+        program: impl Fn(&P) -> F + Send + Sync + 'static,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Sync + Clone,
+        P: Send + Sync + Clone + Arbitrary + UnwindSafe + 'static,
+        F: FnMut(&[(ConcOp<Conc>, ConcRet<Conc>)]) -> Option<ConcOp<Conc>>,
+    {
+        let program = Arc::new(program);
+        let num_threads = self.num_threads;
+        let strategy = (
+            self.scenarios::<ConcOp<Conc>>(),
+            prop::collection::vec(any::<P>(), num_threads),
+        )
+            .prop_map(|(scenario, mut params)| {
+                // the number of threads follows the configured distribution
+                params.truncate(scenario.parallel_part.len());
+                ScenarioProgram {
+                    init_part: scenario.init_part,
+                    parallel_part: params,
+                    post_part: scenario.post_part,
+                }
+            });
+
+        let max_ops = self.num_ops;
+        let check = |scenario: ScenarioProgram<ConcOp<Conc>, P>| {
+            let program = program.clone();
+            find_failure_with_loom(&self.loom, move || {
+                let execution =
+                    execute_program_with_loom_on(Conc::default(), &scenario, &program, max_ops);
+                (!LinearizabilityChecker::<Conc::Seq>::check(&execution)).then_some(execution)
+            })
+        };
+
+        self.run_cases(
+            &strategy,
+            |program| Scenario {
+                init_part: program.init_part.clone(),
+                // the operations of the threads are chosen by their programs as they run
+                parallel_part: vec![Vec::new(); program.parallel_part.len()],
+                post_part: program.post_part.clone(),
+            },
+            |scenario| check(scenario).map_err(VerificationFailure::NonLinearizable),
+        )
+    }
+}
+
+/// Executes the given scenario program on the given instance of the data structure with [loom] mock threads
+/// and returns the resulting execution. Each thread runs at most `max_ops` operations.
+pub fn execute_program_with_loom_on<Conc, P, F>(
+    conc: Conc,
+    scenario: &ScenarioProgram<ConcOp<Conc>, P>,
+    program: &Arc<impl Fn(&P) -> F + 'static>,
+    max_ops: usize,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Sync + 'static,
+    ConcOp<Conc>: Send + Clone,
+    ConcRet<Conc>: Send + Clone,
+    P: Clone + 'static,
+    F: FnMut(&[(ConcOp<Conc>, ConcRet<Conc>)]) -> Option<ConcOp<Conc>>,
+{
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+
+    // init part
+    for op in scenario.init_part.iter().cloned() {
+        recorder.record(op.clone(), || conc.exec(op));
+    }
+
+    let recorder = Rc::new(recorder.record_parallel_part());
+
+    let conc = Rc::new(conc);
+    let stop_maintenance = Rc::new(AtomicBool::new(false));
+    let maintenance = spawn_maintenance(&conc, &stop_maintenance);
+
+    // parallel part
+    let handles: Vec<_> = scenario
+        .parallel_part
+        .iter()
+        .cloned()
+        .map(|params| {
+            let (conc, recorder, program) = (conc.clone(), recorder.clone(), program.clone());
+
+            thread::spawn(move || {
+                let mut recorder = recorder.record_thread_with_capacity(max_ops);
+                let mut next_op = program(&params);
+                let mut history = Vec::with_capacity(max_ops);
+                while history.len() < max_ops {
+                    let Some(op) = next_op(&history) else {
+                        break;
+                    };
+                    let mut ret = None;
+                    recorder.record(op.clone(), || {
+                        let result = conc.exec(op.clone());
+                        ret = Some(result.clone());
+                        result
+                    });
+                    history.push((op, ret.expect("the operation is executed")));
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    stop_and_join_maintenance(maintenance, &stop_maintenance);
+
+    // post part
+    let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+    for op in scenario.post_part.iter().cloned() {
+        recorder.record(op.clone(), || conc.exec(op));
+    }
+
+    recorder.finish()
+}
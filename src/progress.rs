@@ -0,0 +1,180 @@
+//! Observing the progress of a test run.
+//!
+//! Verification of large configurations runs for minutes, so a [ProgressObserver] set as
+//! [Lincheck::progress](crate::Lincheck::progress) is notified when each generated scenario starts and ends,
+//! as loom explores its interleavings, and at every shrinking step.
+//! With the `indicatif` feature, an [indicatif::ProgressBar] is such an observer:
+//! ```ignore
+//! use indicatif::ProgressBar;
+//! use lincheck::Lincheck;
+//! use std::sync::Arc;
+//!
+//! let lincheck = Lincheck {
+//!     progress: Some(Arc::new(ProgressBar::new(256))),
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! [verify_report](crate::Lincheck::verify_report) works like [verify](crate::Lincheck::verify)
+//! but returns a [VerifyReport] with the shrinking statistics.
+//! Shrinking is often the longest phase of a failing run, so it is worth watching.
+
+#[cfg(feature = "proptest")]
+use proptest::prelude::*;
+use std::fmt::Debug;
+#[cfg(feature = "proptest")]
+use std::panic::UnwindSafe;
+
+use crate::error::VerificationFailure;
+use crate::scenario::*;
+#[cfg(feature = "proptest")]
+use crate::spec::*;
+#[cfg(feature = "proptest")]
+use crate::{Constructors, Lincheck};
+
+/// Receives the progress events of [verify](crate::Lincheck::verify) and the checks of the scenarios
+/// when set as [Lincheck::progress](crate::Lincheck::progress).
+///
+/// The events come from the threads checking the scenarios, so the methods take `&self`.
+/// All of them do nothing by default.
+pub trait ProgressObserver: Debug + Send + Sync {
+    /// The `case`-th generated scenario with `num_ops` operations is about to be checked.
+    fn on_scenario_start(&self, _case: usize, _num_ops: usize) {}
+
+    /// The `case`-th generated scenario was checked.
+    fn on_scenario_end(&self, _case: usize, _passed: bool) {}
+
+    /// Loom explored another interleaving of the scenario being checked, `explored` of them so far.
+    fn on_interleavings_explored(&self, _explored: usize) {}
+
+    /// Shrinking of a failing scenario with `num_ops` operations started.
+    fn on_shrink_start(&self, _num_ops: usize) {}
+
+    /// A shrinking candidate with `num_ops` operations was checked. It is accepted if it still fails.
+    fn on_shrink_step(&self, _num_ops: usize, _accepted: bool) {}
+
+    /// Shrinking, including the [minimization](crate::Lincheck::minimize), finished.
+    fn on_shrink_end(&self, _stats: &ShrinkStats) {}
+}
+
+/// An observer that ignores every event.
+impl ProgressObserver for () {}
+
+/// Shows the generated scenarios checked so far, the interleavings of the current one
+/// and the size of the failing scenario while shrinking.
+/// The length of the bar should be the number of [cases](proptest::test_runner::Config::cases).
+#[cfg(feature = "indicatif")]
+impl ProgressObserver for indicatif::ProgressBar {
+    fn on_scenario_start(&self, case: usize, num_ops: usize) {
+        self.set_position(case as u64 - 1);
+        self.set_message(format!("{} operations", num_ops));
+    }
+
+    fn on_scenario_end(&self, case: usize, passed: bool) {
+        self.set_position(case as u64);
+        if !passed {
+            self.set_message("shrinking");
+        }
+    }
+
+    fn on_interleavings_explored(&self, explored: usize) {
+        self.set_message(format!("{} interleavings", explored));
+    }
+
+    fn on_shrink_step(&self, num_ops: usize, accepted: bool) {
+        if accepted {
+            self.set_message(format!("shrinking: {} operations", num_ops));
+        }
+    }
+}
+
+/// The statistics of the shrinking phase.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShrinkStats {
+    /// The number of checked candidates.
+    pub candidates: usize,
+    /// The number of candidates that still failed and were kept.
+    pub accepted: usize,
+    /// The number of candidates that passed and were discarded.
+    pub rejected: usize,
+    /// The number of operations in the original failing scenario.
+    pub initial_num_ops: usize,
+    /// The number of operations in the minimal failing scenario.
+    pub final_num_ops: usize,
+}
+
+#[cfg(feature = "proptest")]
+impl ShrinkStats {
+    /// The statistics of the shrinking of a failing scenario with `num_ops` operations.
+    pub(crate) fn started(num_ops: usize) -> Self {
+        Self {
+            initial_num_ops: num_ops,
+            final_num_ops: num_ops,
+            ..Self::default()
+        }
+    }
+
+    /// Records a checked candidate with `num_ops` operations, kept if it is `accepted`.
+    pub(crate) fn record(&mut self, num_ops: usize, accepted: bool) {
+        self.candidates += 1;
+        if accepted {
+            self.accepted += 1;
+            self.final_num_ops = num_ops;
+        } else {
+            self.rejected += 1;
+        }
+    }
+}
+
+/// The result of [verify_report](crate::Lincheck::verify_report).
+#[derive(Clone, Debug)]
+pub struct VerifyReport<Op, Ret> {
+    /// The number of generated scenarios checked, not counting the shrinking candidates.
+    pub scenarios_checked: usize,
+    /// The minimal failing scenario, if a generated scenario failed.
+    pub failing_scenario: Option<Scenario<Op>>,
+    /// How the test failed, if it did.
+    pub failure: Option<VerificationFailure<Op, Ret>>,
+    /// The shrinking statistics. Empty if the test passed.
+    pub shrink: ShrinkStats,
+    /// The token reproducing the failing case, if a generated scenario failed. See [repro](crate::Lincheck::repro).
+    pub token: Option<String>,
+}
+
+#[cfg(feature = "proptest")]
+impl<Op, Ret> VerifyReport<Op, Ret> {
+    /// How to rerun the failing case with its [token](VerifyReport::token), if a generated scenario failed.
+    pub fn rerun_instruction(&self) -> Option<String> {
+        self.token.as_ref().map(|token| {
+            format!(
+                "Rerun the failing case with `verify_with_seed({:?})`.",
+                token
+            )
+        })
+    }
+
+    /// Returns the failure, if any.
+    pub(crate) fn into_result(self) -> Result<(), VerificationFailure<Op, Ret>> {
+        self.failure.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl Lincheck {
+    /// The same as [verify](Lincheck::verify) but returns a [VerifyReport] with the number of checked scenarios
+    /// and the shrinking statistics along with the failure.
+    pub fn verify_report<Conc>(&self) -> VerifyReport<ConcOp<Conc>, ConcRet<Conc>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.verify_reproducibly(
+            None,
+            ScenarioConfig::default(),
+            &Constructors::new(Conc::default, Conc::Seq::default),
+            1,
+        )
+    }
+}
@@ -10,6 +10,10 @@
 //! - [PostPartRecorder], which records the post part of the execution
 //!
 //! [ParallelPartRecorder] is split into several [PerThreadRecorder]s, one for each thread.
+//! Blocking operations are recorded with [record_blocking](PerThreadRecorder::record_blocking),
+//! which leaves the invocation pending if the operation never completes.
+//! Cancellable operations, e.g. futures that may be dropped, are recorded with
+//! [record_cancellable](PerThreadRecorder::record_cancellable), after which the thread may go on.
 
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
@@ -21,6 +25,8 @@ pub(crate) struct InternalRecorder<Op, Ret> {
     invocations: ParallelHistory<Op, Ret>,
     current_op: Option<Op>,
     call_timestamp: usize,
+    pending: Option<PendingInvocation<Op>>,
+    cancelled: Vec<PendingInvocation<Op>>,
 }
 
 impl<Op, Ret> InternalRecorder<Op, Ret> {
@@ -30,6 +36,8 @@ impl<Op, Ret> InternalRecorder<Op, Ret> {
             invocations: ParallelHistory::new(),
             current_op: None,
             call_timestamp: 0,
+            pending: None,
+            cancelled: Vec::new(),
         }
     }
 
@@ -39,11 +47,17 @@ impl<Op, Ret> InternalRecorder<Op, Ret> {
             invocations: ParallelHistory::with_capacity(capacity),
             current_op: None,
             call_timestamp: 0,
+            pending: None,
+            cancelled: Vec::new(),
         }
     }
 
     pub(crate) fn add_call(&mut self, op: Op, timestamp: usize) {
         assert!(self.current_op.is_none());
+        assert!(
+            self.pending.is_none(),
+            "a thread can't call operations after a pending one"
+        );
         self.call_timestamp = timestamp;
         self.current_op = Some(op);
     }
@@ -58,6 +72,23 @@ impl<Op, Ret> InternalRecorder<Op, Ret> {
         })
     }
 
+    pub(crate) fn add_pending(&mut self) {
+        self.pending = Some(PendingInvocation::new(
+            self.thread_id,
+            self.call_timestamp,
+            self.current_op.take().unwrap(),
+        ))
+    }
+
+    pub(crate) fn add_cancel(&mut self, timestamp: usize) {
+        self.cancelled.push(PendingInvocation::cancelled(
+            self.thread_id,
+            self.call_timestamp,
+            timestamp,
+            self.current_op.take().unwrap(),
+        ))
+    }
+
     #[allow(dead_code)] // seems to be a bug because the method is used
     pub(crate) fn history(self) -> ParallelHistory<Op, Ret> {
         self.invocations
@@ -96,6 +127,7 @@ pub fn record_post_part<Op, Ret>() -> PostPartRecorder<Op, Ret> {
         init_part: History::new(),
         parallel_part: ParallelHistory::new(),
         post_part: History::new(),
+        pending: Vec::new(),
     }
 }
 
@@ -123,6 +155,7 @@ pub fn record_post_part_with_capacity<Op, Ret>(
         init_part: History::new(),
         parallel_part: ParallelHistory::new(),
         post_part: History::with_capacity(post_part_capacity),
+        pending: Vec::new(),
     }
 }
 
@@ -153,6 +186,7 @@ impl<Op, Ret> InitPartRecorder<Op, Ret> {
             init_part: self.init_part,
             parallel_part: ParallelHistory::new(),
             post_part: History::new(),
+            pending: Vec::new(),
         }
     }
 
@@ -173,6 +207,7 @@ impl<Op, Ret> InitPartRecorder<Op, Ret> {
             init_part: self.init_part,
             parallel_part: ParallelHistory::new(),
             post_part: History::with_capacity(post_part_capacity),
+            pending: Vec::new(),
         }
     }
 
@@ -182,6 +217,7 @@ impl<Op, Ret> InitPartRecorder<Op, Ret> {
             init_part: self.init_part,
             parallel_part: ParallelHistory::new(),
             post_part: History::new(),
+            pending: Vec::new(),
         }
     }
 }
@@ -190,6 +226,7 @@ impl<Op, Ret> InitPartRecorder<Op, Ret> {
 pub struct ParallelPartRecorder<Op, Ret> {
     init_part: Mutex<History<Op, Ret>>,
     parallel_part: Mutex<ParallelHistory<Op, Ret>>,
+    pending: Mutex<Vec<PendingInvocation<Op>>>,
     next_thread_id: AtomicUsize,
     timer: AtomicUsize,
 }
@@ -199,6 +236,7 @@ impl<Op, Ret> ParallelPartRecorder<Op, Ret> {
         ParallelPartRecorder {
             init_part: Mutex::new(init_part),
             parallel_part: Mutex::new(ParallelHistory::new()),
+            pending: Mutex::new(Vec::new()),
             next_thread_id: AtomicUsize::new(0),
             timer: AtomicUsize::new(0),
         }
@@ -208,6 +246,7 @@ impl<Op, Ret> ParallelPartRecorder<Op, Ret> {
         ParallelPartRecorder {
             init_part: Mutex::new(init_part),
             parallel_part: Mutex::new(ParallelHistory::with_capacity(parallel_part_capacity)),
+            pending: Mutex::new(Vec::new()),
             next_thread_id: AtomicUsize::new(0),
             timer: AtomicUsize::new(0),
         }
@@ -242,6 +281,7 @@ impl<Op, Ret> ParallelPartRecorder<Op, Ret> {
             init_part: std::mem::take(&mut self.init_part.lock().unwrap()),
             parallel_part: std::mem::take(&mut self.parallel_part.lock().unwrap()),
             post_part: History::new(),
+            pending: std::mem::take(&mut self.pending.lock().unwrap()),
         }
     }
 
@@ -254,6 +294,7 @@ impl<Op, Ret> ParallelPartRecorder<Op, Ret> {
             init_part: std::mem::take(&mut self.init_part.lock().unwrap()),
             parallel_part: std::mem::take(&mut self.parallel_part.lock().unwrap()),
             post_part: History::with_capacity(post_part_capacity),
+            pending: std::mem::take(&mut self.pending.lock().unwrap()),
         }
     }
 
@@ -263,6 +304,7 @@ impl<Op, Ret> ParallelPartRecorder<Op, Ret> {
             init_part: self.init_part.into_inner().unwrap(),
             parallel_part: self.parallel_part.into_inner().unwrap(),
             post_part: History::new(),
+            pending: self.pending.into_inner().unwrap(),
         }
     }
 }
@@ -288,6 +330,44 @@ impl<'a, Op, Ret> Recorder for PerThreadRecorder<'a, Op, Ret> {
     }
 }
 
+impl<'a, Op, Ret> PerThreadRecorder<'a, Op, Ret> {
+    /// Records an operation that may block forever, e.g. taking from an empty queue.
+    ///
+    /// `f` returns `None` if the operation didn't complete, e.g. it timed out or was cancelled
+    /// without taking effect. Then the invocation is recorded as pending: it has a call but no return.
+    /// A pending invocation must be the last one of the thread.
+    pub fn record_blocking(&mut self, op: Op, f: impl FnOnce() -> Option<Ret>) {
+        let call_timestamp = self.parent_builder.timer.fetch_add(1, Ordering::Relaxed);
+        self.internal_recorder.add_call(op, call_timestamp);
+
+        match f() {
+            Some(ret) => {
+                let return_timestamp = self.parent_builder.timer.fetch_add(1, Ordering::Relaxed);
+                self.internal_recorder.add_return(ret, return_timestamp);
+            }
+            None => self.internal_recorder.add_pending(),
+        }
+    }
+
+    /// Records an operation that may be cancelled mid-flight, e.g. a future that may be dropped.
+    ///
+    /// `f` returns `None` if the operation was cancelled. Then the invocation is recorded as
+    /// [cancelled](PendingInvocation::cancel_timestamp): it may or may not have taken effect before the cancellation,
+    /// and, unlike with [record_blocking](PerThreadRecorder::record_blocking), the thread may call more operations.
+    pub fn record_cancellable(&mut self, op: Op, f: impl FnOnce() -> Option<Ret>) {
+        let call_timestamp = self.parent_builder.timer.fetch_add(1, Ordering::Relaxed);
+        self.internal_recorder.add_call(op, call_timestamp);
+
+        let ret = f();
+
+        let timestamp = self.parent_builder.timer.fetch_add(1, Ordering::Relaxed);
+        match ret {
+            Some(ret) => self.internal_recorder.add_return(ret, timestamp),
+            None => self.internal_recorder.add_cancel(timestamp),
+        }
+    }
+}
+
 impl<'a, Op, Ret> Drop for PerThreadRecorder<'a, Op, Ret> {
     fn drop(&mut self) {
         let invocations = std::mem::take(&mut self.internal_recorder.invocations);
@@ -297,6 +377,10 @@ impl<'a, Op, Ret> Drop for PerThreadRecorder<'a, Op, Ret> {
             .lock()
             .unwrap()
             .extend(invocations);
+
+        let mut pending = self.parent_builder.pending.lock().unwrap();
+        pending.append(&mut self.internal_recorder.cancelled);
+        pending.extend(self.internal_recorder.pending.take());
     }
 }
 
@@ -305,6 +389,7 @@ pub struct PostPartRecorder<Op, Ret> {
     init_part: History<Op, Ret>,
     parallel_part: ParallelHistory<Op, Ret>,
     post_part: History<Op, Ret>,
+    pending: Vec<PendingInvocation<Op>>,
 }
 
 impl<Op, Ret> Recorder for PostPartRecorder<Op, Ret> {
@@ -324,6 +409,7 @@ impl<Op, Ret> PostPartRecorder<Op, Ret> {
             init_part: self.init_part,
             parallel_part: self.parallel_part,
             post_part: self.post_part,
+            pending: self.pending,
         }
     }
 }
@@ -436,6 +522,51 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_record_pending() {
+        let recorder = record_init_part().record_parallel_part();
+
+        {
+            let mut recorder = recorder.record_thread();
+            recorder.record_blocking(Op::A, || Some(Ret::A));
+            recorder.record_blocking(Op::B, || None);
+        }
+
+        let execution = recorder.finish();
+
+        assert_eq!(execution.parallel_part.len(), 1);
+        assert_eq!(execution.pending, vec![PendingInvocation::new(0, 2, Op::B)]);
+    }
+
+    #[test]
+    fn test_record_cancelled() {
+        let recorder = record_init_part().record_parallel_part();
+
+        {
+            let mut recorder = recorder.record_thread();
+            recorder.record_cancellable(Op::A, || None);
+            recorder.record(Op::B, || Ret::B);
+        }
+
+        let execution = recorder.finish();
+
+        assert_eq!(execution.parallel_part.len(), 1);
+        assert_eq!(
+            execution.pending,
+            vec![PendingInvocation::cancelled(0, 0, 1, Op::A)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "after a pending one")]
+    fn test_record_after_pending() {
+        let recorder = record_init_part::<Op, Ret>().record_parallel_part();
+        let mut recorder = recorder.record_thread();
+
+        recorder.record_blocking(Op::A, || None);
+        recorder.record(Op::B, || Ret::B);
+    }
+
     #[test]
     fn test_record_post() {
         let mut recorder = record_init_part().record_post_part();
@@ -0,0 +1,349 @@
+//! Checking intentionally relaxed data structures, e.g. k-FIFO queues and k-stacks.
+//!
+//! A [RelaxedSequentialSpec] returns the strict outcome of an operation together with the relaxed ones,
+//! each with a cost telling how far it is from the strict one, e.g. how deep in a queue the dequeued element was.
+//! [RelaxedChecker] searches the linearizations whose costs stay within a [Relaxation]:
+//! the cost of each operation is bounded for the k-relaxed data structures,
+//! and the total cost is bounded for quasi-linearizability.
+//!
+//! [RelaxedQueue] and [RelaxedStack] are ready-made specifications where any element may be taken
+//! at the cost of its distance from the head or the top. Checked with [Relaxation::PerOperation] of `k - 1`,
+//! they are the k-FIFO queue and the k-stack.
+
+use proptest::prelude::*;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::panic::UnwindSafe;
+
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::nondeterminism::*;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// A [SequentialSpec] whose operations may also have relaxed results at some cost.
+///
+/// [exec](SequentialSpec::exec) must return the outcome of [exec_relaxed](RelaxedSequentialSpec::exec_relaxed)
+/// of zero cost.
+pub trait RelaxedSequentialSpec: SequentialSpec + Sized {
+    /// Returns every legal result of the operation together with the state of the data structure after it
+    /// and the cost of the relaxation, zero for the strict result.
+    fn exec_relaxed(&self, op: Self::Op) -> Vec<(Self::Ret, Self, usize)>;
+}
+
+/// How much a linearization may be relaxed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Relaxation {
+    /// Every operation may cost at most the given amount, e.g. `k - 1` for a k-FIFO queue.
+    PerOperation(usize),
+    /// The costs of all the operations of an execution add up to at most the given amount.
+    Total(usize),
+}
+
+/// The checker of [relaxed](RelaxedSequentialSpec) specifications.
+///
+/// It runs [NonDeterministicChecker] over the outcomes that fit in the [Relaxation].
+pub struct RelaxedChecker<Seq> {
+    _seq: PhantomData<Seq>,
+}
+
+impl<Seq> RelaxedChecker<Seq>
+where
+    Seq: RelaxedSequentialSpec,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// Checks if the given execution linearizes to the relaxed specification `Seq` within the `relaxation`.
+    pub fn check(execution: &Execution<Seq::Op, Seq::Ret>, relaxation: Relaxation) -> bool
+    where
+        Seq: Default,
+    {
+        Self::check_with(execution, &Seq::default, relaxation)
+    }
+
+    /// Same as [check](RelaxedChecker::check) but creates the sequential specification with `new_seq`.
+    pub fn check_with(
+        execution: &Execution<Seq::Op, Seq::Ret>,
+        new_seq: &dyn Fn() -> Seq,
+        relaxation: Relaxation,
+    ) -> bool {
+        NonDeterministicChecker::check_with(execution, &|| Budgeted {
+            seq: new_seq(),
+            relaxation,
+            spent: 0,
+        })
+    }
+}
+
+/// A relaxed specification with the cost spent so far, seen as a non-deterministic one.
+struct Budgeted<Seq> {
+    seq: Seq,
+    relaxation: Relaxation,
+    spent: usize,
+}
+
+impl<Seq: SequentialSpec> SequentialSpec for Budgeted<Seq> {
+    type Op = Seq::Op;
+    type Ret = Seq::Ret;
+
+    fn exec(&mut self, op: Self::Op) -> Self::Ret {
+        self.seq.exec(op)
+    }
+
+    fn ret_matches(expected: &Self::Ret, actual: &Self::Ret) -> bool
+    where
+        Self::Ret: PartialEq,
+    {
+        Seq::ret_matches(expected, actual)
+    }
+}
+
+impl<Seq: RelaxedSequentialSpec> NonDeterministicSequentialSpec for Budgeted<Seq> {
+    fn exec_all(&self, op: Self::Op) -> Vec<(Self::Ret, Self)> {
+        (self.seq.exec_relaxed(op).into_iter())
+            .filter_map(|(ret, seq, cost)| {
+                let spent = self.spent + cost;
+                let fits = match self.relaxation {
+                    Relaxation::PerOperation(max_cost) => cost <= max_cost,
+                    Relaxation::Total(max_cost) => spent <= max_cost,
+                };
+                fits.then_some((
+                    ret,
+                    Budgeted {
+                        seq,
+                        relaxation: self.relaxation,
+                        spent,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+/// An operation on a [RelaxedQueue].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueueOp<T> {
+    /// Add the value to the tail.
+    Enqueue(T),
+    /// Take a value.
+    Dequeue,
+}
+
+/// The result of a [QueueOp].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueueRet<T> {
+    /// The value was added.
+    Enqueue,
+    /// The value that was taken, or [None] if the queue was empty.
+    Dequeue(Option<T>),
+}
+
+/// A FIFO queue whose dequeue may take any element at the cost of the number of elements before it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RelaxedQueue<T> {
+    items: VecDeque<T>,
+}
+
+impl<T> Default for RelaxedQueue<T> {
+    fn default() -> Self {
+        Self {
+            items: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> SequentialSpec for RelaxedQueue<T> {
+    type Op = QueueOp<T>;
+    type Ret = QueueRet<T>;
+
+    fn exec(&mut self, op: Self::Op) -> Self::Ret {
+        match op {
+            QueueOp::Enqueue(value) => {
+                self.items.push_back(value);
+                QueueRet::Enqueue
+            }
+            QueueOp::Dequeue => QueueRet::Dequeue(self.items.pop_front()),
+        }
+    }
+}
+
+impl<T: Clone> RelaxedSequentialSpec for RelaxedQueue<T> {
+    fn exec_relaxed(&self, op: Self::Op) -> Vec<(Self::Ret, Self, usize)> {
+        if !matches!(op, QueueOp::Dequeue) || self.items.is_empty() {
+            let mut next = self.clone();
+            return vec![(next.exec(op), next, 0)];
+        }
+        (0..self.items.len())
+            .map(|index| {
+                let mut next = self.clone();
+                let value = next.items.remove(index);
+                (QueueRet::Dequeue(value), next, index)
+            })
+            .collect()
+    }
+}
+
+impl<T: Arbitrary + Clone + 'static> Arbitrary for QueueOp<T> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            any::<T>().prop_map(QueueOp::Enqueue),
+            Just(QueueOp::Dequeue),
+        ]
+        .boxed()
+    }
+}
+
+/// An operation on a [RelaxedStack].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackOp<T> {
+    /// Put the value on the top.
+    Push(T),
+    /// Take a value.
+    Pop,
+}
+
+/// The result of a [StackOp].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackRet<T> {
+    /// The value was put.
+    Push,
+    /// The value that was taken, or [None] if the stack was empty.
+    Pop(Option<T>),
+}
+
+/// A stack whose pop may take any element at the cost of the number of elements above it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RelaxedStack<T> {
+    items: Vec<T>,
+}
+
+impl<T> Default for RelaxedStack<T> {
+    fn default() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<T> SequentialSpec for RelaxedStack<T> {
+    type Op = StackOp<T>;
+    type Ret = StackRet<T>;
+
+    fn exec(&mut self, op: Self::Op) -> Self::Ret {
+        match op {
+            StackOp::Push(value) => {
+                self.items.push(value);
+                StackRet::Push
+            }
+            StackOp::Pop => StackRet::Pop(self.items.pop()),
+        }
+    }
+}
+
+impl<T: Clone> RelaxedSequentialSpec for RelaxedStack<T> {
+    fn exec_relaxed(&self, op: Self::Op) -> Vec<(Self::Ret, Self, usize)> {
+        if !matches!(op, StackOp::Pop) || self.items.is_empty() {
+            let mut next = self.clone();
+            return vec![(next.exec(op), next, 0)];
+        }
+        (0..self.items.len())
+            .map(|depth| {
+                let mut next = self.clone();
+                let value = next.items.remove(self.items.len() - 1 - depth);
+                (StackRet::Pop(Some(value)), next, depth)
+            })
+            .collect()
+    }
+}
+
+impl<T: Arbitrary + Clone + 'static> Arbitrary for StackOp<T> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![any::<T>().prop_map(StackOp::Push), Just(StackOp::Pop)].boxed()
+    }
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` linearizes to its [relaxed](RelaxedSequentialSpec)
+    /// sequential specification within the `relaxation`.
+    ///
+    /// It returns an execution that doesn't if the test fails.
+    pub fn verify_relaxed<Conc>(
+        &self,
+        relaxation: Relaxation,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: RelaxedSequentialSpec + Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        // the search branches on every relaxed outcome and recurses too deep
+        // for the small stacks loom runs the model on, so it gets a thread of its own
+        let check = move |execution: &Execution<_, _>| {
+            let execution = execution.clone();
+            std::thread::spawn(move || RelaxedChecker::<Conc::Seq>::check(&execution, relaxation))
+                .join()
+                .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+        };
+
+        self.run_cases(&self.scenarios(), Scenario::clone, |scenario| {
+            check_scenario_with_loom_using::<Conc>(&self.loom, scenario, check)
+                .map_err(VerificationFailure::NonLinearizable)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution;
+
+    #[test]
+    fn per_operation_relaxation_bounds_each_operation() {
+        // the second dequeue takes the element behind the head twice
+        let execution = execution! {
+            init: [
+                QueueOp::Enqueue(1) => QueueRet::Enqueue,
+                QueueOp::Enqueue(2) => QueueRet::Enqueue,
+                QueueOp::Enqueue(3) => QueueRet::Enqueue,
+            ],
+            parallel: [
+                0 @ 0..1: QueueOp::Dequeue => QueueRet::Dequeue(Some(2)),
+                0 @ 2..3: QueueOp::Dequeue => QueueRet::Dequeue(Some(3)),
+            ],
+            post: [QueueOp::Dequeue => QueueRet::Dequeue(Some(1))],
+        };
+        let check = |relaxation| RelaxedChecker::<RelaxedQueue<i32>>::check(&execution, relaxation);
+
+        assert!(!check(Relaxation::PerOperation(0)));
+        assert!(check(Relaxation::PerOperation(1)));
+        assert!(!check(Relaxation::Total(1)));
+        assert!(check(Relaxation::Total(2)));
+    }
+
+    #[test]
+    fn relaxed_stack_pops_below_the_top() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..1: StackOp::Push(1) => StackRet::Push,
+                0 @ 2..3: StackOp::Push(2) => StackRet::Push,
+                1 @ 4..5: StackOp::Pop => StackRet::Pop(Some(1)),
+            ],
+        };
+
+        assert!(!RelaxedChecker::<RelaxedStack<i32>>::check(
+            &execution,
+            Relaxation::PerOperation(0)
+        ));
+        assert!(RelaxedChecker::<RelaxedStack<i32>>::check(
+            &execution,
+            Relaxation::PerOperation(1)
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+//! HTML reports of executions, e.g. to attach a counterexample to a bug report.
+//!
+//! The report is a standalone page without scripts: the init and post parts are listed in order,
+//! while the parallel part is drawn as a timeline with a row per thread,
+//! where each operation spans from its call to its return.
+
+use std::fmt::{Debug, Write};
+
+use crate::checker::Diagnosis;
+use crate::execution::*;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+ol { font-family: monospace; }
+.thread { display: flex; align-items: center; margin: 4px 0; }
+.thread-name { width: 7em; flex-shrink: 0; font-weight: bold; }
+.timeline { position: relative; flex-grow: 1; height: 2.5em; background: #f4f4f4; }
+.op { position: absolute; top: 0; bottom: 0; box-sizing: border-box; overflow: hidden; white-space: nowrap;
+      font-family: monospace; font-size: small; padding: 2px 4px; border: 1px solid #4a78b5; background: #cfe0f7; }
+.pending { border-style: dashed; background: #eeeeee; }
+.conflict { border-color: #b53a3a; background: #f7cfcf; font-weight: bold; }
+";
+
+impl<Op: Debug, Ret: Debug> Execution<Op, Ret> {
+    /// Renders the execution as a standalone HTML page with a timeline of the parallel part.
+    pub fn to_html(&self) -> String {
+        self.render_html(|_, _, _| false)
+    }
+
+    /// Renders the page highlighting the invocations for which `conflict` returns `true`.
+    /// The thread of the init and post parts is [None].
+    fn render_html(&self, conflict: impl Fn(Option<usize>, &Op, &Ret) -> bool) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<title>Execution</title>\n");
+        writeln!(html, "<style>\n{}</style>\n</head>\n<body>", STYLE).unwrap();
+
+        self.render_sequential_part(&mut html, "Init part", &self.init_part, &conflict);
+
+        html.push_str("<h2>Parallel part</h2>\n");
+        let end = self
+            .parallel_part
+            .iter()
+            .map(|inv| inv.return_timestamp)
+            .chain(
+                self.pending
+                    .iter()
+                    .map(|inv| (inv.cancel_timestamp).unwrap_or(inv.call_timestamp + 1)),
+            )
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let percent = |timestamp: Timestamp| timestamp as f64 * 100.0 / end as f64;
+
+        let num_threads = self
+            .parallel_part
+            .iter()
+            .map(|inv| inv.thread_id + 1)
+            .chain(self.pending.iter().map(|inv| inv.thread_id + 1))
+            .max()
+            .unwrap_or(0);
+        for thread_id in 0..num_threads {
+            writeln!(
+                html,
+                "<div class=\"thread\"><div class=\"thread-name\">Thread {}</div><div class=\"timeline\">",
+                thread_id
+            )
+            .unwrap();
+            for inv in self
+                .parallel_part
+                .iter()
+                .filter(|inv| inv.thread_id == thread_id)
+            {
+                let label = format!("{:?} : {:?}", inv.op, inv.ret);
+                let class = if conflict(Some(thread_id), &inv.op, &inv.ret) {
+                    "op conflict"
+                } else {
+                    "op"
+                };
+                write_op(
+                    &mut html,
+                    class,
+                    percent(inv.call_timestamp),
+                    percent(inv.return_timestamp - inv.call_timestamp),
+                    &format!("{}..{}", inv.call_timestamp, inv.return_timestamp),
+                    &label,
+                );
+            }
+            for inv in self.pending.iter().filter(|inv| inv.thread_id == thread_id) {
+                let (width, title) = match inv.cancel_timestamp {
+                    Some(cancel_timestamp) => (
+                        percent(cancel_timestamp - inv.call_timestamp),
+                        format!("{}..{} (cancelled)", inv.call_timestamp, cancel_timestamp),
+                    ),
+                    None => (
+                        100.0 - percent(inv.call_timestamp),
+                        format!("{}.. (pending)", inv.call_timestamp),
+                    ),
+                };
+                write_op(
+                    &mut html,
+                    "op pending",
+                    percent(inv.call_timestamp),
+                    width,
+                    &title,
+                    &format!("{:?}", inv.op),
+                );
+            }
+            html.push_str("</div></div>\n");
+        }
+
+        self.render_sequential_part(&mut html, "Post part", &self.post_part, &conflict);
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Lists the operations of the init or the post part.
+    fn render_sequential_part(
+        &self,
+        html: &mut String,
+        title: &str,
+        history: &History<Op, Ret>,
+        conflict: &impl Fn(Option<usize>, &Op, &Ret) -> bool,
+    ) {
+        writeln!(html, "<h2>{}</h2>\n<ol>", title).unwrap();
+        for inv in history.iter() {
+            let class = if conflict(None, &inv.op, &inv.ret) {
+                " class=\"conflict\""
+            } else {
+                ""
+            };
+            let label = escape(&format!("{:?} : {:?}", inv.op, inv.ret));
+            writeln!(html, "<li{}>{}</li>", class, label).unwrap();
+        }
+        html.push_str("</ol>\n");
+    }
+}
+
+impl<Op: Debug + PartialEq, Ret: Debug + PartialEq> Execution<Op, Ret> {
+    /// The same as [to_html](Execution::to_html) but highlights the operations rejected by the `diagnosis`
+    /// of the execution, as returned by [diagnose](crate::checker::LinearizabilityChecker::diagnose),
+    /// and lists the longest linearizable prefix.
+    pub fn to_html_highlighting(&self, diagnosis: &Diagnosis<Op, Ret>) -> String {
+        let mut html = self.render_html(|thread_id, op, ret| {
+            diagnosis.rejected.iter().any(|rejection| {
+                rejection.thread_id == thread_id && rejection.op == *op && rejection.actual == *ret
+            })
+        });
+
+        let mut prefix = String::from("<h2>Longest linearizable prefix</h2>\n<ol>\n");
+        for (op, ret) in diagnosis.prefix.iter() {
+            writeln!(
+                prefix,
+                "<li>{}</li>",
+                escape(&format!("{:?} : {:?}", op, ret))
+            )
+            .unwrap();
+        }
+        prefix.push_str("</ol>\n");
+        html.insert_str(html.rfind("</body>").unwrap(), &prefix);
+        html
+    }
+}
+
+/// Draws an operation on the timeline of its thread, with the position and the width in percents.
+fn write_op(html: &mut String, class: &str, left: f64, width: f64, title: &str, label: &str) {
+    writeln!(
+        html,
+        "<div class=\"{}\" style=\"left: {:.2}%; width: {:.2}%\" title=\"{}\">{}</div>",
+        class,
+        left,
+        width,
+        escape(title),
+        escape(label)
+    )
+    .unwrap();
+}
+
+/// Escapes the special HTML characters of the text.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checker::LinearizabilityChecker;
+    use crate::execution;
+    use crate::specs::{Register, RegisterOp, RegisterRet};
+
+    #[test]
+    fn highlights_rejected_operations() {
+        let execution = execution! {
+            parallel: [
+                0 @ 0..2: RegisterOp::Write(1) => RegisterRet::Write,
+                1 @ 3..4: RegisterOp::Read => RegisterRet::Read(0),
+            ],
+        };
+        let diagnosis = LinearizabilityChecker::<Register<u8>>::diagnose(&execution).unwrap();
+
+        let html = execution.to_html_highlighting(&diagnosis);
+        assert!(html.contains(
+            "<div class=\"op\" style=\"left: 0.00%; width: 50.00%\" title=\"0..2\">Write(1) : Write</div>"
+        ));
+        assert!(html.contains(
+            "<div class=\"op conflict\" style=\"left: 75.00%; width: 25.00%\" title=\"3..4\">Read : Read(0)</div>"
+        ));
+        assert!(html.contains("<li>Write(1) : Write</li>"));
+    }
+
+    #[test]
+    fn escapes_operations() {
+        let execution = execution! {
+            init: ["<push>" => ()],
+            parallel: [],
+        };
+
+        let html = execution.to_html();
+        assert!(html.contains("<li>&quot;&lt;push&gt;&quot; : ()</li>"));
+        assert!(!html.contains("conflict\""));
+    }
+}
@@ -0,0 +1,168 @@
+//! Reproducing a failing case, e.g. a flaky failure on CI.
+//!
+//! The token of a failure, i.e. the [token](crate::progress::VerifyReport::token) of the report
+//! which [verify_or_panic](crate::Lincheck::verify_or_panic) also puts into its message, encodes the seed
+//! of the [proptest](crate::Lincheck::proptest) runner and the number of the failing case among the generated scenarios.
+//! Setting it as [repro](crate::Lincheck::repro) reruns the failing case with the same verification mode,
+//! e.g. [verify_with](crate::Lincheck::verify_with) or [verify_with_config](crate::Lincheck::verify_with_config),
+//! so the scenarios are generated and checked like in the failing run:
+//! ```no_run
+//! # use lincheck::Lincheck;
+//! let lincheck = Lincheck {
+//!     repro: Some("00000000deadbeef-42".into()),
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! With the `serde` feature, [verify_replayable](crate::Lincheck::verify_replayable) also embeds the shrunk scenario
+//! into the token in RON, so the failure is replayed as is even after the generation has changed.
+
+use proptest::prelude::*;
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+
+use crate::error::VerificationFailure;
+#[cfg(feature = "serde")]
+use crate::progress::{ShrinkStats, VerifyReport};
+#[cfg(feature = "serde")]
+use crate::scenario::{Scenario, ScenarioConfig};
+use crate::spec::*;
+#[cfg(feature = "serde")]
+use crate::Constructors;
+use crate::Lincheck;
+
+/// The seed of the runner, the number of the failing case and optionally the shrunk scenario in RON,
+/// printed as `{seed:016x}-{case}` or `{seed:016x}-{case}-{scenario}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ReproToken {
+    pub(crate) seed: u64,
+    pub(crate) case: usize,
+    pub(crate) scenario: Option<String>,
+}
+
+impl ReproToken {
+    pub(crate) fn parse(token: &str) -> Option<Self> {
+        let mut parts = token.trim().splitn(3, '-');
+        Some(ReproToken {
+            seed: u64::from_str_radix(parts.next()?, 16).ok()?,
+            case: parts.next()?.parse().ok()?,
+            scenario: parts.next().map(str::to_string),
+        })
+    }
+}
+
+impl Lincheck {
+    /// Reruns the failing case of [verify](Lincheck::verify) given by the [token](crate::progress::VerifyReport::token) of the failure.
+    /// It is the same as [verify](Lincheck::verify) with [repro](Lincheck::repro) set to `token`.
+    ///
+    /// The configuration must be the same as in the failing run, except for the seed of the [proptest](Lincheck::proptest) runner.
+    /// The scenarios before the failing case are generated but not checked, and the failing one is shrunk as usual.
+    /// The scenario embedded by [verify_replayable](Lincheck::verify_replayable) is ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token is malformed.
+    pub fn verify_with_seed<Conc>(
+        &self,
+        token: &str,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        Lincheck {
+            repro: Some(token.to_string()),
+            ..self.clone()
+        }
+        .verify::<Conc>()
+    }
+
+    /// The same as [verify_report](Lincheck::verify_report) but embeds the shrunk scenario into the [token](VerifyReport::token).
+    ///
+    /// If [repro](Lincheck::repro) is set to such a token, its scenario is checked without generating any,
+    /// otherwise the failing case is rerun like with [verify](Lincheck::verify).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token or its scenario is malformed.
+    #[cfg(feature = "serde")]
+    pub fn verify_replayable<Conc>(&self) -> VerifyReport<ConcOp<Conc>, ConcRet<Conc>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send
+            + Sync
+            + Clone
+            + Arbitrary
+            + Debug
+            + UnwindSafe
+            + serde::Serialize
+            + serde::de::DeserializeOwned
+            + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let embedded = self.repro.as_deref().and_then(|token| {
+            ReproToken::parse(token)
+                .unwrap_or_else(|| panic!("invalid reproducibility token {:?}", token))
+                .scenario
+        });
+        if let Some(scenario) = embedded {
+            let scenario = Scenario::from_ron(&scenario).unwrap_or_else(|error| {
+                panic!("invalid scenario in the reproducibility token: {}", error)
+            });
+            let failure = self.verify_scenarios::<Conc>([scenario.clone()]).err();
+            return VerifyReport {
+                scenarios_checked: 1,
+                failing_scenario: failure.is_some().then_some(scenario),
+                failure,
+                shrink: ShrinkStats::default(),
+                token: self.repro.clone(),
+            };
+        }
+
+        let mut report = self.verify_reproducibly(
+            None,
+            ScenarioConfig::default(),
+            &Constructors::new(Conc::default, Conc::Seq::default),
+            1,
+        );
+        if let (Some(token), Some(scenario)) = (&report.token, &report.failing_scenario) {
+            let token = ReproToken {
+                scenario: ron::to_string(scenario).ok(),
+                ..ReproToken::parse(token).expect("the token of the run is valid")
+            };
+            report.token = Some(token.to_string());
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_roundtrip() {
+        let token = ReproToken {
+            seed: 0xdead_beef,
+            case: 42,
+            scenario: None,
+        };
+        assert_eq!(token.to_string(), "00000000deadbeef-42");
+        assert_eq!(ReproToken::parse(&token.to_string()), Some(token));
+        assert_eq!(ReproToken::parse("deadbeef"), None);
+        assert_eq!(ReproToken::parse("xyz-1"), None);
+    }
+
+    #[test]
+    fn token_with_scenario_roundtrip() {
+        let token = ReproToken {
+            seed: 7,
+            case: 3,
+            scenario: Some("(init_part:[-1],parallel_part:[],post_part:[])".to_string()),
+        };
+        assert_eq!(ReproToken::parse(&token.to_string()), Some(token));
+    }
+}
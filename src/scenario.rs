@@ -1,18 +1,31 @@
 //! The [Scenario] and how to execute and check it.
 
-use loom::thread;
+use loom::sync::atomic::{AtomicBool, Ordering};
+use loom::thread::{self, JoinHandle};
+#[cfg(feature = "proptest")]
+use proptest::strategy::BoxedStrategy;
+use std::collections::hash_map::{Entry, HashMap};
+use std::collections::HashSet;
 use std::fmt::Debug;
-use std::panic::{self, UnwindSafe};
+use std::hash::Hash;
+use std::panic::{self, AssertUnwindSafe, UnwindSafe};
 use std::rc::Rc;
+use std::sync::{Arc, Barrier, Mutex};
 
+use crate::backend::{ExecutionBackend, LoomBackend};
 use crate::checker::*;
 use crate::execution::*;
 use crate::recorder::{self, *};
 use crate::spec::*;
+use crate::LoomConfig;
 
 /// A scenario tells which operations to run in which order.
 /// It consists of three parts: [init_part](Scenario::init_part), [parallel_part](Scenario::parallel_part) and [post_part](Scenario::post_part).
+///
+/// With the `serde` feature, a scenario can be saved with [to_ron](Scenario::to_ron), e.g. as a CI artifact,
+/// and loaded back with [from_ron](Scenario::from_ron) to [replay](crate::Lincheck::replay) it.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Scenario<Op> {
     /// The initial part of the scenario, which is executed sequentially before the parallel part.
     pub init_part: Vec<Op>,
@@ -25,43 +38,400 @@ pub struct Scenario<Op> {
     pub post_part: Vec<Op>,
 }
 
-/// Executes the given scenario and checks the resulting execution for linearizability inside [loom] model-checker.
+impl<Op> Scenario<Op> {
+    /// The total number of operations in all the parts.
+    pub fn num_ops(&self) -> usize {
+        self.init_part.len()
+            + self.parallel_part.iter().map(Vec::len).sum::<usize>()
+            + self.post_part.len()
+    }
+
+    /// Maps every operation of the scenario with `f`, keeping the parts and the threads.
+    #[cfg(feature = "proptest")]
+    pub(crate) fn map<Op2>(&self, mut f: impl FnMut(&Op) -> Op2) -> Scenario<Op2> {
+        let mut map_ops = |ops: &[Op]| ops.iter().map(&mut f).collect::<Vec<_>>();
+        let init_part = map_ops(&self.init_part);
+        let parallel_part = self.parallel_part.iter().map(|ops| map_ops(ops)).collect();
+        let post_part = map_ops(&self.post_part);
+        Scenario {
+            init_part,
+            parallel_part,
+            post_part,
+        }
+    }
+
+    /// Starts writing a scenario by hand, e.g. to pin a known-bad interleaving as a regression test.
+    pub fn builder() -> ScenarioBuilder<Op> {
+        ScenarioBuilder::new()
+    }
+}
+
+/// Writes a [Scenario] by hand instead of generating it.
 ///
-/// It works by panicking inside using the failing execution as the message and catching the panic outside.
-/// This is the only way to return a value from loom model-checker.
+/// The scenario can then be checked with [check_scenario](crate::Lincheck::check_scenario):
+/// ```
+/// use lincheck::scenario::ScenarioBuilder;
+///
+/// let scenario = ScenarioBuilder::new()
+///     .init(["push 1"])
+///     .thread(["pop", "push 2"])
+///     .thread(["pop"])
+///     .post(["pop"])
+///     .build();
+/// assert_eq!(scenario.parallel_part.len(), 2);
+/// assert_eq!(scenario.num_ops(), 5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScenarioBuilder<Op> {
+    scenario: Scenario<Op>,
+}
+
+impl<Op> Default for ScenarioBuilder<Op> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Op> ScenarioBuilder<Op> {
+    /// Starts with an empty scenario.
+    pub fn new() -> Self {
+        Self {
+            scenario: Scenario {
+                init_part: Vec::new(),
+                parallel_part: Vec::new(),
+                post_part: Vec::new(),
+            },
+        }
+    }
+
+    /// Appends the operations to the init part.
+    pub fn init(mut self, ops: impl IntoIterator<Item = Op>) -> Self {
+        self.scenario.init_part.extend(ops);
+        self
+    }
+
+    /// Adds a thread running the operations to the parallel part.
+    pub fn thread(mut self, ops: impl IntoIterator<Item = Op>) -> Self {
+        self.scenario.parallel_part.push(ops.into_iter().collect());
+        self
+    }
+
+    /// Appends the operations to the post part.
+    pub fn post(mut self, ops: impl IntoIterator<Item = Op>) -> Self {
+        self.scenario.post_part.extend(ops);
+        self
+    }
+
+    /// Finishes the scenario.
+    pub fn build(self) -> Scenario<Op> {
+        self.scenario
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Op: serde::Serialize> Scenario<Op> {
+    /// Serializes the scenario to a RON string.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Op: serde::de::DeserializeOwned> Scenario<Op> {
+    /// Parses a scenario from a RON string.
+    pub fn from_ron(s: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(s)
+    }
+}
+
+/// The generation parameters of the operations of each part of a [Scenario].
+///
+/// For example, the init part may only insert, while the post part only reads.
+/// The parameters are used to generate the [Arbitrary](proptest::arbitrary::Arbitrary) operations,
+/// so the shrunk scenarios respect them too.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PartParams<P> {
+    /// The parameters of the operations of the init part.
+    pub init_part: P,
+    /// The parameters of the operations of the parallel part.
+    pub parallel_part: P,
+    /// The parameters of the operations of the post part.
+    pub post_part: P,
+}
+
+/// The strategies of the operations of individual threads of the parallel part, for asymmetric workloads.
+///
+/// For example, the first thread may only push, while the second one only pops.
+/// The `i`-th thread of the parallel part generates its operations with the `i`-th strategy.
+/// The threads without a strategy use the parameters of the parallel part from [PartParams].
+/// The number of threads is still chosen by the [Lincheck](crate::Lincheck) configuration,
+/// so some of the strategies may not be used in a scenario with fewer threads.
+///
+/// The operations may also be restricted to some parts of the scenario with [op_class](ScenarioConfig::op_class),
+/// e.g. `close` only in the post part. The generated operations of the other classes are rejected,
+/// and the shrunk scenarios respect the classes as well.
+#[cfg(feature = "proptest")]
+#[derive(Clone, Debug)]
+pub struct ScenarioConfig<Op> {
+    /// The strategy of the operations of each thread.
+    pub per_thread_strategies: Vec<BoxedStrategy<Op>>,
+    /// The class of each operation. Every operation may be placed anywhere if it is [None].
+    pub op_class: Option<fn(&Op) -> OpClass>,
+}
+
+#[cfg(feature = "proptest")]
+impl<Op> Default for ScenarioConfig<Op> {
+    fn default() -> Self {
+        Self {
+            per_thread_strategies: Vec::new(),
+            op_class: None,
+        }
+    }
+}
+
+/// The parts of a [Scenario] an operation may be placed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OpClass {
+    /// Any part.
+    #[default]
+    Any,
+    /// Only the init part, e.g. `reserve`.
+    InitOnly,
+    /// Only the parallel part.
+    ParallelOnly,
+    /// Only the post part, e.g. `close`.
+    PostOnly,
+    /// The init or the post part, e.g. an operation that needs [exec_mut](ConcurrentSpec::exec_mut).
+    SequentialOnly,
+}
+
+impl OpClass {
+    /// Whether the operation may be placed in the init part.
+    pub fn allowed_in_init(self) -> bool {
+        matches!(
+            self,
+            OpClass::Any | OpClass::InitOnly | OpClass::SequentialOnly
+        )
+    }
+
+    /// Whether the operation may be placed in the parallel part.
+    pub fn allowed_in_parallel(self) -> bool {
+        matches!(self, OpClass::Any | OpClass::ParallelOnly)
+    }
+
+    /// Whether the operation may be placed in the post part.
+    pub fn allowed_in_post(self) -> bool {
+        matches!(
+            self,
+            OpClass::Any | OpClass::PostOnly | OpClass::SequentialOnly
+        )
+    }
+}
+
+/// A part of a [Scenario].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScenarioPart {
+    /// The [init_part](Scenario::init_part).
+    Init,
+    /// The [parallel_part](Scenario::parallel_part).
+    Parallel,
+    /// The [post_part](Scenario::post_part).
+    Post,
+}
+
+/// Executes the given scenario and checks the resulting execution for linearizability inside [loom] model-checker.
 pub fn check_scenario_with_loom<Conc>(
     scenario: Scenario<ConcOp<Conc>>,
 ) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
 where
-    Conc: ConcurrentSpec + Send + Sync + 'static,
-    Conc::Seq: Send + Sync + 'static,
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
     ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
     ConcRet<Conc>: PartialEq + Clone + Debug + Send,
 {
-    // temporarily disable the panic hook to avoid printing the panic message
-    let old_hook = panic::take_hook();
-    panic::set_hook(Box::new(|_| {}));
+    check_scenario_with_loom_using::<Conc>(&LoomConfig::default(), scenario, |execution| {
+        LinearizabilityChecker::<Conc::Seq>::check(execution)
+    })
+}
 
-    // catch the panic and return the panic payload
-    let result = panic::catch_unwind(|| {
+/// Same as [check_scenario_with_loom] but configures loom with `loom`
+/// and checks the executions with `check` instead of [LinearizabilityChecker].
+pub(crate) fn check_scenario_with_loom_using<Conc>(
+    loom: &LoomConfig,
+    scenario: Scenario<ConcOp<Conc>>,
+    check: impl Fn(&Execution<ConcOp<Conc>, ConcRet<Conc>>) -> bool + Send + Sync + 'static,
+) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Clone + Debug + Send,
+{
+    let backend = LoomBackend {
+        config: loom.clone(),
+    };
+    backend.find_failure::<Conc>(scenario, check)
+}
+
+/// Executes the given scenario on `num_instances` independent instances of the data structure inside [loom] model-checker
+/// and checks the history of each instance for linearizability separately.
+///
+/// The init and post parts are executed on every instance.
+/// The operations of the `i`-th thread of the parallel part are executed on the instance `i % num_instances`.
+/// This helps catching bugs caused by state accidentally shared between the instances, e.g. statics.
+///
+/// It returns the history of the first non-linearizable instance.
+pub fn check_multi_instance_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    num_instances: usize,
+) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Clone + Debug + Send,
+{
+    find_failure_with_loom(&LoomConfig::default(), move || {
+        execute_multi_instance_scenario_with_loom::<Conc>(scenario.clone(), num_instances)
+            .into_iter()
+            .find(|execution| !LinearizabilityChecker::<Conc::Seq>::check(execution))
+    })
+}
+
+/// Runs `f` inside [loom] model-checker configured with `loom` and returns the first failure it reports.
+///
+/// The failure is stored in a mutex shared with the model. Then the exploration is stopped by unwinding
+/// with [resume_unwind](panic::resume_unwind), which doesn't invoke the panic hook, so nothing is printed
+/// and the hook doesn't have to be replaced.
+pub(crate) fn find_failure_with_loom<F>(
+    loom: &LoomConfig,
+    f: impl Fn() -> Option<F> + Send + Sync + 'static,
+) -> Result<(), F>
+where
+    F: Send + 'static,
+{
+    let failure = Arc::new(Mutex::new(None));
+
+    let found = failure.clone();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        loom.builder().check(move || {
+            if let Some(execution) = f() {
+                *found.lock().unwrap() = Some(execution);
+                panic::resume_unwind(Box::new(FailureFound));
+            }
+        });
+    }));
+
+    if let Err(payload) = result {
+        if !payload.is::<FailureFound>() {
+            // the model itself failed, e.g. the data structure panicked
+            panic::resume_unwind(payload);
+        }
+    }
+
+    let failure = failure.lock().unwrap().take();
+    match failure {
+        Some(failure) => Err(failure),
+        None => Ok(()),
+    }
+}
+
+/// The payload stopping the exploration of [find_failure_with_loom].
+struct FailureFound;
+
+/// Executes the given scenario inside [loom] model-checker without checking anything
+/// and returns all the distinct executions observed across the interleavings in the order of discovery.
+///
+/// It is useful to see what behaviors the data structure actually exhibits before writing its specification.
+pub fn explore_scenario<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+) -> Vec<Execution<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Hash + Eq + 'static,
+    ConcRet<Conc>: Send + Clone + Hash + Eq + 'static,
+{
+    type Explored<Op, Ret> = (HashSet<Execution<Op, Ret>>, Vec<Execution<Op, Ret>>);
+
+    let explored: Arc<Mutex<Explored<_, _>>> = Arc::default();
+
+    {
+        let explored = explored.clone();
         loom::model(move || {
-            let execution = execute_scenario_with_loom::<Conc>(scenario.clone());
-            if !LinearizabilityChecker::<Conc::Seq>::check(&execution) {
-                // panic with the failing execution as the payload
-                panic::panic_any(execution);
+            let mut execution = execute_scenario_with_loom::<Conc>(scenario.clone());
+            // threads are merged into the parallel part in the order they finish,
+            // so the order is normalized for the deduplication to work
+            execution
+                .parallel_part
+                .sort_by_key(|inv| inv.call_timestamp);
+
+            let (seen, executions) = &mut *explored.lock().unwrap();
+            if seen.insert(execution.clone()) {
+                executions.push(execution);
             }
         });
-    });
+    }
 
-    // restore the panic hook
-    panic::set_hook(old_hook);
+    let (_, executions) = std::mem::take(&mut *explored.lock().unwrap());
+    executions
+}
 
-    result.map_err(|payload| {
-        // recover the failing execution from the panic payload
-        *payload
-            .downcast::<Execution<ConcOp<Conc>, ConcRet<Conc>>>()
-            .unwrap_or_else(|_| panic!("loom::model panicked with unknown payload"))
-    })
+/// A distinct vector of return values observed when executing a scenario.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome<Ret> {
+    /// The return values in the order of the operations in the scenario:
+    /// the init part, then the parallel part thread by thread, then the post part.
+    pub returns: Vec<Ret>,
+    /// Whether every observed execution with these return values is linearizable.
+    pub linearizable: bool,
+}
+
+/// Executes the given scenario inside [loom] model-checker
+/// and returns all the distinct [Outcome]s observed across the interleavings in the order of discovery.
+///
+/// It is the quickest way to notice that the sequential specification is too strict or too loose.
+pub fn enumerate_outcomes<Conc>(scenario: Scenario<ConcOp<Conc>>) -> Vec<Outcome<ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default,
+    ConcOp<Conc>: Send + Sync + Clone + Hash + Eq + 'static,
+    ConcRet<Conc>: Send + Clone + Hash + Eq + 'static,
+{
+    let mut outcomes: Vec<Outcome<_>> = Vec::new();
+    let mut outcome_ids: HashMap<_, usize> = HashMap::new();
+
+    for execution in explore_scenario::<Conc>(scenario) {
+        let linearizable = LinearizabilityChecker::<Conc::Seq>::check(&execution);
+
+        match outcome_ids.entry(returns_in_scenario_order(&execution)) {
+            Entry::Occupied(entry) => outcomes[*entry.get()].linearizable &= linearizable,
+            Entry::Vacant(entry) => {
+                outcomes.push(Outcome {
+                    returns: entry.key().clone(),
+                    linearizable,
+                });
+                entry.insert(outcomes.len() - 1);
+            }
+        }
+    }
+
+    outcomes
+}
+
+fn returns_in_scenario_order<Op, Ret: Clone>(execution: &Execution<Op, Ret>) -> Vec<Ret> {
+    let parallel_part = &execution.parallel_part;
+
+    let init_returns = execution.init_part.iter().map(|inv| &inv.ret);
+    let parallel_returns = (0..parallel_part.num_threads())
+        .flat_map(|thread_id| parallel_part.thread(thread_id))
+        .map(|inv| &inv.ret);
+    let post_returns = execution.post_part.iter().map(|inv| &inv.ret);
+
+    init_returns
+        .chain(parallel_returns)
+        .chain(post_returns)
+        .cloned()
+        .collect()
 }
 
 /// Executes the given scenario with [loom] mock threads and returns the resulting execution.
@@ -69,31 +439,134 @@ pub fn execute_scenario_with_loom<Conc>(
     scenario: Scenario<ConcOp<Conc>>,
 ) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
 where
-    Conc: ConcurrentSpec + Send + Sync + 'static,
-    ConcOp<Conc>: Send + Sync + Clone + 'static,
-    ConcRet<Conc>: PartialEq,
+    Conc: ConcurrentSpec + Default + Sync + 'static,
+    ConcOp<Conc>: Send + Clone,
 {
-    let conc = Rc::new(Conc::default());
+    // a single instance owned by the harness, so the init and post parts use exec_mut
+    let mut executions =
+        execute_multi_instance_scenario_with_loom_on(vec![Conc::default()], scenario);
+    executions.pop().unwrap()
+}
 
-    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+/// Executes the given scenario on `num_instances` independent instances of the data structure with [loom] mock threads
+/// and returns the resulting execution of each instance.
+///
+/// See [check_multi_instance_scenario_with_loom] for how the operations are distributed between the instances.
+pub fn execute_multi_instance_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    num_instances: usize,
+) -> Vec<Execution<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Sync + 'static,
+    ConcOp<Conc>: Send + Clone,
+{
+    assert!(num_instances > 0, "there must be at least one instance");
+
+    let instances = (0..num_instances).map(|_| Conc::default()).collect();
+    execute_multi_instance_scenario_with_loom_on(instances, scenario)
+}
+
+/// Same as [execute_multi_instance_scenario_with_loom] but executes the scenario on the given instances.
+/// The init and post parts are executed with [exec_mut](ConcurrentSpec::exec_mut).
+pub(crate) fn execute_multi_instance_scenario_with_loom_on<Conc>(
+    instances: Vec<Conc>,
+    scenario: Scenario<ConcOp<Conc>>,
+) -> Vec<Execution<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Sync + 'static,
+    ConcOp<Conc>: Send + Clone,
+{
+    execute_multi_instance_scenario_validating_on(instances, scenario).0
+}
+
+/// An instance found in an invalid state by [validate](ConcurrentSpec::validate).
+pub(crate) struct InvalidInstance {
+    pub(crate) instance: usize,
+    pub(crate) after: ScenarioPart,
+    pub(crate) message: String,
+}
+
+/// Validates every instance, unless one was already found invalid.
+fn validate_instances<Conc: ConcurrentSpec>(
+    instances: &[Conc],
+    after: ScenarioPart,
+    invalid: &mut Option<InvalidInstance>,
+) {
+    if invalid.is_some() {
+        return;
+    }
+    *invalid = instances
+        .iter()
+        .enumerate()
+        .find_map(|(instance, conc)| conc.validate().err().map(|message| (instance, message)))
+        .map(|(instance, message)| InvalidInstance {
+            instance,
+            after,
+            message,
+        });
+}
+
+/// Same as [execute_multi_instance_scenario_with_loom_on] but also [validates](ConcurrentSpec::validate) the instances
+/// and returns the first invalid one. The execution goes on after an invalid state, so every execution is complete.
+#[allow(clippy::type_complexity)]
+pub(crate) fn execute_multi_instance_scenario_validating_on<Conc>(
+    mut instances: Vec<Conc>,
+    scenario: Scenario<ConcOp<Conc>>,
+) -> (
+    Vec<Execution<ConcOp<Conc>, ConcRet<Conc>>>,
+    Option<InvalidInstance>,
+)
+where
+    Conc: ConcurrentSpec + Sync + 'static,
+    ConcOp<Conc>: Send + Clone,
+{
+    let num_instances = instances.len();
+    let mut invalid = None;
 
     // init part
-    for op in scenario.init_part {
-        recorder.record(op.clone(), || conc.exec(op));
+    let recorders = instances.iter_mut().map(|conc| {
+        let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+        for op in scenario.init_part.iter() {
+            recorder.record(op.clone(), || conc.exec_mut(op.clone()));
+        }
+        recorder
+    });
+    let recorders: Vec<_> = recorders.collect();
+    if Conc::VALIDATE_BETWEEN_PARTS {
+        validate_instances(&instances, ScenarioPart::Init, &mut invalid);
     }
 
-    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
-    let recorder = Rc::new(recorder.record_parallel_part_with_capacity(total_parallel_ops));
+    let recorders: Vec<_> = recorders
+        .into_iter()
+        .enumerate()
+        .map(|(instance, recorder)| {
+            let instance_parallel_ops = scenario
+                .parallel_part
+                .iter()
+                .skip(instance)
+                .step_by(num_instances)
+                .map(Vec::len)
+                .sum();
+            Rc::new(recorder.record_parallel_part_with_capacity(instance_parallel_ops))
+        })
+        .collect();
+
+    let instances: Vec<_> = instances.into_iter().map(Rc::new).collect();
+    let stop_maintenance = Rc::new(AtomicBool::new(false));
+    let maintenance: Vec<_> = instances
+        .iter()
+        .map(|conc| spawn_maintenance(conc, &stop_maintenance))
+        .collect();
 
     // parallel part
     let handles: Vec<_> = scenario
         .parallel_part
         .into_iter()
-        .map(|thread_ops| {
-            let conc = conc.clone();
-            let recorder = recorder.clone();
+        .enumerate()
+        .map(|(thread_id, thread_ops)| {
+            let conc = instances[thread_id % num_instances].clone();
+            let recorder = recorders[thread_id % num_instances].clone();
 
-            // spawning threads creates a happens-before relation between the threads and the main thread
             thread::spawn(move || {
                 let mut recorder = recorder.record_thread_with_capacity(thread_ops.len());
                 for op in thread_ops {
@@ -103,17 +576,447 @@ where
         })
         .collect();
 
-    // wait for all threads to finish before executing the post part
     for handle in handles {
         handle.join().unwrap();
     }
-    // joining threads creates a happens-before relation between the threads and the main thread
+    for handle in maintenance {
+        stop_and_join_maintenance(handle, &stop_maintenance);
+    }
+    let mut instances: Vec<_> = instances.into_iter().map(unshare).collect();
+    if Conc::VALIDATE_BETWEEN_PARTS {
+        validate_instances(&instances, ScenarioPart::Parallel, &mut invalid);
+    }
+
+    // post part
+    let executions = instances
+        .iter_mut()
+        .zip(recorders)
+        .map(|(conc, recorder)| {
+            let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+            for op in scenario.post_part.iter() {
+                recorder.record(op.clone(), || conc.exec_mut(op.clone()));
+            }
+            recorder.finish()
+        })
+        .collect();
+    validate_instances(&instances, ScenarioPart::Post, &mut invalid);
+
+    (executions, invalid)
+}
+
+/// Executes the given scenario on native threads and returns the resulting execution.
+///
+/// Unlike [execute_scenario_with_loom], it observes a single interleaving chosen by the OS scheduler
+/// and the data structure must not use [loom] primitives.
+/// The [maintenance](ConcurrentSpec::run_maintenance) runs on its own native thread during the parallel part.
+pub fn execute_scenario_with_threads<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Default + Sync,
+    ConcOp<Conc>: Send + Clone,
+    ConcRet<Conc>: Send,
+{
+    execute_scenario_with_threads_on(&Conc::default(), scenario)
+}
+
+/// Same as [execute_scenario_with_threads] but executes the scenario on the given instance of the data structure,
+/// which may borrow state of the caller.
+pub fn execute_scenario_with_threads_on<Conc>(
+    conc: &Conc,
+    scenario: Scenario<ConcOp<Conc>>,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Sync,
+    ConcOp<Conc>: Send + Clone,
+    ConcRet<Conc>: Send,
+{
+    execute_scenario_with_threads_using(conc, scenario, |conc: &Conc, op| conc.exec(op))
+}
+
+/// Same as [execute_scenario_with_threads_on] but executes the operations with `exec`.
+pub(crate) fn execute_scenario_with_threads_using<Conc, Ret>(
+    conc: &Conc,
+    scenario: Scenario<ConcOp<Conc>>,
+    exec: impl Fn(&Conc, ConcOp<Conc>) -> Ret + Sync,
+) -> Execution<ConcOp<Conc>, Ret>
+where
+    Conc: ConcurrentSpec + Sync,
+    ConcOp<Conc>: Send + Clone,
+    Ret: Send,
+{
+    use std::sync::atomic::AtomicBool; // not the loom one, the threads are native
+
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+
+    // init part
+    for op in scenario.init_part {
+        recorder.record(op.clone(), || exec(conc, op));
+    }
+
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
+    let recorder = recorder.record_parallel_part_with_capacity(total_parallel_ops);
+
+    // parallel part
+    // the threads wait on the barrier to start executing the operations at the same time
+    let barrier = Barrier::new(scenario.parallel_part.len());
+    let stop_maintenance = AtomicBool::new(false);
+    std::thread::scope(|s| {
+        let maintenance = (Conc::MAINTENANCE_PASSES > 0).then(|| {
+            s.spawn(|| {
+                for _ in 0..Conc::MAINTENANCE_PASSES {
+                    if stop_maintenance.load(Ordering::Acquire) {
+                        break;
+                    }
+                    conc.run_maintenance();
+                }
+            })
+        });
+
+        let handles: Vec<_> = scenario
+            .parallel_part
+            .into_iter()
+            .map(|thread_ops| {
+                let (exec, recorder, barrier) = (&exec, &recorder, &barrier);
+                s.spawn(move || {
+                    let mut recorder = recorder.record_thread_with_capacity(thread_ops.len());
+                    barrier.wait();
+                    for op in thread_ops {
+                        recorder.record(op.clone(), || exec(conc, op));
+                    }
+                })
+            })
+            .collect();
+
+        let results: Vec<_> = handles.into_iter().map(|handle| handle.join()).collect();
+        if let Some(handle) = maintenance {
+            stop_maintenance.store(true, Ordering::Release);
+            if let Err(payload) = handle.join() {
+                panic::resume_unwind(payload);
+            }
+        }
+        for result in results {
+            if let Err(payload) = result {
+                panic::resume_unwind(payload);
+            }
+        }
+    });
 
     // post part
     let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
     for op in scenario.post_part {
-        recorder.record(op.clone(), || conc.exec(op));
+        recorder.record(op.clone(), || exec(conc, op));
+    }
+
+    recorder.finish()
+}
+
+/// Spawns the thread running the maintenance of `conc` until `stop` is set
+/// if the data structure [has one](ConcurrentSpec::MAINTENANCE_PASSES).
+pub(crate) fn spawn_maintenance<Conc: ConcurrentSpec + 'static>(
+    conc: &Rc<Conc>,
+    stop: &Rc<AtomicBool>,
+) -> Option<JoinHandle<()>> {
+    if Conc::MAINTENANCE_PASSES == 0 {
+        return None;
+    }
+
+    let (conc, stop) = (conc.clone(), stop.clone());
+    Some(thread::spawn(move || {
+        for _ in 0..Conc::MAINTENANCE_PASSES {
+            if stop.load(Ordering::Acquire) {
+                break;
+            }
+            conc.run_maintenance();
+        }
+    }))
+}
+
+pub(crate) fn stop_and_join_maintenance(handle: Option<JoinHandle<()>>, stop: &AtomicBool) {
+    if let Some(handle) = handle {
+        stop.store(true, Ordering::Release);
+        handle.join().unwrap();
+    }
+}
+
+/// Takes back the value shared with the [loom] threads once they are joined and have dropped their clones.
+pub(crate) fn unshare<T>(shared: Rc<T>) -> T {
+    Rc::try_unwrap(shared).unwrap_or_else(|_| panic!("the threads sharing the value are joined"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Op {
+        Write,
+        Read,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Ret {
+        Write,
+        Read(bool),
+    }
+
+    #[derive(Default)]
+    struct SequentialFlag {
+        flag: bool,
+    }
+
+    impl SequentialSpec for SequentialFlag {
+        type Op = Op;
+        type Ret = Ret;
+
+        fn exec(&mut self, op: Op) -> Ret {
+            match op {
+                Op::Write => {
+                    self.flag = true;
+                    Ret::Write
+                }
+                Op::Read => Ret::Read(self.flag),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct AtomicFlag {
+        flag: AtomicBool,
+    }
+
+    impl ConcurrentSpec for AtomicFlag {
+        type Seq = SequentialFlag;
+
+        fn exec(&self, op: Op) -> Ret {
+            match op {
+                Op::Write => {
+                    self.flag.store(true, Ordering::Relaxed);
+                    Ret::Write
+                }
+                Op::Read => Ret::Read(self.flag.load(Ordering::Relaxed)),
+            }
+        }
+    }
+
+    #[test]
+    fn explore_finds_both_read_outcomes() {
+        let scenario = Scenario {
+            init_part: vec![],
+            parallel_part: vec![vec![Op::Write], vec![Op::Read]],
+            post_part: vec![],
+        };
+
+        let executions = explore_scenario::<AtomicFlag>(scenario);
+
+        let reads: HashSet<_> = executions
+            .iter()
+            .flat_map(|execution| execution.parallel_part.iter())
+            .filter(|inv| inv.op == Op::Read)
+            .map(|inv| inv.ret)
+            .collect();
+        assert_eq!(reads, HashSet::from([Ret::Read(false), Ret::Read(true)]));
+
+        let distinct: HashSet<_> = executions.iter().collect();
+        assert_eq!(distinct.len(), executions.len());
+    }
+
+    #[test]
+    fn outcomes_are_deduplicated_return_vectors() {
+        let scenario = Scenario {
+            init_part: vec![],
+            parallel_part: vec![vec![Op::Write, Op::Read], vec![Op::Read]],
+            post_part: vec![Op::Read],
+        };
+
+        let outcomes: HashSet<_> = enumerate_outcomes::<AtomicFlag>(scenario)
+            .into_iter()
+            .map(|outcome| (outcome.returns, outcome.linearizable))
+            .collect();
+
+        assert_eq!(
+            outcomes,
+            HashSet::from([
+                // a relaxed load may miss the store even if it starts after the store finishes
+                (
+                    vec![
+                        Ret::Write,
+                        Ret::Read(true),
+                        Ret::Read(false),
+                        Ret::Read(true)
+                    ],
+                    false
+                ),
+                (
+                    vec![
+                        Ret::Write,
+                        Ret::Read(true),
+                        Ret::Read(true),
+                        Ret::Read(true)
+                    ],
+                    true
+                ),
+            ])
+        );
+    }
+
+    /// Borrows its flag, a native atomic to run on native threads.
+    #[derive(Default)]
+    struct BorrowedFlag<'a> {
+        flag: Option<&'a std::sync::atomic::AtomicBool>,
+    }
+
+    impl ConcurrentSpec for BorrowedFlag<'_> {
+        type Seq = SequentialFlag;
+
+        fn exec(&self, op: Op) -> Ret {
+            let flag = self.flag.unwrap();
+            match op {
+                Op::Write => {
+                    flag.store(true, Ordering::SeqCst);
+                    Ret::Write
+                }
+                Op::Read => Ret::Read(flag.load(Ordering::SeqCst)),
+            }
+        }
+    }
+
+    #[test]
+    fn threads_execute_on_borrowed_instance() {
+        let scenario = Scenario {
+            init_part: vec![Op::Read],
+            parallel_part: vec![vec![Op::Write], vec![Op::Write]],
+            post_part: vec![Op::Read],
+        };
+
+        let flag = std::sync::atomic::AtomicBool::new(false);
+        let conc = BorrowedFlag { flag: Some(&flag) };
+        let execution = execute_scenario_with_threads_on(&conc, scenario);
+        assert!(LinearizabilityChecker::<SequentialFlag>::check(&execution));
+        assert_eq!(execution.post_part[0].ret, Ret::Read(true));
+    }
+
+    /// Writes the flag only in the maintenance, with a native atomic to run on native threads.
+    #[derive(Default)]
+    struct MaintainedFlag {
+        flag: std::sync::atomic::AtomicBool,
+    }
+
+    impl ConcurrentSpec for MaintainedFlag {
+        type Seq = SequentialFlag;
+
+        fn exec(&self, op: Op) -> Ret {
+            match op {
+                Op::Write => Ret::Write,
+                Op::Read => Ret::Read(self.flag.load(Ordering::SeqCst)),
+            }
+        }
+
+        const MAINTENANCE_PASSES: usize = 1;
+
+        fn run_maintenance(&self) {
+            self.flag.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn threads_run_maintenance() {
+        let scenario = Scenario {
+            init_part: vec![],
+            parallel_part: vec![vec![Op::Read], vec![Op::Read]],
+            post_part: vec![Op::Read],
+        };
+
+        // the maintenance may be stopped before it runs, but not every time
+        let maintained = (0..100).any(|_| {
+            let execution = execute_scenario_with_threads::<MaintainedFlag>(scenario.clone());
+            execution.post_part[0].ret == Ret::Read(true)
+        });
+        assert!(maintained);
+    }
+
+    /// Writes only with exclusive access.
+    #[derive(Default)]
+    struct ExclusiveFlag {
+        flag: AtomicBool,
+    }
+
+    impl ConcurrentSpec for ExclusiveFlag {
+        type Seq = SequentialFlag;
+
+        fn exec(&self, op: Op) -> Ret {
+            match op {
+                Op::Write => panic!("writes need exclusive access"),
+                Op::Read => Ret::Read(self.flag.load(Ordering::SeqCst)),
+            }
+        }
+
+        fn exec_mut(&mut self, op: Op) -> Ret {
+            match op {
+                Op::Write => {
+                    self.flag = AtomicBool::new(true);
+                    Ret::Write
+                }
+                Op::Read => self.exec(op),
+            }
+        }
+    }
+
+    #[test]
+    fn exclusive_operations_in_init_and_post_parts() {
+        let scenario = Scenario {
+            init_part: vec![Op::Write],
+            parallel_part: vec![vec![Op::Read], vec![Op::Read]],
+            post_part: vec![Op::Write, Op::Read],
+        };
+
+        assert!(check_scenario_with_loom::<ExclusiveFlag>(scenario).is_ok());
     }
 
-    recorder.finish() // retrieve the recorded execution
+    #[test]
+    fn scenario_display() {
+        let scenario = Scenario {
+            init_part: vec![Op::Write],
+            parallel_part: vec![vec![Op::Read, Op::Write], vec![Op::Read]],
+            post_part: vec![],
+        };
+
+        let expected = "\
+INIT PART:
+|=============|
+| MAIN THREAD |
+|=============|
+|    Write    |
+|-------------|
+
+PARALLEL PART:
+|==========|==========|
+| THREAD 0 | THREAD 1 |
+|==========|==========|
+|   Read   |   Read   |
+|----------|----------|
+|  Write   |          |
+|----------|----------|
+
+POST PART:
+|=============|
+| MAIN THREAD |
+|=============|
+
+";
+        assert_eq!(scenario.to_string(), expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn ron_round_trip() {
+        let scenario = Scenario {
+            init_part: vec![Some(1)],
+            parallel_part: vec![vec![None, Some(2)], vec![]],
+            post_part: vec![None],
+        };
+
+        let ron = scenario.to_ron().unwrap();
+        assert_eq!(Scenario::<Option<u8>>::from_ron(&ron).unwrap(), scenario);
+    }
 }
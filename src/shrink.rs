@@ -0,0 +1,453 @@
+//! Shrinking of the failing scenarios.
+//!
+//! The default shrinking of proptest collections only knows about vectors, so it can't drop a whole thread
+//! or move an operation out of the parallel part. [ScenarioValueTree] simplifies a failing scenario
+//! in the following order:
+//! 1. drops whole threads of the parallel part, keeping at least the minimal number of threads;
+//! 2. drops single operations of every part;
+//! 3. moves the first operation of each thread to the end of the init part and the last one
+//!    to the start of the post part, so only the operations that must race stay in the parallel part;
+//! 4. drops the threads that became empty;
+//! 5. shrinks the remaining operations themselves.
+//!
+//! Operations are moved between the parts only if all the parts are generated with the same parameters,
+//! so the shrunk scenarios respect the [PartParams](crate::scenario::PartParams).
+//!
+//! The simplifications often lead back to an already checked scenario, e.g. dropping an operation
+//! that has just been moved. [verify](crate::Lincheck::verify) remembers the result of every checked scenario
+//! in a cache, so such duplicates are not explored with [loom] again.
+
+use proptest::prelude::*;
+use proptest::strategy::{NewTree, Union, ValueTree};
+use proptest::test_runner::{Reason, TestRunner};
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::sync::Mutex;
+
+use crate::scenario::*;
+
+/// Generates [Scenario]s shrunk by [ScenarioValueTree].
+pub(crate) struct ScenarioStrategy<Op> {
+    /// The number of threads weighted according to the thread distribution.
+    num_threads: Union<Just<usize>>,
+    num_ops: usize,
+    init_part: BoxedStrategy<Op>,
+    parallel_part: BoxedStrategy<Op>,
+    post_part: BoxedStrategy<Op>,
+    /// The strategies overriding `parallel_part` for the first threads.
+    per_thread: Vec<BoxedStrategy<Op>>,
+    min_threads: usize,
+    move_ops: bool,
+}
+
+impl<Op: Debug> ScenarioStrategy<Op> {
+    /// Creates a strategy with `(weight, threads)` alternatives of the number of threads
+    /// and at most `num_ops` operations per part.
+    /// The `i`-th thread uses the `i`-th strategy of `per_thread` instead of the parallel part one, if any.
+    /// If `move_ops` is set, the part strategies must generate the same operations.
+    pub(crate) fn new(
+        weighted_threads: Vec<(u32, usize)>,
+        num_ops: usize,
+        [init_part, parallel_part, post_part]: [BoxedStrategy<Op>; 3],
+        per_thread: Vec<BoxedStrategy<Op>>,
+        move_ops: bool,
+    ) -> Self {
+        let min_threads = weighted_threads
+            .iter()
+            .map(|&(_, threads)| threads)
+            .min()
+            .expect("the thread distribution must allow some number of threads");
+        let num_threads = Union::new_weighted(
+            weighted_threads
+                .into_iter()
+                .map(|(weight, threads)| (weight, Just(threads)))
+                .collect(),
+        );
+
+        Self {
+            num_threads,
+            num_ops,
+            init_part,
+            parallel_part,
+            post_part,
+            per_thread,
+            min_threads,
+            move_ops,
+        }
+    }
+
+    fn ops(
+        &self,
+        strategy: &BoxedStrategy<Op>,
+        runner: &mut TestRunner,
+    ) -> Result<Vec<OpTree<Op>>, Reason> {
+        let len = (runner.rng().next_u64() % (self.num_ops as u64 + 1)) as usize;
+        (0..len).map(|_| strategy.new_tree(runner)).collect()
+    }
+}
+
+impl<Op> Debug for ScenarioStrategy<Op> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScenarioStrategy")
+            .field("num_ops", &self.num_ops)
+            .field("min_threads", &self.min_threads)
+            .field("move_ops", &self.move_ops)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Op: Debug> Strategy for ScenarioStrategy<Op> {
+    type Tree = ScenarioValueTree<Op>;
+    type Value = Scenario<Op>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let threads = self.num_threads.new_tree(runner)?.current();
+        let init_part = self.ops(&self.init_part, runner)?;
+        let parallel_part = (0..threads)
+            .map(|thread| {
+                let strategy = self.per_thread.get(thread).unwrap_or(&self.parallel_part);
+                self.ops(strategy, runner)
+            })
+            .collect::<Result<_, _>>()?;
+        let post_part = self.ops(&self.post_part, runner)?;
+
+        Ok(ScenarioValueTree {
+            init_part,
+            parallel_part,
+            post_part,
+            min_threads: self.min_threads,
+            move_ops: self.move_ops,
+            stage: Stage::DropThreads,
+            cursor: 0,
+            undo: None,
+        })
+    }
+}
+
+type OpTree<Op> = Box<dyn ValueTree<Value = Op>>;
+
+/// The shrinking stages in the order they are tried.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    DropThreads,
+    DropOps,
+    MoveFirstOps,
+    MoveLastOps,
+    DropEmptyThreads,
+    ShrinkOps,
+    Done,
+}
+
+/// The position of an operation in a scenario.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Location {
+    Init(usize),
+    Parallel(usize, usize),
+    Post(usize),
+}
+
+/// How to revert the last simplification.
+enum Undo<Op> {
+    DropThread(usize, Vec<OpTree<Op>>),
+    DropOp(Location, OpTree<Op>),
+    MoveFirstOp(usize),
+    MoveLastOp(usize),
+    ShrinkOp(Location),
+}
+
+/// The [ValueTree] of a [Scenario] with the domain-aware simplifications
+/// described in the [module-level documentation](crate::shrink).
+pub struct ScenarioValueTree<Op> {
+    init_part: Vec<OpTree<Op>>,
+    parallel_part: Vec<Vec<OpTree<Op>>>,
+    post_part: Vec<OpTree<Op>>,
+    min_threads: usize,
+    move_ops: bool,
+    stage: Stage,
+    /// The thread or the operation the current stage is at.
+    cursor: usize,
+    undo: Option<Undo<Op>>,
+}
+
+impl<Op: Debug> ScenarioValueTree<Op> {
+    /// Finds the `index`-th operation of the scenario, counting the parts in order.
+    fn locate(&self, mut index: usize) -> Option<Location> {
+        if index < self.init_part.len() {
+            return Some(Location::Init(index));
+        }
+        index -= self.init_part.len();
+        for (thread, ops) in self.parallel_part.iter().enumerate() {
+            if index < ops.len() {
+                return Some(Location::Parallel(thread, index));
+            }
+            index -= ops.len();
+        }
+        (index < self.post_part.len()).then_some(Location::Post(index))
+    }
+
+    fn op_mut(&mut self, location: Location) -> &mut OpTree<Op> {
+        match location {
+            Location::Init(index) => &mut self.init_part[index],
+            Location::Parallel(thread, index) => &mut self.parallel_part[thread][index],
+            Location::Post(index) => &mut self.post_part[index],
+        }
+    }
+
+    fn remove_op(&mut self, location: Location) -> OpTree<Op> {
+        match location {
+            Location::Init(index) => self.init_part.remove(index),
+            Location::Parallel(thread, index) => self.parallel_part[thread].remove(index),
+            Location::Post(index) => self.post_part.remove(index),
+        }
+    }
+
+    fn insert_op(&mut self, location: Location, op: OpTree<Op>) {
+        match location {
+            Location::Init(index) => self.init_part.insert(index, op),
+            Location::Parallel(thread, index) => self.parallel_part[thread].insert(index, op),
+            Location::Post(index) => self.post_part.insert(index, op),
+        }
+    }
+
+    fn next_stage(&mut self) {
+        self.stage = match self.stage {
+            Stage::DropThreads => Stage::DropOps,
+            Stage::DropOps if self.move_ops => Stage::MoveFirstOps,
+            Stage::DropOps => Stage::DropEmptyThreads,
+            Stage::MoveFirstOps => Stage::MoveLastOps,
+            Stage::MoveLastOps => Stage::DropEmptyThreads,
+            Stage::DropEmptyThreads => Stage::ShrinkOps,
+            Stage::ShrinkOps | Stage::Done => Stage::Done,
+        };
+        self.cursor = 0;
+    }
+
+    /// Applies the next simplification of the current stage, if any.
+    fn try_simplify(&mut self) -> Option<Undo<Op>> {
+        let cursor = self.cursor;
+        match self.stage {
+            Stage::DropThreads | Stage::DropEmptyThreads => {
+                if self.parallel_part.len() <= self.min_threads {
+                    return None;
+                }
+                if self.stage == Stage::DropEmptyThreads
+                    && !self.parallel_part.get(cursor)?.is_empty()
+                {
+                    self.cursor += 1;
+                    return self.try_simplify();
+                }
+                if cursor >= self.parallel_part.len() {
+                    return None;
+                }
+                Some(Undo::DropThread(cursor, self.parallel_part.remove(cursor)))
+            }
+            Stage::DropOps => {
+                let location = self.locate(cursor)?;
+                Some(Undo::DropOp(location, self.remove_op(location)))
+            }
+            Stage::MoveFirstOps => {
+                let ops = self.parallel_part.get_mut(cursor)?;
+                if ops.is_empty() {
+                    self.cursor += 1;
+                    return self.try_simplify();
+                }
+                let op = ops.remove(0);
+                self.init_part.push(op);
+                Some(Undo::MoveFirstOp(cursor))
+            }
+            Stage::MoveLastOps => {
+                let ops = self.parallel_part.get_mut(cursor)?;
+                let Some(op) = ops.pop() else {
+                    self.cursor += 1;
+                    return self.try_simplify();
+                };
+                self.post_part.insert(0, op);
+                Some(Undo::MoveLastOp(cursor))
+            }
+            Stage::ShrinkOps => {
+                let location = self.locate(cursor)?;
+                if self.op_mut(location).simplify() {
+                    Some(Undo::ShrinkOp(location))
+                } else {
+                    self.cursor += 1;
+                    self.try_simplify()
+                }
+            }
+            Stage::Done => None,
+        }
+    }
+}
+
+impl<Op: Debug> ValueTree for ScenarioValueTree<Op> {
+    type Value = Scenario<Op>;
+
+    fn current(&self) -> Scenario<Op> {
+        let current = |ops: &Vec<OpTree<Op>>| ops.iter().map(|op| op.current()).collect();
+        Scenario {
+            init_part: current(&self.init_part),
+            parallel_part: self.parallel_part.iter().map(current).collect(),
+            post_part: current(&self.post_part),
+        }
+    }
+
+    fn simplify(&mut self) -> bool {
+        // the last simplification was accepted, so the cursor already points to the next candidate
+        self.undo = None;
+        while self.stage != Stage::Done {
+            if let Some(undo) = self.try_simplify() {
+                self.undo = Some(undo);
+                return true;
+            }
+            self.next_stage();
+        }
+        false
+    }
+
+    fn complicate(&mut self) -> bool {
+        match self.undo.take() {
+            None => false,
+            Some(Undo::DropThread(thread, ops)) => {
+                self.parallel_part.insert(thread, ops);
+                self.cursor += 1;
+                true
+            }
+            Some(Undo::DropOp(location, op)) => {
+                self.insert_op(location, op);
+                self.cursor += 1;
+                true
+            }
+            Some(Undo::MoveFirstOp(thread)) => {
+                let op = self.init_part.pop().expect("the moved operation");
+                self.parallel_part[thread].insert(0, op);
+                self.cursor += 1;
+                true
+            }
+            Some(Undo::MoveLastOp(thread)) => {
+                let op = self.post_part.remove(0);
+                self.parallel_part[thread].push(op);
+                self.cursor += 1;
+                true
+            }
+            Some(Undo::ShrinkOp(location)) => {
+                // the operation keeps its own shrinking state, so the cursor stays on it
+                let complicated = self.op_mut(location).complicate();
+                if complicated {
+                    self.undo = Some(Undo::ShrinkOp(location));
+                }
+                complicated
+            }
+        }
+    }
+}
+
+impl<Op: Debug> Debug for ScenarioValueTree<Op> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScenarioValueTree")
+            .field("current", &self.current())
+            .field("stage", &self.stage)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The results of the checked scenarios keyed by their [Debug] representation,
+/// which doesn't require the operations to implement [Hash](std::hash::Hash).
+///
+/// It is shared by the [workers](crate::Lincheck::verify_parallel) checking the scenarios at once.
+#[derive(Default)]
+pub(crate) struct CheckCache {
+    passed: Mutex<HashMap<String, bool>>,
+}
+
+impl CheckCache {
+    /// Returns whether `scenario` passed if it was checked before,
+    /// otherwise checks it with `check` and remembers the result.
+    pub(crate) fn check<Op: Debug>(
+        &self,
+        scenario: &Scenario<Op>,
+        check: impl FnOnce() -> bool,
+    ) -> bool {
+        let key = format!("{:?}", scenario);
+        if let Some(&passed) = self.passed.lock().unwrap().get(&key) {
+            return passed;
+        }
+        // a panicking check is not remembered
+        let passed = check();
+        self.passed.lock().unwrap().insert(key, passed);
+        passed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scenario_strategy, ThreadDistribution};
+    use proptest::test_runner::TestError;
+
+    fn shrink(test: impl Fn(&Scenario<u8>) -> bool) -> Scenario<u8> {
+        let strategy = scenario_strategy::<u8>(
+            1..=3,
+            4,
+            &ThreadDistribution::Uniform,
+            None,
+            ScenarioConfig::default(),
+        );
+        let result = TestRunner::deterministic().run(&strategy, |scenario| {
+            prop_assert!(test(&scenario));
+            Ok(())
+        });
+        let Err(TestError::Fail(_, scenario)) = result else {
+            panic!("a failing scenario must be generated");
+        };
+        scenario
+    }
+
+    #[test]
+    fn moves_ops_out_of_parallel_part() {
+        let scenario = shrink(|scenario| {
+            let mut ops = (scenario.init_part.iter())
+                .chain(scenario.parallel_part.iter().flatten())
+                .chain(scenario.post_part.iter());
+            !ops.any(|&op| op >= 10)
+        });
+        assert_eq!(scenario.parallel_part, vec![Vec::<u8>::new()]);
+        let sequential_ops = [scenario.init_part, scenario.post_part].concat();
+        assert_eq!(sequential_ops, vec![10]);
+    }
+
+    #[test]
+    fn keeps_racing_ops() {
+        let scenario = shrink(|scenario| {
+            let busy_threads = scenario.parallel_part.iter().filter(|ops| !ops.is_empty());
+            busy_threads.count() < 2
+        });
+        assert_eq!(
+            scenario,
+            Scenario {
+                init_part: vec![],
+                parallel_part: vec![vec![0], vec![0]],
+                post_part: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn cache_checks_each_scenario_once() {
+        let cache = CheckCache::default();
+        let checks = std::cell::Cell::new(0);
+        let check = |passed| {
+            checks.set(checks.get() + 1);
+            passed
+        };
+        let scenario = |ops: Vec<u8>| Scenario {
+            init_part: vec![],
+            parallel_part: vec![ops],
+            post_part: vec![],
+        };
+
+        assert!(!cache.check(&scenario(vec![1, 2]), || check(false)));
+        assert!(cache.check(&scenario(vec![1]), || check(true)));
+        assert!(!cache.check(&scenario(vec![1, 2]), || check(true)));
+        assert!(cache.check(&scenario(vec![1]), || check(false)));
+        assert_eq!(checks.get(), 2);
+    }
+}
@@ -0,0 +1,242 @@
+//! Checking data structures with the randomized schedulers of [shuttle] instead of [loom].
+//!
+//! [loom] explores every interleaving of a scenario, which becomes infeasible for large scenarios,
+//! and it only models its own synchronization primitives. [verify_shuttle](crate::Lincheck::verify_shuttle)
+//! executes every scenario under [shuttle] instead, which samples [iterations](ShuttleConfig::iterations)
+//! interleavings with the [configured](ShuttleConfig::scheduler) scheduler, and checks the recorded executions
+//! like [verify](crate::Lincheck::verify). The data structure must be built from the [shuttle] primitives,
+//! e.g. [shuttle::sync::Mutex], rather than the [loom] ones. [ShuttleBackend] is the [ExecutionBackend] behind it.
+//!
+//! Unlike with [loom], a passing scenario doesn't prove anything, but the schedules are seeded
+//! from the [proptest](crate::Lincheck::proptest) configuration, so a fixed seed reproduces a failure.
+
+use proptest::prelude::*;
+use proptest::test_runner::{RngAlgorithm, TestRng};
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::mem;
+use std::panic::UnwindSafe;
+use std::sync::{Arc, Mutex};
+
+use shuttle::scheduler::{PctScheduler, RandomScheduler, Scheduler};
+use shuttle::sync::atomic::{AtomicBool, Ordering};
+use shuttle::{thread, Config, Runner};
+
+use crate::backend::ExecutionBackend;
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::recorder::{self, Recorder};
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// The configuration of [verify_shuttle](Lincheck::verify_shuttle).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShuttleConfig {
+    /// The number of interleavings of each scenario to execute.
+    pub iterations: usize,
+    /// The scheduler choosing the interleavings.
+    pub scheduler: ShuttleScheduler,
+}
+
+impl Default for ShuttleConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            scheduler: ShuttleScheduler::Random,
+        }
+    }
+}
+
+/// The [shuttle] scheduler choosing the interleavings of a scenario.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShuttleScheduler {
+    /// Chooses a random thread at every step, see [RandomScheduler].
+    #[default]
+    Random,
+    /// Probabilistic concurrency testing with the given bug depth, see [PctScheduler].
+    /// It finds the bugs requiring at most `depth` ordering constraints with a guaranteed probability.
+    Pct {
+        /// The maximum number of ordering constraints of the bugs to look for.
+        depth: usize,
+    },
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` built from [shuttle] primitives
+    /// is linearizable with respect to its sequential specification by executing the scenarios under [shuttle].
+    ///
+    /// See the [module-level documentation](crate::shuttle_check) for the details.
+    /// It returns a [VerificationFailure] if the test fails, e.g. the last failing execution of the shrunk scenario
+    /// or a panic of an operation.
+    pub fn verify_shuttle<Conc>(
+        &self,
+        config: &ShuttleConfig,
+    ) -> Result<(), VerificationFailure<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Clone + Debug + Send,
+    {
+        let seed = self.runner().rng().next_u64();
+        self.verify_with_backend::<Conc>(&ShuttleBackend::new(config.clone(), seed))
+    }
+}
+
+/// Executes [iterations](ShuttleConfig::iterations) interleavings of the scenario under [shuttle]
+/// with the [configured](ShuttleConfig::scheduler) scheduler.
+///
+/// Every scenario is explored with a new seed, drawn from the seed the backend is created with.
+#[derive(Debug)]
+pub struct ShuttleBackend {
+    config: ShuttleConfig,
+    rng: RefCell<TestRng>,
+}
+
+impl ShuttleBackend {
+    /// Creates a backend with the given configuration drawing the seeds of the schedulers from `seed`.
+    pub fn new(config: ShuttleConfig, seed: u64) -> Self {
+        let mut bytes = [0; 32];
+        bytes[..8].copy_from_slice(&seed.to_le_bytes());
+        Self {
+            config,
+            rng: RefCell::new(TestRng::from_seed(RngAlgorithm::ChaCha, &bytes)),
+        }
+    }
+}
+
+impl ExecutionBackend for ShuttleBackend {
+    fn find_failure<Conc>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+        check: impl Fn(&Execution<ConcOp<Conc>, ConcRet<Conc>>) -> bool + Send + Sync + 'static,
+    ) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Clone + Debug + Send,
+    {
+        let seed = self.rng.borrow_mut().next_u64();
+        let executions = explore_scenario_with_shuttle::<Conc>(scenario, &self.config, seed);
+        match executions.into_iter().find(|execution| !check(execution)) {
+            Some(execution) => Err(execution),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Executes `config.iterations` interleavings of the scenario under [shuttle]
+/// with the configured scheduler seeded with `seed`, and returns their executions.
+///
+/// The executions are checked outside of the test, as [shuttle] runs it on small stacks.
+fn explore_scenario_with_shuttle<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+    config: &ShuttleConfig,
+    seed: u64,
+) -> Vec<Execution<ConcOp<Conc>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + 'static,
+    ConcRet<Conc>: Send,
+{
+    let executions = Arc::new(Mutex::new(Vec::with_capacity(config.iterations)));
+    let recorded = executions.clone();
+    let num_threads = scenario.parallel_part.len() + usize::from(Conc::MAINTENANCE_PASSES > 0);
+    let test = move || {
+        let execution = execute_scenario_with_shuttle::<Conc>(scenario.clone());
+        recorded.lock().unwrap().push(execution);
+    };
+
+    match config.scheduler {
+        // there is nothing to interleave, and PCT requires at least one scheduling decision
+        _ if num_threads < 2 => run(RandomScheduler::new_from_seed(seed, 1), test),
+        ShuttleScheduler::Random => run(
+            RandomScheduler::new_from_seed(seed, config.iterations),
+            test,
+        ),
+        ShuttleScheduler::Pct { depth } => run(
+            PctScheduler::new_from_seed(seed, depth, config.iterations),
+            test,
+        ),
+    }
+
+    let executions = mem::take(&mut *executions.lock().unwrap());
+    executions
+}
+
+fn run(scheduler: impl Scheduler + 'static, test: impl Fn() + Send + Sync + 'static) {
+    Runner::new(scheduler, Config::new()).run(test);
+}
+
+/// Executes the given scenario with [shuttle] threads and returns the resulting execution.
+///
+/// It must be called inside a [shuttle] test, e.g. [shuttle::check_random].
+/// Like [execute_scenario_with_loom], the init and post parts are executed with [exec_mut](ConcurrentSpec::exec_mut)
+/// and the [maintenance](ConcurrentSpec::run_maintenance) runs on its own thread during the parallel part.
+pub fn execute_scenario_with_shuttle<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+) -> Execution<ConcOp<Conc>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Default + Sync,
+    ConcOp<Conc>: Send + Clone,
+    ConcRet<Conc>: Send,
+{
+    let mut conc = Conc::default();
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+
+    // init part
+    for op in scenario.init_part {
+        recorder.record(op.clone(), || conc.exec_mut(op));
+    }
+
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
+    let recorder = recorder.record_parallel_part_with_capacity(total_parallel_ops);
+
+    let stop_maintenance = AtomicBool::new(false);
+
+    // parallel part
+    thread::scope(|s| {
+        let conc = &conc;
+        let maintenance = (Conc::MAINTENANCE_PASSES > 0).then(|| {
+            s.spawn(|| {
+                for _ in 0..Conc::MAINTENANCE_PASSES {
+                    if stop_maintenance.load(Ordering::Acquire) {
+                        break;
+                    }
+                    conc.run_maintenance();
+                }
+            })
+        });
+
+        let handles: Vec<_> = scenario
+            .parallel_part
+            .into_iter()
+            .map(|thread_ops| {
+                let recorder = &recorder;
+                s.spawn(move || {
+                    let mut recorder = recorder.record_thread_with_capacity(thread_ops.len());
+                    for op in thread_ops {
+                        recorder.record(op.clone(), || conc.exec(op));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        if let Some(handle) = maintenance {
+            stop_maintenance.store(true, Ordering::Release);
+            handle.join().unwrap();
+        }
+    });
+
+    // post part
+    let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+    for op in scenario.post_part {
+        recorder.record(op.clone(), || conc.exec_mut(op));
+    }
+
+    recorder.finish()
+}
@@ -0,0 +1,407 @@
+//! Long-running soak testing.
+//!
+//! Unlike [verify](crate::Lincheck::verify), [soak](crate::Lincheck::soak) doesn't stop on the first failure.
+//! It keeps generating and checking scenarios until the time or scenario budget is exhausted,
+//! collecting every failure, including the panics and the deadlocks, and periodically reporting the progress.
+//!
+//! With the `serde` feature, [soak_saving](crate::Lincheck::soak_saving) also saves every failing scenario
+//! as soon as it is found, in the [RON format](crate::Scenario::to_ron) that [replay](crate::Lincheck::replay) accepts.
+
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+#[cfg(feature = "serde")]
+use std::fs;
+use std::mem::{self, Discriminant};
+use std::panic::{self, AssertUnwindSafe, UnwindSafe};
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::checker::LinearizabilityChecker;
+use crate::error::VerificationFailure;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{Constructors, Lincheck};
+
+/// Configuration of a soak run.
+#[derive(Clone, Debug)]
+pub struct SoakConfig {
+    /// How long to run. Runs indefinitely if `None` unless [max_scenarios](SoakConfig::max_scenarios) is set.
+    pub duration: Option<Duration>,
+    /// The maximum number of scenarios to check. Unlimited if `None`.
+    pub max_scenarios: Option<usize>,
+    /// How often to print a progress summary to stderr.
+    pub report_interval: Duration,
+}
+
+impl Default for SoakConfig {
+    fn default() -> Self {
+        Self {
+            duration: None,
+            max_scenarios: None,
+            report_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// A failure found during a soak run.
+#[derive(Clone, Debug)]
+pub struct SoakFailure<Op, Ret> {
+    /// The failing scenario.
+    pub scenario: Scenario<Op>,
+    /// How the scenario failed.
+    pub failure: VerificationFailure<Op, Ret>,
+    /// The length of the longest linearization prefix of a [non-linearizable](VerificationFailure::NonLinearizable)
+    /// execution, i.e. the index of the first operation the sequential specification rejects.
+    /// [None] for the other failures.
+    pub divergence: Option<usize>,
+}
+
+/// The summary of a soak run.
+#[derive(Clone, Debug)]
+pub struct SoakReport<Op, Ret> {
+    /// The number of checked scenarios.
+    pub scenarios_checked: usize,
+    /// The total duration of the run.
+    pub elapsed: Duration,
+    /// All the failures found in the order of discovery.
+    pub failures: Vec<SoakFailure<Op, Ret>>,
+}
+
+/// A group of failures with the same shape, which are likely caused by the same bug.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailureCluster {
+    /// The index of the smallest failure of the cluster in [failures](SoakReport::failures).
+    pub representative: usize,
+    /// The indices of all the failures of the cluster in [failures](SoakReport::failures).
+    pub failures: Vec<usize>,
+}
+
+/// The structure of a failure: its kind, how many operations of each kind ran in parallel,
+/// which of them overlapped and where the execution diverged from the sequential specification.
+/// The kind of an operation is its enum variant.
+struct FailureShape<Op, Ret> {
+    failure: Discriminant<VerificationFailure<Op, Ret>>,
+    kinds: HashMap<Discriminant<Op>, usize>,
+    conflicts: HashSet<(Discriminant<Op>, Discriminant<Op>)>,
+    divergence: Option<usize>,
+}
+
+// not derived since the derive would require `Op: PartialEq` and `Ret: PartialEq`
+impl<Op, Ret> PartialEq for FailureShape<Op, Ret> {
+    fn eq(&self, other: &Self) -> bool {
+        self.failure == other.failure
+            && self.kinds == other.kinds
+            && self.conflicts == other.conflicts
+            && self.divergence == other.divergence
+    }
+}
+
+impl Lincheck {
+    /// Keeps generating and checking scenarios for the concurrent implementation `Conc`
+    /// until the budget in `config` is exhausted, collecting every failure instead of stopping on the first one.
+    ///
+    /// Generated scenarios are not shrunk.
+    pub fn soak<Conc>(&self, config: &SoakConfig) -> SoakReport<ConcOp<Conc>, ConcRet<Conc>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.soak_reporting::<Conc>(config, |_, _| {})
+    }
+
+    /// The same as [soak](Lincheck::soak) but saves every failing scenario into `artifacts_dir`
+    /// as `failure-N.ron` as soon as it is found.
+    ///
+    /// A saved scenario is loaded with [from_ron](Scenario::from_ron) and rerun with [replay](Lincheck::replay).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the directory can't be created or a scenario can't be saved.
+    #[cfg(feature = "serde")]
+    pub fn soak_saving<Conc>(
+        &self,
+        config: &SoakConfig,
+        artifacts_dir: impl AsRef<Path>,
+    ) -> SoakReport<ConcOp<Conc>, ConcRet<Conc>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>:
+            Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + serde::Serialize + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let dir = artifacts_dir.as_ref();
+        fs::create_dir_all(dir).expect("failed to create the artifacts directory");
+        self.soak_reporting::<Conc>(config, |index, failure| {
+            let scenario = failure
+                .scenario
+                .to_ron()
+                .expect("failed to serialize the scenario");
+            fs::write(dir.join(format!("failure-{}.ron", index)), scenario)
+                .expect("failed to save the failure");
+        })
+    }
+
+    /// Same as [soak](Lincheck::soak) but passes every failure and its index to `on_failure` as soon as it is found.
+    fn soak_reporting<Conc>(
+        &self,
+        config: &SoakConfig,
+        mut on_failure: impl FnMut(usize, &SoakFailure<ConcOp<Conc>, ConcRet<Conc>>),
+    ) -> SoakReport<ConcOp<Conc>, ConcRet<Conc>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let strategy = self.scenarios();
+        let mut runner = self.runner();
+        let constructors = Constructors::new(Conc::default, Conc::Seq::default);
+
+        let start = Instant::now();
+        let mut last_report = start;
+        let mut report = SoakReport {
+            scenarios_checked: 0,
+            elapsed: Duration::ZERO,
+            failures: Vec::new(),
+        };
+
+        while !config.is_exhausted(report.scenarios_checked, start.elapsed()) {
+            let scenario = strategy
+                .new_tree(&mut runner)
+                .expect("failed to generate a scenario")
+                .current();
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                self.check_scenario_with(scenario.clone(), &constructors)
+            }));
+            let failure = match result {
+                Ok(Ok(())) => None,
+                Ok(Err(failure)) => Some(failure),
+                // a panicking or deadlocking scenario is diagnosed like a failing one
+                Err(_) => Some(self.diagnose_failure(scenario.clone(), &constructors)),
+            };
+            if let Some(failure) = failure {
+                let divergence = match &failure {
+                    VerificationFailure::NonLinearizable(execution) => {
+                        LinearizabilityChecker::<Conc::Seq>::diagnose(execution)
+                            .map(|diagnosis| diagnosis.prefix.len())
+                    }
+                    _ => None,
+                };
+                let failure = SoakFailure {
+                    scenario,
+                    failure,
+                    divergence,
+                };
+                on_failure(report.failures.len(), &failure);
+                report.failures.push(failure);
+            }
+            report.scenarios_checked += 1;
+
+            if last_report.elapsed() >= config.report_interval {
+                last_report = Instant::now();
+                eprintln!(
+                    "soak: {:?} elapsed, {} scenarios checked, {} failures found ({} distinct)",
+                    start.elapsed(),
+                    report.scenarios_checked,
+                    report.failures.len(),
+                    report.clusters().len()
+                );
+            }
+        }
+
+        report.elapsed = start.elapsed();
+        report
+    }
+}
+
+impl SoakConfig {
+    fn is_exhausted(&self, scenarios_checked: usize, elapsed: Duration) -> bool {
+        self.max_scenarios
+            .is_some_and(|max_scenarios| scenarios_checked >= max_scenarios)
+            || self.duration.is_some_and(|duration| elapsed >= duration)
+    }
+}
+
+impl<Op, Ret> SoakReport<Op, Ret> {
+    /// Groups the failures by their shape so that the failures caused by the same bug are reported once.
+    ///
+    /// Two failures have the same shape if they are the same [kind](VerificationFailure) of failure,
+    /// the same numbers of operations of each kind ran in parallel, the same pairs of kinds overlapped in time
+    /// and the executions [diverged](SoakFailure::divergence) at the same operation.
+    /// The failures without an execution, e.g. the generator errors, are grouped by their kind.
+    /// The clusters are in the order of discovery.
+    pub fn clusters(&self) -> Vec<FailureCluster> {
+        let mut shapes: Vec<FailureShape<Op, Ret>> = Vec::new();
+        let mut clusters: Vec<FailureCluster> = Vec::new();
+
+        for (index, failure) in self.failures.iter().enumerate() {
+            let shape = failure.shape();
+            match shapes.iter().position(|other| *other == shape) {
+                Some(cluster_id) => {
+                    let cluster = &mut clusters[cluster_id];
+                    cluster.failures.push(index);
+                    let representative = &self.failures[cluster.representative];
+                    if failure.scenario.num_ops() < representative.scenario.num_ops() {
+                        cluster.representative = index;
+                    }
+                }
+                None => {
+                    shapes.push(shape);
+                    clusters.push(FailureCluster {
+                        representative: index,
+                        failures: vec![index],
+                    });
+                }
+            }
+        }
+
+        clusters
+    }
+}
+
+impl<Op, Ret> SoakFailure<Op, Ret> {
+    fn shape(&self) -> FailureShape<Op, Ret> {
+        let mut shape = FailureShape {
+            failure: mem::discriminant(&self.failure),
+            kinds: HashMap::new(),
+            conflicts: HashSet::new(),
+            divergence: self.divergence,
+        };
+        let Some(execution) = self.failure.execution() else {
+            return shape;
+        };
+        let parallel_part = &execution.parallel_part;
+        for inv in parallel_part.iter() {
+            *shape.kinds.entry(mem::discriminant(&inv.op)).or_default() += 1;
+        }
+
+        for inv_a in parallel_part.iter() {
+            for inv_b in parallel_part.during(inv_a.call_timestamp, inv_a.return_timestamp) {
+                if inv_a.thread_id != inv_b.thread_id {
+                    (shape.conflicts)
+                        .insert((mem::discriminant(&inv_a.op), mem::discriminant(&inv_b.op)));
+                }
+            }
+        }
+
+        shape
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution;
+    use crate::execution::Execution;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Op {
+        Increment,
+        Get,
+    }
+
+    fn non_linearizable(
+        execution: Execution<Op, usize>,
+        divergence: usize,
+    ) -> SoakFailure<Op, usize> {
+        SoakFailure {
+            scenario: Scenario {
+                init_part: vec![],
+                parallel_part: vec![],
+                post_part: vec![],
+            },
+            failure: VerificationFailure::NonLinearizable(execution),
+            divergence: Some(divergence),
+        }
+    }
+
+    fn clusters(failures: Vec<SoakFailure<Op, usize>>) -> Vec<Vec<usize>> {
+        let report = SoakReport {
+            scenarios_checked: failures.len(),
+            elapsed: Duration::ZERO,
+            failures,
+        };
+        report
+            .clusters()
+            .into_iter()
+            .map(|cluster| cluster.failures)
+            .collect()
+    }
+
+    #[test]
+    fn shapes_count_the_operations() {
+        let two_increments = || {
+            execution! {
+                parallel: [
+                    0 @ 0..2: Op::Increment => 0,
+                    1 @ 1..3: Op::Increment => 0,
+                ],
+            }
+        };
+        let three_increments = execution! {
+            parallel: [
+                0 @ 0..2: Op::Increment => 0,
+                1 @ 1..3: Op::Increment => 0,
+                1 @ 4..5: Op::Increment => 1,
+            ],
+        };
+        let increment_and_get = execution! {
+            parallel: [
+                0 @ 0..2: Op::Increment => 0,
+                1 @ 1..3: Op::Get => 2,
+            ],
+        };
+
+        let failures = vec![
+            non_linearizable(two_increments(), 1),
+            non_linearizable(three_increments, 1),
+            non_linearizable(two_increments(), 1),
+            // the same operations rejected at another point
+            non_linearizable(two_increments(), 0),
+            non_linearizable(increment_and_get, 1),
+        ];
+        assert_eq!(
+            clusters(failures),
+            vec![vec![0, 2], vec![1], vec![3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn failures_without_execution_are_grouped_by_kind() {
+        let generator_error = || SoakFailure {
+            scenario: Scenario {
+                init_part: vec![],
+                parallel_part: vec![],
+                post_part: vec![],
+            },
+            failure: VerificationFailure::GeneratorError("too many rejects".to_string()),
+            divergence: None,
+        };
+        let deadlock = SoakFailure {
+            failure: VerificationFailure::Deadlock {
+                execution_so_far: Execution::default(),
+                blocked_threads: vec![],
+            },
+            ..generator_error()
+        };
+        let empty_non_linearizable = SoakFailure {
+            failure: VerificationFailure::NonLinearizable(Execution::default()),
+            ..generator_error()
+        };
+
+        let failures = vec![
+            generator_error(),
+            deadlock,
+            empty_non_linearizable,
+            generator_error(),
+        ];
+        assert_eq!(clusters(failures), vec![vec![0, 3], vec![1], vec![2]]);
+    }
+}
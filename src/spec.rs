@@ -1,5 +1,8 @@
 /// The sequential implementation of a data structure.
-pub trait SequentialSpec: Default {
+///
+/// The harness creates fresh instances with [Default] unless the constructors are given explicitly,
+/// e.g. with [verify_with](crate::Lincheck::verify_with).
+pub trait SequentialSpec {
     /// The type of operations.
     type Op;
 
@@ -8,15 +11,173 @@ pub trait SequentialSpec: Default {
 
     /// Executes an operation on the data structure.
     fn exec(&mut self, op: Self::Op) -> Self::Ret;
+
+    /// Whether the `actual` result of the concurrent implementation is acceptable
+    /// when the specification returns `expected`, e.g. a snapshot in any order or an error of the same class.
+    ///
+    /// The checkers compare the results with it. By default the results must be equal.
+    fn ret_matches(expected: &Self::Ret, actual: &Self::Ret) -> bool
+    where
+        Self::Ret: PartialEq,
+    {
+        expected == actual
+    }
+}
+
+/// A [SequentialSpec] whose operations can be undone.
+///
+/// [check_undoable](crate::checker::LinearizabilityChecker::check_undoable) backtracks by undoing the operations
+/// instead of rebuilding the specification from scratch, which is much faster for long histories.
+pub trait UndoableSequentialSpec: SequentialSpec {
+    /// The information needed to undo an operation, e.g. the overwritten value.
+    type UndoToken;
+
+    /// Executes an operation on the data structure and returns the token to undo it.
+    fn exec_undoable(&mut self, op: Self::Op) -> (Self::Ret, Self::UndoToken);
+
+    /// Undoes the last executed operation that is not undone yet.
+    fn undo(&mut self, token: Self::UndoToken);
+}
+
+/// A [SequentialSpec] with an equivalence relation on its states.
+///
+/// Two states are equivalent if every sequence of operations returns the same results from them,
+/// even if they are represented differently. [check_pruned](crate::checker::LinearizabilityChecker::check_pruned)
+/// doesn't explore a linearization further if it reached a state equivalent to one already explored
+/// with the same invocations linearized.
+pub trait StateEquivalence: SequentialSpec + Clone {
+    /// Whether the states are equivalent.
+    fn equivalent(&self, other: &Self) -> bool;
+}
+
+/// A [SequentialSpec] whose operations on different partitions, e.g. the keys of a map, are independent.
+///
+/// The state of such a specification is a product of the states of the partitions,
+/// and an operation only reads and writes the state of its partition.
+/// Then a history is linearizable if and only if the sub-history of each partition is
+/// (P-compositionality, as introduced by Horn and Kroening), so [check_partitioned](crate::checker::LinearizabilityChecker::check_partitioned) checks them separately.
+pub trait PartitionableSpec: SequentialSpec {
+    /// The partition of an operation.
+    type Key: Ord;
+
+    /// Returns the partition the operation acts on.
+    fn partition_key(op: &Self::Op) -> Self::Key;
+}
+
+/// Replaying of sequential histories, implemented for every [SequentialSpec].
+pub trait Replay: SequentialSpec + Default {
+    /// Executes the operations on a fresh instance and returns the results.
+    fn replay(ops: impl IntoIterator<Item = Self::Op>) -> Vec<Self::Ret> {
+        let mut spec = Self::default();
+        ops.into_iter().map(|op| spec.exec(op)).collect()
+    }
+
+    /// Executes the operations of the recorded history on a fresh instance
+    /// and returns the invocations whose results differ from the recorded ones.
+    fn replay_diff(
+        history: impl IntoIterator<Item = (Self::Op, Self::Ret)>,
+    ) -> Vec<ReplayMismatch<Self::Op, Self::Ret>>
+    where
+        Self::Op: Clone,
+        Self::Ret: PartialEq,
+    {
+        let mut spec = Self::default();
+        history
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, (op, recorded))| {
+                let expected = spec.exec(op.clone());
+                (!Self::ret_matches(&expected, &recorded)).then_some(ReplayMismatch {
+                    index,
+                    op,
+                    expected,
+                    recorded,
+                })
+            })
+            .collect()
+    }
+}
+
+impl<Seq: SequentialSpec + Default> Replay for Seq {}
+
+/// An invocation whose recorded result differs from the one of the sequential specification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayMismatch<Op, Ret> {
+    /// The position of the invocation in the history.
+    pub index: usize,
+    /// The operation.
+    pub op: Op,
+    /// The result returned by the sequential specification.
+    pub expected: Ret,
+    /// The recorded result.
+    pub recorded: Ret,
 }
 
 /// The concurrent implementation of a data structure.
-pub trait ConcurrentSpec: Default {
+///
+/// The harness creates fresh instances with [Default] unless the constructors are given explicitly,
+/// e.g. with [verify_with](crate::Lincheck::verify_with).
+pub trait ConcurrentSpec {
     /// The sequential specification for the data structure.
     type Seq: SequentialSpec;
 
     /// Executes an operation on the data structure.
     fn exec(&self, op: ConcOp<Self>) -> ConcRet<Self>;
+
+    /// Executes an operation of the init or the post part, where no other thread touches the data structure.
+    ///
+    /// It receives an exclusive reference, so the operations that need one, e.g. draining without synchronization,
+    /// can be included in the scenarios. The harness calls it whenever it owns the instance,
+    /// while the executors of a borrowed instance, like [execute_scenario_with_threads_on](crate::scenario::execute_scenario_with_threads_on),
+    /// call [exec](ConcurrentSpec::exec) instead. By default it calls [exec](ConcurrentSpec::exec).
+    fn exec_mut(&mut self, op: ConcOp<Self>) -> ConcRet<Self> {
+        self.exec(op)
+    }
+
+    /// The maximum number of times [run_maintenance](ConcurrentSpec::run_maintenance) is called per execution.
+    /// If it is not zero, the harness runs the maintenance on an extra thread during the parallel part.
+    ///
+    /// Every pass multiplies the number of interleavings explored by [loom], so keep it small.
+    const MAINTENANCE_PASSES: usize = 0;
+
+    /// Performs a single pass of background work, e.g. memory reclamation, flushing or rebalancing.
+    ///
+    /// The harness stops calling it once the parallel part finishes, before the post part.
+    fn run_maintenance(&self) {}
+
+    /// Validates the internal state of the data structure, e.g. that no node leaked
+    /// or that the size counter matches the number of elements, describing the violated invariant otherwise.
+    ///
+    /// [verify](crate::Lincheck::verify) calls it on every instance after the post part,
+    /// and after the init and the parallel parts too if [VALIDATE_BETWEEN_PARTS](ConcurrentSpec::VALIDATE_BETWEEN_PARTS) is set.
+    /// An invalid state fails the test as [InvalidState](crate::VerificationFailure::InvalidState)
+    /// even if the execution is linearizable.
+    /// In [chaos mode](crate::chaos) it is also called after the parallel part
+    /// to check that the data structure survived an operation panicking midway.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Whether [validate](ConcurrentSpec::validate) is also called after the init and the parallel parts.
+    const VALIDATE_BETWEEN_PARTS: bool = false;
+}
+
+/// A concurrent implementation of a data structure that persists its state, e.g. in persistent memory,
+/// and can be recovered after a crash.
+///
+/// See [durable linearizability](crate::durable) for how it is checked.
+pub trait RecoverableSpec: ConcurrentSpec + Sized {
+    /// Rebuilds the data structure from what the crashed instance persisted, discarding its volatile state.
+    fn recover(&self) -> Self;
+}
+
+/// A concurrent implementation of a data structure that can be built in a given state of its sequential specification,
+/// e.g. a queue prefilled with some elements.
+///
+/// See [prefilled scenarios](crate::prefill) for how it is used.
+pub trait PrefillableSpec: ConcurrentSpec + Sized {
+    /// Builds the data structure in the same state as `seq_state`.
+    fn from_seq(seq_state: &Self::Seq) -> Self;
 }
 
 /// Type alias not to have always write down FQP.
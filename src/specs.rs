@@ -0,0 +1,189 @@
+//! Ready-made sequential specifications of classic shared objects.
+//!
+//! Every specification comes with its operations, their results and [Arbitrary] implementations,
+//! so only the concurrent implementation needs to be written:
+//! - [Register]: an atomic read/write register,
+//! - [Snapshot]: an atomic snapshot object of `N` components,
+//! - [Consensus]: a consensus object.
+//!
+//! The generated scenarios issue every kind of operation from every thread.
+//! So the registers are tested as multi-reader multi-writer ones.
+
+#[cfg(feature = "proptest")]
+use proptest::prelude::*;
+
+use crate::spec::*;
+
+/// An operation on a [Register].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegisterOp<T> {
+    /// Read the value.
+    Read,
+    /// Write the value.
+    Write(T),
+}
+
+/// The result of a [RegisterOp].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegisterRet<T> {
+    /// The value that was read.
+    Read(T),
+    /// The value was written.
+    Write,
+}
+
+/// An atomic register holding a value which is initially `T::default()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Register<T> {
+    value: T,
+}
+
+impl<T: Clone + Default> SequentialSpec for Register<T> {
+    type Op = RegisterOp<T>;
+    type Ret = RegisterRet<T>;
+
+    fn exec(&mut self, op: Self::Op) -> Self::Ret {
+        match op {
+            RegisterOp::Read => RegisterRet::Read(self.value.clone()),
+            RegisterOp::Write(value) => {
+                self.value = value;
+                RegisterRet::Write
+            }
+        }
+    }
+}
+
+impl<T: Clone + Default> UndoableSequentialSpec for Register<T> {
+    /// The overwritten value.
+    type UndoToken = T;
+
+    fn exec_undoable(&mut self, op: Self::Op) -> (Self::Ret, Self::UndoToken) {
+        let old_value = self.value.clone();
+        (self.exec(op), old_value)
+    }
+
+    fn undo(&mut self, old_value: Self::UndoToken) {
+        self.value = old_value;
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: Arbitrary + Clone + 'static> Arbitrary for RegisterOp<T> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(RegisterOp::Read),
+            any::<T>().prop_map(RegisterOp::Write)
+        ]
+        .boxed()
+    }
+}
+
+/// An operation on a [Snapshot] of `N` components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapshotOp<T, const N: usize> {
+    /// Update the component with the given index.
+    Update(usize, T),
+    /// Read all the components atomically.
+    Scan,
+}
+
+/// The result of a [SnapshotOp].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SnapshotRet<T> {
+    /// The component was updated.
+    Update,
+    /// The values of all the components.
+    Scan(Vec<T>),
+}
+
+/// An atomic snapshot object of `N` components which are initially `T::default()`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Snapshot<T, const N: usize> {
+    components: [T; N],
+}
+
+impl<T: Default, const N: usize> Default for Snapshot<T, N> {
+    fn default() -> Self {
+        Self {
+            components: std::array::from_fn(|_| T::default()),
+        }
+    }
+}
+
+impl<T: Clone + Default, const N: usize> SequentialSpec for Snapshot<T, N> {
+    type Op = SnapshotOp<T, N>;
+    type Ret = SnapshotRet<T>;
+
+    fn exec(&mut self, op: Self::Op) -> Self::Ret {
+        match op {
+            SnapshotOp::Update(index, value) => {
+                self.components[index] = value;
+                SnapshotRet::Update
+            }
+            SnapshotOp::Scan => SnapshotRet::Scan(self.components.to_vec()),
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: Arbitrary + Clone + 'static, const N: usize> Arbitrary for SnapshotOp<T, N> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            (0..N, any::<T>()).prop_map(|(index, value)| SnapshotOp::Update(index, value)),
+            Just(SnapshotOp::Scan),
+        ]
+        .boxed()
+    }
+}
+
+/// An operation on a [Consensus] object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConsensusOp<T> {
+    /// Propose the value.
+    Propose(T),
+}
+
+/// The result of a [ConsensusOp].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConsensusRet<T> {
+    /// The value that was decided.
+    Decided(T),
+}
+
+/// A consensus object: every proposal returns the value of the first one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Consensus<T> {
+    decided: Option<T>,
+}
+
+impl<T> Default for Consensus<T> {
+    fn default() -> Self {
+        Self { decided: None }
+    }
+}
+
+impl<T: Clone> SequentialSpec for Consensus<T> {
+    type Op = ConsensusOp<T>;
+    type Ret = ConsensusRet<T>;
+
+    fn exec(&mut self, op: Self::Op) -> Self::Ret {
+        let ConsensusOp::Propose(value) = op;
+        ConsensusRet::Decided(self.decided.get_or_insert(value).clone())
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: Arbitrary + 'static> Arbitrary for ConsensusOp<T> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<T>().prop_map(ConsensusOp::Propose).boxed()
+    }
+}
@@ -0,0 +1,247 @@
+//! Operations that may fail spuriously, e.g. the ones built on `compare_exchange_weak`.
+//!
+//! A weak compare-and-swap may fail even if the value is the expected one, so the result of an operation
+//! that gives up or retries after such a failure depends on non-determinism the sequential specification doesn't capture.
+//! The data structure declares every spurious failure with [spurious_failure], e.g. when `compare_exchange_weak`
+//! returns the expected value as the actual one, and the declarations are recorded next to the operations
+//! like the internal retries they cause, so they are displayed with the execution.
+//!
+//! The sequential specification decides what an operation that failed spuriously returns with [SpuriousSpec::exec_spurious].
+//! The checker accepts either result, but only for the operations that declared a spurious failure,
+//! so an undeclared failure is still a violation.
+//!
+//! Outside of [verify_spurious](crate::Lincheck::verify_spurious) the declarations are ignored.
+
+use proptest::prelude::*;
+use std::cell::Cell;
+use std::fmt::{self, Debug, Formatter};
+use std::panic::UnwindSafe;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use loom::thread;
+
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::nondeterminism::*;
+use crate::recorder::{self, *};
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{Lincheck, LoomConfig};
+
+/// The number of checks recording the spurious failures. Guards the access to the [loom] thread locals
+/// so that [spurious_failure] can be called outside of [loom] too. It is a counter rather than a flag,
+/// so that a check finishing on another thread doesn't stop the recording for the ones still running.
+static SPURIOUS_TRACKING: AtomicUsize = AtomicUsize::new(0);
+
+loom::thread_local! {
+    /// The number of spurious failures declared during the current operation.
+    static DECLARED: Cell<usize> = Cell::new(0);
+}
+
+/// Declares that the current operation observed a spurious failure,
+/// e.g. its `compare_exchange_weak` failed although the value was the expected one.
+///
+/// It must be called whether the operation retries or returns the failure.
+pub fn spurious_failure() {
+    if SPURIOUS_TRACKING.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+    DECLARED.with(|declared| declared.set(declared.get() + 1));
+}
+
+/// The sequential specification of a data structure whose operations may fail spuriously.
+pub trait SpuriousSpec: SequentialSpec + Clone {
+    /// Executes an operation that failed spuriously, usually returning the failure without changing the state.
+    fn exec_spurious(&mut self, op: Self::Op) -> Self::Ret;
+}
+
+/// An operation along with the number of spurious failures it declared.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Retried<Op> {
+    /// The operation.
+    pub op: Op,
+    /// The number of [spurious failures](spurious_failure) declared during the operation.
+    pub spurious_failures: usize,
+}
+
+impl<Op: Debug> Debug for Retried<Op> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.op.fmt(f)?;
+        if self.spurious_failures > 0 {
+            write!(f, " ~{} spurious", self.spurious_failures)?;
+        }
+        Ok(())
+    }
+}
+
+/// The sequential specification of the operations with spurious failures.
+#[derive(Clone, Default)]
+pub(crate) struct Spurious<Seq>(Seq);
+
+impl<Seq: SpuriousSpec> SequentialSpec for Spurious<Seq> {
+    type Op = Retried<Seq::Op>;
+    type Ret = Seq::Ret;
+
+    fn exec(&mut self, retried: Self::Op) -> Self::Ret {
+        self.0.exec(retried.op)
+    }
+
+    fn ret_matches(expected: &Self::Ret, actual: &Self::Ret) -> bool
+    where
+        Self::Ret: PartialEq,
+    {
+        Seq::ret_matches(expected, actual)
+    }
+}
+
+impl<Seq: SpuriousSpec> NonDeterministicSequentialSpec for Spurious<Seq>
+where
+    Seq::Op: Clone,
+{
+    fn exec_all(&self, retried: Self::Op) -> Vec<(Self::Ret, Self)> {
+        let mut next = self.clone();
+        let mut outcomes = vec![(next.0.exec(retried.op.clone()), next)];
+        if retried.spurious_failures > 0 {
+            let mut next = self.clone();
+            outcomes.push((next.0.exec_spurious(retried.op), next));
+        }
+        outcomes
+    }
+}
+
+impl Lincheck {
+    /// Verifies that the concurrent implementation `Conc` is linearizable
+    /// if the operations that declared a [spurious failure](spurious_failure) may fail as [SpuriousSpec::exec_spurious] says.
+    ///
+    /// See the [module-level documentation](crate::spurious) for the details.
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_spurious<Conc>(
+        &self,
+    ) -> Result<(), VerificationFailure<Retried<ConcOp<Conc>>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + SpuriousSpec + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        self.run_cases(
+            &self.scenarios(),
+            |scenario| {
+                scenario.map(|op: &ConcOp<Conc>| Retried {
+                    op: op.clone(),
+                    spurious_failures: 0,
+                })
+            },
+            |scenario| {
+                check_spurious_scenario_with_loom_using::<Conc>(&self.loom, scenario)
+                    .map_err(VerificationFailure::NonLinearizable)
+            },
+        )
+    }
+}
+
+/// Executes the given scenario recording the spurious failures
+/// and checks the resulting execution for linearizability inside [loom] model-checker.
+#[allow(clippy::type_complexity)]
+pub fn check_spurious_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+) -> Result<(), Execution<Retried<ConcOp<Conc>>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + SpuriousSpec + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    check_spurious_scenario_with_loom_using::<Conc>(&LoomConfig::default(), scenario)
+}
+
+/// Same as [check_spurious_scenario_with_loom] but configures loom with `loom`.
+#[allow(clippy::type_complexity)]
+pub(crate) fn check_spurious_scenario_with_loom_using<Conc>(
+    loom: &LoomConfig,
+    scenario: Scenario<ConcOp<Conc>>,
+) -> Result<(), Execution<Retried<ConcOp<Conc>>, ConcRet<Conc>>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    Conc::Seq: Default + SpuriousSpec + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    ConcRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+{
+    SPURIOUS_TRACKING.fetch_add(1, Ordering::Relaxed);
+    let result = find_failure_with_loom(loom, move || {
+        let execution = execute_spurious_scenario_with_loom::<Conc>(scenario.clone());
+        (!NonDeterministicChecker::<Spurious<Conc::Seq>>::check(&execution)).then_some(execution)
+    });
+    SPURIOUS_TRACKING.fetch_sub(1, Ordering::Relaxed);
+    result
+}
+
+/// Executes the given scenario with [loom] mock threads recording the spurious failures
+/// and returns the resulting execution.
+pub fn execute_spurious_scenario_with_loom<Conc>(
+    scenario: Scenario<ConcOp<Conc>>,
+) -> Execution<Retried<ConcOp<Conc>>, ConcRet<Conc>>
+where
+    Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+    ConcOp<Conc>: Send + Sync + Clone + 'static,
+{
+    let conc = Rc::new(Conc::default());
+
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+
+    // init part
+    for op in scenario.init_part {
+        recorder.record(op.clone(), || exec(&*conc, op));
+    }
+
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum();
+    let recorder = Rc::new(recorder.record_parallel_part_with_capacity(total_parallel_ops));
+
+    // parallel part
+    let handles: Vec<_> = scenario
+        .parallel_part
+        .into_iter()
+        .map(|thread_ops| {
+            let conc = conc.clone();
+            let recorder = recorder.clone();
+
+            thread::spawn(move || {
+                let mut recorder = recorder.record_thread_with_capacity(thread_ops.len());
+                for op in thread_ops {
+                    recorder.record(op.clone(), || exec(&*conc, op));
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    // post part
+    let mut recorder = recorder.record_post_part_with_capacity(scenario.post_part.len());
+    for op in scenario.post_part {
+        recorder.record(op.clone(), || exec(&*conc, op));
+    }
+
+    // the spurious failures are recorded along with the results and moved to the operations afterwards
+    recorder.finish().map(|op, (ret, spurious_failures)| {
+        (
+            Retried {
+                op,
+                spurious_failures,
+            },
+            ret,
+        )
+    })
+}
+
+/// Executes the operation and takes the spurious failures declared during it.
+fn exec<Conc: ConcurrentSpec>(conc: &Conc, op: ConcOp<Conc>) -> (ConcRet<Conc>, usize) {
+    let ret = conc.exec(op);
+    let spurious_failures = DECLARED.with(|declared| declared.replace(0));
+    (ret, spurious_failures)
+}
@@ -0,0 +1,128 @@
+//! Statistics of a verification run, e.g. to tune [num_ops](crate::Lincheck::num_ops)
+//! and [num_threads](crate::Lincheck::num_threads) for coverage vs. runtime.
+
+#[cfg(feature = "proptest")]
+use proptest::{prelude::*, test_runner::TestError};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+#[cfg(feature = "proptest")]
+use std::{cell::Cell, fmt::Debug, panic::UnwindSafe, sync::Arc, time::Instant};
+
+use crate::error::VerificationFailure;
+use crate::execution::*;
+#[cfg(feature = "proptest")]
+use crate::{spec::*, Constructors, Lincheck};
+
+/// The outcome of [run](Lincheck::run) together with the statistics of the run.
+#[derive(Clone, Debug)]
+pub struct RunReport<Op, Ret> {
+    /// The same as the result of [verify](Lincheck::verify).
+    pub result: Result<(), VerificationFailure<Op, Ret>>,
+    /// The number of generated scenarios, including the failing one.
+    pub scenarios: usize,
+    /// The number of times a scenario was rerun while shrinking the failing one.
+    pub shrink_iterations: usize,
+    /// The total number of interleavings explored by [loom], including the ones explored while shrinking.
+    pub interleavings: usize,
+    /// The number of checked executions by the number of their invocations.
+    pub history_lengths: BTreeMap<usize, usize>,
+    /// The total number of allocations [tracked](crate::leaks) in the executions,
+    /// if [check_leaks](crate::LoomConfig::check_leaks) is set.
+    pub allocations: usize,
+    /// The total time spent checking the executions for the [correctness](Lincheck::correctness) condition.
+    pub checker_time: Duration,
+    /// The total duration of the run.
+    pub elapsed: Duration,
+}
+
+/// The statistics collected inside the [loom] model.
+#[derive(Default)]
+pub(crate) struct RunStats {
+    interleavings: AtomicUsize,
+    allocations: AtomicUsize,
+    history_lengths: Mutex<BTreeMap<usize, usize>>,
+    checker_time: Mutex<Duration>,
+}
+
+impl RunStats {
+    pub(crate) fn record_interleaving(&self) {
+        self.interleavings.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_allocations(&self, allocations: usize) {
+        self.allocations.fetch_add(allocations, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_check<Op, Ret>(&self, execution: &Execution<Op, Ret>, elapsed: Duration) {
+        let length = execution.init_part.len()
+            + execution.parallel_part.len()
+            + execution.pending.len()
+            + execution.post_part.len();
+        *self
+            .history_lengths
+            .lock()
+            .unwrap()
+            .entry(length)
+            .or_default() += 1;
+        *self.checker_time.lock().unwrap() += elapsed;
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl Lincheck {
+    /// The same as [verify](Lincheck::verify) but also reports how much work the run took.
+    pub fn run<Conc>(&self) -> RunReport<ConcOp<Conc>, ConcRet<Conc>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let start = Instant::now();
+        let constructors = Constructors::new(Conc::default, Conc::Seq::default);
+        let stats = Arc::new(RunStats::default());
+        let (scenarios, shrink_iterations, failed) = (Cell::new(0), Cell::new(0), Cell::new(false));
+
+        let result = self.runner().run(&self.scenarios(), |scenario| {
+            // every test case after the first failure is a shrinking step
+            if failed.get() {
+                shrink_iterations.set(shrink_iterations.get() + 1);
+            } else {
+                scenarios.set(scenarios.get() + 1);
+            }
+            // a panicking scenario counts as a failure too
+            let failed_before = failed.replace(true);
+            let result =
+                self.check_scenario_recording(scenario, &constructors, Some(stats.clone()));
+            if result.is_ok() {
+                failed.set(failed_before);
+            }
+            result.map_err(|_| TestCaseError::Fail("Non-linearizable execution".into()))
+        });
+
+        let result = match result {
+            Ok(_) => Ok(()),
+            Err(TestError::Fail(_, scenario)) => {
+                Err(self.diagnose_failure(scenario, &constructors))
+            }
+            Err(TestError::Abort(reason)) => {
+                Err(VerificationFailure::GeneratorError(reason.to_string()))
+            }
+        };
+
+        let history_lengths = stats.history_lengths.lock().unwrap().clone();
+        let checker_time = *stats.checker_time.lock().unwrap();
+        RunReport {
+            result,
+            scenarios: scenarios.get(),
+            shrink_iterations: shrink_iterations.get(),
+            interleavings: stats.interleavings.load(Ordering::Relaxed),
+            history_lengths,
+            allocations: stats.allocations.load(Ordering::Relaxed),
+            checker_time,
+            elapsed: start.elapsed(),
+        }
+    }
+}
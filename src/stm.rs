@@ -0,0 +1,274 @@
+//! Opacity checking for software transactional memory.
+//!
+//! Here the operations of a [Scenario] are [Transaction]s, i.e. sequences of operations of the
+//! sequential specification that must appear to execute atomically. A transaction may abort,
+//! in which case its effects are discarded.
+//!
+//! [Opacity](https://doi.org/10.1145/1345206.1345233) requires that:
+//! - committed transactions are linearizable as a whole,
+//! - even aborted transactions observe a consistent snapshot, i.e. the results they observed before aborting
+//!   are the results of executing them alone at some point of the linearization.
+
+use proptest::prelude::*;
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// A sequence of operations that must appear to execute atomically.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Transaction<Op>(pub Vec<Op>);
+
+/// The outcome of a [Transaction].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TxOutcome<Ret> {
+    /// The transaction committed with the results of all its operations.
+    Committed(Vec<Ret>),
+    /// The transaction aborted after observing the results of its first operations.
+    Aborted(Vec<Ret>),
+}
+
+impl<Op: Arbitrary + 'static> Arbitrary for Transaction<Op> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop::collection::vec(any::<Op>(), 1..=3)
+            .prop_map(Transaction)
+            .boxed()
+    }
+}
+
+/// The transactional implementation of a data structure.
+pub trait TransactionalSpec: Default {
+    /// The sequential specification for the operations inside transactions.
+    type Seq: SequentialSpec + Default;
+
+    /// Executes a transaction on the data structure.
+    fn exec_transaction(&self, tx: &[TxOp<Self>]) -> TxOutcome<TxRet<Self>>;
+}
+
+/// Type alias not to have always write down FQP.
+pub type TxOp<T> = <<T as TransactionalSpec>::Seq as SequentialSpec>::Op;
+
+/// Type alias not to have always write down FQP.
+pub type TxRet<T> = <<T as TransactionalSpec>::Seq as SequentialSpec>::Ret;
+
+/// Adapts a [TransactionalSpec] to a [ConcurrentSpec] executing whole transactions
+/// so that scenarios of transactions can be executed as usual.
+#[derive(Default)]
+struct Stm<T>(T);
+
+/// The sequential specification executing whole transactions one by one.
+#[derive(Default)]
+struct Serial<Seq>(Seq);
+
+impl<Seq: SequentialSpec> SequentialSpec for Serial<Seq> {
+    type Op = Transaction<Seq::Op>;
+    type Ret = TxOutcome<Seq::Ret>;
+
+    fn exec(&mut self, tx: Self::Op) -> Self::Ret {
+        TxOutcome::Committed(tx.0.into_iter().map(|op| self.0.exec(op)).collect())
+    }
+}
+
+impl<T: TransactionalSpec> ConcurrentSpec for Stm<T>
+where
+    TxOp<T>: Clone,
+{
+    type Seq = Serial<T::Seq>;
+
+    fn exec(&self, tx: Transaction<TxOp<T>>) -> TxOutcome<TxRet<T>> {
+        self.0.exec_transaction(&tx.0)
+    }
+}
+
+/// The opacity checker.
+///
+/// Like [LinearizabilityChecker](crate::checker::LinearizabilityChecker), it brute-forces
+/// the topological orderings of the happens-before graph. Committed transactions are applied to the
+/// sequential specification as a whole. Aborted transactions are only checked against a copy of it.
+pub struct OpacityChecker<Seq> {
+    _seq: std::marker::PhantomData<Seq>,
+}
+
+impl<Seq> OpacityChecker<Seq>
+where
+    Seq: SequentialSpec + Default + Clone,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq,
+{
+    /// Checks if the given execution of transactions is opaque with respect to the sequential specification `Seq`.
+    pub fn check(execution: &Execution<Transaction<Seq::Op>, TxOutcome<Seq::Ret>>) -> bool {
+        let mut state = Seq::default();
+        if !execution
+            .init_part
+            .iter()
+            .all(|inv| Self::apply(&mut state, &inv.op, &inv.ret))
+        {
+            return false;
+        }
+
+        let mut linearized = vec![false; execution.parallel_part.len()];
+        Self::check_parallel_part(execution, &mut linearized, state)
+    }
+
+    fn check_parallel_part(
+        execution: &Execution<Transaction<Seq::Op>, TxOutcome<Seq::Ret>>,
+        linearized: &mut [bool],
+        state: Seq,
+    ) -> bool {
+        let parallel_part = &execution.parallel_part;
+        if linearized.iter().all(|&linearized| linearized) {
+            let mut state = state;
+            return execution
+                .post_part
+                .iter()
+                .all(|inv| Self::apply(&mut state, &inv.op, &inv.ret));
+        }
+
+        (0..parallel_part.len()).any(|inv_id| {
+            let inv = &parallel_part[inv_id];
+            let is_minimal = !linearized[inv_id]
+                && parallel_part
+                    .iter()
+                    .zip(linearized.iter())
+                    .all(|(other, &linearized)| {
+                        linearized || other.return_timestamp > inv.call_timestamp
+                    });
+            if !is_minimal {
+                return false;
+            }
+
+            let mut next_state = state.clone();
+            if !Self::apply(&mut next_state, &inv.op, &inv.ret) {
+                return false;
+            }
+
+            linearized[inv_id] = true;
+            let result = Self::check_parallel_part(execution, linearized, next_state);
+            linearized[inv_id] = false;
+            result
+        })
+    }
+
+    /// Applies the transaction to the state and checks the observed results.
+    /// The effects of an aborted transaction are discarded.
+    fn apply(state: &mut Seq, tx: &Transaction<Seq::Op>, outcome: &TxOutcome<Seq::Ret>) -> bool {
+        match outcome {
+            TxOutcome::Committed(rets) => {
+                rets.len() == tx.0.len()
+                    && tx
+                        .0
+                        .iter()
+                        .zip(rets)
+                        .all(|(op, ret)| Seq::ret_matches(&state.exec(op.clone()), ret))
+            }
+            TxOutcome::Aborted(rets) => {
+                let mut snapshot = state.clone();
+                rets.len() <= tx.0.len()
+                    && tx
+                        .0
+                        .iter()
+                        .zip(rets)
+                        .all(|(op, ret)| Seq::ret_matches(&snapshot.exec(op.clone()), ret))
+            }
+        }
+    }
+}
+
+impl Lincheck {
+    /// Verifies that the transactional implementation `T` is opaque with respect to its sequential specification.
+    ///
+    /// It returns a non-opaque execution if the test fails.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_opacity<T>(
+        &self,
+    ) -> Result<(), VerificationFailure<Transaction<TxOp<T>>, TxOutcome<TxRet<T>>>>
+    where
+        T: TransactionalSpec + Send + Sync + 'static,
+        T::Seq: Clone + Send + Sync + 'static,
+        TxOp<T>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        TxRet<T>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        let check = |execution: &Execution<_, _>| OpacityChecker::<T::Seq>::check(execution);
+
+        self.run_cases(&self.scenarios(), Scenario::clone, |scenario| {
+            check_scenario_with_loom_using::<Stm<T>>(&self.loom, scenario, check)
+                .map_err(VerificationFailure::NonLinearizable)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Op {
+        Read(usize),
+        Write(usize, u8),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Ret {
+        Read(u8),
+        Write,
+    }
+
+    #[derive(Default, Clone)]
+    struct SequentialRegisters {
+        values: [u8; 2],
+    }
+
+    impl SequentialSpec for SequentialRegisters {
+        type Op = Op;
+        type Ret = Ret;
+
+        fn exec(&mut self, op: Op) -> Ret {
+            match op {
+                Op::Read(i) => Ret::Read(self.values[i]),
+                Op::Write(i, v) => {
+                    self.values[i] = v;
+                    Ret::Write
+                }
+            }
+        }
+    }
+
+    fn write_both_then_read_aborted(
+        observed: Vec<Ret>,
+    ) -> Execution<Transaction<Op>, TxOutcome<Ret>> {
+        let mut recorder = record_init_part();
+        recorder.record(Transaction(vec![Op::Write(0, 1), Op::Write(1, 1)]), || {
+            TxOutcome::Committed(vec![Ret::Write, Ret::Write])
+        });
+
+        let mut recorder = recorder.record_post_part();
+        recorder.record(Transaction(vec![Op::Read(0), Op::Read(1)]), || {
+            TxOutcome::Aborted(observed)
+        });
+        recorder.record(Transaction(vec![Op::Read(1)]), || {
+            TxOutcome::Committed(vec![Ret::Read(1)])
+        });
+
+        recorder.finish()
+    }
+
+    #[test]
+    fn aborted_transaction_observing_snapshot_is_opaque() {
+        let execution = write_both_then_read_aborted(vec![Ret::Read(1), Ret::Read(1)]);
+        assert!(OpacityChecker::<SequentialRegisters>::check(&execution));
+    }
+
+    #[test]
+    fn aborted_transaction_observing_inconsistent_state_is_not_opaque() {
+        let execution = write_both_then_read_aborted(vec![Ret::Read(1), Ret::Read(0)]);
+        assert!(!OpacityChecker::<SequentialRegisters>::check(&execution));
+    }
+}
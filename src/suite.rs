@@ -0,0 +1,170 @@
+//! Verifying several data structures in one go.
+//!
+//! A crate with many concurrent data structures would otherwise need a test function per structure.
+//! A [Suite] collects the structures, each with its own configuration, verifies them one after another,
+//! and reports all the results together, so a failure of one structure doesn't hide the others.
+//!
+//! ```no_run
+//! # use lincheck::{suite, Lincheck};
+//! # #[derive(Default)] struct Queue;
+//! # #[derive(Default)] struct Stack;
+//! # #[derive(Default)] struct SeqQueue;
+//! # impl lincheck::SequentialSpec for SeqQueue { type Op = bool; type Ret = (); fn exec(&mut self, _: bool) {} }
+//! # impl lincheck::ConcurrentSpec for Queue { type Seq = SeqQueue; fn exec(&self, _: bool) {} }
+//! # impl lincheck::ConcurrentSpec for Stack { type Seq = SeqQueue; fn exec(&self, _: bool) {} }
+//! suite()
+//!     .register::<Queue>(Lincheck::default())
+//!     .register::<Stack>(Lincheck {
+//!         num_threads: 3,
+//!         ..Default::default()
+//!     })
+//!     .run_or_panic();
+//! ```
+
+use proptest::prelude::*;
+use std::any;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe, UnwindSafe};
+use std::time::{Duration, Instant};
+
+use crate::deadlock::panic_message;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// Creates an empty [Suite].
+pub fn suite() -> Suite {
+    Suite::default()
+}
+
+/// The data structures to verify, each with its own configuration.
+#[derive(Default)]
+pub struct Suite {
+    entries: Vec<Entry>,
+}
+
+/// A data structure of a [Suite] with its type erased.
+struct Entry {
+    name: String,
+    verify: Box<dyn Fn() -> SuiteResult>,
+}
+
+/// The summary of [Suite::run].
+#[derive(Clone, Debug)]
+pub struct SuiteReport {
+    /// The results of the data structures in the order they were registered.
+    pub results: Vec<SuiteResult>,
+    /// The total duration of the run.
+    pub elapsed: Duration,
+}
+
+/// The result of verifying a data structure of a [Suite].
+#[derive(Clone, Debug)]
+pub struct SuiteResult {
+    /// The name of the data structure, its type name unless given with [register_named](Suite::register_named).
+    pub name: String,
+    /// The number of generated scenarios, including the failing one.
+    pub scenarios: usize,
+    /// The total number of interleavings explored by [loom].
+    pub interleavings: usize,
+    /// How long the verification took, including shrinking.
+    pub elapsed: Duration,
+    /// The printed failure, or [None] if the data structure passed.
+    pub failure: Option<String>,
+}
+
+impl Suite {
+    /// Adds the data structure `Conc`, verified with `config` like [verify](Lincheck::verify).
+    pub fn register<Conc>(self, config: Lincheck) -> Self
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        self.register_named::<Conc>(any::type_name::<Conc>(), config)
+    }
+
+    /// The same as [register](Suite::register) but names the data structure in the report,
+    /// e.g. to register the same one with different configurations.
+    pub fn register_named<Conc>(mut self, name: impl Into<String>, config: Lincheck) -> Self
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        Conc::Seq: Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Debug + Send + Clone,
+    {
+        let name = name.into();
+        let verify = {
+            let name = name.clone();
+            move || {
+                let start = Instant::now();
+                // a panic outside of the scenarios, e.g. in the generator, fails only this data structure
+                let report = panic::catch_unwind(AssertUnwindSafe(|| config.run::<Conc>()));
+                let (scenarios, interleavings, failure) = match report {
+                    Ok(report) => (
+                        report.scenarios,
+                        report.interleavings,
+                        report
+                            .result
+                            .err()
+                            .map(|failure| match config.output_width {
+                                Some(width) => format!("{:width$}", failure, width = width),
+                                None => failure.to_string(),
+                            }),
+                    ),
+                    Err(payload) => {
+                        let message = panic_message(&*payload).unwrap_or("Box<dyn Any>");
+                        (0, 0, Some(format!("panicked: {}", message)))
+                    }
+                };
+                SuiteResult {
+                    name: name.clone(),
+                    scenarios,
+                    interleavings,
+                    elapsed: start.elapsed(),
+                    failure,
+                }
+            }
+        };
+        self.entries.push(Entry {
+            name,
+            verify: Box::new(verify),
+        });
+        self
+    }
+
+    /// Verifies the data structures one after another, printing the name of each to stderr as it starts.
+    pub fn run(self) -> SuiteReport {
+        let start = Instant::now();
+        let results = (self.entries.iter())
+            .map(|entry| {
+                eprintln!("lincheck: verifying {}", entry.name);
+                (entry.verify)()
+            })
+            .collect();
+        SuiteReport {
+            results,
+            elapsed: start.elapsed(),
+        }
+    }
+
+    /// The same as [run](Suite::run) but panics with the report if any data structure failed.
+    pub fn run_or_panic(self) {
+        let report = self.run();
+        if !report.passed() {
+            panic!("{}", report);
+        }
+    }
+}
+
+impl SuiteReport {
+    /// Whether every data structure passed.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.failure.is_none())
+    }
+
+    /// The results of the data structures that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &SuiteResult> + '_ {
+        (self.results.iter()).filter(|result| result.failure.is_some())
+    }
+}
@@ -0,0 +1,250 @@
+//! Concurrent-drop testing.
+//!
+//! The three-phase scenarios never drop the data structure while it is in use.
+//! Here the data structure is owned by the first thread of the parallel part, which drops it
+//! after executing its operations, while the other threads still execute theirs through [handles](DroppableSpec::Handle).
+//!
+//! Dropping is modeled as the [close](ClosableSpec::close) operation of the sequential specification.
+//! So every operation racing with the drop must either complete as if it was executed before the drop
+//! or return what the sequential specification returns after closing, e.g. a "closed" error.
+//! There is no post part since there is nothing to execute it on.
+
+use proptest::prelude::*;
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+use std::rc::Rc;
+
+use loom::thread;
+
+use crate::checker::*;
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::recorder::{self, *};
+use crate::scenario::*;
+use crate::spec::*;
+use crate::{scenario_strategy, Lincheck, LoomConfig};
+
+/// The sequential specification of a data structure that can be closed by dropping it.
+pub trait ClosableSpec: SequentialSpec {
+    /// Closes the data structure. The subsequent operations must return what it should return after being dropped.
+    fn close(&mut self);
+}
+
+/// The concurrent implementation of a data structure that can be dropped while its handles are still in use.
+pub trait DroppableSpec: Default {
+    /// The sequential specification for the data structure.
+    type Seq: ClosableSpec + Default;
+
+    /// A handle to the data structure that may outlive it, e.g. the other end of a channel or a weak reference.
+    type Handle;
+
+    /// Creates a new handle to the data structure.
+    fn handle(&self) -> Self::Handle;
+
+    /// Executes an operation on the data structure through the handle.
+    fn exec(handle: &Self::Handle, op: DropOp<Self>) -> DropRet<Self>;
+}
+
+/// Type alias not to have always write down FQP.
+pub type DropOp<T> = <<T as DroppableSpec>::Seq as SequentialSpec>::Op;
+
+/// Type alias not to have always write down FQP.
+pub type DropRet<T> = <<T as DroppableSpec>::Seq as SequentialSpec>::Ret;
+
+/// An operation of a concurrent-drop execution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TeardownOp<Op> {
+    /// An operation on the data structure.
+    Op(Op),
+    /// Dropping the data structure.
+    Drop,
+}
+
+/// The result of a [TeardownOp].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TeardownRet<Ret> {
+    /// The result of an operation on the data structure.
+    Ret(Ret),
+    /// The data structure was dropped.
+    Dropped,
+}
+
+/// The sequential specification of a concurrent-drop execution.
+#[derive(Default)]
+struct Teardown<Seq>(Seq);
+
+impl<Seq> SequentialSpec for Teardown<Seq>
+where
+    Seq: ClosableSpec,
+    Seq::Ret: PartialEq,
+{
+    type Op = TeardownOp<Seq::Op>;
+    type Ret = TeardownRet<Seq::Ret>;
+
+    fn exec(&mut self, op: Self::Op) -> Self::Ret {
+        match op {
+            TeardownOp::Op(op) => TeardownRet::Ret(self.0.exec(op)),
+            TeardownOp::Drop => {
+                self.0.close();
+                TeardownRet::Dropped
+            }
+        }
+    }
+
+    fn ret_matches(expected: &Self::Ret, actual: &Self::Ret) -> bool {
+        match (expected, actual) {
+            (TeardownRet::Ret(expected), TeardownRet::Ret(actual)) => {
+                Seq::ret_matches(expected, actual)
+            }
+            (expected, actual) => expected == actual,
+        }
+    }
+}
+
+impl Lincheck {
+    /// Verifies that the data structure `D` is linearizable even if it is dropped concurrently with its operations.
+    ///
+    /// See the [module-level documentation](crate::teardown) for the details.
+    /// The scenarios have at least two threads regardless of [num_threads](Lincheck::num_threads).
+    ///
+    /// It returns a [VerificationFailure] if the test fails, e.g. a non-linearizable execution or a panic.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_drop<D>(
+        &self,
+    ) -> Result<(), VerificationFailure<TeardownOp<DropOp<D>>, TeardownRet<DropRet<D>>>>
+    where
+        D: DroppableSpec + 'static,
+        D::Handle: 'static,
+        DropOp<D>: Send + Sync + Clone + Arbitrary + Debug + UnwindSafe + 'static,
+        DropRet<D>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        let strategy = scenario_strategy(
+            2..=self.num_threads.max(2),
+            self.num_ops,
+            &self.thread_distribution,
+            None,
+            ScenarioConfig::default(),
+        )
+        .prop_map(|scenario: Scenario<DropOp<D>>| Scenario {
+            post_part: Vec::new(),
+            ..scenario
+        });
+
+        self.run_cases(
+            &strategy,
+            |scenario| scenario.map(|op: &DropOp<D>| TeardownOp::Op(op.clone())),
+            |scenario| {
+                check_drop_scenario_with_loom_using::<D>(&self.loom, scenario)
+                    .map_err(VerificationFailure::NonLinearizable)
+            },
+        )
+    }
+}
+
+/// Executes the given scenario dropping the data structure on the first thread and checks the resulting execution
+/// for linearizability inside [loom] model-checker.
+#[allow(clippy::type_complexity)]
+pub fn check_drop_scenario_with_loom<D>(
+    scenario: Scenario<DropOp<D>>,
+) -> Result<(), Execution<TeardownOp<DropOp<D>>, TeardownRet<DropRet<D>>>>
+where
+    D: DroppableSpec + 'static,
+    D::Handle: 'static,
+    DropOp<D>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    DropRet<D>: PartialEq + Debug + Send + Clone + 'static,
+{
+    check_drop_scenario_with_loom_using::<D>(&LoomConfig::default(), scenario)
+}
+
+/// Same as [check_drop_scenario_with_loom] but configures loom with `loom`.
+#[allow(clippy::type_complexity)]
+pub(crate) fn check_drop_scenario_with_loom_using<D>(
+    loom: &LoomConfig,
+    scenario: Scenario<DropOp<D>>,
+) -> Result<(), Execution<TeardownOp<DropOp<D>>, TeardownRet<DropRet<D>>>>
+where
+    D: DroppableSpec + 'static,
+    D::Handle: 'static,
+    DropOp<D>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+    DropRet<D>: PartialEq + Debug + Send + Clone + 'static,
+{
+    find_failure_with_loom(loom, move || {
+        let execution = execute_drop_scenario_with_loom::<D>(scenario.clone());
+        (!LinearizabilityChecker::<Teardown<D::Seq>>::check(&execution)).then_some(execution)
+    })
+}
+
+/// Executes the given scenario with [loom] mock threads dropping the data structure
+/// after the operations of the first thread and returns the resulting execution.
+///
+/// The post part of the scenario must be empty.
+pub fn execute_drop_scenario_with_loom<D>(
+    scenario: Scenario<DropOp<D>>,
+) -> Execution<TeardownOp<DropOp<D>>, TeardownRet<DropRet<D>>>
+where
+    D: DroppableSpec + 'static,
+    D::Handle: 'static,
+    DropOp<D>: Clone + 'static,
+    DropRet<D>: 'static,
+{
+    assert!(
+        scenario.post_part.is_empty(),
+        "the data structure is dropped before the post part"
+    );
+
+    let owner = D::default();
+
+    // init part
+    let handle = owner.handle();
+    let mut recorder = recorder::record_init_part_with_capacity(scenario.init_part.len());
+    for op in scenario.init_part {
+        recorder.record(TeardownOp::Op(op.clone()), || {
+            TeardownRet::Ret(D::exec(&handle, op))
+        });
+    }
+    drop(handle);
+
+    // the first thread also records dropping the data structure
+    let total_parallel_ops = scenario.parallel_part.iter().map(Vec::len).sum::<usize>() + 1;
+    let recorder = Rc::new(recorder.record_parallel_part_with_capacity(total_parallel_ops));
+
+    // parallel part
+    let thread_handles: Vec<_> = scenario
+        .parallel_part
+        .iter()
+        .map(|_| owner.handle())
+        .collect();
+    let mut owner = Some(owner);
+    let handles: Vec<_> = scenario
+        .parallel_part
+        .into_iter()
+        .zip(thread_handles)
+        .map(|(thread_ops, handle)| {
+            let owner = owner.take();
+            let recorder = recorder.clone();
+
+            thread::spawn(move || {
+                let mut recorder = recorder.record_thread_with_capacity(thread_ops.len() + 1);
+                for op in thread_ops {
+                    recorder.record(TeardownOp::Op(op.clone()), || {
+                        TeardownRet::Ret(D::exec(&handle, op))
+                    });
+                }
+                if let Some(owner) = owner {
+                    recorder.record(TeardownOp::Drop, || {
+                        drop(owner);
+                        TeardownRet::Dropped
+                    });
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    Rc::try_unwrap(recorder)
+        .unwrap_or_else(|_| unreachable!("all the threads are joined"))
+        .finish()
+}
@@ -0,0 +1,270 @@
+//! Fuzzing asynchronous data structures on a real Tokio runtime.
+//!
+//! [loom] explores every interleaving, but only of the synchronization it models.
+//! Asynchronous data structures often rely on behavior it can't model, e.g. the wakers of IO resources or timers.
+//! [verify_tokio](crate::Lincheck::verify_tokio) runs such an [AsyncConcurrentSpec] on a multi-threaded Tokio runtime instead,
+//! with a task per thread of the parallel part, and checks the recorded executions like [verify](crate::Lincheck::verify).
+//!
+//! The tasks yield to the scheduler a random number of times around each operation to shake up the interleavings,
+//! and every scenario is run [several times](TokioConfig::runs_per_scenario). Unlike with [loom], a passing run
+//! doesn't prove anything, and a failing scenario may not fail every time it is rerun while shrinking.
+//!
+//! The operations that [may be cancelled](AsyncConcurrentSpec::may_cancel) are sometimes dropped mid-flight,
+//! after a random number of polls, to test the cancel safety of the data structure. The thread goes on
+//! with its next operation, and the dropped one is recorded as [cancelled](PendingInvocation::cancel_timestamp):
+//! it must have taken effect before it was dropped or not at all.
+
+use proptest::prelude::*;
+use proptest::test_runner::{RngAlgorithm, TestRng};
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::future::Future;
+use std::panic;
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::Poll;
+
+use tokio::runtime::{Builder, Runtime};
+use tokio::task;
+
+use crate::error::VerificationFailure;
+use crate::execution::*;
+use crate::scenario::*;
+use crate::spec::*;
+use crate::Lincheck;
+
+/// The asynchronous implementation of a data structure.
+pub trait AsyncConcurrentSpec: Send + Sync {
+    /// The sequential specification for the data structure.
+    type Seq: SequentialSpec;
+
+    /// Executes an operation on the data structure.
+    fn exec(&self, op: AsyncOp<Self>) -> impl Future<Output = AsyncRet<Self>> + Send;
+
+    /// Whether the future of the operation may be dropped before it completes, e.g. a `recv` raced with a timeout.
+    /// No operation may be cancelled by default.
+    fn may_cancel(_op: &AsyncOp<Self>) -> bool {
+        false
+    }
+}
+
+/// Type alias not to have always write down FQP.
+pub type AsyncOp<T> = <<T as AsyncConcurrentSpec>::Seq as SequentialSpec>::Op;
+
+/// Type alias not to have always write down FQP.
+pub type AsyncRet<T> = <<T as AsyncConcurrentSpec>::Seq as SequentialSpec>::Ret;
+
+/// The configuration of [verify_tokio](Lincheck::verify_tokio).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokioConfig {
+    /// The number of worker threads of the runtime.
+    pub worker_threads: usize,
+    /// How many times each scenario is run, each time with other yields.
+    pub runs_per_scenario: usize,
+    /// The maximum number of yields before and after each operation of the parallel part.
+    pub max_yields: usize,
+    /// The maximum number of polls after which the future of an operation that
+    /// [may be cancelled](AsyncConcurrentSpec::may_cancel) is dropped. Zero never cancels the operations.
+    pub max_polls_before_cancel: usize,
+}
+
+impl Default for TokioConfig {
+    fn default() -> Self {
+        Self {
+            worker_threads: 4,
+            runs_per_scenario: 100,
+            max_yields: 3,
+            max_polls_before_cancel: 3,
+        }
+    }
+}
+
+impl Lincheck {
+    /// Verifies that the asynchronous implementation `Conc` is linearizable with respect to its sequential specification
+    /// by running the scenarios on a multi-threaded Tokio runtime.
+    ///
+    /// See the [module-level documentation](crate::tokio_fuzz) for the details.
+    /// It returns a [VerificationFailure] if the test fails, e.g. the last failing execution of the shrunk scenario
+    /// or a panic of an operation.
+    pub fn verify_tokio<Conc>(
+        &self,
+        config: &TokioConfig,
+    ) -> Result<(), VerificationFailure<AsyncOp<Conc>, AsyncRet<Conc>>>
+    where
+        Conc: AsyncConcurrentSpec + Default + 'static,
+        Conc::Seq: Default,
+        AsyncOp<Conc>: Send + Sync + Clone + Arbitrary + Debug + 'static,
+        AsyncRet<Conc>: PartialEq + Debug + Send + Clone + 'static,
+    {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(config.worker_threads)
+            .build()
+            .expect("failed to build the Tokio runtime");
+        let mut seed = [0; 32];
+        self.runner().rng().fill_bytes(&mut seed);
+        let rng = RefCell::new(TestRng::from_seed(RngAlgorithm::ChaCha, &seed));
+
+        self.run_cases(&self.scenarios(), Scenario::clone, |scenario| {
+            for _ in 0..config.runs_per_scenario {
+                let schedule = random_schedule::<Conc>(&scenario, config, &mut rng.borrow_mut());
+                let execution = execute_scenario_with_tokio::<Conc>(&runtime, &scenario, schedule);
+                let linearizable = (self.correctness).check_within(
+                    &execution,
+                    &Conc::Seq::default,
+                    self.checker.as_ref(),
+                    self.check_timeout,
+                );
+                // a timed out check passes, as the scenario may not fail again anyway
+                if linearizable == Some(false) {
+                    return Err(VerificationFailure::NonLinearizable(execution));
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// How a task runs an operation of the parallel part.
+#[derive(Clone, Copy, Debug)]
+struct OpSchedule {
+    yields_before: usize,
+    yields_after: usize,
+    cancel_after_polls: Option<usize>, // the number of polls after which the operation is dropped, if it is
+}
+
+/// The schedule of each operation of each thread of the parallel part.
+/// Half of the operations that may be cancelled are.
+fn random_schedule<Conc: AsyncConcurrentSpec>(
+    scenario: &Scenario<AsyncOp<Conc>>,
+    config: &TokioConfig,
+    rng: &mut TestRng,
+) -> Vec<Vec<OpSchedule>> {
+    let mut random = |max: usize| rng.next_u32() as usize % (max + 1);
+    (scenario.parallel_part.iter())
+        .map(|ops| {
+            ops.iter()
+                .map(|op| OpSchedule {
+                    yields_before: random(config.max_yields),
+                    yields_after: random(config.max_yields),
+                    cancel_after_polls: (config.max_polls_before_cancel > 0
+                        && Conc::may_cancel(op)
+                        && random(1) == 0)
+                        .then(|| 1 + random(config.max_polls_before_cancel - 1)),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Polls the future at most `max_polls` times and drops it if it isn't ready by then.
+/// The task is rescheduled after each poll, so the future is dropped even if it waits for something that never happens.
+async fn poll_at_most<F: Future>(future: F, max_polls: usize) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let mut polls = 0;
+    std::future::poll_fn(|cx| {
+        if let Poll::Ready(ret) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(ret));
+        }
+        polls += 1;
+        if polls == max_polls {
+            return Poll::Ready(None);
+        }
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
+
+/// Executes the scenario on the runtime with a task per thread of the parallel part,
+/// running the operations as given by `schedule`.
+fn execute_scenario_with_tokio<Conc>(
+    runtime: &Runtime,
+    scenario: &Scenario<AsyncOp<Conc>>,
+    schedule: Vec<Vec<OpSchedule>>,
+) -> Execution<AsyncOp<Conc>, AsyncRet<Conc>>
+where
+    Conc: AsyncConcurrentSpec + Default + 'static,
+    AsyncOp<Conc>: Send + Sync + Clone + 'static,
+    AsyncRet<Conc>: Send + 'static,
+{
+    let conc = Arc::new(Conc::default());
+    runtime.block_on(async {
+        let mut init_part = History::with_capacity(scenario.init_part.len());
+        for op in scenario.init_part.iter() {
+            let ret = conc.exec(op.clone()).await;
+            init_part.push(Invocation {
+                op: op.clone(),
+                ret,
+            });
+        }
+
+        let timer = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (scenario.parallel_part.iter().zip(schedule).enumerate())
+            .map(|(thread_id, (ops, schedule))| {
+                let (conc, timer, ops) = (conc.clone(), timer.clone(), ops.clone());
+                task::spawn(async move {
+                    let mut invocations = Vec::with_capacity(ops.len());
+                    let mut cancelled = Vec::new();
+                    for (op, schedule) in ops.into_iter().zip(schedule) {
+                        for _ in 0..schedule.yields_before {
+                            task::yield_now().await;
+                        }
+                        let call_timestamp = timer.fetch_add(1, Ordering::SeqCst);
+                        let ret = match schedule.cancel_after_polls {
+                            Some(max_polls) => poll_at_most(conc.exec(op.clone()), max_polls).await,
+                            None => Some(conc.exec(op.clone()).await),
+                        };
+                        let timestamp = timer.fetch_add(1, Ordering::SeqCst);
+                        match ret {
+                            Some(ret) => invocations.push(ParallelInvocation {
+                                thread_id,
+                                call_timestamp,
+                                return_timestamp: timestamp,
+                                op,
+                                ret,
+                            }),
+                            None => cancelled.push(PendingInvocation::cancelled(
+                                thread_id,
+                                call_timestamp,
+                                timestamp,
+                                op,
+                            )),
+                        }
+                        for _ in 0..schedule.yields_after {
+                            task::yield_now().await;
+                        }
+                    }
+                    (invocations, cancelled)
+                })
+            })
+            .collect();
+        let mut parallel_part = ParallelHistory::new();
+        let mut pending = Vec::new();
+        for handle in handles {
+            match handle.await {
+                Ok((invocations, cancelled)) => {
+                    parallel_part.extend(invocations);
+                    pending.extend(cancelled);
+                }
+                Err(error) => panic::resume_unwind(error.into_panic()),
+            }
+        }
+
+        let mut post_part = History::with_capacity(scenario.post_part.len());
+        for op in scenario.post_part.iter() {
+            let ret = conc.exec(op.clone()).await;
+            post_part.push(Invocation {
+                op: op.clone(),
+                ret,
+            });
+        }
+
+        Execution {
+            init_part,
+            parallel_part,
+            post_part,
+            pending,
+        }
+    })
+}
@@ -0,0 +1,516 @@
+//! An interactive terminal explorer of executions.
+//!
+//! The text table of an execution gets hard to read past a few threads and a dozen operations.
+//! [explore] opens a terminal UI instead: the parallel part is drawn as a timeline with a row per thread,
+//! and below it are the candidate linearizations, i.e. the orders of the completed invocations that respect their real-time order.
+//! Each step of a candidate shows the recorded return next to what the sequential specification would have returned,
+//! and the candidates that match for the longest come first.
+//! The pending invocations are drawn on the timeline but left out of the candidates.
+//!
+//! The keys are:
+//! - `←`/`→` or `h`/`l` to switch to the previous or the next candidate;
+//! - `↑`/`↓` or `k`/`j` to select the previous or the next step, whose timestamps are shown aside;
+//! - `c` to collapse or expand the thread of the selected step, and `e` to expand all the threads;
+//! - `q` or `Esc` to quit.
+
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::io;
+use std::iter;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::checker::ExplainedStep;
+use crate::execution::*;
+use crate::spec::*;
+
+/// The maximum number of candidate linearizations to enumerate.
+const MAX_CANDIDATES: usize = 256;
+
+/// The width of the thread names on the timeline.
+const LABEL_WIDTH: usize = 11;
+
+const HELP: &str = " ←/→ candidate   ↑/↓ step   c collapse thread   e expand all   q quit";
+
+/// Opens the explorer of the execution in the terminal, checking the candidate linearizations against `Seq`,
+/// and returns once it is closed.
+pub fn explore<Seq>(execution: &Execution<Seq::Op, Seq::Ret>) -> io::Result<()>
+where
+    Seq: SequentialSpec + Default,
+    Seq::Op: Clone + Debug,
+    Seq::Ret: PartialEq + Clone + Debug,
+{
+    let mut explorer = Explorer::new::<Seq>(execution);
+    let mut terminal = ratatui::init();
+    let result = loop {
+        if let Err(error) = terminal.draw(|frame| explorer.render(frame)) {
+            break Err(error);
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                if !explorer.handle_key(key.code) {
+                    break Ok(());
+                }
+            }
+            Ok(_) => {}
+            Err(error) => break Err(error),
+        }
+    };
+    ratatui::restore();
+    result
+}
+
+/// A candidate linearization of the whole execution.
+struct Candidate<Op, Ret> {
+    steps: Vec<CandidateStep<Op, Ret>>,
+    /// The number of the first steps whose returns match the sequential specification.
+    matched: usize,
+}
+
+struct CandidateStep<Op, Ret> {
+    /// The invocation of the parallel part, or [None] for the init and post parts.
+    inv_id: Option<InvocationId>,
+    step: ExplainedStep<Op, Ret>,
+}
+
+/// The state of the explorer, kept apart from the terminal.
+struct Explorer<'e, Op, Ret> {
+    execution: &'e Execution<Op, Ret>,
+    candidates: Vec<Candidate<Op, Ret>>,
+    /// The shown candidate.
+    candidate: usize,
+    /// The selected step of the shown candidate.
+    step: usize,
+    collapsed: BTreeSet<ThreadId>,
+}
+
+impl<'e, Op: Clone + Debug, Ret: PartialEq + Clone + Debug> Explorer<'e, Op, Ret> {
+    fn new<Seq>(execution: &'e Execution<Op, Ret>) -> Self
+    where
+        Seq: SequentialSpec<Op = Op, Ret = Ret> + Default,
+    {
+        let mut candidates: Vec<_> = candidate_orders(&execution.parallel_part)
+            .into_iter()
+            .map(|order| candidate::<Seq>(execution, order))
+            .collect();
+        // stable, so the candidates that match equally long stay in the order they were enumerated
+        candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.matched));
+
+        Self {
+            execution,
+            candidates,
+            candidate: 0,
+            step: 0,
+            collapsed: BTreeSet::new(),
+        }
+    }
+
+    /// Handles a pressed key, returning `false` if the explorer should quit.
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        let num_steps = self.candidates[self.candidate].steps.len();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.candidate = (self.candidate + 1).min(self.candidates.len() - 1)
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.candidate = self.candidate.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.step = (self.step + 1).min(num_steps.saturating_sub(1))
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.step = self.step.saturating_sub(1),
+            KeyCode::Char('c') => {
+                if let Some(thread_id) = self.selected().and_then(|step| step.step.thread_id) {
+                    if !self.collapsed.remove(&thread_id) {
+                        self.collapsed.insert(thread_id);
+                    }
+                }
+            }
+            KeyCode::Char('e') => self.collapsed.clear(),
+            _ => {}
+        }
+        true
+    }
+
+    fn selected(&self) -> Option<&CandidateStep<Op, Ret>> {
+        self.candidates[self.candidate].steps.get(self.step)
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [timeline, bottom, help] = Layout::vertical([
+            Constraint::Percentage(45),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [steps, details] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(bottom);
+
+        self.render_timeline(frame, timeline);
+        self.render_steps(frame, steps);
+        self.render_details(frame, details);
+        frame.render_widget(
+            Paragraph::new(HELP).style(Style::new().fg(Color::DarkGray)),
+            help,
+        );
+    }
+
+    /// Draws each invocation of the parallel part from its call to its return, a thread per row.
+    fn render_timeline(&self, frame: &mut Frame, area: Rect) {
+        let parallel_part = &self.execution.parallel_part;
+        let pending = &self.execution.pending;
+        let num_threads = (parallel_part.iter().map(|inv| inv.thread_id + 1))
+            .chain(pending.iter().map(|inv| inv.thread_id + 1))
+            .max()
+            .unwrap_or(0);
+        let end = (parallel_part.iter().map(|inv| inv.return_timestamp + 1))
+            .chain(
+                pending
+                    .iter()
+                    .map(|inv| (inv.cancel_timestamp).unwrap_or(inv.call_timestamp + 1)),
+            )
+            .max()
+            .unwrap_or(1);
+        let width = (area.width as usize).saturating_sub(2 + LABEL_WIDTH).max(1);
+
+        let lines: Vec<_> = (0..num_threads)
+            .map(|thread_id| {
+                let label = Span::styled(
+                    format!("{:<1$}", format!("THREAD {}", thread_id), LABEL_WIDTH),
+                    Style::new().add_modifier(Modifier::BOLD),
+                );
+                let mut spans = vec![label];
+                if self.collapsed.contains(&thread_id) {
+                    let num_ops = parallel_part.thread(thread_id).count()
+                        + (pending.iter())
+                            .filter(|inv| inv.thread_id == thread_id)
+                            .count();
+                    spans.push(Span::styled(
+                        format!("({} operations, collapsed)", num_ops),
+                        Style::new().fg(Color::DarkGray),
+                    ));
+                } else {
+                    spans.extend(self.thread_spans(thread_id, width, end));
+                }
+                Line::from(spans)
+            })
+            .collect();
+
+        let block = Block::new().borders(Borders::ALL).title(" PARALLEL PART ");
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    /// The invocations of the thread laid out on `width` cells that span the timestamps up to `end`.
+    fn thread_spans(
+        &self,
+        thread_id: ThreadId,
+        width: usize,
+        end: Timestamp,
+    ) -> Vec<Span<'static>> {
+        let column = |timestamp: Timestamp| timestamp * width / end;
+        let candidate = &self.candidates[self.candidate];
+        let selected = self.selected().and_then(|step| step.inv_id);
+        let mismatched = (candidate.steps.get(candidate.matched)).and_then(|step| step.inv_id);
+
+        // the first and the last cell, the text and the style of each invocation
+        let mut segments = Vec::new();
+        for (inv_id, inv) in self.execution.parallel_part.iter().enumerate() {
+            if inv.thread_id != thread_id {
+                continue;
+            }
+            let style = if Some(inv_id) == selected {
+                Style::new().fg(Color::Black).bg(Color::Yellow)
+            } else if Some(inv_id) == mismatched {
+                Style::new().fg(Color::White).bg(Color::Red)
+            } else {
+                Style::new().fg(Color::Black).bg(Color::Cyan)
+            };
+            let text = format!("{:?} : {:?}", inv.op, inv.ret);
+            segments.push((
+                column(inv.call_timestamp),
+                column(inv.return_timestamp),
+                text,
+                style,
+            ));
+        }
+        for inv in self.execution.pending.iter() {
+            if inv.thread_id == thread_id {
+                let style = Style::new().fg(Color::Black).bg(Color::Gray);
+                let (end, text) = match inv.cancel_timestamp {
+                    Some(cancel_timestamp) => (
+                        column(cancel_timestamp),
+                        format!("{:?} : cancelled", inv.op),
+                    ),
+                    None => (width, format!("{:?} : pending", inv.op)),
+                };
+                segments.push((column(inv.call_timestamp), end, text, style));
+            }
+        }
+        segments.sort_by_key(|&(start, ..)| start);
+
+        let mut spans = Vec::new();
+        let mut cursor = 0;
+        for (start, end, text, style) in segments {
+            // the rounding may squeeze the invocations together, but each one keeps a cell
+            let start = start.max(cursor);
+            let end = end.max(start + 1);
+            if start > cursor {
+                spans.push(Span::raw(" ".repeat(start - cursor)));
+            }
+            let text: String = (text.chars().chain(iter::repeat(' ')))
+                .take(end - start)
+                .collect();
+            spans.push(Span::styled(text, style));
+            cursor = end;
+        }
+        spans
+    }
+
+    /// Lists the steps of the shown candidate with the returns of the sequential specification.
+    fn render_steps(&self, frame: &mut Frame, area: Rect) {
+        let candidate = &self.candidates[self.candidate];
+        let items: Vec<_> = candidate
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, CandidateStep { step, .. })| {
+                let thread = match step.thread_id {
+                    Some(thread_id) => format!("THREAD {}", thread_id),
+                    None => "MAIN THREAD".to_string(),
+                };
+                let mut spans = vec![Span::raw(format!(
+                    "{:>3} {}: {:?} : ",
+                    i + 1,
+                    thread,
+                    step.op
+                ))];
+                match &step.actual {
+                    Some(actual) if *actual == step.expected => {
+                        spans.push(Span::raw(format!("{:?}", actual)))
+                    }
+                    Some(actual) => spans.push(Span::styled(
+                        format!("{:?}, expected {:?}", actual, step.expected),
+                        Style::new().fg(Color::Red),
+                    )),
+                    None => spans.push(Span::raw(format!("pending, expected {:?}", step.expected))),
+                }
+                let style = if i > candidate.matched {
+                    Style::new().fg(Color::DarkGray)
+                } else {
+                    Style::new()
+                };
+                ListItem::new(Line::from(spans)).style(style)
+            })
+            .collect();
+
+        let verdict = if candidate.matched == candidate.steps.len() {
+            "a linearization".to_string()
+        } else {
+            format!(
+                "matches {} of {} steps",
+                candidate.matched,
+                candidate.steps.len()
+            )
+        };
+        let title = format!(
+            " CANDIDATE {} OF {}: {} ",
+            self.candidate + 1,
+            self.candidates.len(),
+            verdict
+        );
+        let list = List::new(items)
+            .block(Block::new().borders(Borders::ALL).title(title))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.step));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    /// Shows the selected step with its timestamps.
+    fn render_details(&self, frame: &mut Frame, area: Rect) {
+        let mut lines = Vec::new();
+        if let Some(CandidateStep { inv_id, step }) = self.selected() {
+            lines.push(Line::from(format!("operation: {:?}", step.op)));
+            match step.thread_id {
+                Some(thread_id) => lines.push(Line::from(format!("thread: {}", thread_id))),
+                None => lines.push(Line::from("thread: main")),
+            }
+            if let Some(inv) = inv_id.and_then(|inv_id| self.execution.parallel_part.get(inv_id)) {
+                lines.push(Line::from(format!("called at: {}", inv.call_timestamp)));
+                lines.push(Line::from(format!("returned at: {}", inv.return_timestamp)));
+            }
+            match &step.actual {
+                Some(actual) => lines.push(Line::from(format!("returned: {:?}", actual))),
+                None => lines.push(Line::from("returned: pending")),
+            }
+            lines.push(Line::from(format!("expected: {:?}", step.expected)));
+        }
+
+        let block = Block::new().borders(Borders::ALL).title(" STEP ");
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+/// The orders of the completed invocations of the parallel part that respect their real-time order, up to [MAX_CANDIDATES].
+fn candidate_orders<Op, Ret>(parallel_part: &ParallelHistory<Op, Ret>) -> Vec<Vec<InvocationId>> {
+    fn extend<Op, Ret>(
+        parallel_part: &ParallelHistory<Op, Ret>,
+        order: &mut Vec<InvocationId>,
+        used: &mut [bool],
+        orders: &mut Vec<Vec<InvocationId>>,
+    ) {
+        if orders.len() == MAX_CANDIDATES {
+            return;
+        }
+        if order.len() == parallel_part.len() {
+            orders.push(order.clone());
+            return;
+        }
+        for inv_id in 0..parallel_part.len() {
+            // an invocation may come next if no remaining one returned before it was called
+            let is_minimal = !used[inv_id]
+                && (0..parallel_part.len()).all(|other| {
+                    used[other]
+                        || parallel_part[other].return_timestamp
+                            > parallel_part[inv_id].call_timestamp
+                });
+            if is_minimal {
+                used[inv_id] = true;
+                order.push(inv_id);
+                extend(parallel_part, order, used, orders);
+                order.pop();
+                used[inv_id] = false;
+            }
+        }
+    }
+
+    let mut orders = Vec::new();
+    extend(
+        parallel_part,
+        &mut Vec::new(),
+        &mut vec![false; parallel_part.len()],
+        &mut orders,
+    );
+    orders
+}
+
+/// Replays the execution with the parallel part in the given order on a fresh sequential specification.
+fn candidate<Seq>(
+    execution: &Execution<Seq::Op, Seq::Ret>,
+    order: Vec<InvocationId>,
+) -> Candidate<Seq::Op, Seq::Ret>
+where
+    Seq: SequentialSpec + Default,
+    Seq::Op: Clone,
+    Seq::Ret: PartialEq + Clone,
+{
+    let parallel_part = &execution.parallel_part;
+    let init_part = (execution.init_part.iter()).map(|inv| (None, None, &inv.op, &inv.ret));
+    let parallel = order.into_iter().map(|inv_id| {
+        let inv = &parallel_part[inv_id];
+        (Some(inv_id), Some(inv.thread_id), &inv.op, &inv.ret)
+    });
+    let post_part = (execution.post_part.iter()).map(|inv| (None, None, &inv.op, &inv.ret));
+
+    let mut seq_spec = Seq::default();
+    let mut matched = None;
+    let steps: Vec<_> = init_part
+        .chain(parallel)
+        .chain(post_part)
+        .enumerate()
+        .map(|(i, (inv_id, thread_id, op, actual))| {
+            let expected = seq_spec.exec(op.clone());
+            if matched.is_none() && !Seq::ret_matches(&expected, actual) {
+                matched = Some(i);
+            }
+            CandidateStep {
+                inv_id,
+                step: ExplainedStep {
+                    op: op.clone(),
+                    thread_id,
+                    expected,
+                    actual: Some(actual.clone()),
+                },
+            }
+        })
+        .collect();
+
+    Candidate {
+        matched: matched.unwrap_or(steps.len()),
+        steps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution;
+    use crate::specs::{Register, RegisterOp, RegisterRet};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    type Op = RegisterOp<i32>;
+    type Ret = RegisterRet<i32>;
+
+    fn stale_read() -> Execution<Op, Ret> {
+        execution! {
+            parallel: [
+                0 @ 0..1: Op::Write(1) => Ret::Write,
+                1 @ 2..5: Op::Read => Ret::Read(0),
+                0 @ 3..4: Op::Write(2) => Ret::Write,
+            ],
+        }
+    }
+
+    fn screen(explorer: &Explorer<Op, Ret>) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| explorer.render(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn candidates_match_longest_first() {
+        let execution = stale_read();
+        let explorer = Explorer::new::<Register<i32>>(&execution);
+
+        // the write of 1 comes first, then the read and the write of 2 in either order
+        let matched: Vec<_> = (explorer.candidates.iter())
+            .map(|candidate| candidate.matched)
+            .collect();
+        assert_eq!(matched, vec![2, 1]);
+
+        let screen = screen(&explorer);
+        assert!(screen.contains("CANDIDATE 1 OF 2: matches 2 of 3 steps"));
+        assert!(screen.contains("Read(0), expected Read(2)"));
+        assert!(screen.contains("called at: 0"));
+    }
+
+    #[test]
+    fn keys_navigate_and_collapse() {
+        let execution = stale_read();
+        let mut explorer = Explorer::new::<Register<i32>>(&execution);
+
+        assert!(explorer.handle_key(KeyCode::Right));
+        assert!(explorer.handle_key(KeyCode::Right));
+        assert_eq!(explorer.candidate, 1);
+
+        assert!(explorer.handle_key(KeyCode::Down));
+        assert!(explorer.handle_key(KeyCode::Down));
+        assert_eq!(explorer.selected().unwrap().step.thread_id, Some(0));
+        assert!(explorer.handle_key(KeyCode::Char('c')));
+        assert!(screen(&explorer).contains("(2 operations, collapsed)"));
+        assert!(explorer.handle_key(KeyCode::Char('e')));
+        assert!(!screen(&explorer).contains("collapsed"));
+
+        assert!(!explorer.handle_key(KeyCode::Char('q')));
+    }
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use lincheck::alloc::{AllocFailures, FailingAllocator};
+use lincheck::faults::FaultySpec;
+use lincheck::{ConcurrentSpec, SequentialSpec};
+use proptest::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+#[global_allocator]
+static ALLOCATOR: FailingAllocator = FailingAllocator::system();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Push(u8),
+    Len,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Push(Result<(), OutOfMemory>),
+    Len(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OutOfMemory;
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![any::<u8>().prop_map(Op::Push), Just(Op::Len)].boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialStack {
+    len: usize,
+}
+
+impl SequentialSpec for SequentialStack {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Push(_) => {
+                self.len += 1;
+                Ret::Push(Ok(()))
+            }
+            Op::Len => Ret::Len(self.len),
+        }
+    }
+}
+
+impl FaultySpec for SequentialStack {
+    fn exec_faulted(&mut self, op: Op, _faults: &[&'static str]) -> Ret {
+        match op {
+            Op::Push(_) => Ret::Push(Err(OutOfMemory)),
+            Op::Len => self.exec(op),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Stack {
+    values: Mutex<Vec<u8>>,
+}
+
+impl ConcurrentSpec for Stack {
+    type Seq = SequentialStack;
+
+    fn exec(&self, op: Op) -> Ret {
+        let mut values = self.values.lock().unwrap();
+        match op {
+            Op::Push(value) => {
+                if values.try_reserve(1).is_err() {
+                    return Ret::Push(Err(OutOfMemory));
+                }
+                values.push(value);
+                Ret::Push(Ok(()))
+            }
+            Op::Len => Ret::Len(values.len()),
+        }
+    }
+}
+
+/// Keeps the length separately and doesn't roll it back if the allocation fails.
+#[derive(Default)]
+struct CountingStack {
+    len: AtomicUsize,
+    values: Mutex<Vec<u8>>,
+}
+
+impl ConcurrentSpec for CountingStack {
+    type Seq = SequentialStack;
+
+    fn exec(&self, op: Op) -> Ret {
+        let mut values = self.values.lock().unwrap();
+        match op {
+            Op::Push(value) => {
+                self.len.fetch_add(1, Ordering::Relaxed);
+                if values.try_reserve(1).is_err() {
+                    return Ret::Push(Err(OutOfMemory));
+                }
+                values.push(value);
+                Ret::Push(Ok(()))
+            }
+            Op::Len => Ret::Len(self.len.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[test]
+fn stack() {
+    assert!(lincheck(3)
+        .verify_alloc_failures::<Stack>(AllocFailures::OneIn(2))
+        .is_ok());
+}
+
+#[test]
+fn counting_stack() {
+    assert!(lincheck(3)
+        .verify_alloc_failures::<CountingStack>(AllocFailures::Nth(0))
+        .is_err());
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use common::{Op, RacyCounter, SequentialCounter};
+use lincheck::backend::{ExecutionBackend, LoomBackend, RoundRobinBackend, ThreadsBackend};
+use lincheck::scenario::{execute_scenario_with_threads, Scenario};
+use lincheck::{ConcOp, ConcRet, ConcurrentSpec, Execution};
+use std::fmt::Debug;
+use std::panic::UnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Default)]
+struct AtomicCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for AtomicCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, _: Op) -> usize {
+        self.x.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+/// A counter that skips the value two, even without concurrency.
+#[derive(Default)]
+struct SkippingCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for SkippingCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, _: Op) -> usize {
+        let x = self.x.fetch_add(1, Ordering::SeqCst);
+        if x == 2 {
+            self.x.fetch_add(1, Ordering::SeqCst)
+        } else {
+            x
+        }
+    }
+}
+
+/// A backend defined outside of the crate, executing every scenario once on native threads.
+struct OnceOnThreads;
+
+impl ExecutionBackend for OnceOnThreads {
+    fn find_failure<Conc>(
+        &self,
+        scenario: Scenario<ConcOp<Conc>>,
+        check: impl Fn(&Execution<ConcOp<Conc>, ConcRet<Conc>>) -> bool + Send + Sync + 'static,
+    ) -> Result<(), Execution<ConcOp<Conc>, ConcRet<Conc>>>
+    where
+        Conc: ConcurrentSpec + Default + Send + Sync + 'static,
+        ConcOp<Conc>: Send + Sync + Clone + Debug + UnwindSafe + 'static,
+        ConcRet<Conc>: PartialEq + Clone + Debug + Send,
+    {
+        let execution = execute_scenario_with_threads::<Conc>(scenario);
+        if check(&execution) {
+            Ok(())
+        } else {
+            Err(execution)
+        }
+    }
+}
+
+#[test]
+fn loom_backend() {
+    assert!(lincheck(4)
+        .verify_with_backend::<RacyCounter>(&LoomBackend::default())
+        .is_err());
+}
+
+#[test]
+fn threads_backend() {
+    let threads = ThreadsBackend { runs: 10 };
+    assert!(lincheck(4)
+        .verify_with_backend::<AtomicCounter>(&threads)
+        .is_ok());
+}
+
+#[test]
+fn round_robin_backend() {
+    assert!(lincheck(4)
+        .verify_with_backend::<AtomicCounter>(&RoundRobinBackend)
+        .is_ok());
+
+    let failure = lincheck(4)
+        .verify_with_backend::<SkippingCounter>(&RoundRobinBackend)
+        .unwrap_err();
+    let execution = failure.execution().expect("the execution is reported");
+    // the third increment skips the value
+    let num_ops =
+        execution.init_part().len() + execution.parallel_part().len() + execution.post_part().len();
+    assert_eq!(num_ops, 3);
+}
+
+#[test]
+fn custom_backend() {
+    assert!(lincheck(4)
+        .verify_with_backend::<SkippingCounter>(&OnceOnThreads)
+        .is_err());
+}
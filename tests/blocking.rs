@@ -0,0 +1,98 @@
+use lincheck::checker::LinearizabilityChecker;
+use lincheck::recorder::{self, Recorder};
+use lincheck::SequentialSpec;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Put(u8),
+    Take,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Put,
+    /// Always `Some` when returned by the blocking queue.
+    Take(Option<u8>),
+}
+
+/// A queue whose `take` blocks while the queue is empty.
+#[derive(Default)]
+struct BlockingQueue {
+    queue: Mutex<VecDeque<u8>>,
+    not_empty: Condvar,
+}
+
+impl BlockingQueue {
+    fn put(&self, value: u8) {
+        self.queue.lock().unwrap().push_back(value);
+        self.not_empty.notify_one();
+    }
+
+    /// Gives up after the timeout without taking anything.
+    fn take_timeout(&self, timeout: Duration) -> Option<u8> {
+        let queue = self.queue.lock().unwrap();
+        let (mut queue, _) = self
+            .not_empty
+            .wait_timeout_while(queue, timeout, |queue| queue.is_empty())
+            .unwrap();
+        queue.pop_front()
+    }
+
+    fn exec(&self, op: Op) -> Option<Ret> {
+        match op {
+            Op::Put(value) => {
+                self.put(value);
+                Some(Ret::Put)
+            }
+            Op::Take => self
+                .take_timeout(Duration::from_millis(50))
+                .map(|value| Ret::Take(Some(value))),
+        }
+    }
+}
+
+#[derive(Default)]
+struct SequentialQueue(VecDeque<u8>);
+
+impl SequentialSpec for SequentialQueue {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Put(value) => {
+                self.0.push_back(value);
+                Ret::Put
+            }
+            // a completed take can't be linearized at an empty queue since it never returns `None`
+            Op::Take => Ret::Take(self.0.pop_front()),
+        }
+    }
+}
+
+#[test]
+fn blocked_take_is_pending() {
+    let queue = BlockingQueue::default();
+    let recorder = recorder::record_init_part().record_parallel_part();
+
+    thread::scope(|s| {
+        for thread_ops in [vec![Op::Put(1)], vec![Op::Take, Op::Take]] {
+            let (queue, mut recorder) = (&queue, recorder.record_thread());
+            s.spawn(move || {
+                for op in thread_ops {
+                    recorder.record_blocking(op, || queue.exec(op));
+                }
+            });
+        }
+    });
+
+    let mut recorder = recorder.record_post_part();
+    recorder.record(Op::Put(2), || queue.exec(Op::Put(2)).unwrap());
+    let execution = recorder.finish();
+
+    assert!(LinearizabilityChecker::<SequentialQueue>::check(&execution));
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
+use loom::sync::Mutex;
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Push(u8),
+    Pop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Push(bool),
+    Pop(Option<u8>),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![any::<u8>().prop_map(Op::Push), Just(Op::Pop)].boxed()
+    }
+}
+
+/// A stack that rejects the pushes beyond its capacity. It has no sensible default capacity.
+struct SequentialBoundedStack {
+    capacity: usize,
+    stack: Vec<u8>,
+}
+
+impl SequentialBoundedStack {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl SequentialSpec for SequentialBoundedStack {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Push(value) => {
+                let accepted = self.stack.len() < self.capacity;
+                if accepted {
+                    self.stack.push(value);
+                }
+                Ret::Push(accepted)
+            }
+            Op::Pop => Ret::Pop(self.stack.pop()),
+        }
+    }
+}
+
+struct BoundedStack {
+    capacity: usize,
+    stack: Mutex<Vec<u8>>,
+}
+
+impl BoundedStack {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            stack: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl ConcurrentSpec for BoundedStack {
+    type Seq = SequentialBoundedStack;
+
+    fn exec(&self, op: Op) -> Ret {
+        let mut stack = self.stack.lock().unwrap();
+        match op {
+            Op::Push(value) => {
+                let accepted = stack.len() < self.capacity;
+                if accepted {
+                    stack.push(value);
+                }
+                Ret::Push(accepted)
+            }
+            Op::Pop => Ret::Pop(stack.pop()),
+        }
+    }
+}
+
+#[test]
+fn bounded_stack() {
+    let result =
+        Lincheck::default().verify_with(|| BoundedStack::new(2), || SequentialBoundedStack::new(2));
+    assert!(result.is_ok());
+}
+
+#[test]
+fn bounded_stack_capacity_mismatch() {
+    let result =
+        Lincheck::default().verify_with(|| BoundedStack::new(2), || SequentialBoundedStack::new(3));
+    assert!(result.is_err());
+}
@@ -0,0 +1,189 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use lincheck::chaos::chaos_point;
+use lincheck::{ConcurrentSpec, SequentialSpec};
+use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use loom::thread;
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Transfer,
+    Read,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Transfer,
+    Read(usize, usize),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![Just(Op::Transfer), Just(Op::Read)].boxed()
+    }
+}
+
+/// Two accounts that always hold 2 units in total.
+#[derive(Clone, Copy)]
+struct SequentialAccounts {
+    from: usize,
+    to: usize,
+}
+
+impl Default for SequentialAccounts {
+    fn default() -> Self {
+        Self { from: 2, to: 0 }
+    }
+}
+
+impl SequentialSpec for SequentialAccounts {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Transfer => {
+                if self.from > 0 {
+                    self.from -= 1;
+                    self.to += 1;
+                }
+                Ret::Transfer
+            }
+            Op::Read => Ret::Read(self.from, self.to),
+        }
+    }
+}
+
+/// A spin lock guarding two accounts. Unlike the mutexes of loom, it isn't poisoned by panics.
+struct Accounts {
+    locked: AtomicBool,
+    from: AtomicUsize,
+    to: AtomicUsize,
+}
+
+impl Default for Accounts {
+    fn default() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            from: AtomicUsize::new(2),
+            to: AtomicUsize::new(0),
+        }
+    }
+}
+
+struct Guard<'a>(&'a Accounts);
+
+impl Accounts {
+    fn lock(&self) -> Guard<'_> {
+        while self
+            .locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            thread::yield_now();
+        }
+        Guard(self)
+    }
+}
+
+impl Drop for Guard<'_> {
+    fn drop(&mut self) {
+        self.0.locked.store(false, Ordering::Release);
+    }
+}
+
+impl Guard<'_> {
+    fn read(&self) -> Ret {
+        Ret::Read(
+            self.0.from.load(Ordering::Relaxed),
+            self.0.to.load(Ordering::Relaxed),
+        )
+    }
+
+    fn is_consistent(&self) -> bool {
+        self.0.from.load(Ordering::Relaxed) + self.0.to.load(Ordering::Relaxed) == 2
+    }
+}
+
+/// Checks the balance before touching the accounts.
+#[derive(Default)]
+struct PanicSafeAccounts(Accounts);
+
+impl ConcurrentSpec for PanicSafeAccounts {
+    type Seq = SequentialAccounts;
+
+    fn exec(&self, op: Op) -> Ret {
+        let guard = self.0.lock();
+        match op {
+            Op::Transfer => {
+                let from = self.0.from.load(Ordering::Relaxed);
+                if from > 0 {
+                    chaos_point();
+                    let to = self.0.to.load(Ordering::Relaxed);
+                    self.0.from.store(from - 1, Ordering::Relaxed);
+                    self.0.to.store(to + 1, Ordering::Relaxed);
+                }
+                Ret::Transfer
+            }
+            Op::Read => guard.read(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.0.lock().is_consistent() {
+            Ok(())
+        } else {
+            Err("the money is lost".into())
+        }
+    }
+}
+
+/// Leaves the accounts half-updated if it panics in the middle of a transfer.
+#[derive(Default)]
+struct PanicUnsafeAccounts(Accounts);
+
+impl ConcurrentSpec for PanicUnsafeAccounts {
+    type Seq = SequentialAccounts;
+
+    fn exec(&self, op: Op) -> Ret {
+        let guard = self.0.lock();
+        match op {
+            Op::Transfer => {
+                let from = self.0.from.load(Ordering::Relaxed);
+                if from > 0 {
+                    self.0.from.store(from - 1, Ordering::Relaxed);
+                    chaos_point();
+                    let to = self.0.to.load(Ordering::Relaxed);
+                    self.0.to.store(to + 1, Ordering::Relaxed);
+                }
+                Ret::Transfer
+            }
+            Op::Read => guard.read(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.0.lock().is_consistent() {
+            Ok(())
+        } else {
+            Err("the money is lost".into())
+        }
+    }
+}
+
+#[test]
+fn panic_safe_accounts() {
+    assert!(lincheck(2).verify_chaos::<PanicSafeAccounts>().is_ok());
+}
+
+#[test]
+fn panic_unsafe_accounts() {
+    assert!(lincheck(2).verify_chaos::<PanicUnsafeAccounts>().is_err());
+}
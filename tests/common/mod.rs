@@ -0,0 +1,86 @@
+//! The counter and the configuration shared by the integration tests.
+
+#![allow(dead_code)] // every test uses only a part of it
+
+pub mod readable;
+
+use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
+use loom::sync::atomic::{AtomicUsize, Ordering};
+
+/// The configuration of the tests running `num_ops` operations on two threads.
+pub fn lincheck(num_ops: usize) -> Lincheck {
+    Lincheck {
+        num_threads: 2,
+        num_ops,
+        ..Default::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Increment,
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::{Just, Strategy};
+
+        Just(Op::Increment).boxed()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Op {
+    fn arbitrary(_: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Op::Increment)
+    }
+}
+
+/// Returns the value before the increment.
+#[derive(Default)]
+pub struct SequentialCounter {
+    pub x: usize,
+}
+
+impl SequentialSpec for SequentialCounter {
+    type Op = Op;
+    type Ret = usize;
+
+    fn exec(&mut self, _: Op) -> usize {
+        self.x += 1;
+        self.x - 1
+    }
+}
+
+#[derive(Default)]
+pub struct AtomicCounter {
+    pub x: AtomicUsize,
+}
+
+impl ConcurrentSpec for AtomicCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, _: Op) -> usize {
+        self.x.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+/// A counter that loads and stores the value separately, losing the concurrent increments.
+#[derive(Default)]
+pub struct RacyCounter {
+    pub x: AtomicUsize,
+}
+
+impl ConcurrentSpec for RacyCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, _: Op) -> usize {
+        let x = self.x.load(Ordering::SeqCst);
+        self.x.store(x + 1, Ordering::SeqCst);
+        x
+    }
+}
@@ -0,0 +1,81 @@
+//! The counter of [the parent module](super) with an operation reading it.
+
+use lincheck::{ConcurrentSpec, SequentialSpec};
+use loom::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Increment,
+    Get,
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::prop_oneof;
+        use proptest::strategy::{Just, Strategy};
+
+        prop_oneof![Just(Op::Increment), Just(Op::Get)].boxed()
+    }
+}
+
+/// Returns the value before the increment.
+#[derive(Default, Clone)]
+pub struct SequentialCounter {
+    pub x: usize,
+}
+
+impl SequentialSpec for SequentialCounter {
+    type Op = Op;
+    type Ret = usize;
+
+    fn exec(&mut self, op: Op) -> usize {
+        match op {
+            Op::Increment => {
+                self.x += 1;
+                self.x - 1
+            }
+            Op::Get => self.x,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AtomicCounter {
+    pub x: AtomicUsize,
+}
+
+impl ConcurrentSpec for AtomicCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> usize {
+        match op {
+            Op::Increment => self.x.fetch_add(1, Ordering::SeqCst),
+            Op::Get => self.x.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// A counter that loads and stores the value separately, losing the concurrent increments.
+#[derive(Default)]
+pub struct RacyCounter {
+    pub x: AtomicUsize,
+}
+
+impl ConcurrentSpec for RacyCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> usize {
+        match op {
+            Op::Increment => {
+                let x = self.x.load(Ordering::SeqCst);
+                self.x.store(x + 1, Ordering::SeqCst);
+                x
+            }
+            Op::Get => self.x.load(Ordering::SeqCst),
+        }
+    }
+}
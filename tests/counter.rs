@@ -1,10 +1,20 @@
-use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
+#![cfg(feature = "proptest")]
+
+use lincheck::{
+    progress::{ProgressObserver, ShrinkStats},
+    scenario::Scenario,
+    ConcurrentSpec, Lincheck, LoomConfig, SequentialSpec, VerificationFailure,
+};
+use std::sync::atomic::{self, AtomicUsize as StdAtomicUsize};
+use std::sync::Arc;
+use std::time::Duration;
 
 use loom::{
     sync::atomic::{AtomicUsize, Ordering},
     thread,
 };
 use proptest::prelude::*;
+use proptest::test_runner::RngSeed;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Op {
@@ -76,6 +86,335 @@ fn counter() {
     Lincheck {
         num_threads: 1,
         num_ops: 1,
+        ..Default::default()
     }
     .verify_or_panic::<ConcurrentCounter>()
 }
+
+loom::lazy_static! {
+    static ref GLOBAL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+}
+
+/// A counter that mistakenly keeps its value in a static, so all the instances share it.
+#[derive(Default)]
+struct GlobalCounter;
+
+impl ConcurrentSpec for GlobalCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::Increment => Ret::OldValue(GLOBAL_COUNTER.fetch_add(1, Ordering::Relaxed)),
+        }
+    }
+}
+
+#[test]
+fn global_counter_single_instance() {
+    Lincheck {
+        num_threads: 2,
+        num_ops: 1,
+        ..Default::default()
+    }
+    .verify_or_panic::<GlobalCounter>()
+}
+
+#[test]
+fn global_counter_multi_instance() {
+    let result = Lincheck {
+        num_threads: 2,
+        num_ops: 1,
+        num_instances: 2,
+        ..Default::default()
+    }
+    .verify::<GlobalCounter>();
+    assert!(result.is_err());
+}
+
+/// A counter that increments with a separate load and store, so concurrent increments get lost.
+#[derive(Default)]
+struct RacyCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for RacyCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::Increment => {
+                let val = self.x.load(Ordering::Relaxed);
+                self.x.store(val + 1, Ordering::Relaxed);
+                Ret::OldValue(val)
+            }
+        }
+    }
+}
+
+/// A counter that may return a stale value, i.e. one lost to a concurrent increment.
+#[derive(Default)]
+struct StaleCounter(SequentialCounter);
+
+impl SequentialSpec for StaleCounter {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        self.0.exec(op)
+    }
+
+    fn ret_matches(expected: &Ret, actual: &Ret) -> bool {
+        let (Ret::OldValue(expected), Ret::OldValue(actual)) = (expected, actual);
+        actual <= expected
+    }
+}
+
+#[test]
+fn verify_against_relaxed_spec() {
+    let lincheck = Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        ..Default::default()
+    };
+    assert!(lincheck.verify::<RacyCounter>().is_err());
+    assert!(lincheck
+        .verify_against::<RacyCounter, StaleCounter>()
+        .is_ok());
+}
+
+#[test]
+fn replay_scenario() {
+    let sequential = Scenario {
+        init_part: vec![Op::Increment],
+        parallel_part: vec![vec![Op::Increment]],
+        post_part: vec![Op::Increment],
+    };
+    let concurrent = Scenario {
+        init_part: vec![],
+        parallel_part: vec![vec![Op::Increment], vec![Op::Increment]],
+        post_part: vec![],
+    };
+
+    let lincheck = Lincheck::default();
+    assert!(lincheck.replay::<RacyCounter>(sequential).is_ok());
+    assert!(lincheck.replay::<RacyCounter>(concurrent).is_err());
+}
+
+#[test]
+fn parallel_verification() {
+    let lincheck = Lincheck {
+        num_threads: 2,
+        num_ops: 1,
+        ..Default::default()
+    };
+    assert!(lincheck.verify_parallel::<ConcurrentCounter>(4).is_ok());
+    assert!(lincheck.verify_parallel::<RacyCounter>(4).is_err());
+}
+
+#[test]
+fn parallel_verification_is_reproducible() {
+    let lincheck = Lincheck {
+        num_threads: 2,
+        num_ops: 3,
+        proptest: ProptestConfig {
+            rng_seed: RngSeed::Fixed(7),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert_eq!(
+        lincheck.verify_parallel::<RacyCounter>(4),
+        lincheck.verify::<RacyCounter>()
+    );
+}
+
+#[test]
+#[should_panic(expected = "maximum number of branches")]
+fn loom_max_branches() {
+    let scenario = Scenario {
+        init_part: vec![],
+        parallel_part: vec![vec![Op::Increment], vec![Op::Increment]],
+        post_part: vec![],
+    };
+
+    let lincheck = Lincheck {
+        loom: LoomConfig {
+            max_branches: Some(1),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let _ = lincheck.replay::<ConcurrentCounter>(scenario);
+}
+
+#[test]
+fn checker_timeout() {
+    // the lost increments don't linearize in a trivial order, so the checker has to search
+    let failure = Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        check_timeout: Some(Duration::ZERO),
+        ..Default::default()
+    }
+    .verify::<RacyCounter>()
+    .unwrap_err();
+    assert!(matches!(
+        failure,
+        VerificationFailure::CheckerTimeout { .. }
+    ));
+}
+
+#[test]
+fn run_reports_statistics() {
+    let lincheck = Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        proptest: ProptestConfig {
+            cases: 10,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let report = lincheck.run::<ConcurrentCounter>();
+    assert!(report.result.is_ok());
+    assert_eq!(report.scenarios, 10);
+    assert_eq!(report.shrink_iterations, 0);
+    assert!(report.interleavings >= report.scenarios);
+    assert_eq!(
+        report.history_lengths.values().sum::<usize>(),
+        report.interleavings
+    );
+
+    let report = lincheck.run::<RacyCounter>();
+    assert!(matches!(
+        report.result,
+        Err(VerificationFailure::NonLinearizable(_))
+    ));
+    assert!(report.scenarios <= 10);
+    assert!(report.shrink_iterations > 0);
+}
+
+#[test]
+fn reproduce_failure_with_seed() {
+    let lincheck = Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        ..Default::default()
+    };
+
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        lincheck.verify_or_panic::<RacyCounter>()
+    }))
+    .unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    let token = message
+        .split("verify_with_seed(\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .unwrap();
+
+    assert!(matches!(
+        lincheck.verify_with_seed::<RacyCounter>(token),
+        Err(VerificationFailure::NonLinearizable(_))
+    ));
+    assert!(lincheck
+        .verify_with_seed::<ConcurrentCounter>(token)
+        .is_ok());
+}
+
+#[test]
+fn reproduce_failure_with_constructors() {
+    let lincheck = Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        ..Default::default()
+    };
+    let token = lincheck.verify_report::<RacyCounter>().token.unwrap();
+
+    let replaying = Lincheck {
+        repro: Some(token),
+        ..lincheck
+    };
+    assert!(replaying
+        .verify_with(RacyCounter::default, SequentialCounter::default)
+        .is_err());
+    assert!(replaying
+        .verify_with(ConcurrentCounter::default, SequentialCounter::default)
+        .is_ok());
+}
+
+#[test]
+fn minimize_failing_scenario() {
+    let lincheck = Lincheck {
+        num_threads: 3,
+        num_ops: 3,
+        minimize: true,
+        ..Default::default()
+    };
+    let Err(VerificationFailure::NonLinearizable(execution)) = lincheck.verify::<RacyCounter>()
+    else {
+        panic!("the racy counter must fail");
+    };
+    // two racing increments are enough
+    assert!(execution.init_part().is_empty());
+    assert!(execution.post_part().is_empty());
+    assert_eq!(execution.parallel_part().len(), 2);
+    assert_eq!(execution.parallel_part().num_threads(), 2);
+}
+
+#[derive(Debug, Default)]
+struct CountingProgress {
+    started: StdAtomicUsize,
+    ended: StdAtomicUsize,
+    interleavings: StdAtomicUsize,
+    shrink_steps: StdAtomicUsize,
+    shrink_ends: StdAtomicUsize,
+}
+
+impl ProgressObserver for CountingProgress {
+    fn on_scenario_start(&self, case: usize, _num_ops: usize) {
+        let started = self.started.fetch_add(1, atomic::Ordering::SeqCst);
+        assert_eq!(case, started + 1);
+    }
+
+    fn on_scenario_end(&self, _case: usize, _passed: bool) {
+        self.ended.fetch_add(1, atomic::Ordering::SeqCst);
+    }
+
+    fn on_interleavings_explored(&self, explored: usize) {
+        assert!(explored > 0);
+        self.interleavings.fetch_add(1, atomic::Ordering::SeqCst);
+    }
+
+    fn on_shrink_step(&self, _num_ops: usize, _accepted: bool) {
+        self.shrink_steps.fetch_add(1, atomic::Ordering::SeqCst);
+    }
+
+    fn on_shrink_end(&self, stats: &ShrinkStats) {
+        assert_eq!(
+            stats.candidates,
+            self.shrink_steps.load(atomic::Ordering::SeqCst)
+        );
+        self.shrink_ends.fetch_add(1, atomic::Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn observe_progress() {
+    let progress = Arc::new(CountingProgress::default());
+    let lincheck = Lincheck {
+        num_threads: 2,
+        num_ops: 3,
+        progress: Some(progress.clone()),
+        ..Default::default()
+    };
+    assert!(lincheck.verify::<RacyCounter>().is_err());
+
+    let started = progress.started.load(atomic::Ordering::SeqCst);
+    assert!(started > 0);
+    assert_eq!(progress.ended.load(atomic::Ordering::SeqCst), started);
+    assert!(progress.interleavings.load(atomic::Ordering::SeqCst) >= started);
+    assert!(progress.shrink_steps.load(atomic::Ordering::SeqCst) > 0);
+    assert_eq!(progress.shrink_ends.load(atomic::Ordering::SeqCst), 1);
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use common::readable::{Op, SequentialCounter};
+use lincheck::crash::{check_crash_scenario_with_loom, crash_point, CrashTarget};
+use lincheck::scenario::ScenarioBuilder;
+use lincheck::{ConcurrentSpec, VerificationFailure};
+use loom::sync::atomic::{AtomicUsize, Ordering};
+use loom::sync::Mutex;
+
+/// Increments in a single step, so a crash either before or after it leaves the counter consistent.
+#[derive(Default)]
+struct AtomicCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for AtomicCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> usize {
+        match op {
+            Op::Increment => {
+                crash_point();
+                let x = self.x.fetch_add(1, Ordering::SeqCst);
+                crash_point();
+                x
+            }
+            Op::Get => self.x.fetch_add(0, Ordering::SeqCst),
+        }
+    }
+}
+
+/// Increments under a lock, which stays locked forever if the thread holding it is killed.
+#[derive(Default)]
+struct LockedCounter {
+    x: Mutex<usize>,
+}
+
+impl ConcurrentSpec for LockedCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> usize {
+        let mut x = self.x.lock().unwrap();
+        match op {
+            Op::Increment => {
+                crash_point();
+                *x += 1;
+                *x - 1
+            }
+            Op::Get => *x,
+        }
+    }
+}
+
+#[test]
+fn atomic_counter_survives_crashes() {
+    assert!(lincheck(2).verify_crash::<AtomicCounter>().is_ok());
+}
+
+#[test]
+fn locked_counter_deadlocks_after_crash() {
+    assert!(matches!(
+        lincheck(2).verify_crash::<LockedCounter>(),
+        Err(VerificationFailure::Deadlock { .. })
+    ));
+}
+
+#[test]
+fn crashed_increment_stays_pending() {
+    let scenario = ScenarioBuilder::new()
+        .thread([Op::Increment, Op::Increment])
+        .thread([Op::Increment])
+        .post([Op::Get])
+        .build();
+    // the first thread is killed after its first increment took effect
+    let target = CrashTarget {
+        thread: 0,
+        point: 1,
+    };
+    assert!(check_crash_scenario_with_loom::<AtomicCounter>(scenario, target).is_ok());
+}
+
+#[test]
+fn lock_held_by_crashed_thread_blocks_the_others() {
+    let scenario = ScenarioBuilder::new()
+        .thread([Op::Increment])
+        .thread([Op::Get])
+        .build();
+    let target = CrashTarget {
+        thread: 0,
+        point: 0,
+    };
+    match check_crash_scenario_with_loom::<LockedCounter>(scenario, target) {
+        Err(VerificationFailure::Deadlock {
+            blocked_threads, ..
+        }) => assert!(blocked_threads.contains(&0)),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
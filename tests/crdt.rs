@@ -0,0 +1,142 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::crdt::{ConvergenceFailure, ConvergenceViolation, ReplOp, ReplRet, ReplicatedSpec};
+use lincheck::{Lincheck, SequentialSpec};
+
+use proptest::prelude::*;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Insert(u8),
+    Contains(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Insert,
+    Contains(bool),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            (0..4u8).prop_map(Op::Insert),
+            (0..4u8).prop_map(Op::Contains),
+        ]
+        .boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialSet {
+    set: BTreeSet<u8>,
+}
+
+impl SequentialSpec for SequentialSet {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Insert(value) => {
+                self.set.insert(value);
+                Ret::Insert
+            }
+            Op::Contains(value) => Ret::Contains(self.set.contains(&value)),
+        }
+    }
+}
+
+// A grow-only set merged by union.
+#[derive(Default, Clone)]
+struct GrowOnlySet {
+    set: BTreeSet<u8>,
+}
+
+impl ReplicatedSpec for GrowOnlySet {
+    type Seq = SequentialSet;
+    type State = BTreeSet<u8>;
+
+    fn fork(&self, _: usize) -> Self {
+        self.clone()
+    }
+
+    fn exec(&mut self, op: ReplOp<Self>) -> ReplRet<Self> {
+        match op {
+            Op::Insert(value) => {
+                self.set.insert(value);
+                Ret::Insert
+            }
+            Op::Contains(value) => Ret::Contains(self.set.contains(&value)),
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.set.extend(other.set.iter().copied());
+    }
+
+    fn state(&self) -> Self::State {
+        self.set.clone()
+    }
+
+    fn seq_state(seq: &SequentialSet) -> Self::State {
+        seq.set.clone()
+    }
+}
+
+// A set whose merge throws away the local state.
+#[derive(Default, Clone)]
+struct OverwritingSet {
+    set: GrowOnlySet,
+}
+
+impl ReplicatedSpec for OverwritingSet {
+    type Seq = SequentialSet;
+    type State = BTreeSet<u8>;
+
+    fn fork(&self, _: usize) -> Self {
+        self.clone()
+    }
+
+    fn exec(&mut self, op: ReplOp<Self>) -> ReplRet<Self> {
+        self.set.exec(op)
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.set = other.set.clone();
+    }
+
+    fn state(&self) -> Self::State {
+        self.set.state()
+    }
+
+    fn seq_state(seq: &SequentialSet) -> Self::State {
+        seq.set.clone()
+    }
+}
+
+#[test]
+fn grow_only_set_converges() {
+    Lincheck::default()
+        .verify_convergence::<GrowOnlySet>()
+        .unwrap();
+}
+
+#[test]
+fn overwriting_set_does_not_converge() {
+    let failure = Lincheck::default()
+        .verify_convergence::<OverwritingSet>()
+        .unwrap_err();
+
+    let ConvergenceFailure::Violation { violation, .. } = failure else {
+        panic!("the scenarios are generated");
+    };
+    assert!(matches!(
+        violation,
+        ConvergenceViolation::NotCommutative { .. }
+    ));
+}
@@ -0,0 +1,326 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::backend::LoomBackend;
+use lincheck::deadlock::check_scenario_for_deadlocks_with_loom;
+use lincheck::scenario::Scenario;
+use lincheck::soak::SoakConfig;
+use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec, VerificationFailure};
+use loom::sync::atomic::{AtomicBool, Ordering};
+use loom::sync::Mutex;
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    MoveAToB,
+    MoveBToA,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Moved,
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![Just(Op::MoveAToB), Just(Op::MoveBToA)].boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialAccounts;
+
+impl SequentialSpec for SequentialAccounts {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, _: Op) -> Ret {
+        Ret::Moved
+    }
+}
+
+/// Locks the source account first, so two opposite moves may wait for each other forever.
+#[derive(Default)]
+struct SourceFirstAccounts {
+    a: Mutex<usize>,
+    b: Mutex<usize>,
+}
+
+impl ConcurrentSpec for SourceFirstAccounts {
+    type Seq = SequentialAccounts;
+
+    fn exec(&self, op: Op) -> Ret {
+        let (from, to) = match op {
+            Op::MoveAToB => (&self.a, &self.b),
+            Op::MoveBToA => (&self.b, &self.a),
+        };
+        let mut from = from.lock().unwrap();
+        let mut to = to.lock().unwrap();
+        *from = from.wrapping_sub(1);
+        *to = to.wrapping_add(1);
+        Ret::Moved
+    }
+}
+
+/// Always locks `a` first.
+#[derive(Default)]
+struct OrderedAccounts {
+    a: Mutex<usize>,
+    b: Mutex<usize>,
+}
+
+impl ConcurrentSpec for OrderedAccounts {
+    type Seq = SequentialAccounts;
+
+    fn exec(&self, op: Op) -> Ret {
+        let mut a = self.a.lock().unwrap();
+        let mut b = self.b.lock().unwrap();
+        match op {
+            Op::MoveAToB => {
+                *a = a.wrapping_sub(1);
+                *b = b.wrapping_add(1);
+            }
+            Op::MoveBToA => {
+                *b = b.wrapping_sub(1);
+                *a = a.wrapping_add(1);
+            }
+        }
+        Ret::Moved
+    }
+}
+
+/// Doesn't support moving from `b`.
+#[derive(Default)]
+struct OneWayAccounts {
+    a: Mutex<usize>,
+}
+
+impl ConcurrentSpec for OneWayAccounts {
+    type Seq = SequentialAccounts;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::MoveAToB => {
+                *self.a.lock().unwrap() += 1;
+                Ret::Moved
+            }
+            Op::MoveBToA => panic!("can't move from b"),
+        }
+    }
+}
+
+fn opposite_moves() -> Scenario<Op> {
+    Scenario {
+        init_part: vec![Op::MoveAToB],
+        parallel_part: vec![vec![Op::MoveAToB], vec![Op::MoveBToA]],
+        post_part: vec![],
+    }
+}
+
+#[test]
+fn opposite_lock_orders_deadlock() {
+    let error = check_scenario_for_deadlocks_with_loom::<SourceFirstAccounts>(opposite_moves())
+        .unwrap_err();
+    let VerificationFailure::Deadlock {
+        execution_so_far,
+        blocked_threads,
+    } = error
+    else {
+        panic!("expected a deadlock, got {:?}", error);
+    };
+    assert_eq!(blocked_threads, vec![0, 1]);
+    let execution_so_far = execution_so_far.to_string();
+    assert!(execution_so_far.contains("THREAD 0: MoveAToB"));
+    assert!(execution_so_far.contains("THREAD 1: MoveBToA"));
+}
+
+#[test]
+fn same_lock_order_completes() {
+    assert!(check_scenario_for_deadlocks_with_loom::<OrderedAccounts>(opposite_moves()).is_ok());
+}
+
+#[test]
+fn panicked_operation() {
+    let failure =
+        check_scenario_for_deadlocks_with_loom::<OneWayAccounts>(opposite_moves()).unwrap_err();
+    let VerificationFailure::Panicked {
+        thread,
+        payload,
+        partial_execution,
+        ..
+    } = failure
+    else {
+        panic!("expected a panic, got {:?}", failure);
+    };
+    assert_eq!(thread, Some(1));
+    assert_eq!(payload, "can't move from b");
+    assert!(partial_execution.to_string().contains("THREAD 1: MoveBToA"));
+}
+
+#[test]
+fn verify_reports_deadlock() {
+    let failure = Lincheck {
+        num_threads: 2,
+        num_ops: 1,
+        ..Default::default()
+    }
+    .verify::<SourceFirstAccounts>()
+    .unwrap_err();
+    assert!(matches!(failure, VerificationFailure::Deadlock { .. }));
+}
+
+#[test]
+fn verify_reports_panic_with_minimized_scenario() {
+    let failure = Lincheck {
+        num_threads: 2,
+        num_ops: 3,
+        ..Default::default()
+    }
+    .verify::<OneWayAccounts>()
+    .unwrap_err();
+    let VerificationFailure::Panicked {
+        scenario, payload, ..
+    } = failure
+    else {
+        panic!("expected a panic, got {:?}", failure);
+    };
+    assert_eq!(payload, "can't move from b");
+    assert_eq!(scenario.num_ops(), 1);
+}
+
+#[test]
+fn verify_parallel_reports_panic() {
+    let failure = Lincheck {
+        num_threads: 2,
+        num_ops: 3,
+        ..Default::default()
+    }
+    .verify_parallel::<OneWayAccounts>(4)
+    .unwrap_err();
+    assert!(matches!(failure, VerificationFailure::Panicked { .. }));
+}
+
+#[test]
+fn verify_with_backend_reports_panic() {
+    let failure = Lincheck {
+        num_threads: 2,
+        num_ops: 3,
+        ..Default::default()
+    }
+    .verify_with_backend::<OneWayAccounts>(&LoomBackend::default())
+    .unwrap_err();
+    assert!(matches!(failure, VerificationFailure::Panicked { .. }));
+}
+
+#[test]
+fn soak_collects_panics() {
+    let report = Lincheck {
+        num_threads: 2,
+        num_ops: 3,
+        ..Default::default()
+    }
+    .soak::<OneWayAccounts>(&SoakConfig {
+        max_scenarios: Some(20),
+        ..SoakConfig::default()
+    });
+    assert_eq!(report.scenarios_checked, 20);
+    assert!(!report.failures.is_empty());
+    assert!(report
+        .failures
+        .iter()
+        .all(|failure| matches!(failure.failure, VerificationFailure::Panicked { .. })));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagOp {
+    Wait,
+    Check,
+}
+
+impl Arbitrary for FlagOp {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![Just(FlagOp::Wait), Just(FlagOp::Check)].boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialFlag;
+
+impl SequentialSpec for SequentialFlag {
+    type Op = FlagOp;
+    type Ret = bool;
+
+    fn exec(&mut self, _: FlagOp) -> bool {
+        true
+    }
+}
+
+/// Spins on a flag that nobody sets.
+#[derive(Default)]
+struct NeverSetFlag {
+    set: AtomicBool,
+}
+
+impl ConcurrentSpec for NeverSetFlag {
+    type Seq = SequentialFlag;
+
+    fn exec(&self, op: FlagOp) -> bool {
+        match op {
+            FlagOp::Wait => {
+                while !self.set.load(Ordering::Acquire) {
+                    loom::hint::spin_loop();
+                }
+                true
+            }
+            FlagOp::Check => true,
+        }
+    }
+}
+
+#[test]
+fn spinning_operation_livelocks() {
+    let scenario = Scenario {
+        init_part: vec![],
+        parallel_part: vec![vec![FlagOp::Check], vec![FlagOp::Check, FlagOp::Wait]],
+        post_part: vec![],
+    };
+    let failure = check_scenario_for_deadlocks_with_loom::<NeverSetFlag>(scenario).unwrap_err();
+    let VerificationFailure::Livelock {
+        spinning_threads,
+        spinning_thread,
+        spinning_op,
+        ..
+    } = &failure
+    else {
+        panic!("expected a livelock, got {:?}", failure);
+    };
+    assert_eq!(spinning_threads, &vec![1]);
+    assert_eq!(*spinning_thread, Some(1));
+    assert_eq!(*spinning_op, Some(FlagOp::Wait));
+    assert!(failure
+        .to_string()
+        .contains("Wait of thread 1 never returns"));
+}
+
+#[test]
+fn verify_reports_livelock() {
+    let failure = Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        ..Default::default()
+    }
+    .verify::<NeverSetFlag>()
+    .unwrap_err();
+    assert!(matches!(
+        failure,
+        VerificationFailure::Livelock {
+            spinning_op: Some(FlagOp::Wait),
+            ..
+        }
+    ));
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "dsl")]
+
+use lincheck::dsl::ScenarioFamily;
+use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
+use loom::sync::atomic::{AtomicBool, Ordering};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+enum Op {
+    WriteX,
+    WriteY,
+    ReadX,
+    ReadY,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Write,
+    Read(bool),
+}
+
+#[derive(Default)]
+struct TwoSlotsSequential {
+    x: bool,
+    y: bool,
+}
+
+impl SequentialSpec for TwoSlotsSequential {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::WriteX => {
+                self.x = true;
+                Ret::Write
+            }
+            Op::WriteY => {
+                self.y = true;
+                Ret::Write
+            }
+            Op::ReadX => Ret::Read(self.x),
+            Op::ReadY => Ret::Read(self.y),
+        }
+    }
+}
+
+#[derive(Default)]
+struct TwoSlotsParallel {
+    x: AtomicBool,
+    y: AtomicBool,
+}
+
+impl ConcurrentSpec for TwoSlotsParallel {
+    type Seq = TwoSlotsSequential;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::WriteX => {
+                self.x.store(true, Ordering::Relaxed);
+                Ret::Write
+            }
+            Op::WriteY => {
+                self.y.store(true, Ordering::Relaxed);
+                Ret::Write
+            }
+            Op::ReadX => Ret::Read(self.x.load(Ordering::Relaxed)),
+            Op::ReadY => Ret::Read(self.y.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[test]
+fn write_only_family() {
+    let family = ScenarioFamily::from_ron(
+        "(
+            parallel_part: [
+                (ops: [(op: WriteX), (op: WriteY)], max_ops: 2),
+                (ops: [(op: WriteY)], max_ops: 2),
+            ],
+            post_part: (ops: [(op: ReadX), (op: ReadY)], max_ops: 2),
+        )",
+    )
+    .unwrap();
+
+    assert!(Lincheck::default()
+        .verify_family::<TwoSlotsParallel>(&family)
+        .is_ok());
+}
+
+#[test]
+fn store_buffering_family() {
+    let family = ScenarioFamily::from_ron(
+        "(
+            parallel_part: [
+                (ops: [(op: WriteX, weight: 2), (op: ReadY)], min_ops: 2, max_ops: 2),
+                (ops: [(op: WriteY, weight: 2), (op: ReadX)], min_ops: 2, max_ops: 2),
+            ],
+            post_part: (ops: [(op: ReadX), (op: ReadY)], max_ops: 1),
+        )",
+    )
+    .unwrap();
+
+    assert!(Lincheck::default()
+        .verify_family::<TwoSlotsParallel>(&family)
+        .is_err());
+}
+
+#[test]
+fn invalid_family() {
+    assert!(ScenarioFamily::<Op>::from_ron("(parallel_part: [(ops: [(op: Push)])])").is_err());
+}
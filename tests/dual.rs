@@ -0,0 +1,134 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::dual::{DualConcurrentSpec, DualSequentialSpec, Ticket};
+use lincheck::Lincheck;
+use proptest::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Put(u8),
+    Take,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Put,
+    Take(u8),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![any::<u8>().prop_map(Op::Put), Just(Op::Take)].boxed()
+    }
+}
+
+/// A queue where the takes wait for the puts in the order they were registered.
+#[derive(Default)]
+struct SequentialDualQueue {
+    values: VecDeque<u8>,
+    requests: VecDeque<Ticket>,
+    fulfilled: HashMap<Ticket, u8>,
+}
+
+impl DualSequentialSpec for SequentialDualQueue {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn request(&mut self, op: Op, ticket: Ticket) -> Option<Ret> {
+        match op {
+            Op::Put(value) => {
+                match self.requests.pop_front() {
+                    Some(request) => {
+                        self.fulfilled.insert(request, value);
+                    }
+                    None => self.values.push_back(value),
+                }
+                Some(Ret::Put)
+            }
+            Op::Take => {
+                let value = self.values.pop_front();
+                if value.is_none() {
+                    self.requests.push_back(ticket);
+                }
+                value.map(Ret::Take)
+            }
+        }
+    }
+
+    fn follow_up(&mut self, ticket: Ticket) -> Option<Ret> {
+        self.fulfilled.remove(&ticket).map(Ret::Take)
+    }
+}
+
+/// The slot a waiting take receives its value in.
+#[derive(Default)]
+struct Slot {
+    value: Mutex<Option<u8>>,
+    filled: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    values: VecDeque<u8>,
+    requests: VecDeque<Arc<Slot>>,
+}
+
+#[derive(Default)]
+struct DualQueue {
+    state: Mutex<State>,
+}
+
+impl DualConcurrentSpec for DualQueue {
+    type Seq = SequentialDualQueue;
+    type Waiter = Arc<Slot>;
+
+    fn request(&self, op: Op) -> Result<Ret, Arc<Slot>> {
+        let mut state = self.state.lock().unwrap();
+        match op {
+            Op::Put(value) => {
+                match state.requests.pop_front() {
+                    Some(slot) => {
+                        *slot.value.lock().unwrap() = Some(value);
+                        slot.filled.notify_one();
+                    }
+                    None => state.values.push_back(value),
+                }
+                Ok(Ret::Put)
+            }
+            Op::Take => match state.values.pop_front() {
+                Some(value) => Ok(Ret::Take(value)),
+                None => {
+                    let slot = Arc::new(Slot::default());
+                    state.requests.push_back(slot.clone());
+                    Err(slot)
+                }
+            },
+        }
+    }
+
+    fn follow_up(&self, slot: Arc<Slot>) -> Option<Ret> {
+        let value = slot.value.lock().unwrap();
+        let (value, _) = slot
+            .filled
+            .wait_timeout_while(value, Duration::from_millis(10), |value| value.is_none())
+            .unwrap();
+        value.map(Ret::Take)
+    }
+}
+
+#[test]
+fn dual_queue() {
+    let result = Lincheck {
+        num_threads: 2,
+        num_ops: 4,
+        ..Default::default()
+    }
+    .verify_dual::<DualQueue>();
+    assert!(result.is_ok());
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use common::readable::{Op, SequentialCounter};
+use lincheck::crash::crash_point;
+use lincheck::durable::check_durable_scenario_with_loom;
+use lincheck::scenario::ScenarioBuilder;
+use lincheck::{ConcurrentSpec, RecoverableSpec, VerificationFailure};
+use loom::sync::atomic::{AtomicUsize, Ordering};
+
+/// Persists every increment before returning.
+#[derive(Default)]
+struct PersistentCounter {
+    persisted: AtomicUsize,
+}
+
+impl ConcurrentSpec for PersistentCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> usize {
+        match op {
+            Op::Increment => {
+                crash_point();
+                let x = self.persisted.fetch_add(1, Ordering::SeqCst);
+                crash_point();
+                x
+            }
+            Op::Get => self.persisted.fetch_add(0, Ordering::SeqCst),
+        }
+    }
+}
+
+impl RecoverableSpec for PersistentCounter {
+    fn recover(&self) -> Self {
+        Self {
+            persisted: AtomicUsize::new(self.persisted.fetch_add(0, Ordering::SeqCst)),
+        }
+    }
+}
+
+/// Persists only every second increment, losing the completed odd ones on a crash.
+#[derive(Default)]
+struct BufferedCounter {
+    volatile: AtomicUsize,
+    persisted: AtomicUsize,
+}
+
+impl ConcurrentSpec for BufferedCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> usize {
+        match op {
+            Op::Increment => {
+                crash_point();
+                let x = self.volatile.fetch_add(1, Ordering::SeqCst);
+                if x % 2 == 1 {
+                    self.persisted.fetch_max(x + 1, Ordering::SeqCst);
+                }
+                crash_point();
+                x
+            }
+            Op::Get => self.volatile.fetch_add(0, Ordering::SeqCst),
+        }
+    }
+}
+
+impl RecoverableSpec for BufferedCounter {
+    fn recover(&self) -> Self {
+        let persisted = self.persisted.fetch_add(0, Ordering::SeqCst);
+        Self {
+            volatile: AtomicUsize::new(persisted),
+            persisted: AtomicUsize::new(persisted),
+        }
+    }
+}
+
+#[test]
+fn persistent_counter_is_durable() {
+    assert!(lincheck(2).verify_durable::<PersistentCounter>().is_ok());
+}
+
+#[test]
+fn buffered_counter_loses_increments() {
+    assert!(matches!(
+        lincheck(2).verify_durable::<BufferedCounter>(),
+        Err(VerificationFailure::NonLinearizable(_))
+    ));
+}
+
+#[test]
+fn increments_in_flight_may_be_lost() {
+    let scenario = ScenarioBuilder::new()
+        .init([Op::Increment])
+        .thread([Op::Increment, Op::Increment])
+        .thread([Op::Increment])
+        .post([Op::Get, Op::Increment])
+        .build();
+    for crash_at in 0..6 {
+        assert!(
+            check_durable_scenario_with_loom::<PersistentCounter>(scenario.clone(), crash_at)
+                .is_ok()
+        );
+    }
+}
+
+#[test]
+fn completed_increment_is_lost_after_the_parallel_part() {
+    let scenario = ScenarioBuilder::new()
+        .thread([Op::Increment])
+        .post([Op::Get])
+        .build();
+    // the crash comes after the only two crash points
+    assert!(check_durable_scenario_with_loom::<BufferedCounter>(scenario, 2).is_err());
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use lincheck::faults::FaultySpec;
+use lincheck::{fail_point, ConcurrentSpec, SequentialSpec};
+use loom::sync::Mutex;
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Push(u8),
+    Pop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Push(Result<(), OutOfMemory>),
+    Pop(Option<u8>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OutOfMemory;
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![(1..=2u8).prop_map(Op::Push), Just(Op::Pop)].boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialStack {
+    values: Vec<u8>,
+}
+
+impl SequentialSpec for SequentialStack {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Push(value) => {
+                self.values.push(value);
+                Ret::Push(Ok(()))
+            }
+            Op::Pop => Ret::Pop(self.values.pop()),
+        }
+    }
+}
+
+impl FaultySpec for SequentialStack {
+    fn exec_faulted(&mut self, op: Op, _faults: &[&'static str]) -> Ret {
+        match op {
+            // a failed allocation leaves the stack untouched
+            Op::Push(_) => Ret::Push(Err(OutOfMemory)),
+            Op::Pop => self.exec(op),
+        }
+    }
+}
+
+/// Reserves the slot before pushing the value into it.
+#[derive(Default)]
+struct Stack {
+    slots: Mutex<Vec<Option<u8>>>,
+}
+
+impl Stack {
+    fn pop(&self) -> Ret {
+        Ret::Pop(self.slots.lock().unwrap().pop().flatten())
+    }
+}
+
+#[derive(Default)]
+struct RollingBackStack(Stack);
+
+impl ConcurrentSpec for RollingBackStack {
+    type Seq = SequentialStack;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::Push(value) => {
+                let mut slots = self.0.slots.lock().unwrap();
+                slots.push(None);
+                if fail_point!("alloc") {
+                    slots.pop();
+                    return Ret::Push(Err(OutOfMemory));
+                }
+                *slots.last_mut().unwrap() = Some(value);
+                Ret::Push(Ok(()))
+            }
+            Op::Pop => self.0.pop(),
+        }
+    }
+}
+
+/// Forgets to release the reserved slot if the allocation fails.
+#[derive(Default)]
+struct LeakingStack(Stack);
+
+impl ConcurrentSpec for LeakingStack {
+    type Seq = SequentialStack;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::Push(value) => {
+                let mut slots = self.0.slots.lock().unwrap();
+                slots.push(None);
+                fail_point!("alloc", Ret::Push(Err(OutOfMemory)));
+                *slots.last_mut().unwrap() = Some(value);
+                Ret::Push(Ok(()))
+            }
+            Op::Pop => self.0.pop(),
+        }
+    }
+}
+
+#[test]
+fn rolling_back_stack() {
+    assert!(lincheck(2).verify_faults::<RollingBackStack>().is_ok());
+}
+
+#[test]
+fn leaking_stack() {
+    assert!(lincheck(2).verify_faults::<LeakingStack>().is_err());
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::fuzz::FuzzConfig;
+use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
+use proptest::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Increment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    OldValue(usize),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        Just(Op::Increment).boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialCounter {
+    x: usize,
+}
+
+impl SequentialSpec for SequentialCounter {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Increment => {
+                let val = self.x;
+                self.x += 1;
+                Ret::OldValue(val)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct AtomicCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for AtomicCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::Increment => Ret::OldValue(self.x.fetch_add(1, Ordering::SeqCst)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct RacyCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for RacyCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::Increment => {
+                let val = self.x.load(Ordering::SeqCst);
+                // widen the race window
+                thread::yield_now();
+                self.x.store(val + 1, Ordering::SeqCst);
+                Ret::OldValue(val)
+            }
+        }
+    }
+}
+
+#[test]
+fn atomic_counter() {
+    let config = FuzzConfig {
+        runs_per_scenario: 10,
+        ..Default::default()
+    };
+    let result = Lincheck::default().fuzz_with::<AtomicCounter>(&config);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn racy_counter() {
+    let lincheck = Lincheck {
+        num_threads: 2,
+        num_ops: 3,
+        ..Default::default()
+    };
+    assert!(lincheck.fuzz::<RacyCounter>().is_err());
+}
+
+#[test]
+fn report_records_latencies() {
+    let config = FuzzConfig {
+        runs_per_scenario: 10,
+        ..Default::default()
+    };
+    let report = Lincheck::default().fuzz_report::<AtomicCounter>(&config);
+    assert!(report.result.is_ok());
+    assert!(report.executions > 0);
+
+    let [latencies] = &report.latencies[..] else {
+        panic!("the counter has a single operation kind");
+    };
+    assert_eq!(latencies.op, "Increment");
+    assert!(latencies.histogram.count() > 0);
+    assert!(latencies.histogram.percentile(99.0).is_some());
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use lincheck::coverage::GuidedConfig;
+use lincheck::{ConcurrentSpec, SequentialSpec};
+use loom::sync::atomic::{AtomicUsize, Ordering};
+
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Increment,
+    Reset,
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![Just(Op::Increment), Just(Op::Reset)].boxed()
+    }
+}
+
+#[derive(Default, Hash)]
+struct SequentialCounter {
+    x: usize,
+}
+
+impl SequentialSpec for SequentialCounter {
+    type Op = Op;
+    type Ret = usize;
+
+    fn exec(&mut self, op: Op) -> usize {
+        match op {
+            Op::Increment => {
+                self.x += 1;
+                self.x - 1
+            }
+            Op::Reset => std::mem::take(&mut self.x),
+        }
+    }
+}
+
+#[derive(Default)]
+struct AtomicCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for AtomicCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> usize {
+        match op {
+            Op::Increment => self.x.fetch_add(1, Ordering::SeqCst),
+            Op::Reset => self.x.swap(0, Ordering::SeqCst),
+        }
+    }
+}
+
+/// A counter that loads and stores the value separately, losing the concurrent increments.
+#[derive(Default)]
+struct RacyCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for RacyCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> usize {
+        match op {
+            Op::Increment => {
+                let x = self.x.load(Ordering::SeqCst);
+                self.x.store(x + 1, Ordering::SeqCst);
+                x
+            }
+            Op::Reset => self.x.swap(0, Ordering::SeqCst),
+        }
+    }
+}
+
+#[test]
+fn guided_linearizable() {
+    assert!(lincheck(3)
+        .verify_guided::<AtomicCounter>(&GuidedConfig::default())
+        .is_ok());
+}
+
+#[test]
+fn guided_non_linearizable() {
+    let failure = lincheck(3)
+        .verify_guided::<RacyCounter>(&GuidedConfig::default())
+        .unwrap_err();
+    let execution = failure.execution().expect("the execution is reported");
+    assert_eq!(execution.parallel_part().num_threads(), 2);
+}
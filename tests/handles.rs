@@ -0,0 +1,217 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::handles::{HandleSpec, RoleSpec};
+use lincheck::{Lincheck, SequentialSpec};
+use loom::sync::{Arc, Mutex};
+use proptest::prelude::*;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Send(u8),
+    Recv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Send,
+    Recv(Option<u8>),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![(1..=2u8).prop_map(Op::Send), Just(Op::Recv)].boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialChannel {
+    queue: VecDeque<u8>,
+}
+
+impl SequentialSpec for SequentialChannel {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Send(value) => {
+                self.queue.push_back(value);
+                Ret::Send
+            }
+            Op::Recv => Ret::Recv(self.queue.pop_front()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Channel {
+    queue: Mutex<VecDeque<u8>>,
+}
+
+/// An end of the channel that keeps the channel alive.
+struct Port {
+    channel: Arc<Channel>,
+}
+
+impl HandleSpec for Channel {
+    type Seq = SequentialChannel;
+    type Handle = Port;
+
+    fn handle(this: &Arc<Self>) -> Port {
+        Port {
+            channel: this.clone(),
+        }
+    }
+
+    fn exec(port: &mut Port, op: Op) -> Ret {
+        let mut queue = port.channel.queue.lock().unwrap();
+        match op {
+            Op::Send(value) => {
+                queue.push_back(value);
+                Ret::Send
+            }
+            Op::Recv => Ret::Recv(queue.pop_front()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct BatchingChannel {
+    queue: Mutex<VecDeque<u8>>,
+}
+
+/// Buffers the sent values and flushes them on the next operation of the same port.
+struct BatchingPort {
+    channel: Arc<BatchingChannel>,
+    buffer: Vec<u8>,
+}
+
+impl HandleSpec for BatchingChannel {
+    type Seq = SequentialChannel;
+    type Handle = BatchingPort;
+
+    fn handle(this: &Arc<Self>) -> BatchingPort {
+        BatchingPort {
+            channel: this.clone(),
+            buffer: Vec::new(),
+        }
+    }
+
+    fn exec(port: &mut BatchingPort, op: Op) -> Ret {
+        let mut queue = port.channel.queue.lock().unwrap();
+        queue.extend(port.buffer.drain(..));
+        match op {
+            Op::Send(value) => {
+                port.buffer.push(value);
+                Ret::Send
+            }
+            Op::Recv => Ret::Recv(queue.pop_front()),
+        }
+    }
+}
+
+/// A channel with a single receiver.
+#[derive(Default)]
+struct Mpsc {
+    queue: Mutex<VecDeque<u8>>,
+}
+
+enum MpscRole {
+    Sender,
+    Receiver,
+}
+
+enum MpscHandle {
+    Sender(Arc<Mpsc>),
+    Receiver(Arc<Mpsc>),
+}
+
+impl HandleSpec for Mpsc {
+    type Seq = SequentialChannel;
+    type Handle = MpscHandle;
+
+    fn handle(this: &Arc<Self>) -> MpscHandle {
+        Self::handle_with_role(this, &Self::main_role())
+    }
+
+    fn exec(handle: &mut MpscHandle, op: Op) -> Ret {
+        match (handle, op) {
+            (MpscHandle::Sender(channel), Op::Send(value)) => {
+                channel.queue.lock().unwrap().push_back(value);
+                Ret::Send
+            }
+            (MpscHandle::Receiver(channel), Op::Recv) => {
+                Ret::Recv(channel.queue.lock().unwrap().pop_front())
+            }
+            (_, op) => panic!("{:?} isn't allowed for the handle", op),
+        }
+    }
+}
+
+impl RoleSpec for Mpsc {
+    type Role = MpscRole;
+
+    fn role(thread_id: usize) -> MpscRole {
+        if thread_id == 0 {
+            MpscRole::Receiver
+        } else {
+            MpscRole::Sender
+        }
+    }
+
+    fn main_role() -> MpscRole {
+        MpscRole::Receiver
+    }
+
+    fn handle_with_role(this: &Arc<Self>, role: &MpscRole) -> MpscHandle {
+        match role {
+            MpscRole::Sender => MpscHandle::Sender(this.clone()),
+            MpscRole::Receiver => MpscHandle::Receiver(this.clone()),
+        }
+    }
+
+    fn ops(role: &MpscRole) -> BoxedStrategy<Op> {
+        match role {
+            MpscRole::Sender => (1..=2u8).prop_map(Op::Send).boxed(),
+            MpscRole::Receiver => Just(Op::Recv).boxed(),
+        }
+    }
+}
+
+#[test]
+fn per_thread_handles() {
+    Lincheck {
+        num_threads: 2,
+        num_ops: 4,
+        ..Default::default()
+    }
+    .verify_handles::<Channel>()
+    .unwrap();
+}
+
+#[test]
+fn batching_handles_are_not_linearizable() {
+    let execution = Lincheck {
+        num_threads: 2,
+        num_ops: 4,
+        ..Default::default()
+    }
+    .verify_handles::<BatchingChannel>()
+    .unwrap_err();
+    assert!(execution.to_string().contains("Send"));
+}
+
+#[test]
+fn operations_match_the_roles() {
+    Lincheck {
+        num_threads: 2,
+        num_ops: 4,
+        ..Default::default()
+    }
+    .verify_roles::<Mpsc>()
+    .unwrap();
+}
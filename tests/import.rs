@@ -0,0 +1,106 @@
+use lincheck::checker::LinearizabilityChecker;
+use lincheck::import::{from_edn, from_porcupine_json, Edn, ImportError, Json};
+use lincheck::specs::{Register, RegisterOp, RegisterRet};
+use lincheck::{Execution, InvalidExecution};
+
+type RegisterExecution = Execution<RegisterOp<i64>, RegisterRet<i64>>;
+
+fn register_from_edn(history: &str) -> Result<RegisterExecution, ImportError> {
+    from_edn(
+        history,
+        |f, value| match (f.as_keyword(), value) {
+            (Some("read"), _) => Ok(RegisterOp::Read),
+            (Some("write"), Edn::Int(value)) => Ok(RegisterOp::Write(*value)),
+            _ => Err(format!("unknown operation {:?} {:?}", f, value)),
+        },
+        |f, value| match (f.as_keyword(), value) {
+            (Some("read"), Edn::Int(value)) => Ok(RegisterRet::Read(*value)),
+            (Some("write"), _) => Ok(RegisterRet::Write),
+            _ => Err(format!("unknown result {:?} {:?}", f, value)),
+        },
+    )
+}
+
+/// The operations are `{"write": 1}` or `{"read": null}` and the outputs are the values read.
+fn register_from_porcupine(history: &str) -> Result<RegisterExecution, ImportError> {
+    from_porcupine_json(
+        history,
+        |input| match (input.get("write"), input.get("read")) {
+            (Some(Json::Int(value)), _) => Ok(RegisterOp::Write(*value)),
+            (_, Some(_)) => Ok(RegisterOp::Read),
+            _ => Err(format!("unknown input {:?}", input)),
+        },
+        |output| match output {
+            Json::Int(value) => Ok(RegisterRet::Read(*value)),
+            Json::Null => Ok(RegisterRet::Write),
+            _ => Err(format!("unknown output {:?}", output)),
+        },
+    )
+}
+
+#[test]
+fn knossos_history() {
+    // process 1 crashes while writing 2 and is replaced by process 3, the failed write of 3 never happened
+    let history = r#"
+        {:process 0, :type :invoke, :f :write, :value 1}
+        {:process :nemesis, :type :info, :f :start, :value nil}
+        {:process 0, :type :ok, :f :write, :value 1}
+        {:process 1, :type :invoke, :f :write, :value 2}
+        {:process 2, :type :invoke, :f :write, :value 3}
+        {:process 1, :type :info, :f :write, :value 2}
+        {:process 2, :type :fail, :f :write, :value 3}
+        {:process 3, :type :invoke, :f :read, :value nil}
+        {:process 3, :type :ok, :f :read, :value 2}
+    "#;
+    let execution = register_from_edn(history).unwrap();
+    assert_eq!(execution.parallel_part().len(), 2);
+    assert_eq!(execution.pending().len(), 1);
+    assert!(LinearizabilityChecker::<Register<i64>>::check(&execution));
+
+    // reads the value of the failed write
+    let execution =
+        register_from_edn(&history.replace(":f :read, :value 2", ":f :read, :value 3")).unwrap();
+    assert!(!LinearizabilityChecker::<Register<i64>>::check(&execution));
+}
+
+#[test]
+fn porcupine_history() {
+    let history = r#"[
+        {"ClientId": 0, "Input": {"write": 1}, "Call": 0, "Output": null, "Return": 10},
+        {"ClientId": 1, "Input": {"read": null}, "Call": 5, "Output": 1, "Return": 15},
+        {"ClientId": 1, "Input": {"read": null}, "Call": 20, "Output": 0, "Return": 30}
+    ]"#;
+    let execution = register_from_porcupine(history).unwrap();
+    assert_eq!(execution.parallel_part().num_threads(), 2);
+    assert!(!LinearizabilityChecker::<Register<i64>>::check(&execution));
+
+    let execution =
+        register_from_porcupine(&history.replace("\"Output\": 0", "\"Output\": 1")).unwrap();
+    assert!(LinearizabilityChecker::<Register<i64>>::check(&execution));
+}
+
+#[test]
+fn import_errors() {
+    assert_eq!(
+        register_from_edn("{:process 0, :type :ok, :f :read, :value 1}"),
+        Err(ImportError::Malformed {
+            index: 0,
+            message: "the process completes an operation it didn't invoke".into()
+        })
+    );
+    assert!(matches!(
+        register_from_edn("{:process 0, :type :invoke, :f :cas, :value [1 2]}"),
+        Err(ImportError::Decode { index: 0, .. })
+    ));
+    assert_eq!(
+        register_from_porcupine(
+            r#"[
+                {"clientId": 0, "input": {"read": null}, "call": 0, "output": 0, "return": 10},
+                {"clientId": 0, "input": {"read": null}, "call": 5, "output": 0, "return": 15}
+            ]"#
+        ),
+        Err(ImportError::Invalid(
+            InvalidExecution::OverlappingInvocations { thread_id: 0 }
+        ))
+    );
+}
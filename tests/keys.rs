@@ -0,0 +1,93 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::keys::KeySpace;
+use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
+use loom::sync::Mutex;
+use std::collections::HashMap;
+
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    /// Increments the counter of the key and returns its previous value.
+    Increment(u64),
+}
+
+impl Arbitrary for Op {
+    type Parameters = KeySpace<u64>;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(keys: KeySpace<u64>) -> Self::Strategy {
+        keys.strategy().prop_map(Op::Increment).boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialCounters {
+    counters: HashMap<u64, usize>,
+}
+
+impl SequentialSpec for SequentialCounters {
+    type Op = Op;
+    type Ret = usize;
+
+    fn exec(&mut self, Op::Increment(key): Op) -> usize {
+        let counter = self.counters.entry(key).or_default();
+        *counter += 1;
+        *counter - 1
+    }
+}
+
+/// Counters that read and write the counter of a key under separate locks,
+/// losing the concurrent increments of the same key.
+#[derive(Default)]
+struct RacyCounters {
+    counters: Mutex<HashMap<u64, usize>>,
+}
+
+impl ConcurrentSpec for RacyCounters {
+    type Seq = SequentialCounters;
+
+    fn exec(&self, Op::Increment(key): Op) -> usize {
+        let counter = self
+            .counters
+            .lock()
+            .unwrap()
+            .get(&key)
+            .copied()
+            .unwrap_or(0);
+        self.counters.lock().unwrap().insert(key, counter + 1);
+        counter
+    }
+}
+
+#[test]
+fn weighted_keys() {
+    let keys = KeySpace::weighted([(3, 7), (0, 8), (1, 9)]);
+    assert_eq!(keys.keys().copied().collect::<Vec<_>>(), vec![7, 9]);
+
+    let strategy = keys.strategy();
+    let mut runner = TestRunner::deterministic();
+    let drawn: Vec<_> = (0..100)
+        .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+        .collect();
+    assert!(drawn.iter().all(|key| [7, 9].contains(key)));
+    let hot = drawn.iter().filter(|&&key| key == 7).count();
+    assert!(hot > 50);
+}
+
+#[test]
+fn races_on_shared_keys() {
+    let lincheck = Lincheck {
+        num_threads: 2,
+        num_ops: 3,
+        ..Default::default()
+    };
+    let failure = lincheck
+        .verify_with_keys::<RacyCounters, _>(KeySpace::new([0, 1]))
+        .unwrap_err();
+    let execution = failure.execution().expect("the execution is reported");
+    assert_eq!(execution.parallel_part().num_threads(), 2);
+}
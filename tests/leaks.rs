@@ -0,0 +1,121 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::relaxed::{RelaxedStack, StackOp, StackRet};
+use lincheck::{leaks, ConcurrentSpec, Lincheck, LoomConfig, VerificationFailure};
+use loom::sync::Mutex;
+
+/// How [BoxedStack] handles the popped nodes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Reclamation {
+    /// Freed when popped.
+    Free,
+    /// Never freed.
+    Leak,
+    /// Freed when popped and again when the stack is dropped.
+    FreeTwice,
+}
+
+/// A stack of boxed nodes, with the addresses of the pushed and the popped ones under a lock.
+#[derive(Default)]
+struct BoxedStack<const RECLAMATION: u8> {
+    nodes: Mutex<Vec<usize>>,
+    popped: Mutex<Vec<usize>>,
+}
+
+impl<const RECLAMATION: u8> BoxedStack<RECLAMATION> {
+    const RECLAMATION: Reclamation = match RECLAMATION {
+        0 => Reclamation::Free,
+        1 => Reclamation::Leak,
+        _ => Reclamation::FreeTwice,
+    };
+}
+
+impl<const RECLAMATION: u8> ConcurrentSpec for BoxedStack<RECLAMATION> {
+    type Seq = RelaxedStack<u8>;
+
+    fn exec(&self, op: StackOp<u8>) -> StackRet<u8> {
+        match op {
+            StackOp::Push(value) => {
+                let node = leaks::into_raw(Box::new(value));
+                self.nodes.lock().unwrap().push(node as usize);
+                StackRet::Push
+            }
+            StackOp::Pop => {
+                let Some(node) = self.nodes.lock().unwrap().pop() else {
+                    return StackRet::Pop(None);
+                };
+                let node = node as *mut u8;
+                let value = unsafe { *node };
+                match Self::RECLAMATION {
+                    Reclamation::Leak => {}
+                    Reclamation::Free => drop(unsafe { leaks::from_raw(node) }),
+                    Reclamation::FreeTwice => {
+                        drop(unsafe { leaks::from_raw(node) });
+                        self.popped.lock().unwrap().push(node as usize);
+                    }
+                }
+                StackRet::Pop(Some(value))
+            }
+        }
+    }
+}
+
+impl<const RECLAMATION: u8> Drop for BoxedStack<RECLAMATION> {
+    fn drop(&mut self) {
+        let nodes = std::mem::take(&mut *self.nodes.lock().unwrap());
+        let popped = std::mem::take(&mut *self.popped.lock().unwrap());
+        for node in nodes.into_iter().chain(popped) {
+            drop(unsafe { leaks::from_raw(node as *mut u8) });
+        }
+    }
+}
+
+fn config() -> Lincheck {
+    Lincheck {
+        num_threads: 2,
+        num_ops: 3,
+        loom: LoomConfig {
+            check_leaks: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn no_memory_errors() {
+    let report = config().run::<BoxedStack<0>>();
+    assert!(report.result.is_ok());
+    assert!(report.allocations > 0);
+}
+
+#[test]
+fn leaked_nodes() {
+    match config().verify::<BoxedStack<1>>() {
+        Err(VerificationFailure::MemoryError {
+            leaked,
+            double_frees,
+            ..
+        }) => {
+            assert_eq!(leaked, 1);
+            assert_eq!(double_frees, 0);
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn double_freed_nodes() {
+    let failure = config().verify::<BoxedStack<2>>().unwrap_err();
+    assert!(matches!(
+        failure,
+        VerificationFailure::MemoryError {
+            leaked: 0,
+            double_frees: 1,
+            ..
+        }
+    ));
+    assert!(failure
+        .to_string()
+        .starts_with("Memory error: 0 allocations leaked, 1 double frees"));
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "arbitrary")]
+
+mod common;
+
+use arbitrary::Unstructured;
+use common::{AtomicCounter, Op, RacyCounter};
+use lincheck::scenario::Scenario;
+use lincheck::{fuzz_target, Lincheck};
+use std::panic;
+
+/// Every input of four bytes below four, some of them running the increments concurrently.
+fn corpus() -> Vec<Vec<u8>> {
+    (0..256u32)
+        .map(|i| (0..4).map(|byte| (i >> (2 * byte) & 3) as u8).collect())
+        .collect()
+}
+
+#[test]
+fn scenarios_within_bounds() {
+    for data in corpus() {
+        let scenario =
+            Scenario::<Op>::arbitrary_within(&mut Unstructured::new(&data), 3, 2).unwrap();
+        assert!((1..=3).contains(&scenario.parallel_part.len()));
+        assert!(scenario.init_part.len() <= 2 && scenario.post_part.len() <= 2);
+        assert!(scenario
+            .parallel_part
+            .iter()
+            .all(|thread| thread.len() <= 2));
+    }
+}
+
+#[test]
+fn linearizable_corpus() {
+    for data in corpus() {
+        fuzz_target::<AtomicCounter>(&data);
+    }
+    // too short to build a scenario from
+    fuzz_target::<RacyCounter>(&[]);
+}
+
+#[test]
+fn non_linearizable_corpus() {
+    let crashed = corpus().iter().any(|data| {
+        panic::catch_unwind(|| {
+            Lincheck {
+                num_threads: 2,
+                num_ops: 2,
+                ..Default::default()
+            }
+            .fuzz_target::<RacyCounter>(data)
+        })
+        .is_err()
+    });
+    assert!(crashed);
+}
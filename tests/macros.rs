@@ -0,0 +1,141 @@
+#![cfg(feature = "macros")]
+
+use lincheck::{Lincheck, Replay};
+use loom::sync::atomic::{AtomicUsize, Ordering};
+use loom::sync::Mutex;
+
+#[derive(Default)]
+struct SequentialQueue {
+    queue: std::collections::VecDeque<u8>,
+}
+
+#[lincheck::operations]
+impl SequentialQueue {
+    #[operation]
+    fn push(&mut self, value: u8) {
+        self.queue.push_back(value);
+    }
+
+    #[operation]
+    fn pop(&mut self) -> Option<u8> {
+        self.queue.pop_front()
+    }
+
+    #[operation]
+    fn push_both(&mut self, first: u8, second: u8) {
+        self.queue.extend([first, second]);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct ConcurrentQueue {
+    queue: Mutex<std::collections::VecDeque<u8>>,
+}
+
+#[lincheck::operations(seq = SequentialQueue)]
+impl ConcurrentQueue {
+    #[operation]
+    fn push(&self, value: u8) {
+        self.queue.lock().unwrap().push_back(value);
+    }
+
+    #[operation]
+    fn pop(&self) -> Option<u8> {
+        self.queue.lock().unwrap().pop_front()
+    }
+
+    #[operation]
+    fn push_both(&self, first: u8, second: u8) {
+        self.queue.lock().unwrap().extend([first, second]);
+    }
+}
+
+/// Pushes the two values with separate locks, so another thread may push in between.
+#[derive(Default)]
+struct SplitPushQueue {
+    queue: Mutex<std::collections::VecDeque<u8>>,
+}
+
+#[lincheck::operations(seq = SequentialQueue, op = SequentialQueueOp, ret = SequentialQueueRet)]
+impl SplitPushQueue {
+    #[operation]
+    fn push(&self, value: u8) {
+        self.queue.lock().unwrap().push_back(value);
+    }
+
+    #[operation]
+    fn pop(&self) -> Option<u8> {
+        self.queue.lock().unwrap().pop_front()
+    }
+
+    #[operation]
+    fn push_both(&self, first: u8, second: u8) {
+        self.push(first);
+        self.push(second);
+    }
+}
+
+#[derive(Default)]
+struct SequentialCounter {
+    value: usize,
+}
+
+#[lincheck::operations(op = CounterOp, ret = CounterRet)]
+impl SequentialCounter {
+    #[operation]
+    fn increment(&mut self) -> usize {
+        self.value += 1;
+        self.value
+    }
+}
+
+#[derive(Default)]
+struct AtomicCounter {
+    value: AtomicUsize,
+}
+
+#[lincheck::operations(seq = SequentialCounter, op = CounterOp, ret = CounterRet)]
+impl AtomicCounter {
+    #[operation]
+    fn increment(&self) -> usize {
+        self.value.fetch_add(1, Ordering::SeqCst) + 1
+    }
+}
+
+#[test]
+fn generates_the_operations() {
+    assert_eq!(
+        SequentialQueue::replay([
+            SequentialQueueOp::PushBoth(1, 2),
+            SequentialQueueOp::Pop,
+            SequentialQueueOp::Push(3),
+        ]),
+        vec![
+            SequentialQueueRet::PushBoth,
+            SequentialQueueRet::Pop(Some(1)),
+            SequentialQueueRet::Push,
+        ]
+    );
+    assert!(SequentialQueue::default().is_empty());
+}
+
+#[test]
+fn verifies_generated_operations() {
+    Lincheck::default().verify_or_panic::<ConcurrentQueue>();
+    Lincheck::default().verify_or_panic::<AtomicCounter>();
+}
+
+#[test]
+fn finds_bugs_with_generated_operations() {
+    let failure = Lincheck {
+        num_threads: 2,
+        num_ops: 4,
+        ..Default::default()
+    }
+    .verify::<SplitPushQueue>();
+    assert!(failure.is_err());
+}
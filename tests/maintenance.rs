@@ -0,0 +1,132 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
+use loom::sync::Mutex;
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Increment,
+    Get,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Increment,
+    Get(usize),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![Just(Op::Increment), Just(Op::Get)].boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialCounter {
+    x: usize,
+}
+
+impl SequentialSpec for SequentialCounter {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Increment => {
+                self.x += 1;
+                Ret::Increment
+            }
+            Op::Get => Ret::Get(self.x),
+        }
+    }
+}
+
+/// The increments are appended to a log which is compacted into the base in the background.
+#[derive(Default)]
+struct LoggingCounter {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    base: usize,
+    log: usize,
+}
+
+impl LoggingCounter {
+    fn exec(&self, op: Op) -> Ret {
+        let mut inner = self.inner.lock().unwrap();
+        match op {
+            Op::Increment => {
+                inner.log += 1;
+                Ret::Increment
+            }
+            Op::Get => Ret::Get(inner.base + inner.log),
+        }
+    }
+}
+
+#[derive(Default)]
+struct CompactingCounter(LoggingCounter);
+
+impl ConcurrentSpec for CompactingCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> Ret {
+        self.0.exec(op)
+    }
+
+    const MAINTENANCE_PASSES: usize = 1;
+
+    fn run_maintenance(&self) {
+        let mut inner = self.0.inner.lock().unwrap();
+        inner.base += inner.log;
+        inner.log = 0;
+    }
+}
+
+/// Compacts the log without holding the lock in between, losing concurrent increments.
+#[derive(Default)]
+struct LossyCompactingCounter(LoggingCounter);
+
+impl ConcurrentSpec for LossyCompactingCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> Ret {
+        self.0.exec(op)
+    }
+
+    const MAINTENANCE_PASSES: usize = 1;
+
+    fn run_maintenance(&self) {
+        let log = self.0.inner.lock().unwrap().log;
+        let mut inner = self.0.inner.lock().unwrap();
+        inner.base += log;
+        inner.log = 0;
+    }
+}
+
+#[test]
+fn compacting_counter() {
+    Lincheck {
+        num_threads: 2,
+        num_ops: 1,
+        ..Default::default()
+    }
+    .verify_or_panic::<CompactingCounter>();
+}
+
+#[test]
+fn lossy_compacting_counter() {
+    let result = Lincheck {
+        num_threads: 2,
+        num_ops: 1,
+        ..Default::default()
+    }
+    .verify::<LossyCompactingCounter>();
+    assert!(result.is_err());
+}
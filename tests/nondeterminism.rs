@@ -0,0 +1,115 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::nondeterminism::NonDeterministicSequentialSpec;
+use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
+use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    TryLock,
+    Unlock,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    TryLock(bool),
+    Unlock,
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![Just(Op::TryLock), Just(Op::Unlock)].boxed()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SequentialLock {
+    locked: bool,
+}
+
+impl SequentialSpec for SequentialLock {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::TryLock => {
+                let acquired = !self.locked;
+                self.locked = true;
+                Ret::TryLock(acquired)
+            }
+            Op::Unlock => {
+                self.locked = false;
+                Ret::Unlock
+            }
+        }
+    }
+}
+
+// `try_lock` may also fail when the lock is free.
+impl NonDeterministicSequentialSpec for SequentialLock {
+    fn exec_all(&self, op: Op) -> Vec<(Ret, Self)> {
+        let mut next = self.clone();
+        let mut outcomes = vec![(next.exec(op), next)];
+        if op == Op::TryLock && !self.locked {
+            outcomes.push((Ret::TryLock(false), self.clone()));
+        }
+        outcomes
+    }
+}
+
+// Gives up on `try_lock` whenever another operation is in progress, even if the lock is free.
+#[derive(Default)]
+struct ContendedLock {
+    locked: AtomicBool,
+    in_progress: AtomicUsize,
+}
+
+impl ConcurrentSpec for ContendedLock {
+    type Seq = SequentialLock;
+
+    fn exec(&self, op: Op) -> Ret {
+        let contended = self.in_progress.fetch_add(1, Ordering::SeqCst) > 0;
+        let ret = match op {
+            Op::TryLock => Ret::TryLock(
+                !contended
+                    && self
+                        .locked
+                        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                        .is_ok(),
+            ),
+            Op::Unlock => {
+                self.locked.store(false, Ordering::SeqCst);
+                Ret::Unlock
+            }
+        };
+        self.in_progress.fetch_sub(1, Ordering::SeqCst);
+        ret
+    }
+}
+
+#[test]
+fn spurious_failures_are_linearizable() {
+    Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        ..Default::default()
+    }
+    .verify_nondeterministic::<ContendedLock>()
+    .unwrap();
+}
+
+#[test]
+fn spurious_failures_are_not_linearizable_deterministically() {
+    assert!(Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        ..Default::default()
+    }
+    .verify::<ContendedLock>()
+    .is_err());
+}
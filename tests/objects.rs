@@ -0,0 +1,91 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use common::{AtomicCounter, Op, RacyCounter, SequentialCounter};
+use lincheck::checker::{LinearizabilityChecker, SpecChecker};
+use lincheck::objects::{ObjectOp, Objects};
+use lincheck::{ConcurrentSpec, Lincheck};
+use loom::sync::atomic::{AtomicUsize, Ordering};
+
+use proptest::test_runner::{Config as ProptestConfig, RngSeed};
+
+/// Two counters whose increments are also counted by a racy total, which must match their sum.
+#[derive(Default)]
+struct CountedCounters {
+    counters: Objects<AtomicCounter, 2>,
+    total: AtomicUsize,
+}
+
+impl ConcurrentSpec for CountedCounters {
+    type Seq = Objects<SequentialCounter, 2>;
+
+    fn exec(&self, op: ObjectOp<Op, 2>) -> usize {
+        let total = self.total.load(Ordering::SeqCst);
+        self.total.store(total + 1, Ordering::SeqCst);
+        self.counters.exec(op)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let sum: usize = self
+            .counters
+            .objects()
+            .iter()
+            .map(|counter| counter.x.load(Ordering::SeqCst))
+            .sum();
+        let total = self.total.load(Ordering::SeqCst);
+        if sum == total {
+            Ok(())
+        } else {
+            Err(format!("counted {} increments of {}", total, sum))
+        }
+    }
+}
+
+#[test]
+fn independent_objects() {
+    assert!(lincheck(3).verify::<Objects<AtomicCounter, 2>>().is_ok());
+}
+
+/// A seed whose failing scenario shrinks both racing increments to the object `#0`.
+/// Shrinking can't move them to another object at once, so it depends on the seed.
+const SEED: u64 = 0;
+
+#[test]
+fn racy_object() {
+    let lincheck = Lincheck {
+        proptest: ProptestConfig {
+            rng_seed: RngSeed::Fixed(SEED),
+            ..ProptestConfig::default()
+        },
+        ..lincheck(3)
+    };
+    let failure = lincheck.verify::<Objects<RacyCounter, 2>>().unwrap_err();
+    let execution = failure.execution().expect("the execution is reported");
+    assert!(!LinearizabilityChecker::<Objects<SequentialCounter, 2>>::check_partitioned(execution));
+    // the lost increments target the same object
+    let objects: Vec<_> = execution
+        .parallel_part()
+        .iter()
+        .map(|inv| inv.op().object)
+        .collect();
+    assert_eq!(objects, [0, 0]);
+    assert!(failure.to_string().contains("#0 Increment"));
+}
+
+#[test]
+fn partitioned_checker() {
+    let lincheck = Lincheck {
+        checker: Some(SpecChecker::partitioned::<Objects<SequentialCounter, 2>>()),
+        ..lincheck(3)
+    };
+    assert!(lincheck.verify::<Objects<AtomicCounter, 2>>().is_ok());
+    assert!(lincheck.verify::<Objects<RacyCounter, 2>>().is_err());
+}
+
+#[test]
+fn invariant_across_objects() {
+    let failure = lincheck(3).verify::<CountedCounters>().unwrap_err();
+    assert!(failure.to_string().starts_with("Invalid state"));
+}
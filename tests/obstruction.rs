@@ -0,0 +1,113 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use lincheck::obstruction::{stall_point, ObstructionFailure};
+use lincheck::{ConcurrentSpec, SequentialSpec};
+use loom::sync::atomic::{AtomicUsize, Ordering};
+use loom::sync::Mutex;
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Increment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    OldValue(usize),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        Just(Op::Increment).boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialCounter {
+    x: usize,
+}
+
+impl SequentialSpec for SequentialCounter {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Increment => {
+                self.x += 1;
+                Ret::OldValue(self.x - 1)
+            }
+        }
+    }
+}
+
+/// Retries a compare-and-swap, so a suspended thread can't block the others.
+#[derive(Default)]
+struct LockFreeCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for LockFreeCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::Increment => loop {
+                let val = self.x.load(Ordering::SeqCst);
+                stall_point();
+                if self
+                    .x
+                    .compare_exchange(val, val + 1, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+                {
+                    return Ret::OldValue(val);
+                }
+            },
+        }
+    }
+}
+
+/// Increments under a lock, so a thread suspended inside the critical section blocks the others.
+#[derive(Default)]
+struct LockingCounter {
+    x: Mutex<usize>,
+}
+
+impl ConcurrentSpec for LockingCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::Increment => {
+                let mut x = self.x.lock().unwrap();
+                stall_point();
+                *x += 1;
+                Ret::OldValue(*x - 1)
+            }
+        }
+    }
+}
+
+#[test]
+fn lock_free_counter() {
+    assert!(lincheck(2)
+        .verify_obstruction_freedom::<LockFreeCounter>()
+        .is_ok());
+}
+
+#[test]
+fn locking_counter() {
+    let failure = lincheck(2)
+        .verify_obstruction_freedom::<LockingCounter>()
+        .unwrap_err();
+    let ObstructionFailure::Blocked { scenario, .. } = failure else {
+        panic!("the counter is linearizable");
+    };
+    assert_eq!(scenario.parallel_part.len(), 2);
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use common::readable::{Op, SequentialCounter};
+use lincheck::scenario::ScenarioBuilder;
+use lincheck::{ConcurrentSpec, PrefillableSpec, VerificationFailure};
+use loom::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number from which the increments race.
+const THRESHOLD: usize = 8;
+
+/// Increments atomically below [THRESHOLD] and with a racy load and store from it.
+struct ThresholdCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for ThresholdCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> usize {
+        match op {
+            Op::Increment => {
+                let x = self.x.load(Ordering::SeqCst);
+                if x < THRESHOLD {
+                    return self.x.fetch_add(1, Ordering::SeqCst);
+                }
+                self.x.store(x + 1, Ordering::SeqCst);
+                x
+            }
+            Op::Get => self.x.fetch_add(0, Ordering::SeqCst),
+        }
+    }
+}
+
+impl PrefillableSpec for ThresholdCounter {
+    fn from_seq(seq_state: &SequentialCounter) -> Self {
+        Self {
+            x: AtomicUsize::new(seq_state.x),
+        }
+    }
+}
+
+impl Default for ThresholdCounter {
+    fn default() -> Self {
+        Self::from_seq(&SequentialCounter::default())
+    }
+}
+
+#[test]
+fn race_is_out_of_reach_without_prefill() {
+    assert!(lincheck(2).verify::<ThresholdCounter>().is_ok());
+}
+
+#[test]
+fn prefill_reaches_the_race() {
+    let failure = lincheck(2)
+        .verify_prefilled::<ThresholdCounter>(16)
+        .unwrap_err();
+    assert!(matches!(
+        failure.failure,
+        VerificationFailure::NonLinearizable(_)
+    ));
+    assert!(!failure.prefill.is_empty());
+    assert!(failure.to_string().starts_with("After the prefill"));
+}
+
+#[test]
+fn prefilled_scenario_starts_from_the_prefill() {
+    let scenario = ScenarioBuilder::new()
+        .thread([Op::Increment])
+        .thread([Op::Increment])
+        .build();
+    let lincheck = lincheck(2);
+    let below = [Op::Increment; THRESHOLD - 1];
+    assert!(lincheck
+        .check_prefilled_scenario::<ThresholdCounter>(&below, scenario.clone())
+        .is_ok());
+    let above = [Op::Increment; THRESHOLD];
+    assert!(lincheck
+        .check_prefilled_scenario::<ThresholdCounter>(&above, scenario)
+        .is_err());
+}
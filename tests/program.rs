@@ -0,0 +1,124 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use lincheck::{ConcurrentSpec, SequentialSpec};
+use loom::sync::atomic::{AtomicU8, Ordering};
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Read,
+    CompareAndSet(u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Read(u8),
+    CompareAndSet(bool),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(Op::Read),
+            (any::<u8>(), any::<u8>()).prop_map(|(old, new)| Op::CompareAndSet(old, new)),
+        ]
+        .boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialRegister {
+    value: u8,
+}
+
+impl SequentialSpec for SequentialRegister {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Read => Ret::Read(self.value),
+            Op::CompareAndSet(old, new) => {
+                let success = self.value == old;
+                if success {
+                    self.value = new;
+                }
+                Ret::CompareAndSet(success)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct AtomicRegister {
+    value: AtomicU8,
+}
+
+impl ConcurrentSpec for AtomicRegister {
+    type Seq = SequentialRegister;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            // loom may let a load read a value overwritten by an operation that already returned,
+            // while a read-modify-write always reads the latest one
+            Op::Read => Ret::Read(self.value.fetch_add(0, Ordering::SeqCst)),
+            Op::CompareAndSet(old, new) => Ret::CompareAndSet(
+                self.value
+                    .compare_exchange(old, new, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok(),
+            ),
+        }
+    }
+}
+
+/// Compares and sets with a separate load and store.
+#[derive(Default)]
+struct RacyRegister {
+    value: AtomicU8,
+}
+
+impl ConcurrentSpec for RacyRegister {
+    type Seq = SequentialRegister;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::Read => Ret::Read(self.value.load(Ordering::SeqCst)),
+            Op::CompareAndSet(old, new) => {
+                let success = self.value.load(Ordering::SeqCst) == old;
+                if success {
+                    self.value.store(new, Ordering::SeqCst);
+                }
+                Ret::CompareAndSet(success)
+            }
+        }
+    }
+}
+
+/// Reads the value and then adds `delta` to it.
+fn increment(&delta: &u8) -> impl FnMut(&[(Op, Ret)]) -> Option<Op> {
+    move |history| match history {
+        [] => Some(Op::Read),
+        [(_, Ret::Read(value))] => Some(Op::CompareAndSet(*value, value.wrapping_add(delta))),
+        _ => None,
+    }
+}
+
+#[test]
+fn atomic_compare_and_set() {
+    assert!(lincheck(2)
+        .verify_program::<AtomicRegister, _, _>(increment)
+        .is_ok());
+}
+
+#[test]
+fn racy_compare_and_set() {
+    assert!(lincheck(2)
+        .verify_program::<RacyRegister, _, _>(increment)
+        .is_err());
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use common::readable::{Op, RacyCounter};
+use lincheck::scenario::ScenarioBuilder;
+use lincheck::Lincheck;
+
+#[test]
+fn sequential_increments_pass() {
+    let scenario = ScenarioBuilder::new()
+        .init([Op::Increment])
+        .thread([Op::Increment, Op::Get])
+        .post([Op::Get])
+        .build();
+    assert!(Lincheck::default()
+        .check_scenario::<RacyCounter>(scenario)
+        .is_ok());
+}
+
+#[test]
+fn concurrent_increments_fail() {
+    let scenario = ScenarioBuilder::new()
+        .thread([Op::Increment])
+        .thread([Op::Increment])
+        .post([Op::Get])
+        .build();
+    let execution = Lincheck::default()
+        .check_scenario::<RacyCounter>(scenario)
+        .unwrap_err();
+    assert_eq!(execution.parallel_part().num_threads(), 2);
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use lincheck::relaxed::{QueueOp, QueueRet, Relaxation, RelaxedQueue};
+use lincheck::ConcurrentSpec;
+use loom::sync::Mutex;
+use loom::thread;
+use std::collections::VecDeque;
+
+/// A queue that dequeues the second element instead of the head when it finds the lock taken,
+/// i.e. a 2-FIFO queue.
+#[derive(Default)]
+struct ContendedQueue {
+    items: Mutex<VecDeque<u8>>,
+}
+
+impl ConcurrentSpec for ContendedQueue {
+    type Seq = RelaxedQueue<u8>;
+
+    fn exec(&self, op: QueueOp<u8>) -> QueueRet<u8> {
+        let (mut items, contended) = match self.items.try_lock() {
+            Ok(items) => (items, false),
+            Err(_) => (self.items.lock().unwrap(), true),
+        };
+        // lets the other thread find the lock taken
+        thread::yield_now();
+        match op {
+            QueueOp::Enqueue(value) => {
+                items.push_back(value);
+                QueueRet::Enqueue
+            }
+            QueueOp::Dequeue if contended && items.len() > 1 => QueueRet::Dequeue(items.remove(1)),
+            QueueOp::Dequeue => QueueRet::Dequeue(items.pop_front()),
+        }
+    }
+}
+
+#[test]
+fn contended_queue_is_not_linearizable() {
+    assert!(lincheck(4).verify::<ContendedQueue>().is_err());
+}
+
+#[test]
+fn contended_queue_is_2_fifo() {
+    assert!(lincheck(4)
+        .verify_relaxed::<ContendedQueue>(Relaxation::PerOperation(1))
+        .is_ok());
+}
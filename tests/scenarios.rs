@@ -0,0 +1,36 @@
+mod common;
+
+use common::readable::{AtomicCounter, Op, RacyCounter};
+use lincheck::scenario::{Scenario, ScenarioBuilder};
+use lincheck::{Lincheck, VerificationFailure};
+
+/// Every scenario of two threads running one or two increments each.
+fn scenarios() -> impl Iterator<Item = Scenario<Op>> {
+    const THREADS: [&[Op]; 2] = [&[Op::Increment], &[Op::Increment, Op::Increment]];
+    THREADS.into_iter().flat_map(|first| {
+        THREADS.into_iter().map(move |second| {
+            ScenarioBuilder::new()
+                .thread(first.iter().copied())
+                .thread(second.iter().copied())
+                .post([Op::Get])
+                .build()
+        })
+    })
+}
+
+#[test]
+fn linearizable_scenarios() {
+    assert!(Lincheck::default()
+        .verify_scenarios::<AtomicCounter>(scenarios())
+        .is_ok());
+}
+
+#[test]
+fn non_linearizable_scenario() {
+    match Lincheck::default().verify_scenarios::<RacyCounter>(scenarios()) {
+        Err(VerificationFailure::NonLinearizable(execution)) => {
+            assert_eq!(execution.parallel_part().num_threads(), 2);
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "shuttle")]
+
+mod common;
+
+use common::{Op, SequentialCounter};
+use lincheck::shuttle_check::{ShuttleConfig, ShuttleScheduler};
+use lincheck::{ConcurrentSpec, Lincheck};
+use shuttle::sync::atomic::{AtomicUsize, Ordering};
+use shuttle::sync::Mutex;
+
+#[derive(Default)]
+struct LockedCounter {
+    x: Mutex<usize>,
+}
+
+impl ConcurrentSpec for LockedCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, _: Op) -> usize {
+        let mut x = self.x.lock().unwrap();
+        *x += 1;
+        *x - 1
+    }
+}
+
+/// A counter that loads and stores the value separately, losing the concurrent increments.
+#[derive(Default)]
+struct RacyCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for RacyCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, _: Op) -> usize {
+        let x = self.x.load(Ordering::SeqCst);
+        self.x.store(x + 1, Ordering::SeqCst);
+        x
+    }
+}
+
+fn config() -> Lincheck {
+    Lincheck {
+        num_threads: 3,
+        num_ops: 3,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn locked_counter() {
+    assert!(config()
+        .verify_shuttle::<LockedCounter>(&ShuttleConfig::default())
+        .is_ok());
+}
+
+#[test]
+fn racy_counter() {
+    let failure = config()
+        .verify_shuttle::<RacyCounter>(&ShuttleConfig::default())
+        .unwrap_err();
+    let execution = failure.execution().expect("the execution is reported");
+    assert!(execution.parallel_part().num_threads() > 1);
+}
+
+#[test]
+fn racy_counter_with_pct() {
+    let shuttle = ShuttleConfig {
+        scheduler: ShuttleScheduler::Pct { depth: 2 },
+        ..Default::default()
+    };
+    assert!(config().verify_shuttle::<RacyCounter>(&shuttle).is_err());
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use lincheck::specs::*;
+use lincheck::ConcurrentSpec;
+
+use loom::sync::Mutex;
+
+#[derive(Default)]
+struct LockedRegister {
+    value: Mutex<u8>,
+}
+
+impl ConcurrentSpec for LockedRegister {
+    type Seq = Register<u8>;
+
+    fn exec(&self, op: RegisterOp<u8>) -> RegisterRet<u8> {
+        let mut value = self.value.lock().unwrap();
+        match op {
+            RegisterOp::Read => RegisterRet::Read(*value),
+            RegisterOp::Write(new_value) => {
+                *value = new_value;
+                RegisterRet::Write
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct LockedSnapshot {
+    components: Mutex<[u8; 2]>,
+}
+
+impl ConcurrentSpec for LockedSnapshot {
+    type Seq = Snapshot<u8, 2>;
+
+    fn exec(&self, op: SnapshotOp<u8, 2>) -> SnapshotRet<u8> {
+        let mut components = self.components.lock().unwrap();
+        match op {
+            SnapshotOp::Update(index, value) => {
+                components[index] = value;
+                SnapshotRet::Update
+            }
+            SnapshotOp::Scan => SnapshotRet::Scan(components.to_vec()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct CasConsensus {
+    decided: Mutex<Option<u8>>,
+}
+
+impl ConcurrentSpec for CasConsensus {
+    type Seq = Consensus<u8>;
+
+    fn exec(&self, op: ConsensusOp<u8>) -> ConsensusRet<u8> {
+        let ConsensusOp::Propose(value) = op;
+        ConsensusRet::Decided(*self.decided.lock().unwrap().get_or_insert(value))
+    }
+}
+
+// Checks and decides in separate critical sections.
+#[derive(Default)]
+struct RacyConsensus {
+    decided: Mutex<Option<u8>>,
+}
+
+impl ConcurrentSpec for RacyConsensus {
+    type Seq = Consensus<u8>;
+
+    fn exec(&self, op: ConsensusOp<u8>) -> ConsensusRet<u8> {
+        let ConsensusOp::Propose(value) = op;
+        let decided = *self.decided.lock().unwrap();
+        match decided {
+            Some(decided) => ConsensusRet::Decided(decided),
+            None => {
+                *self.decided.lock().unwrap() = Some(value);
+                ConsensusRet::Decided(value)
+            }
+        }
+    }
+}
+
+#[test]
+fn locked_register() {
+    lincheck(2).verify_or_panic::<LockedRegister>();
+}
+
+#[test]
+fn locked_snapshot() {
+    lincheck(2).verify_or_panic::<LockedSnapshot>();
+}
+
+#[test]
+fn cas_consensus() {
+    lincheck(2).verify_or_panic::<CasConsensus>();
+}
+
+#[test]
+fn racy_consensus() {
+    assert!(lincheck(2).verify::<RacyConsensus>().is_err());
+}
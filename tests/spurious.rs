@@ -0,0 +1,111 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use lincheck::spurious::{spurious_failure, SpuriousSpec};
+use lincheck::{ConcurrentSpec, SequentialSpec};
+use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    TryIncrement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    TryIncrement(Option<usize>),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        Just(Op::TryIncrement).boxed()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SequentialCounter {
+    value: usize,
+}
+
+impl SequentialSpec for SequentialCounter {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::TryIncrement => {
+                self.value += 1;
+                Ret::TryIncrement(Some(self.value))
+            }
+        }
+    }
+}
+
+impl SpuriousSpec for SequentialCounter {
+    fn exec_spurious(&mut self, op: Op) -> Ret {
+        match op {
+            // a spurious failure leaves the counter untouched
+            Op::TryIncrement => Ret::TryIncrement(None),
+        }
+    }
+}
+
+/// Increments with a single weak compare-and-swap, which fails spuriously every other time,
+/// and retries on contention but not on a spurious failure.
+#[derive(Default)]
+struct WeakCounter<const DECLARE: bool> {
+    value: AtomicUsize,
+    fail_next: AtomicBool,
+}
+
+impl<const DECLARE: bool> WeakCounter<DECLARE> {
+    /// `compare_exchange_weak` with a deterministic spurious failure, since loom never fails spuriously.
+    fn compare_exchange_weak(&self, current: usize, new: usize) -> Result<usize, usize> {
+        if !self.fail_next.fetch_xor(true, Ordering::SeqCst) {
+            return Err(current);
+        }
+        self.value
+            .compare_exchange(current, new, Ordering::SeqCst, Ordering::SeqCst)
+    }
+}
+
+impl<const DECLARE: bool> ConcurrentSpec for WeakCounter<DECLARE> {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::TryIncrement => loop {
+                let current = self.value.load(Ordering::SeqCst);
+                match self.compare_exchange_weak(current, current + 1) {
+                    Ok(_) => break Ret::TryIncrement(Some(current + 1)),
+                    Err(actual) if actual == current => {
+                        if DECLARE {
+                            spurious_failure();
+                        }
+                        break Ret::TryIncrement(None);
+                    }
+                    Err(_) => continue,
+                }
+            },
+        }
+    }
+}
+
+#[test]
+fn declared_spurious_failures() {
+    assert!(lincheck(3).verify_spurious::<WeakCounter<true>>().is_ok());
+}
+
+#[test]
+fn undeclared_spurious_failures() {
+    let execution = lincheck(3)
+        .verify_spurious::<WeakCounter<false>>()
+        .unwrap_err();
+    assert!(execution.to_string().contains("TryIncrement"));
+    assert!(!execution.to_string().contains("spurious"));
+}
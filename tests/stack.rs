@@ -1,4 +1,6 @@
-use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
+#![cfg(feature = "proptest")]
+
+use lincheck::{ConcurrentSpec, Lincheck, Replay, ReplayMismatch, SequentialSpec};
 
 use loom::sync::Mutex;
 use proptest::prelude::*;
@@ -80,3 +82,26 @@ impl<T> ConcurrentSpec for ConcurrentStack<T> {
 fn models_stack() {
     Lincheck::default().verify_or_panic::<ConcurrentStack<u8>>();
 }
+
+#[test]
+fn replays_stack() {
+    assert_eq!(
+        SequentialStack::replay([Op::Push(1), Op::Push(2), Op::Pop]),
+        vec![Ret::Push, Ret::Push, Ret::Pop(Some(2))]
+    );
+
+    let history = [
+        (Op::Push(1), Ret::Push),
+        (Op::Pop, Ret::Pop(Some(2))),
+        (Op::Pop, Ret::Pop(None)),
+    ];
+    assert_eq!(
+        SequentialStack::replay_diff(history),
+        vec![ReplayMismatch {
+            index: 1,
+            op: Op::Pop,
+            expected: Ret::Pop(Some(1)),
+            recorded: Ret::Pop(Some(2)),
+        }]
+    );
+}
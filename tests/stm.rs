@@ -0,0 +1,107 @@
+#![cfg(feature = "proptest")]
+
+use lincheck::stm::{TransactionalSpec, TxOp, TxOutcome, TxRet};
+use lincheck::{Lincheck, SequentialSpec};
+
+use loom::sync::Mutex;
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Read(usize),
+    Write(usize, u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Read(u8),
+    Write,
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            (0..2usize).prop_map(Op::Read),
+            (0..2usize, 1..3u8).prop_map(|(i, v)| Op::Write(i, v)),
+        ]
+        .boxed()
+    }
+}
+
+#[derive(Default, Clone)]
+struct SequentialRegisters {
+    values: [u8; 2],
+}
+
+impl SequentialSpec for SequentialRegisters {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::Read(i) => Ret::Read(self.values[i]),
+            Op::Write(i, v) => {
+                self.values[i] = v;
+                Ret::Write
+            }
+        }
+    }
+}
+
+// Runs every transaction under a global lock.
+#[derive(Default)]
+struct GlobalLockStm {
+    values: Mutex<SequentialRegisters>,
+}
+
+impl TransactionalSpec for GlobalLockStm {
+    type Seq = SequentialRegisters;
+
+    fn exec_transaction(&self, tx: &[TxOp<Self>]) -> TxOutcome<TxRet<Self>> {
+        let mut values = self.values.lock().unwrap();
+        TxOutcome::Committed(tx.iter().map(|&op| values.exec(op)).collect())
+    }
+}
+
+// Locks every operation separately, so transactions are not atomic.
+#[derive(Default)]
+struct PerOpLockStm {
+    values: Mutex<SequentialRegisters>,
+}
+
+impl TransactionalSpec for PerOpLockStm {
+    type Seq = SequentialRegisters;
+
+    fn exec_transaction(&self, tx: &[TxOp<Self>]) -> TxOutcome<TxRet<Self>> {
+        TxOutcome::Committed(
+            tx.iter()
+                .map(|&op| self.values.lock().unwrap().exec(op))
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn global_lock_stm_is_opaque() {
+    Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        ..Default::default()
+    }
+    .verify_opacity::<GlobalLockStm>()
+    .unwrap();
+}
+
+#[test]
+fn per_op_lock_stm_is_not_opaque() {
+    assert!(Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        ..Default::default()
+    }
+    .verify_opacity::<PerOpLockStm>()
+    .is_err());
+}
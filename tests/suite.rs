@@ -0,0 +1,44 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use common::{AtomicCounter, RacyCounter};
+use lincheck::{suite, Lincheck};
+
+#[test]
+fn suite_reports_every_structure() {
+    let report = suite()
+        .register::<AtomicCounter>(lincheck(2))
+        .register_named::<RacyCounter>("racy counter", lincheck(2))
+        .register_named::<AtomicCounter>(
+            "single-threaded counter",
+            Lincheck {
+                num_threads: 1,
+                ..lincheck(2)
+            },
+        )
+        .run();
+
+    assert!(!report.passed());
+    let names: Vec<_> = report
+        .failures()
+        .map(|result| result.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["racy counter"]);
+    assert!(report.results[0].name.ends_with("AtomicCounter"));
+    assert!(report.results.iter().all(|result| result.scenarios > 0));
+
+    let printed = report.to_string();
+    assert!(printed.starts_with("lincheck suite: 2 passed, 1 failed"));
+    assert!(printed.contains("---- racy counter ----\nNon-linearizable execution"));
+}
+
+#[test]
+#[should_panic(expected = "FAILED racy counter")]
+fn suite_panics_on_failure() {
+    suite()
+        .register::<AtomicCounter>(lincheck(2))
+        .register_named::<RacyCounter>("racy counter", lincheck(2))
+        .run_or_panic();
+}
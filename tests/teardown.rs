@@ -0,0 +1,144 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use lincheck::teardown::{ClosableSpec, DroppableSpec};
+use lincheck::SequentialSpec;
+use loom::sync::{Arc, Mutex};
+use proptest::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Put(u8),
+    Take,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Put,
+    Take(Option<u8>),
+    Closed,
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![(1..=2u8).prop_map(Op::Put), Just(Op::Take)].boxed()
+    }
+}
+
+#[derive(Default)]
+struct SequentialSlot {
+    value: Option<u8>,
+    closed: bool,
+}
+
+impl SequentialSpec for SequentialSlot {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        if self.closed {
+            return Ret::Closed;
+        }
+        match op {
+            Op::Put(value) => {
+                self.value = Some(value);
+                Ret::Put
+            }
+            Op::Take => Ret::Take(self.value.take()),
+        }
+    }
+}
+
+impl ClosableSpec for SequentialSlot {
+    fn close(&mut self) {
+        self.closed = true;
+    }
+}
+
+#[derive(Default)]
+struct State {
+    value: Option<u8>,
+    closed: bool,
+}
+
+fn exec(state: &Mutex<State>, op: Op) -> Ret {
+    let mut state = state.lock().unwrap();
+    if state.closed {
+        return Ret::Closed;
+    }
+    match op {
+        Op::Put(value) => {
+            state.value = Some(value);
+            Ret::Put
+        }
+        Op::Take => Ret::Take(state.value.take()),
+    }
+}
+
+/// Closes the slot atomically when dropped.
+#[derive(Default)]
+struct Slot {
+    state: Arc<Mutex<State>>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        state.value = None;
+        state.closed = true;
+    }
+}
+
+impl DroppableSpec for Slot {
+    type Seq = SequentialSlot;
+    type Handle = Arc<Mutex<State>>;
+
+    fn handle(&self) -> Self::Handle {
+        self.state.clone()
+    }
+
+    fn exec(handle: &Self::Handle, op: Op) -> Ret {
+        exec(handle, op)
+    }
+}
+
+/// Clears the value before marking the slot as closed, so a concurrent `Take` may observe an empty open slot.
+#[derive(Default)]
+struct LeakySlot {
+    state: Arc<Mutex<State>>,
+}
+
+impl Drop for LeakySlot {
+    fn drop(&mut self) {
+        self.state.lock().unwrap().value = None;
+        self.state.lock().unwrap().closed = true;
+    }
+}
+
+impl DroppableSpec for LeakySlot {
+    type Seq = SequentialSlot;
+    type Handle = Arc<Mutex<State>>;
+
+    fn handle(&self) -> Self::Handle {
+        self.state.clone()
+    }
+
+    fn exec(handle: &Self::Handle, op: Op) -> Ret {
+        exec(handle, op)
+    }
+}
+
+#[test]
+fn slot() {
+    assert!(lincheck(2).verify_drop::<Slot>().is_ok());
+}
+
+#[test]
+fn leaky_slot() {
+    assert!(lincheck(2).verify_drop::<LeakySlot>().is_err());
+}
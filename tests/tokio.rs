@@ -0,0 +1,120 @@
+#![cfg(feature = "tokio")]
+
+mod common;
+
+use common::{Op, SequentialCounter};
+use lincheck::tokio_fuzz::{AsyncConcurrentSpec, TokioConfig};
+use lincheck::Lincheck;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use tokio::task;
+
+#[derive(Default)]
+struct AtomicCounter {
+    x: AtomicUsize,
+}
+
+impl AsyncConcurrentSpec for AtomicCounter {
+    type Seq = SequentialCounter;
+
+    async fn exec(&self, _: Op) -> usize {
+        task::yield_now().await;
+        self.x.fetch_add(1, Ordering::Relaxed)
+    }
+
+    // the increment is either done or not at any await point
+    fn may_cancel(_: &Op) -> bool {
+        true
+    }
+}
+
+/// Unlocks the spin lock of [CancelUnsafeCounter] when dropped, even by a cancellation.
+struct Unlock<'a>(&'a AtomicBool);
+
+impl Drop for Unlock<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// A counter that adds two under a lock, yields and then takes one back,
+/// leaving the counter two ahead if it is cancelled in between.
+#[derive(Default)]
+struct CancelUnsafeCounter {
+    locked: AtomicBool,
+    x: AtomicUsize,
+}
+
+impl AsyncConcurrentSpec for CancelUnsafeCounter {
+    type Seq = SequentialCounter;
+
+    async fn exec(&self, _: Op) -> usize {
+        while self.locked.swap(true, Ordering::Acquire) {
+            task::yield_now().await;
+        }
+        let _unlock = Unlock(&self.locked);
+        let x = self.x.fetch_add(2, Ordering::Relaxed);
+        task::yield_now().await;
+        self.x.fetch_sub(1, Ordering::Relaxed);
+        x
+    }
+
+    fn may_cancel(_: &Op) -> bool {
+        true
+    }
+}
+
+/// A counter that yields between loading and storing the value, losing the concurrent increments.
+#[derive(Default)]
+struct YieldingCounter {
+    x: AtomicUsize,
+}
+
+impl AsyncConcurrentSpec for YieldingCounter {
+    type Seq = SequentialCounter;
+
+    async fn exec(&self, _: Op) -> usize {
+        let x = self.x.load(Ordering::Relaxed);
+        task::yield_now().await;
+        self.x.store(x + 1, Ordering::Relaxed);
+        x
+    }
+}
+
+fn config() -> Lincheck {
+    Lincheck {
+        num_threads: 3,
+        num_ops: 3,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn atomic_counter() {
+    let tokio = TokioConfig {
+        runs_per_scenario: 20,
+        ..Default::default()
+    };
+    assert!(config().verify_tokio::<AtomicCounter>(&tokio).is_ok());
+}
+
+#[test]
+fn yielding_counter() {
+    let failure = config()
+        .verify_tokio::<YieldingCounter>(&TokioConfig::default())
+        .unwrap_err();
+    let execution = failure.execution().expect("the execution is reported");
+    assert!(execution.parallel_part().num_threads() > 1);
+}
+
+#[test]
+fn cancel_unsafe_counter() {
+    let failure = config()
+        .verify_tokio::<CancelUnsafeCounter>(&TokioConfig::default())
+        .unwrap_err();
+    let execution = failure.execution().expect("the execution is reported");
+    assert!(execution
+        .pending()
+        .iter()
+        .any(|inv| inv.cancel_timestamp().is_some()));
+}
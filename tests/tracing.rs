@@ -0,0 +1,97 @@
+#![cfg(all(feature = "tracing", feature = "proptest"))]
+
+mod common;
+
+use common::RacyCounter;
+use lincheck::Lincheck;
+use std::fmt::Debug;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Records the names of the spans and the messages and the fields of the events of lincheck.
+#[derive(Clone, Default)]
+struct Recorder {
+    next_id: Arc<AtomicU64>,
+    spans: Arc<Mutex<Vec<String>>>,
+    events: Arc<Mutex<Vec<RecordedEvent>>>,
+}
+
+#[derive(Default)]
+struct RecordedEvent {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl Visit for RecordedEvent {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push(field.name().to_string());
+        }
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with("lincheck")
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        self.spans
+            .lock()
+            .unwrap()
+            .push(span.metadata().name().to_string());
+        Id::from_u64(self.next_id.fetch_add(1, atomic::Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut recorded = RecordedEvent::default();
+        event.record(&mut recorded);
+        self.events.lock().unwrap().push(recorded);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn verification_is_traced() {
+    let recorder = Recorder::default();
+    let lincheck = Lincheck {
+        num_threads: 2,
+        num_ops: 2,
+        minimize: true,
+        ..Default::default()
+    };
+    let failed = tracing::subscriber::with_default(recorder.clone(), || {
+        lincheck.verify::<RacyCounter>().is_err()
+    });
+    assert!(failed);
+
+    let spans = recorder.spans.lock().unwrap();
+    for name in ["verify", "scenario", "loom", "minimize"] {
+        assert!(spans.iter().any(|span| span == name), "no {} span", name);
+    }
+
+    let events = recorder.events.lock().unwrap();
+    let fields = |message: &str| {
+        events
+            .iter()
+            .find(|event| event.message == message)
+            .map(|event| event.fields.clone())
+            .unwrap_or_else(|| panic!("no {:?} event", message))
+    };
+    assert!(fields("explored the interleavings").contains(&"interleavings".to_string()));
+    assert!(fields("checked the linearizability").contains(&"backtracks".to_string()));
+    assert!(fields("checked the scenario").contains(&"passed".to_string()));
+    assert!(fields("found a failing case").contains(&"token".to_string()));
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "proptest")]
+
+mod common;
+
+use common::lincheck;
+use common::{Op, SequentialCounter};
+use lincheck::scenario::ScenarioPart;
+use lincheck::{ConcurrentSpec, VerificationFailure};
+use loom::sync::atomic::{AtomicUsize, Ordering};
+
+/// A linearizable counter that also counts its increments in a racy statistic,
+/// which loses the concurrent updates.
+#[derive(Default)]
+struct CountingCounter<const BETWEEN_PARTS: bool> {
+    x: AtomicUsize,
+    increments: AtomicUsize,
+}
+
+impl<const BETWEEN_PARTS: bool> ConcurrentSpec for CountingCounter<BETWEEN_PARTS> {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, _: Op) -> usize {
+        let increments = self.increments.load(Ordering::SeqCst);
+        self.increments.store(increments + 1, Ordering::SeqCst);
+        self.x.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let (x, increments) = (
+            self.x.load(Ordering::SeqCst),
+            self.increments.load(Ordering::SeqCst),
+        );
+        if x == increments {
+            Ok(())
+        } else {
+            Err(format!("counted {} increments of {}", increments, x))
+        }
+    }
+
+    const VALIDATE_BETWEEN_PARTS: bool = BETWEEN_PARTS;
+}
+
+#[derive(Default)]
+struct AtomicCounter {
+    x: AtomicUsize,
+}
+
+impl ConcurrentSpec for AtomicCounter {
+    type Seq = SequentialCounter;
+
+    fn exec(&self, _: Op) -> usize {
+        self.x.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[test]
+fn valid_state() {
+    assert!(lincheck(3).verify::<AtomicCounter>().is_ok());
+}
+
+#[test]
+fn invalid_state_after_post_part() {
+    match lincheck(3).verify::<CountingCounter<false>>() {
+        Err(VerificationFailure::InvalidState {
+            execution,
+            after,
+            message,
+            ..
+        }) => {
+            assert_eq!(after, ScenarioPart::Post);
+            assert!(message.starts_with("counted"));
+            assert!(execution.parallel_part().num_threads() > 1);
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn invalid_state_between_parts() {
+    let failure = lincheck(3)
+        .verify::<CountingCounter<true>>()
+        .expect_err("the statistic loses increments");
+    assert!(matches!(
+        failure,
+        VerificationFailure::InvalidState {
+            after: ScenarioPart::Parallel,
+            ..
+        }
+    ));
+    assert!(failure
+        .to_string()
+        .starts_with("Invalid state after the parallel part: counted"));
+}
@@ -1,9 +1,16 @@
-use lincheck::{ConcurrentSpec, Lincheck, SequentialSpec};
+#![cfg(feature = "proptest")]
+
+use lincheck::progress::{ProgressObserver, ShrinkStats};
+#[cfg(feature = "serde")]
+use lincheck::scenario::Scenario;
+use lincheck::{soak::SoakConfig, ConcurrentSpec, Correctness, Lincheck, SequentialSpec};
 
 use loom::sync::atomic::{AtomicBool, Ordering};
 use proptest::prelude::*;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Op {
     WriteX,
     WriteY,
@@ -88,3 +95,183 @@ impl ConcurrentSpec for TwoSlotsParallel {
 fn two_slots() {
     Lincheck::default().verify_or_panic::<TwoSlotsParallel>()
 }
+
+/// A single relaxed slot: a read may miss a write that finished before it,
+/// but the reads of a thread never go back in time.
+#[derive(Default)]
+struct OneSlotParallel {
+    x: AtomicBool,
+}
+
+impl ConcurrentSpec for OneSlotParallel {
+    type Seq = OneSlotSequential;
+
+    fn exec(&self, op: Op) -> Ret {
+        match op {
+            Op::WriteX | Op::WriteY => {
+                self.x.store(true, Ordering::Relaxed);
+                Ret::Write
+            }
+            Op::ReadX | Op::ReadY => Ret::Read(self.x.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct OneSlotSequential {
+    x: bool,
+}
+
+impl SequentialSpec for OneSlotSequential {
+    type Op = Op;
+    type Ret = Ret;
+
+    fn exec(&mut self, op: Op) -> Ret {
+        match op {
+            Op::WriteX | Op::WriteY => {
+                self.x = true;
+                Ret::Write
+            }
+            Op::ReadX | Op::ReadY => Ret::Read(self.x),
+        }
+    }
+}
+
+#[test]
+fn one_slot_is_sequentially_consistent() {
+    let sequentially_consistent = Lincheck {
+        correctness: Correctness::SequentialConsistency,
+        ..Default::default()
+    };
+    assert!(sequentially_consistent.verify::<OneSlotParallel>().is_ok());
+    assert!(Lincheck::default().verify::<OneSlotParallel>().is_err());
+}
+
+#[test]
+fn two_slots_soak() {
+    let report = Lincheck::default().soak::<TwoSlotsParallel>(&SoakConfig {
+        max_scenarios: Some(50),
+        ..SoakConfig::default()
+    });
+
+    assert_eq!(report.scenarios_checked, 50);
+    assert!(!report.failures.is_empty());
+
+    let clusters = report.clusters();
+    assert!(!clusters.is_empty());
+    assert_eq!(
+        clusters
+            .iter()
+            .map(|cluster| cluster.failures.len())
+            .sum::<usize>(),
+        report.failures.len()
+    );
+    assert!(clusters
+        .iter()
+        .all(|cluster| cluster.failures.contains(&cluster.representative)));
+}
+
+#[derive(Debug, Default)]
+struct CountingObserver {
+    events: Mutex<ObservedEvents>,
+}
+
+#[derive(Debug, Default)]
+struct ObservedEvents {
+    scenarios: usize,
+    candidates: usize,
+    accepted_num_ops: Vec<usize>,
+    stats: Option<ShrinkStats>,
+}
+
+impl ProgressObserver for CountingObserver {
+    fn on_scenario_end(&self, _case: usize, _passed: bool) {
+        self.events.lock().unwrap().scenarios += 1;
+    }
+
+    fn on_shrink_step(&self, num_ops: usize, accepted: bool) {
+        let mut events = self.events.lock().unwrap();
+        events.candidates += 1;
+        if accepted {
+            events.accepted_num_ops.push(num_ops);
+        }
+    }
+
+    fn on_shrink_end(&self, stats: &ShrinkStats) {
+        self.events.lock().unwrap().stats = Some(stats.clone());
+    }
+}
+
+#[test]
+fn two_slots_observed() {
+    let observer = Arc::new(CountingObserver::default());
+    let lincheck = Lincheck {
+        progress: Some(observer.clone()),
+        ..Default::default()
+    };
+    let report = lincheck.verify_report::<TwoSlotsParallel>();
+
+    assert!(report.failure.is_some());
+    let scenario = report.failing_scenario.expect("the test fails");
+    let events = observer.events.lock().unwrap();
+    assert_eq!(events.scenarios, report.scenarios_checked);
+    assert_eq!(events.candidates, report.shrink.candidates);
+    assert_eq!(events.stats, Some(report.shrink.clone()));
+    assert_eq!(
+        report.shrink.accepted + report.shrink.rejected,
+        report.shrink.candidates
+    );
+    assert_eq!(report.shrink.final_num_ops, scenario.num_ops());
+    assert!(report.shrink.final_num_ops <= report.shrink.initial_num_ops);
+    assert!(events
+        .accepted_num_ops
+        .windows(2)
+        .all(|pair| pair[1] <= pair[0]));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn two_slots_soak_artifacts_replay() {
+    let artifacts_dir = std::env::temp_dir().join("lincheck-two-slots-soak");
+    let _ = std::fs::remove_dir_all(&artifacts_dir);
+
+    let lincheck = Lincheck::default();
+    let report = lincheck.soak_saving::<TwoSlotsParallel>(
+        &SoakConfig {
+            max_scenarios: Some(50),
+            ..SoakConfig::default()
+        },
+        &artifacts_dir,
+    );
+
+    assert!(!report.failures.is_empty());
+    assert_eq!(
+        std::fs::read_dir(&artifacts_dir).unwrap().count(),
+        report.failures.len()
+    );
+    let saved = std::fs::read_to_string(artifacts_dir.join("failure-0.ron")).unwrap();
+    let scenario = Scenario::<Op>::from_ron(&saved).unwrap();
+    assert_eq!(scenario, report.failures[0].scenario);
+    assert!(lincheck.replay::<TwoSlotsParallel>(scenario).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn two_slots_replayable_token() {
+    let scenario = Lincheck::default()
+        .verify_report::<TwoSlotsParallel>()
+        .failing_scenario
+        .unwrap();
+
+    // the case is far beyond the configured ones, so the embedded scenario is checked without generating any
+    let lincheck = Lincheck {
+        repro: Some(format!(
+            "0000000000000000-1000000-{}",
+            scenario.to_ron().unwrap()
+        )),
+        ..Default::default()
+    };
+    let report = lincheck.verify_replayable::<TwoSlotsParallel>();
+    assert!(report.failure.is_some());
+    assert_eq!(report.token, lincheck.repro);
+}